@@ -0,0 +1,19 @@
+// Embeds the git commit for GET /version. Docker builds have no .git, so GIT_COMMIT
+// can be passed in instead (e.g. `--build-arg GIT_COMMIT=$(git rev-parse --short HEAD)`).
+use std::process::Command;
+
+fn main() {
+    let commit = std::env::var("GIT_COMMIT").ok().filter(|c| !c.is_empty()).or_else(|| {
+        Command::new("git")
+            .args(["rev-parse", "--short", "HEAD"])
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .and_then(|o| String::from_utf8(o.stdout).ok())
+            .map(|s| s.trim().to_string())
+    });
+    println!("cargo:rustc-env=GIT_COMMIT={}", commit.unwrap_or_else(|| "unknown".to_string()));
+    println!("cargo:rerun-if-env-changed=GIT_COMMIT");
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/refs");
+}
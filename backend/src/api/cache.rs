@@ -0,0 +1,62 @@
+use std::any::Any;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use moka::future::Cache;
+
+type Entry = Arc<dyn Any + Send + Sync>;
+
+/// TTL cache for read endpoints whose data only changes when the scheduler refreshes.
+///
+/// Keys carry the current generation, so bumping it after a refresh makes every earlier
+/// entry unreachable at once; the TTL bounds staleness from writes made outside a refresh.
+#[derive(Clone)]
+pub struct ResponseCache {
+    entries: Option<Cache<String, Entry>>,
+    generation: Arc<AtomicU64>,
+}
+
+impl ResponseCache {
+    /// TTL from RESPONSE_CACHE_TTL_SECS (default 60); 0 disables caching.
+    pub fn from_env() -> Self {
+        let ttl = std::env::var("RESPONSE_CACHE_TTL_SECS").ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(60);
+        Self::with_ttl(Duration::from_secs(ttl))
+    }
+
+    pub fn with_ttl(ttl: Duration) -> Self {
+        let entries = (!ttl.is_zero()).then(|| Cache::builder().max_capacity(1_000).time_to_live(ttl).build());
+        Self { entries, generation: Arc::new(AtomicU64::new(0)) }
+    }
+
+    /// Invalidate everything cached so far. Called once a refresh has written new data.
+    pub fn bump_generation(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        if let Some(entries) = &self.entries {
+            entries.invalidate_all();
+        }
+    }
+
+    /// Cached value for `key`, or the result of `load` (cached on success).
+    /// `bypass` skips the cache in both directions, for `?nocache=true` debugging.
+    pub async fn get_or_load<T, F, Fut>(&self, key: &str, bypass: bool, load: F) -> anyhow::Result<T>
+    where
+        T: Clone + Send + Sync + 'static,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let Some(entries) = self.entries.as_ref().filter(|_| !bypass) else {
+            return load().await;
+        };
+        let key = format!("{}:{}", self.generation.load(Ordering::SeqCst), key);
+        if let Some(hit) = entries.get(&key).await.and_then(|e| e.downcast_ref::<T>().cloned()) {
+            return Ok(hit);
+        }
+        let value = load().await?;
+        entries.insert(key, Arc::new(value.clone())).await;
+        Ok(value)
+    }
+}
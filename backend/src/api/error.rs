@@ -0,0 +1,43 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+
+use crate::models::ApiResponse;
+
+/// Handler error that reaches the client as an `ApiResponse` error body with a matching
+/// status, instead of an empty response whose cause only shows up in the server log.
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self { status, message: message.into() }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, message)
+    }
+
+    /// A 500 carrying `context` and the underlying error, which is also logged.
+    pub fn internal(context: &str, err: impl std::fmt::Display) -> Self {
+        tracing::error!("{}: {}", context, err);
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, format!("{}: {}", context, err))
+    }
+}
+
+/// Bare status codes get the status's reason phrase as their message.
+impl From<StatusCode> for ApiError {
+    fn from(status: StatusCode) -> Self {
+        Self::new(status, status.canonical_reason().unwrap_or("Request failed"))
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(ApiResponse::<()>::error(self.message))).into_response()
+    }
+}
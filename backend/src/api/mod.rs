@@ -1,48 +1,115 @@
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
-    routing::{get, post},
+    body::Body,
+    extract::{FromRef, Path, Query, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post, put},
     Router,
 };
+use futures_util::{stream, StreamExt};
 use serde::{Deserialize, Serialize};
 use sqlx::{SqlitePool, Row};
 use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
 use tower::ServiceBuilder;
 use tower_http::{cors::CorsLayer, services::ServeDir, trace::TraceLayer};
 
+mod cache;
+mod error;
+pub use cache::ResponseCache;
+pub use error::ApiError;
+
 use crate::db::{
-    clear_all_data, create_pool, get_all_teams, get_elo_history, get_finished_matches_ordered,
-    get_team_by_id, get_team_current_stats, get_team_recent_matches, get_teams_by_league,
-    get_upcoming_matches, get_prediction_by_match_id, init_database_with_pool, insert_elo_history,
-    get_players_by_team, save_backtest_result, save_model_params, seed_data,
+    clear_all_data, count_matches_by_status, count_predictions, count_teams, count_upcoming, create_pool, get_all_teams, get_confident_predictions, get_elo_history, get_recent_elo_history, get_league_elo_history, get_finished_matches_ordered, get_idempotency_record, save_idempotency_record, get_matches_between, MatchDateFilter, get_team_xpts_between, get_preseason_rating_seasons, carry_over_ratings, save_season_final_ratings,
+    find_teams_by_name, get_team_by_id, get_team_current_stats, get_team_recent_matches, get_teams_by_league,
+    get_upcoming_matches, get_upcoming_matches_within, DEFAULT_UPCOMING_LIMIT, get_market_odds, get_market_odds_history, get_match_by_id, get_match_status, get_remaining_league_fixtures, get_prediction_by_match_id, get_prediction_history, get_resolved_predictions_for_team, get_prediction_for_version, init_database_with_pool, get_home_advantages, replace_elo_state,
+    get_live_matches, get_players_by_team, get_stale_matches, count_stale_matches, get_total_prediction, get_matches_for_team, MatchFilter, merge_teams, set_home_advantage, find_duplicate_matches, validate_data_integrity, get_postponed_matches, get_prediction_inputs, stream_finished_matches, get_schema_status, get_schema_version, get_standings, upsert_match_xpts, seed_data,
+};
+use crate::ml::retrain::{retrain_if_due, retrain_status, train_and_save, RetrainPolicy};
+use crate::ml::match_simulator::ScoreModel;
+use crate::ml::season_simulator::{simulate_season, RemainingFixture, SeasonSimulation, TableEntry};
+use crate::models::{AdvancedTeamStats, ApiResponse, CarryoverReport, CarryoverRequest, BatchPredictionResult, DatasetRequest, DuplicateMatches, EdgeReport, EdgeDiagnostic, EdgeTimelinePoint, EloComponent, EloProjection, EloProjectionStep, EloVsAverage, EloVsAveragePoint, FeatureContribution, FetchSummary, FormComponent, FormTableRow, IntegrityIssue, H2hComponent, HypotheticalFixture, Outcome, ParlayLeg, ParlayLegQuote, ParlayQuote, Prediction, LeaderboardRow, LeagueInsights, LiveMatch, MarketDivergence, Match, MatchAnalysis, MatchDetail, MatchPick, MatchSimulation, MatchTotals, MlEvaluation, ModelComparison, NbaPlayerStats, OddsCoverage, PredictionInputs, ProjectedResult, RecomputePhase, RuntimeConfig, RecomputeReport, SchemaStatus, ScoreDistribution, ScheduleComponent, SportCounts, StandingsRow, StatsOverview, TeamAccuracy, TeamProjection, TotalLine, TotalsPrediction, UpcomingMatchWithPrediction, TeamProfile, Team, VersionInfo};
+use crate::services::{
+    blend_with_market, edge_timeline, expected_scores, fraction_remaining, home_advantage_for, market_blend_weight, prediction_edge, in_play_probabilities, parlay_probability, refresh_odds_if_stale, DataFetcher, EloCalculator, FetchLeague,
+    NbaPlayersFetcher, NbaStatsFetcher, PredictionEngine, FOOTBALL_MODEL_VERSION,
 };
-use crate::ml::backtest::train_and_evaluate;
-use crate::models::{ApiResponse, DatasetRequest, EloComponent, FeatureContribution, FormComponent, H2hComponent, MatchAnalysis, MlEvaluation, NbaPlayerStats, ScoreDistribution, ScheduleComponent, UpcomingMatchWithPrediction, TeamProfile, Team};
-use crate::services::{DataFetcher, EloCalculator, NbaPlayersFetcher, NbaStatsFetcher, PredictionEngine, refresh_odds_if_stale};
-use crate::services::nba_predictor::{load_ml_model, set_ml_model};
+use crate::services::model_registry::{evaluate_model_config, find_model_config, model_registry, ModelConfig};
+use crate::services::nba_predictor::{active_model_version, load_ml_model, set_ml_model, NbaPredictor};
+use crate::utils::{actual_points, brier_score, match_outcome, elo_percentile, elo_tier, elo_tier_cutoffs, expected_points, kelly_criterion, nights_between, season_bounds, season_of, sha256_hex};
+
+/// Shared handler state. Handlers that only need the database keep extracting
+/// `State<SqlitePool>`; the fetcher (and its HTTP connection pool) is built once.
+#[derive(Clone)]
+pub struct AppState {
+    pub pool: SqlitePool,
+    pub fetcher: Arc<DataFetcher>,
+    pub cache: ResponseCache,
+}
+
+impl AppState {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool, fetcher: Arc::new(DataFetcher::new()), cache: ResponseCache::from_env() }
+    }
+}
+
+impl FromRef<AppState> for ResponseCache {
+    fn from_ref(state: &AppState) -> Self {
+        state.cache.clone()
+    }
+}
+
+impl FromRef<AppState> for SqlitePool {
+    fn from_ref(state: &AppState) -> Self {
+        state.pool.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<DataFetcher> {
+    fn from_ref(state: &AppState) -> Self {
+        state.fetcher.clone()
+    }
+}
 
-pub async fn serve(port: u16) -> anyhow::Result<()> {
+/// Address to listen on: `bind` (the --bind flag), else BIND_ADDR, else 0.0.0.0 — every
+/// interface, so set 127.0.0.1 to keep the API local-only.
+pub fn listen_addr(bind: Option<&str>, port: u16) -> anyhow::Result<std::net::SocketAddr> {
+    let bind = bind.map(str::to_string)
+        .or_else(|| std::env::var("BIND_ADDR").ok().filter(|b| !b.trim().is_empty()))
+        .unwrap_or_else(|| "0.0.0.0".to_string());
+    let ip: std::net::IpAddr = bind.trim().parse().map_err(|_| {
+        anyhow::anyhow!("invalid bind address '{}': expected an IP address such as 127.0.0.1, 0.0.0.0 or ::", bind)
+    })?;
+    Ok(std::net::SocketAddr::new(ip, port))
+}
+
+pub async fn serve(addr: std::net::SocketAddr) -> anyhow::Result<()> {
     let pool = create_pool().await?;
     init_database_with_pool(&pool).await?;
+    let state = AppState::new(pool.clone());
 
     // ── HTTP server starts immediately ───────────────────────────────────────
-    let app = create_router().with_state(pool.clone());
-    let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port)).await?;
-    tracing::info!("OddsForge API server listening on port {}", port);
+    let app = create_router().with_state(state.clone());
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    tracing::info!("OddsForge API server listening on {}", addr);
 
     // ── Initial data load + scheduler both run in background ─────────────────
     let init_pool = pool.clone();
+    let fetcher = state.fetcher.clone();
+    let cache = state.cache.clone();
     tokio::spawn(async move {
-        let team_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM teams")
-            .fetch_one(&init_pool).await.unwrap_or(0);
+        let team_count = count_teams(&init_pool).await.unwrap_or(0);
 
         if team_count == 0 {
-            let fetcher = DataFetcher::new();
-            if fetcher.has_football_key() || fetcher.has_nba_key() {
+            if FetchLeague::ALL.into_iter().any(|l| fetcher.will_fetch(l)) {
                 tracing::info!("API keys detected — fetching real data in background…");
-                if let Err(e) = fetcher.fetch_all_data(&init_pool).await {
-                    tracing::error!("Initial fetch failed: {}. Seeding fallback.", e);
+                let summary = fetcher.fetch_all_data(&init_pool).await;
+                for s in &summary.sports {
+                    tracing::info!("Initial {} fetch: {:?}{}", s.sport, s.outcome,
+                        s.reason.as_deref().map_or(String::new(), |r| format!(" ({})", r)));
+                }
+                if summary.all_failed() {
+                    tracing::error!("Initial fetch failed for every sport. Seeding fallback.");
                     let _ = seed_data(&init_pool).await;
                 } else {
                     rebuild_elo(&init_pool).await;
@@ -55,7 +122,7 @@ pub async fn serve(port: u16) -> anyhow::Result<()> {
         }
 
         // Fetch NBA advanced stats on startup (6-hour throttle enforced internally)
-        if NbaStatsFetcher::should_refresh(&init_pool).await {
+        if fetcher.is_enabled(FetchLeague::Nba) && NbaStatsFetcher::should_refresh(&init_pool).await {
             match NbaStatsFetcher::new() {
                 Ok(fetcher) => {
                     if let Err(e) = fetcher.fetch_and_store(&init_pool).await {
@@ -67,7 +134,7 @@ pub async fn serve(port: u16) -> anyhow::Result<()> {
         }
 
         // Fetch NBA player rosters & season averages (24-hour throttle enforced internally)
-        if NbaPlayersFetcher::should_refresh(&init_pool).await {
+        if fetcher.is_enabled(FetchLeague::Nba) && NbaPlayersFetcher::should_refresh(&init_pool).await {
             match NbaPlayersFetcher::new() {
                 Ok(fetcher) => {
                     if let Err(e) = fetcher.fetch_and_store(&init_pool).await {
@@ -87,9 +154,10 @@ pub async fn serve(port: u16) -> anyhow::Result<()> {
 
         // Always regenerate predictions on startup so model changes take effect immediately
         refresh_predictions(&init_pool).await;
+        cache.bump_generation();
 
         // After initial load, hand off to the recurring scheduler
-        background_scheduler(init_pool).await;
+        background_scheduler(init_pool, fetcher, cache).await;
     });
 
     axum::serve(listener, app).await?;
@@ -104,10 +172,10 @@ pub async fn serve(port: u16) -> anyhow::Result<()> {
 //
 // Schedule (per 60-second tick):
 //   Every tick  : EPL matches (1 req) + NBA recent games (1–3 req)
-//   Every 10 min: EPL teams (1 req) + NBA teams (1 req)
-//   After fetch : rebuild ELO → regenerate predictions
+//   Every 10 min: EPL teams + standings (2 req) + NBA teams (1 req)
+//   After fetch : rebuild ELO → regenerate predictions → invalidate response cache
 //
-async fn background_scheduler(pool: SqlitePool) {
+async fn background_scheduler(pool: SqlitePool, fetcher: Arc<DataFetcher>, cache: ResponseCache) {
     // Stagger first run by 5 s so startup logs are readable
     tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
 
@@ -119,43 +187,52 @@ async fn background_scheduler(pool: SqlitePool) {
         cycle += 1;
         tracing::info!("🔄  Background refresh cycle {}", cycle);
 
-        let fetcher = DataFetcher::new();
-
-        // ── football-data.org ────────────────────────────────────────────────
-        if fetcher.has_football_key() {
-            // Every tick: EPL match statuses / scores  (1 req)
-            if let Err(e) = fetcher.fetch_epl_matches(&pool).await {
-                tracing::error!("EPL match refresh failed: {}", e);
+        // Enabled leagues in FETCH_ORDER; ones without an API key are skipped
+        for &league in fetcher.leagues() {
+            if !fetcher.will_fetch(league) {
+                continue;
             }
+            match league {
+                // ── football-data.org ────────────────────────────────────────
+                FetchLeague::Epl => {
+                    // Every tick: EPL match statuses / scores  (1 req)
+                    if let Err(e) = fetcher.fetch_epl_matches(&pool).await {
+                        tracing::error!("EPL match refresh failed: {}", e);
+                    }
 
-            // Every 10 cycles (~10 min): also refresh team list  (1 req)
-            if cycle % 10 == 0 {
-                // Wait 6 s to stay inside 10-req/min window
-                tokio::time::sleep(tokio::time::Duration::from_secs(6)).await;
-                if let Err(e) = fetcher.fetch_epl_teams(&pool).await {
-                    tracing::error!("EPL team refresh failed: {}", e);
+                    // Every 10 cycles (~10 min): also refresh team list  (1 req)
+                    if cycle % 10 == 0 {
+                        // Wait 6 s to stay inside 10-req/min window
+                        tokio::time::sleep(tokio::time::Duration::from_secs(6)).await;
+                        if let Err(e) = fetcher.fetch_epl_teams(&pool).await {
+                            tracing::error!("EPL team refresh failed: {}", e);
+                        }
+                        // Official table, cross-checked against last cycle's computed stats  (1 req)
+                        if let Err(e) = fetcher.fetch_epl_standings(&pool).await {
+                            tracing::warn!("EPL standings refresh failed: {}", e);
+                        }
+                    }
                 }
-            }
-        }
-
-        // ── balldontlie.io ───────────────────────────────────────────────────
-        if fetcher.has_nba_key() {
-            // Every tick: last 3 days of NBA games  (1–3 req)
-            if let Err(e) = fetcher.fetch_recent_nba_games(&pool, 3).await {
-                tracing::error!("NBA recent-game refresh failed: {}", e);
-            }
+                // ── balldontlie.io ───────────────────────────────────────────
+                FetchLeague::Nba => {
+                    // Every tick: last 3 days of NBA games  (1–3 req)
+                    if let Err(e) = fetcher.fetch_recent_nba_games(&pool, 3).await {
+                        tracing::error!("NBA recent-game refresh failed: {}", e);
+                    }
 
-            // Every 10 cycles (~10 min): also refresh team list  (1 req)
-            if cycle % 10 == 0 {
-                if let Err(e) = fetcher.fetch_nba_teams(&pool).await {
-                    tracing::error!("NBA team refresh failed: {}", e);
+                    // Every 10 cycles (~10 min): also refresh team list  (1 req)
+                    if cycle % 10 == 0 {
+                        if let Err(e) = fetcher.fetch_nba_teams(&pool).await {
+                            tracing::error!("NBA team refresh failed: {}", e);
+                        }
+                    }
                 }
             }
         }
 
         // ── NBA Advanced Stats (stats.nba.com, no API key needed) ────────────
         // Refresh every 6 hours; the fetcher checks staleness internally.
-        if NbaStatsFetcher::should_refresh(&pool).await {
+        if fetcher.is_enabled(FetchLeague::Nba) && NbaStatsFetcher::should_refresh(&pool).await {
             match NbaStatsFetcher::new() {
                 Ok(fetcher) => {
                     if let Err(e) = fetcher.fetch_and_store(&pool).await {
@@ -167,7 +244,7 @@ async fn background_scheduler(pool: SqlitePool) {
         }
 
         // ── NBA player stats (24-hour throttle) ──────────────────────────────
-        if NbaPlayersFetcher::should_refresh(&pool).await {
+        if fetcher.is_enabled(FetchLeague::Nba) && NbaPlayersFetcher::should_refresh(&pool).await {
             match NbaPlayersFetcher::new() {
                 Ok(fetcher) => {
                     if let Err(e) = fetcher.fetch_and_store(&pool).await {
@@ -178,217 +255,317 @@ async fn background_scheduler(pool: SqlitePool) {
             }
         }
 
-        // ── Post-fetch: ELO + stats + predictions ────────────────────────────
+        // ── Past-dated "scheduled" rows: try to resolve, otherwise flag ──────
+        reconcile_stale_matches(&pool, &fetcher).await;
+
+        // ── Post-fetch: ELO + stats + predictions + odds ─────────────────────
+        // Held for the whole chain so /admin/recompute can't interleave with it
+        let _pipeline = pipeline_lock().lock().await;
         rebuild_elo(&pool).await;
         compute_season_stats(&pool).await;
+        retrain_if_due_and_load(&pool).await;
         refresh_predictions(&pool).await;
+        cache.bump_generation();
 
         // ── Odds refresh (The Odds API) ───────────────────────────────────────
-        // Internally throttled to ≤ 1 call/sport/12 h — safe with 500 req/month budget
-        if let Ok(api_key) = std::env::var("ODDS_API_KEY") {
-            let n = refresh_odds_if_stale(&pool, &api_key).await;
-            if n > 0 {
-                tracing::info!("Odds refresh: {} matches updated", n);
-            }
+        refresh_odds(&pool).await;
+    }
+}
+
+/// Retrain the NBA ML model once RETRAIN_AFTER_RESULTS / RETRAIN_EVERY_HOURS call for it,
+/// and load it so the predictions that follow use the new version.
+async fn retrain_if_due_and_load(pool: &SqlitePool) {
+    match retrain_if_due(pool, &RetrainPolicy::from_env()).await {
+        Ok(Some((version, state))) => {
+            set_ml_model(state).await;
+            tracing::info!("ML model retrained — saved as version {}", version);
         }
+        Ok(None) => {}
+        Err(e) => tracing::error!("Scheduled ML retrain failed: {}", e),
     }
 }
 
-/// Reset all team ELOs to 1200 then replay every finished match in chronological order,
-/// recording an elo_history point after each match for both teams.
-async fn rebuild_elo(pool: &SqlitePool) {
-    // Clear old history and reset ratings
-    let _ = sqlx::query("DELETE FROM elo_history").execute(pool).await;
-    if let Err(e) = sqlx::query("UPDATE teams SET elo_rating = 1200.0").execute(pool).await {
-        tracing::error!("ELO reset failed: {}", e);
-        return;
+/// Serialises the ELO → stats → predictions → odds chain between the scheduler,
+/// /admin/recompute and the wipe-and-rebuild endpoints (/admin/reseed, /data/refresh).
+static PIPELINE_LOCK: OnceLock<tokio::sync::Mutex<()>> = OnceLock::new();
+
+fn pipeline_lock() -> &'static tokio::sync::Mutex<()> {
+    PIPELINE_LOCK.get_or_init(|| tokio::sync::Mutex::new(()))
+}
+
+/// Pull odds for sports whose last fetch is stale. Internally throttled to ≤ 1 call per
+/// sport per 12 h — safe with a 500 req/month budget. None when ODDS_API_KEY isn't set.
+async fn refresh_odds(pool: &SqlitePool) -> Option<u32> {
+    let api_key = std::env::var("ODDS_API_KEY").ok()?;
+    let n = refresh_odds_if_stale(pool, &api_key).await;
+    if n > 0 {
+        tracing::info!("Odds refresh: {} matches updated", n);
     }
+    Some(n)
+}
 
-    let matches = match get_finished_matches_ordered(pool).await {
-        Ok(m) => m,
-        Err(e) => { tracing::error!("Could not load finished matches: {}", e); return; }
+/// Reset all team ELOs to 1200 then replay every finished match in chronological order,
+/// recording an elo_history point after each match for both teams. Where a carried-over
+/// season begins, its teams are reset to their carried-over rating and only that season's
+/// matches are replayed on top. Ratings are tracked in memory during the replay and
+/// written back, with the history, in one transaction.
+/// Returns how many matches were replayed (0 when the rebuild failed).
+async fn rebuild_elo(pool: &SqlitePool) -> usize {
+    let loaded = tokio::try_join!(get_all_teams(pool), get_finished_matches_ordered(pool), get_home_advantages(pool), get_preseason_rating_seasons(pool));
+    let (teams, matches, home_advantage, preseason) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => { tracing::error!("Could not load teams and finished matches: {}", e); return 0; }
     };
 
-    let calc = EloCalculator::new();
-    let mut updated = 0u32;
+    // A rating saved by a wipe has no season: teams yet to play keep it rather than a flat
+    // 1200, anyone with results is rebuilt from 1200 so those results aren't counted twice
+    let played: std::collections::HashSet<&str> = matches.iter()
+        .flat_map(|m| [m.home_team_id.as_str(), m.away_team_id.as_str()])
+        .collect();
+    let mut ratings: HashMap<String, f64> = teams.into_iter().map(|t| (t.id, 1200.0)).collect();
+    let mut resets: std::collections::BTreeMap<chrono::DateTime<chrono::Utc>, Vec<(String, f64)>> = Default::default();
+    for (team_id, rating, season) in preseason {
+        match season.as_deref().and_then(season_bounds) {
+            Some((season_start, _)) => resets.entry(season_start).or_default().push((team_id, rating)),
+            None if !played.contains(team_id.as_str()) => {
+                if let Some(start) = ratings.get_mut(&team_id) {
+                    *start = rating;
+                }
+            }
+            None => {}
+        }
+    }
 
-    for m in &matches {
-        if calc.update_team_ratings(pool, m).await.is_err() {
-            continue;
+    let calculator = EloCalculator::new();
+    let mut history = Vec::with_capacity(matches.len() * 2);
+    let mut remaining = &matches[..];
+    for (season_start, carried) in resets {
+        let split = remaining.partition_point(|m| m.match_date < season_start);
+        history.extend(calculator.replay(&remaining[..split], &mut ratings, &home_advantage));
+        remaining = &remaining[split..];
+        for (team_id, rating) in carried {
+            if let Some(current) = ratings.get_mut(&team_id) {
+                *current = rating;
+            }
         }
-        // Record ELO history for both teams after this match
-        if let Ok(Some(ht)) = get_team_by_id(pool, &m.home_team_id).await {
-            let _ = insert_elo_history(pool, &ht.id, m.match_date, ht.elo_rating, &m.id).await;
-        }
-        if let Ok(Some(at)) = get_team_by_id(pool, &m.away_team_id).await {
-            let _ = insert_elo_history(pool, &at.id, m.match_date, at.elo_rating, &m.id).await;
-        }
-        updated += 1;
-    }
-    tracing::info!("ELO rebuilt from {} finished matches", updated);
-}
-
-/// Compute W/D/L, goals/points, and recent form for every team from real match data,
-/// then upsert into team_stats.
-async fn compute_season_stats(pool: &SqlitePool) {
-    // Football stats
-    let football_sql = r#"
-        SELECT team_id, sport, SUM(played) as mp,
-               SUM(wins) as w, SUM(draws) as d, SUM(losses) as l,
-               SUM(gf) as gf, SUM(ga) as ga
-        FROM (
-            SELECT home_team_id as team_id, sport,
-                   COUNT(*) as played,
-                   SUM(CASE WHEN home_score > away_score THEN 1 ELSE 0 END) as wins,
-                   SUM(CASE WHEN home_score = away_score THEN 1 ELSE 0 END) as draws,
-                   SUM(CASE WHEN home_score < away_score THEN 1 ELSE 0 END) as losses,
-                   SUM(home_score) as gf, SUM(away_score) as ga
-            FROM matches WHERE status = 'finished' AND home_score IS NOT NULL AND sport = 'football'
-            GROUP BY home_team_id, sport
-            UNION ALL
-            SELECT away_team_id, sport,
-                   COUNT(*),
-                   SUM(CASE WHEN away_score > home_score THEN 1 ELSE 0 END),
-                   SUM(CASE WHEN away_score = home_score THEN 1 ELSE 0 END),
-                   SUM(CASE WHEN away_score < home_score THEN 1 ELSE 0 END),
-                   SUM(away_score), SUM(home_score)
-            FROM matches WHERE status = 'finished' AND away_score IS NOT NULL AND sport = 'football'
-            GROUP BY away_team_id, sport
-        ) GROUP BY team_id, sport
-    "#;
-
-    // Basketball stats (no draws)
-    let basketball_sql = r#"
-        SELECT team_id, sport, SUM(played) as mp,
-               SUM(wins) as w, 0 as d, SUM(losses) as l,
-               SUM(pf) as pf, SUM(pa) as pa
-        FROM (
-            SELECT home_team_id as team_id, sport,
-                   COUNT(*) as played,
-                   SUM(CASE WHEN home_score > away_score THEN 1 ELSE 0 END) as wins,
-                   SUM(CASE WHEN home_score < away_score THEN 1 ELSE 0 END) as losses,
-                   SUM(home_score) as pf, SUM(away_score) as pa
-            FROM matches WHERE status = 'finished' AND home_score IS NOT NULL AND sport = 'basketball'
-            GROUP BY home_team_id, sport
-            UNION ALL
-            SELECT away_team_id, sport,
-                   COUNT(*),
-                   SUM(CASE WHEN away_score > home_score THEN 1 ELSE 0 END),
-                   SUM(CASE WHEN away_score < home_score THEN 1 ELSE 0 END),
-                   SUM(away_score), SUM(home_score)
-            FROM matches WHERE status = 'finished' AND away_score IS NOT NULL AND sport = 'basketball'
-            GROUP BY away_team_id, sport
-        ) GROUP BY team_id, sport
-    "#;
-
-    for (sql, is_football) in [(football_sql, true), (basketball_sql, false)] {
-        let rows = match sqlx::query(sql).fetch_all(pool).await {
-            Ok(r) => r,
+    }
+    history.extend(calculator.replay(remaining, &mut ratings, &home_advantage));
+    if let Err(e) = replace_elo_state(pool, &ratings, &history).await {
+        tracing::error!("ELO rebuild failed: {}", e);
+        return 0;
+    }
+    tracing::info!("ELO rebuilt from {} finished matches", history.len() / 2);
+    history.len() / 2
+}
+
+/// Compute W/D/L, goals/points, xPts and recent form for every team from real match data,
+/// then upsert into team_stats. Returns how many teams' rows were written.
+async fn compute_season_stats(pool: &SqlitePool) -> usize {
+    compute_match_xpts(pool).await;
+    let mut teams_written = 0;
+    let season = season_of(chrono::Utc::now());
+    let (season_start, season_end) = season_bounds(&season).expect("season_of yields a valid label");
+
+    for sport in ["football", "basketball"] {
+        let is_football = sport == "football";
+        let matches = match get_matches_between(pool, &MatchDateFilter {
+            sport: Some(sport),
+            status: Some("finished"),
+            scored_only: true,
+            ..Default::default()
+        }).await {
+            Ok(m) => m,
             Err(e) => { tracing::error!("Season stats query failed: {}", e); continue; }
         };
 
-        for row in rows {
-            let team_id: String = row.get("team_id");
-            let mp: i64 = row.get("mp");
-            let w: i64  = row.get("w");
-            let d: i64  = row.get("d");
-            let l: i64  = row.get("l");
-            let stat1: i64 = if is_football { row.get("gf") } else { row.get("pf") };
-            let stat2: i64 = if is_football { row.get("ga") } else { row.get("pa") };
+        // (played, wins, draws, losses, scored, conceded) per team
+        let mut tallies: std::collections::BTreeMap<&str, (i32, i32, i32, i32, i32, i32)> = Default::default();
+        for m in &matches {
+            let (Some(hs), Some(aws)) = (m.home_score, m.away_score) else { continue };
+            let outcome = match_outcome(hs, aws);
+            for (team_id, side, scored, conceded) in [(&m.home_team_id, Outcome::Home, hs, aws), (&m.away_team_id, Outcome::Away, aws, hs)] {
+                let t = tallies.entry(team_id.as_str()).or_default();
+                t.0 += 1;
+                match outcome {
+                    o if o == side => t.1 += 1,
+                    Outcome::Draw => t.2 += 1,
+                    _ => t.3 += 1,
+                }
+                t.4 += scored;
+                t.5 += conceded;
+            }
+        }
 
+        for (team_id, (mp, w, d, l, stat1, stat2)) in tallies {
             // Compute last-5 form string from most recent matches
-            let form = recent_form(pool, &team_id, is_football).await;
+            let form = recent_form(pool, team_id, is_football).await;
+
+            let xpts = get_team_xpts_between(pool, team_id, season_start, season_end).await.unwrap_or(None);
 
-            let id = uuid::Uuid::new_v4().to_string();
+            // One row per team per season, so recomputing replaces rather than appends
+            let id = format!("{}_{}", team_id, season);
             let now = chrono::Utc::now().to_rfc3339();
 
-            let _ = sqlx::query(
+            let written = sqlx::query(
                 r#"INSERT OR REPLACE INTO team_stats
                    (id, team_id, season, matches_played, wins, draws, losses,
-                    goals_for, goals_against, points_for, points_against, form, updated_at)
-                   VALUES (?, ?, '2025-26', ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+                    goals_for, goals_against, points_for, points_against, form, xpts, updated_at)
+                   VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
             )
             .bind(&id)
-            .bind(&team_id)
-            .bind(mp as i32)
-            .bind(w as i32)
-            .bind(if is_football { Some(d as i32) } else { None::<i32> })
-            .bind(l as i32)
-            .bind(if is_football { Some(stat1 as i32) } else { None::<i32> })
-            .bind(if is_football { Some(stat2 as i32) } else { None::<i32> })
-            .bind(if !is_football { Some(stat1 as i32) } else { None::<i32> })
-            .bind(if !is_football { Some(stat2 as i32) } else { None::<i32> })
+            .bind(team_id)
+            .bind(&season)
+            .bind(mp)
+            .bind(w)
+            .bind(is_football.then_some(d))
+            .bind(l)
+            .bind(is_football.then_some(stat1))
+            .bind(is_football.then_some(stat2))
+            .bind((!is_football).then_some(stat1))
+            .bind((!is_football).then_some(stat2))
             .bind(&form)
+            .bind(xpts)
             .bind(&now)
             .execute(pool)
             .await;
+            if written.is_ok() {
+                teams_written += 1;
+            }
         }
     }
+
+    // Drop rows written under the old random-id scheme
+    let _ = sqlx::query("DELETE FROM team_stats WHERE season = ? AND id != team_id || '_' || season")
+        .bind(&season)
+        .execute(pool)
+        .await;
     tracing::info!("Season stats computed for all teams");
+    teams_written
+}
+
+/// Expected vs actual points for both sides of every finished match that has a prediction.
+async fn compute_match_xpts(pool: &SqlitePool) {
+    let matches = match get_finished_matches_ordered(pool).await {
+        Ok(m) => m,
+        Err(e) => { tracing::error!("Could not load finished matches for xPts: {}", e); return; }
+    };
+
+    for m in &matches {
+        let (Some(hs), Some(aws)) = (m.home_score, m.away_score) else { continue };
+        let Ok(Some(p)) = get_prediction_by_match_id(pool, &m.id).await else { continue };
+
+        let sides = [
+            (&m.home_team_id, p.home_win_probability, hs, aws),
+            (&m.away_team_id, p.away_win_probability, aws, hs),
+        ];
+        for (team_id, win_prob, scored, conceded) in sides {
+            let xpts = expected_points(win_prob, p.draw_probability, &m.sport);
+            let actual = actual_points(scored, conceded, &m.sport);
+            if let Err(e) = upsert_match_xpts(pool, &m.id, team_id, xpts, actual).await {
+                tracing::warn!("xPts upsert failed for {}: {}", m.id, e);
+            }
+        }
+    }
 }
 
 /// Last 5 results as a string like "WWDLW" (football) or "WWLLW" (basketball).
 async fn recent_form(pool: &SqlitePool, team_id: &str, is_football: bool) -> String {
-    let rows = sqlx::query(
-        r#"SELECT home_team_id, home_score, away_score
-           FROM matches
-           WHERE (home_team_id = ? OR away_team_id = ?) AND status = 'finished' AND home_score IS NOT NULL
-           ORDER BY match_date DESC LIMIT 5"#,
-    )
-    .bind(team_id)
-    .bind(team_id)
-    .fetch_all(pool)
+    let games = get_matches_for_team(pool, team_id, &MatchFilter {
+        status: Some("finished"),
+        scored_only: true,
+        limit: Some(5),
+        ..Default::default()
+    })
     .await
     .unwrap_or_default();
 
-    rows.iter().map(|row| {
-        let is_home = row.get::<String, _>("home_team_id") == team_id;
-        let hs: i32 = row.get("home_score");
-        let aws: i32 = row.get("away_score");
-        let (ts, os) = if is_home { (hs, aws) } else { (aws, hs) };
-        if ts > os { 'W' }
-        else if ts < os { 'L' }
-        else if is_football { 'D' }
-        else { 'L' }
+    games.iter().filter_map(|m| {
+        let side = m.result()?.for_side(m.home_team_id == team_id);
+        // Basketball has no draws: a level score without a recorded tiebreak isn't a win
+        Some(if side == "D" && !is_football { "L" } else { side })
     }).collect()
 }
 
-/// Generate / refresh predictions for all upcoming matches.
-async fn refresh_predictions(pool: &SqlitePool) {
+/// Generate / refresh predictions for all upcoming matches. Returns how many matches were
+/// predicted (0 on failure); unchanged predictions still count but are not stored again.
+async fn refresh_predictions(pool: &SqlitePool) -> usize {
     let engine = PredictionEngine::new();
     match get_upcoming_matches(pool, None).await {
         Ok(matches) if !matches.is_empty() => {
             if let Err(e) = engine.generate_predictions(pool, &matches).await {
                 tracing::error!("Prediction generation failed: {}", e);
+                0
             } else {
                 tracing::info!("Predictions refreshed for {} matches", matches.len());
+                matches.len()
             }
         }
-        Ok(_) => tracing::info!("No upcoming matches to predict"),
-        Err(e) => tracing::error!("Failed to fetch upcoming matches: {}", e),
+        Ok(_) => {
+            tracing::info!("No upcoming matches to predict");
+            0
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch upcoming matches: {}", e);
+            0
+        }
     }
 }
 
-fn create_router() -> Router<SqlitePool> {
+fn create_router() -> Router<AppState> {
     Router::new()
         .route("/health", get(health_check))
+        .route("/version", get(get_version_handler))
+        .route("/config", get(get_config_handler))
+        .route("/stats/overview", get(get_stats_overview_handler))
         .route("/matches/upcoming", get(get_upcoming_matches_handler))
+        .route("/matches/postponed", get(get_postponed_matches_handler))
+        .route("/matches/live", get(get_live_matches_handler))
+        .route("/matches/stale", get(get_stale_matches_handler))
+        .route("/matches/results.ndjson", get(get_match_results_ndjson_handler))
         .route("/teams", get(get_all_teams_handler))
+        .route("/teams/search", get(search_teams_handler))
         .route("/teams/league/{sport}/{league}", get(get_teams_by_league_handler))
         .route("/teams/{id}/stats", get(get_team_stats_handler))
+        .route("/teams/{id}/advanced", get(get_team_advanced_stats_handler))
+        .route("/teams/{id}/matches.csv", get(get_team_matches_csv_handler))
+        .route("/teams/{id}/elo/project", get(project_team_elo_handler))
+        .route("/teams/{id}/elo/vs-average", get(get_elo_vs_average_handler))
+        .route("/teams/{id}/accuracy", get(get_team_accuracy_handler))
+        .route("/teams/{id}/projection", get(get_team_projection_handler))
+        .route("/predictions", get(get_confident_predictions_handler))
         .route("/predictions/edges", get(get_prediction_edges_handler))
+        .route("/predictions/edges.csv", get(get_prediction_edges_csv_handler))
+        .route("/predictions/edges/debug", get(get_edge_diagnostics_handler))
+        .route("/predictions/divergence", get(get_market_divergence_handler))
+        .route("/predictions/compare", get(compare_models_handler))
+        .route("/odds/coverage", get(get_odds_coverage_handler))
+        .route("/leagues/{sport}/{league}/insights", get(get_league_insights_handler))
+        .route("/leagues/{sport}/{league}/standings", get(get_standings_handler))
+        .route("/leagues/{sport}/{league}/leaders", get(get_league_leaders_handler))
+        .route("/leagues/{sport}/{league}/form-table", get(get_form_table_handler))
         .route("/datasets/generate", post(generate_dataset_handler))
         .route("/data/fetch", post(fetch_data_handler))
         .route("/data/refresh", post(refresh_all_data_handler))
         .route("/predictions/generate", post(generate_predictions_handler))
+        .route("/predict/batch", post(predict_batch_handler))
+        .route("/predict/parlay", post(predict_parlay_handler))
+        .route("/matches/{id}", get(get_match_detail_handler))
         .route("/matches/{id}/analysis", get(get_match_analysis_handler))
+        .route("/matches/{id}/simulate", get(simulate_match_handler))
+        .route("/matches/{id}/totals", get(get_match_totals_handler))
+        .route("/matches/{id}/edge/timeline", get(get_edge_timeline_handler))
+        .route("/matches/{id}/prediction", get(get_match_prediction_handler))
         .route("/teams/{id}/players", get(get_team_players_handler))
         // ML endpoints
         .route("/models/train", post(trigger_train_handler))
         .route("/models/evaluate", get(get_model_evaluations_handler))
         .route("/matches/{id}/explain", get(explain_prediction_handler))
         .route("/predictions/{id}/distribution", get(get_score_distribution_handler))
+        .route("/predictions/{id}/explain", get(get_prediction_inputs_handler))
+        .route("/predictions/{id}/history", get(get_prediction_history_handler))
         .route("/matches/history", get(get_match_history_handler))
+        // Admin endpoints (ADMIN_TOKEN bearer auth)
+        .nest("/admin", admin_router())
         // Serve generated export files (CSV / JSON) from the exports directory
         .nest_service("/downloads", ServeDir::new("../data/exports"))
         .layer(
@@ -398,52 +575,306 @@ fn create_router() -> Router<SqlitePool> {
         )
 }
 
+fn admin_router() -> Router<AppState> {
+    Router::new()
+        .route("/reseed", post(reseed_handler))
+        .route("/recompute", post(recompute_handler))
+        .route("/carryover", post(carryover_handler))
+        .route("/teams/merge", post(merge_teams_handler))
+        .route("/duplicates", get(get_duplicate_matches_handler))
+        .route("/integrity", get(get_integrity_handler))
+        .route("/schema", get(get_schema_status_handler))
+        .route("/teams/{id}/home-advantage", put(set_home_advantage_handler))
+        .route_layer(middleware::from_fn(require_admin_token))
+}
+
+/// Admin routes require `Authorization: Bearer $ADMIN_TOKEN`; with no token configured
+/// they are disabled entirely rather than left open.
+async fn require_admin_token(request: Request, next: Next) -> Result<Response, StatusCode> {
+    let expected = std::env::var("ADMIN_TOKEN").ok().filter(|t| !t.is_empty())
+        .ok_or(StatusCode::FORBIDDEN)?;
+    let provided = request.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+
+    if provided != Some(expected.as_str()) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    Ok(next.run(request).await)
+}
+
 // Health check endpoint
 async fn health_check() -> Json<ApiResponse<&'static str>> {
     Json(ApiResponse::success("OddsForge API is running"))
 }
 
+// GET /version - Crate version, git commit, active model versions and schema version
+async fn get_version_handler(
+    State(pool): State<SqlitePool>,
+) -> Result<Json<ApiResponse<VersionInfo>>, StatusCode> {
+    match get_schema_version(&pool).await {
+        Ok(schema_version) => Ok(Json(ApiResponse::success(VersionInfo {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_commit: env!("GIT_COMMIT").to_string(),
+            football_model_version: FOOTBALL_MODEL_VERSION.to_string(),
+            basketball_model_version: active_model_version().await,
+            schema_version,
+        }))),
+        Err(e) => {
+            tracing::error!("Failed to read schema version: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// GET /config - Retraining settings and when the NBA ML model was last trained
+async fn get_config_handler(
+    State(pool): State<SqlitePool>,
+) -> Result<Json<ApiResponse<RuntimeConfig>>, StatusCode> {
+    let policy = RetrainPolicy::from_env();
+    match retrain_status(&pool).await {
+        Ok(status) => Ok(Json(ApiResponse::success(RuntimeConfig {
+            basketball_model_version: active_model_version().await,
+            retrain_after_results: policy.after_results,
+            retrain_every_hours: policy.every_hours,
+            last_trained_at: status.last_trained_at,
+            trained_model_version: status.version,
+            results_since_training: status.results_since_training,
+        }))),
+        Err(e) => {
+            tracing::error!("Failed to read training status: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// GET /stats/overview - Single-call summary for the landing dashboard
+async fn get_stats_overview_handler(
+    State(pool): State<SqlitePool>,
+) -> Result<Json<ApiResponse<StatsOverview>>, StatusCode> {
+    match compute_stats_overview(&pool).await {
+        Ok(overview) => Ok(Json(ApiResponse::success(overview))),
+        Err(e) => {
+            tracing::error!("Failed to build stats overview: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn compute_stats_overview(pool: &SqlitePool) -> anyhow::Result<StatsOverview> {
+    let sports = sqlx::query(
+        r#"SELECT t.sport,
+                  COUNT(*) AS teams,
+                  (SELECT COUNT(*) FROM matches m WHERE m.sport = t.sport) AS matches
+           FROM teams t GROUP BY t.sport ORDER BY t.sport"#,
+    )
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .map(|r| SportCounts { sport: r.get("sport"), teams: r.get("teams"), matches: r.get("matches") })
+    .collect();
+
+    let upcoming_next_7_days = count_upcoming(pool, None, Some(7)).await?;
+
+    let value_edges = PredictionEngine::new().find_market_edges(pool, false).await?.len();
+
+    // get_all_teams is ordered by sport, league, elo DESC — first of each league is the leader
+    let mut top_teams: Vec<Team> = Vec::new();
+    for team in get_all_teams(pool).await? {
+        if top_teams.last().is_none_or(|t| t.league != team.league || t.sport != team.sport) {
+            top_teams.push(team);
+        }
+    }
+
+    let last_data_refresh: Option<String> = sqlx::query_scalar("SELECT MAX(updated_at) FROM matches")
+        .fetch_one(pool)
+        .await?;
+
+    Ok(StatsOverview { sports, upcoming_next_7_days, value_edges, top_teams, last_data_refresh })
+}
+
 // GET /matches/upcoming - Get upcoming matches with predictions
 #[derive(Deserialize)]
 struct UpcomingMatchesQuery {
     sport: Option<String>,
     limit: Option<usize>,
+    /// Only matches kicking off within this many days; defaults to UPCOMING_WITHIN_DAYS, else unbounded
+    within_days: Option<i64>,
+    /// Skip the response cache (debugging)
+    #[serde(default)]
+    nocache: bool,
+    /// Only matches updated, or newly predicted, after this instant. Pass the previous
+    /// response's `timestamp` to poll incrementally.
+    updated_since: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Largest `limit` /matches/upcoming will honour (`MAX_UPCOMING_LIMIT`, default 100).
+fn max_upcoming_limit() -> usize {
+    std::env::var("MAX_UPCOMING_LIMIT").ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(100)
 }
 
 async fn get_upcoming_matches_handler(
     State(pool): State<SqlitePool>,
+    State(cache): State<ResponseCache>,
     Query(params): Query<UpcomingMatchesQuery>,
-) -> Result<Json<ApiResponse<Vec<UpcomingMatchWithPrediction>>>, StatusCode> {
-    match get_upcoming_matches(&pool, params.sport.as_deref()).await {
-        Ok(matches) => {
-            let mut matches_with_predictions = Vec::new();
-            let limit = params.limit.unwrap_or(50).min(100); // Cap at 100
-            
-            for match_data in matches.into_iter().take(limit) {
-                let prediction = get_prediction_by_match_id(&pool, &match_data.id).await.ok().flatten();
-                
-                matches_with_predictions.push(UpcomingMatchWithPrediction {
-                    match_info: match_data,
-                    prediction,
-                    home_team_stats: None, // TODO: Implement team stats fetching
-                    away_team_stats: None,
-                });
-            }
-            
-            Ok(Json(ApiResponse::success(matches_with_predictions)))
+) -> Result<Json<ApiResponse<Vec<UpcomingMatchWithPrediction>>>, ApiError> {
+    let within_days = params.within_days.or_else(|| {
+        std::env::var("UPCOMING_WITHIN_DAYS").ok().and_then(|v| v.parse().ok())
+    });
+    let limit = params.limit.unwrap_or(DEFAULT_UPCOMING_LIMIT).min(max_upcoming_limit());
+    let key = format!("upcoming:{:?}:{}:{:?}", params.sport, limit, within_days);
+    // Taken before querying so nothing written during this request is missed by the next poll
+    let polled_at = chrono::Utc::now();
+    // Incremental polls must see the database as of now, not as of the cached copy
+    let bypass = params.nocache || params.updated_since.is_some();
+
+    let loaded = cache.get_or_load(&key, bypass, || async {
+        let matches = get_upcoming_matches_within(&pool, params.sport.as_deref(), within_days, params.updated_since, limit).await?;
+        let engine = PredictionEngine::new();
+        let mut matches_with_predictions = Vec::new();
+
+        for match_data in matches {
+            let prediction = engine.fresh_prediction(&pool, &match_data).await.ok().flatten();
+
+            matches_with_predictions.push(UpcomingMatchWithPrediction {
+                match_info: match_data,
+                prediction,
+                home_team_stats: None, // TODO: Implement team stats fetching
+                away_team_stats: None,
+            });
         }
+        Ok(matches_with_predictions)
+    }).await;
+
+    match loaded {
+        Ok(matches_with_predictions) => Ok(Json(ApiResponse {
+            timestamp: polled_at,
+            ..ApiResponse::success(matches_with_predictions)
+        })),
+        Err(e) => Err(ApiError::internal("Failed to fetch upcoming matches", e)),
+    }
+}
+
+// GET /matches/postponed - Postponed and cancelled matches, kept out of /matches/upcoming
+#[derive(Deserialize)]
+struct SportQuery {
+    sport: Option<String>,
+}
+
+async fn get_postponed_matches_handler(
+    State(pool): State<SqlitePool>,
+    Query(params): Query<SportQuery>,
+) -> Result<Json<ApiResponse<Vec<Match>>>, StatusCode> {
+    match get_postponed_matches(&pool, params.sport.as_deref()).await {
+        Ok(matches) => Ok(Json(ApiResponse::success(matches))),
         Err(e) => {
-            tracing::error!("Failed to fetch upcoming matches: {}", e);
+            tracing::error!("Failed to fetch postponed matches: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// GET /matches/live - In-play matches with current score and in-play probabilities
+async fn get_live_matches_handler(
+    State(pool): State<SqlitePool>,
+    Query(params): Query<SportQuery>,
+) -> Result<Json<ApiResponse<Vec<LiveMatch>>>, StatusCode> {
+    match compute_live_matches(&pool, params.sport.as_deref()).await {
+        Ok(live) => Ok(Json(ApiResponse::success(live))),
+        Err(e) => {
+            tracing::error!("Failed to fetch live matches: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn compute_live_matches(pool: &SqlitePool, sport: Option<&str>) -> anyhow::Result<Vec<LiveMatch>> {
+    let now = chrono::Utc::now();
+    let mut live = Vec::new();
+    for m in get_live_matches(pool, sport).await? {
+        let prediction = get_prediction_by_match_id(pool, &m.id).await?;
+        let remaining = fraction_remaining(&m.sport, m.match_date, now);
+        let in_play = prediction.as_ref().map(|p| {
+            in_play_probabilities(p, m.home_score.unwrap_or(0), m.away_score.unwrap_or(0), remaining, &m.sport)
+        });
+        live.push(LiveMatch {
+            in_play_home_probability: in_play.map(|(h, _, _)| h),
+            in_play_away_probability: in_play.map(|(_, a, _)| a),
+            in_play_draw_probability: in_play.and_then(|(_, _, d)| d),
+            fraction_remaining: remaining,
+            prediction,
+            match_info: m,
+        });
+    }
+    Ok(live)
+}
+
+/// Hours past kickoff before a still-scheduled match counts as stale (STALE_MATCH_GRACE_HOURS).
+fn stale_grace_hours() -> i64 {
+    std::env::var("STALE_MATCH_GRACE_HOURS").ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(6)
+}
+
+// GET /matches/stale - Past-dated matches still marked scheduled (missing results)
+async fn get_stale_matches_handler(
+    State(pool): State<SqlitePool>,
+    Query(params): Query<SportQuery>,
+) -> Result<Json<ApiResponse<Vec<Match>>>, StatusCode> {
+    match get_stale_matches(&pool, params.sport.as_deref(), stale_grace_hours()).await {
+        Ok(matches) => Ok(Json(ApiResponse::success(matches))),
+        Err(e) => {
+            tracing::error!("Failed to fetch stale matches: {}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
 
+/// Re-fetch results for stale matches. EPL is re-fetched in full every tick, so only NBA
+/// needs a wider window than the usual 3 days; anything still stale afterwards is logged.
+async fn reconcile_stale_matches(pool: &SqlitePool, fetcher: &DataFetcher) {
+    let stale = match get_stale_matches(pool, None, stale_grace_hours()).await {
+        Ok(stale) => stale,
+        Err(e) => {
+            tracing::error!("Stale match check failed: {}", e);
+            return;
+        }
+    };
+
+    let oldest_nba = stale.iter().find(|m| m.sport == "basketball").map(|m| m.match_date);
+    if let (Some(oldest), true) = (oldest_nba, fetcher.will_fetch(FetchLeague::Nba)) {
+        let days = (chrono::Utc::now() - oldest).num_days() + 1;
+        if days > 3 {
+            // Cap the look-back so a very old orphan can't trigger a full-season page walk
+            if let Err(e) = fetcher.fetch_recent_nba_games(pool, days.min(30)).await {
+                tracing::warn!("Stale NBA re-fetch failed: {}", e);
+            }
+        }
+    }
+
+    let remaining = count_stale_matches(pool, None, stale_grace_hours()).await.unwrap_or(0);
+    if remaining > 0 {
+        tracing::warn!("{} scheduled matches are past kickoff with no result (see /matches/stale)", remaining);
+    }
+}
+
 // GET /teams - List all teams
+/// `?nocache=true` on a cached read endpoint skips the response cache (debugging).
+#[derive(Deserialize)]
+struct CacheQuery {
+    #[serde(default)]
+    nocache: bool,
+}
+
 async fn get_all_teams_handler(
     State(pool): State<SqlitePool>,
+    State(cache): State<ResponseCache>,
+    Query(params): Query<CacheQuery>,
 ) -> Result<Json<ApiResponse<Vec<Team>>>, StatusCode> {
-    match get_all_teams(&pool).await {
+    match cache.get_or_load("teams", params.nocache, || with_elo_tiers(&pool, None)).await {
         Ok(teams) => Ok(Json(ApiResponse::success(teams))),
         Err(e) => {
             tracing::error!("Failed to fetch teams: {}", e);
@@ -452,11 +883,45 @@ async fn get_all_teams_handler(
     }
 }
 
-// GET /teams/:id/stats - Get team analytics
+/// Fill elo_percentile / elo_tier relative to each team's league. `None` ranks every team.
+async fn with_elo_tiers(pool: &SqlitePool, teams: Option<Vec<Team>>) -> anyhow::Result<Vec<Team>> {
+    let all = get_all_teams(pool).await?;
+    let mut league_ratings: HashMap<(String, String), Vec<f64>> = HashMap::new();
+    for t in &all {
+        league_ratings.entry((t.sport.clone(), t.league.clone())).or_default().push(t.elo_rating);
+    }
+
+    let cutoffs = elo_tier_cutoffs();
+    let mut teams = teams.unwrap_or(all);
+    for team in &mut teams {
+        let ratings = league_ratings.get(&(team.sport.clone(), team.league.clone()));
+        let percentile = elo_percentile(team.elo_rating, ratings.map_or(&[][..], |r| r.as_slice()));
+        team.elo_percentile = Some(percentile);
+        team.elo_tier = Some(elo_tier(percentile, cutoffs).to_string());
+    }
+    Ok(teams)
+}
+
+// GET /teams/:id/stats?recent=8&elo_limit= - Get team analytics
+const DEFAULT_RECENT_MATCHES: usize = 8;
+const MAX_RECENT_MATCHES: usize = 100;
+const MAX_ELO_HISTORY_POINTS: usize = 1_000;
+
+#[derive(Deserialize)]
+struct TeamStatsQuery {
+    /// Recent finished matches to include (capped at MAX_RECENT_MATCHES)
+    recent: Option<usize>,
+    /// Newest ELO history points to include (capped at MAX_ELO_HISTORY_POINTS)
+    elo_limit: Option<usize>,
+}
+
 async fn get_team_stats_handler(
     State(pool): State<SqlitePool>,
     Path(team_id): Path<String>,
-) -> Result<Json<ApiResponse<TeamProfile>>, StatusCode> {
+    Query(params): Query<TeamStatsQuery>,
+) -> Result<Json<ApiResponse<TeamProfile>>, ApiError> {
+    let recent = params.recent.unwrap_or(DEFAULT_RECENT_MATCHES).min(MAX_RECENT_MATCHES);
+    let elo_limit = params.elo_limit.unwrap_or(MAX_ELO_HISTORY_POINTS).min(MAX_ELO_HISTORY_POINTS);
     match get_team_by_id(&pool, &team_id).await {
         Ok(Some(team)) => {
             let current_stats = get_team_current_stats(&pool, &team_id)
@@ -466,7 +931,7 @@ async fn get_team_stats_handler(
                 .unwrap_or_else(|| crate::models::TeamStats {
                     id: uuid::Uuid::new_v4().to_string(),
                     team_id: team_id.clone(),
-                    season: "2025-26".to_string(),
+                    season: season_of(chrono::Utc::now()),
                     matches_played: 0,
                     wins: 0,
                     draws: Some(0),
@@ -476,17 +941,23 @@ async fn get_team_stats_handler(
                     points_for: Some(0),
                     points_against: Some(0),
                     form: String::new(),
+                    xpts: None,
                     updated_at: chrono::Utc::now(),
                 });
 
-            let recent_matches = get_team_recent_matches(&pool, &team_id, 8)
+            let recent_matches = get_team_recent_matches(&pool, &team_id, recent as i64)
                 .await
                 .unwrap_or_default();
 
-            let elo_history = get_elo_history(&pool, &team_id)
+            let elo_history = get_recent_elo_history(&pool, &team_id, elo_limit as i64)
                 .await
                 .unwrap_or_default();
 
+            let team = with_elo_tiers(&pool, Some(vec![team.clone()])).await
+                .ok()
+                .and_then(|mut ranked| ranked.pop())
+                .unwrap_or(team);
+
             let profile = TeamProfile {
                 team,
                 current_stats,
@@ -496,14 +967,103 @@ async fn get_team_stats_handler(
 
             Ok(Json(ApiResponse::success(profile)))
         }
+        Ok(None) => Err(ApiError::not_found(format!("Team {} not found", team_id))),
+        Err(e) => Err(ApiError::internal("Failed to fetch team stats", e)),
+    }
+}
+
+// GET /teams/:id/advanced - Opponent-adjusted scoring and expected vs actual points
+async fn get_team_advanced_stats_handler(
+    State(pool): State<SqlitePool>,
+    Path(team_id): Path<String>,
+) -> Result<Json<ApiResponse<AdvancedTeamStats>>, StatusCode> {
+    match compute_team_advanced_stats(&pool, &team_id).await {
+        Ok(Some(stats)) => Ok(Json(ApiResponse::success(stats))),
         Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(e) => {
-            tracing::error!("Failed to fetch team stats: {}", e);
+            tracing::error!("Failed to compute advanced stats for {}: {}", team_id, e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
 
+/// Rating `history` (oldest first) held going into `m`: the point before the one `m`
+/// produced, else the latest before kickoff, else the 1200 replay start.
+fn pre_match_rating(history: &[crate::models::EloHistoryPoint], m: &Match) -> f64 {
+    let before = match history.iter().position(|p| p.match_id.as_deref() == Some(m.id.as_str())) {
+        Some(i) => i,
+        None => history.partition_point(|p| p.date < m.match_date),
+    };
+    before.checked_sub(1).map_or(1200.0, |i| history[i].elo_rating)
+}
+
+/// None when the team doesn't exist; zeroed fields when it has no finished matches.
+async fn compute_team_advanced_stats(pool: &SqlitePool, team_id: &str) -> anyhow::Result<Option<AdvancedTeamStats>> {
+    let Some(team) = get_team_by_id(pool, team_id).await? else {
+        return Ok(None);
+    };
+    let matches = get_matches_for_team(pool, team_id, &MatchFilter {
+        status: Some("finished"),
+        scored_only: true,
+        ..Default::default()
+    })
+    .await?;
+    let league = get_teams_by_league(pool, &team.sport, &team.league).await?;
+    let league_average_elo = league.iter().map(|t| t.elo_rating).sum::<f64>() / league.len().max(1) as f64;
+
+    let own_history = get_elo_history(pool, team_id).await?;
+    let mut histories: HashMap<String, Vec<crate::models::EloHistoryPoint>> = HashMap::new();
+    let calc = EloCalculator::new();
+    let (mut opponent_elo, mut scored, mut conceded, mut adj_scored, mut adj_conceded) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    let (mut expected, mut actual) = (0.0, 0.0);
+
+    for m in &matches {
+        let (Some(hs), Some(aws)) = (m.home_score, m.away_score) else { continue };
+        let is_home = m.home_team_id == team_id;
+        let opponent_id = if is_home { &m.away_team_id } else { &m.home_team_id };
+        if !histories.contains_key(opponent_id) {
+            histories.insert(opponent_id.clone(), get_elo_history(pool, opponent_id).await?);
+        }
+        let own_rating = pre_match_rating(&own_history, m);
+        let opp_rating = pre_match_rating(&histories[opponent_id], m);
+        let (own, other) = if is_home { (hs, aws) } else { (aws, hs) };
+
+        opponent_elo += opp_rating;
+        scored += own as f64;
+        conceded += other as f64;
+        adj_scored += own as f64 * opp_rating / league_average_elo;
+        adj_conceded += other as f64 * league_average_elo / opp_rating;
+
+        let (win, draw) = match get_prediction_by_match_id(pool, &m.id).await? {
+            Some(p) => (if is_home { p.home_win_probability } else { p.away_win_probability }, p.draw_probability),
+            None => {
+                let (home_rating, away_rating) = if is_home { (own_rating, opp_rating) } else { (opp_rating, own_rating) };
+                let (h, a, d) = calc.win_probability(home_rating, away_rating, &m.sport, &m.league);
+                (if is_home { h } else { a }, d)
+            }
+        };
+        expected += expected_points(win, draw, &m.sport);
+        actual += actual_points(own, other, &m.sport);
+    }
+
+    let n = matches.len();
+    let per_game = |total: f64| if n == 0 { 0.0 } else { total / n as f64 };
+    Ok(Some(AdvancedTeamStats {
+        team_id: team.id,
+        team_name: team.name,
+        matches_played: n,
+        avg_opponent_elo: per_game(opponent_elo),
+        league_average_elo,
+        goals_for_per_game: per_game(scored),
+        goals_against_per_game: per_game(conceded),
+        adjusted_goals_for_per_game: per_game(adj_scored),
+        adjusted_goals_against_per_game: per_game(adj_conceded),
+        expected_points: expected,
+        actual_points: actual,
+        points_delta: actual - expected,
+    }))
+}
+
 // GET /teams/:id/players - NBA player roster with season averages
 async fn get_team_players_handler(
     State(pool): State<SqlitePool>,
@@ -525,7 +1085,13 @@ async fn get_teams_by_league_handler(
     Path((sport, league)): Path<(String, String)>,
 ) -> Result<Json<ApiResponse<Vec<Team>>>, StatusCode> {
     match get_teams_by_league(&pool, &sport, &league).await {
-        Ok(teams) => Ok(Json(ApiResponse::success(teams))),
+        Ok(teams) => match with_elo_tiers(&pool, Some(teams)).await {
+            Ok(teams) => Ok(Json(ApiResponse::success(teams))),
+            Err(e) => {
+                tracing::error!("Failed to rank teams: {}", e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        },
         Err(e) => {
             tracing::error!("Failed to fetch teams by league: {}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
@@ -533,697 +1099,3987 @@ async fn get_teams_by_league_handler(
     }
 }
 
-// GET /predictions/edges - Get market edge opportunities
-async fn get_prediction_edges_handler(
+// GET /teams/search?name=&sport=&league= - Find teams by name
+#[derive(Deserialize)]
+struct TeamSearchQuery {
+    name: String,
+    sport: Option<String>,
+    league: Option<String>,
+}
+
+/// Every team whose name contains `name`, each carrying its sport and league so a club
+/// entered in several competitions can be told apart; `sport` / `league` narrow the list.
+async fn search_teams_handler(
     State(pool): State<SqlitePool>,
-) -> Result<Json<ApiResponse<Vec<crate::models::Edge>>>, StatusCode> {
-    let prediction_engine = PredictionEngine::new();
-    
-    match prediction_engine.find_market_edges(&pool).await {
-        Ok(edges) => Ok(Json(ApiResponse::success(edges))),
+    Query(params): Query<TeamSearchQuery>,
+) -> Result<Json<ApiResponse<Vec<Team>>>, StatusCode> {
+    let name = params.name.trim();
+    if name.is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let teams = find_teams_by_name(&pool, name, params.sport.as_deref(), params.league.as_deref()).await;
+    match teams {
+        Ok(teams) => match with_elo_tiers(&pool, Some(teams)).await {
+            Ok(teams) => Ok(Json(ApiResponse::success(teams))),
+            Err(e) => {
+                tracing::error!("Failed to rank teams: {}", e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        },
         Err(e) => {
-            tracing::error!("Failed to find market edges: {}", e);
+            tracing::error!("Failed to search teams: {}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
 
-// POST /datasets/generate - Generate custom dataset
-#[derive(Serialize)]
-struct DatasetResponse {
-    download_url: String,
-    format: String,
-    rows: usize,
-    generated_at: chrono::DateTime<chrono::Utc>,
+// GET /predictions/edges?per_outcome= - Get market edge opportunities
+#[derive(Deserialize)]
+struct EdgesQuery {
+    /// List every outcome above the threshold instead of only each match's best side
+    #[serde(default)]
+    per_outcome: bool,
 }
 
-async fn generate_dataset_handler(
+async fn get_prediction_edges_handler(
     State(pool): State<SqlitePool>,
-    Json(request): Json<DatasetRequest>,
-) -> Result<Json<ApiResponse<DatasetResponse>>, StatusCode> {
-    match generate_custom_dataset(&pool, request).await {
-        Ok(response) => Ok(Json(ApiResponse::success(response))),
+    Query(params): Query<EdgesQuery>,
+) -> Result<Json<ApiResponse<EdgeReport>>, StatusCode> {
+    match PredictionEngine::new().edge_report(&pool, params.per_outcome).await {
+        Ok(report) => Ok(Json(ApiResponse::success(report))),
         Err(e) => {
-            tracing::error!("Failed to generate dataset: {}", e);
+            tracing::error!("Failed to find market edges: {}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
 
-// POST /data/fetch - Fetch sports data from APIs
-#[derive(Deserialize)]
-struct FetchDataRequest {
-    sport: Option<String>,
-    force_refresh: Option<bool>,
-}
-
-async fn fetch_data_handler(
+// GET /predictions/edges.csv?per_outcome= - Current edges as a spreadsheet-ready CSV
+async fn get_prediction_edges_csv_handler(
     State(pool): State<SqlitePool>,
-    Json(request): Json<FetchDataRequest>,
-) -> Result<Json<ApiResponse<String>>, StatusCode> {
-    let fetcher = DataFetcher::new();
-    
-    let result: anyhow::Result<&str> = match request.sport.as_deref() {
-        Some("football") => {
-            fetcher.fetch_epl_teams(&pool).await
-                .and(fetcher.fetch_epl_matches(&pool).await)
-                .map(|_| "Football data fetched successfully")
-        }
-        Some("basketball") => {
-            fetcher.fetch_nba_teams(&pool).await
-                .and(fetcher.fetch_nba_games(&pool).await)
-                .map(|_| "Basketball data fetched successfully")
-        }
-        _ => fetcher.fetch_all_data(&pool).await.map(|_| "All sports data fetched successfully"),
+    Query(params): Query<EdgesQuery>,
+) -> Result<([(header::HeaderName, &'static str); 2], String), StatusCode> {
+    let csv = match PredictionEngine::new().find_market_edges(&pool, params.per_outcome).await {
+        Ok(edges) => edges_to_csv(&edges),
+        Err(e) => Err(e),
     };
-
-    match result {
-        Ok(message) => Ok(Json(ApiResponse::success(message.to_string()))),
+    match csv {
+        Ok(body) => Ok((
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+                (header::CONTENT_DISPOSITION, "attachment; filename=\"edges.csv\""),
+            ],
+            body,
+        )),
         Err(e) => {
-            tracing::error!("Failed to fetch data: {}", e);
+            tracing::error!("Failed to export market edges: {}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
 
-// POST /data/refresh - Wipe and re-fetch all real data, then rebuild ELO + predictions
-async fn refresh_all_data_handler(
-    State(pool): State<SqlitePool>,
-) -> Result<Json<ApiResponse<String>>, StatusCode> {
-    tracing::info!("Manual /data/refresh triggered");
-
-    let fetcher = DataFetcher::new();
-    if !fetcher.has_football_key() && !fetcher.has_nba_key() {
-        return Ok(Json(ApiResponse::success(
-            "No API keys configured — set FOOTBALL_DATA_API_KEY and/or BALLDONTLIE_API_KEY".to_string()
-        )));
+/// One row per edge, on the side the edge is on. EV is per unit staked; the Kelly
+/// stake is a fraction of bankroll (capped at 25%).
+fn edges_to_csv(edges: &[crate::models::Edge]) -> anyhow::Result<String> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer.write_record([
+        "match_id", "home_team", "away_team", "match_date", "side", "our_probability",
+        "market_odds", "edge_pct", "expected_value", "kelly_stake", "bookmaker", "odds_fetched_at",
+    ])?;
+    for edge in edges {
+        let p = &edge.our_prediction;
+        let (prob, odds) = match edge.side.as_str() {
+            "away" => (p.away_win_probability, edge.market_away_odds),
+            "draw" => (p.draw_probability.unwrap_or(0.0), edge.market_draw_odds.unwrap_or(0.0)),
+            _ => (p.home_win_probability, edge.market_home_odds),
+        };
+        writer.write_record([
+            edge.match_id.clone(),
+            edge.match_info.home_team_name.clone(),
+            edge.match_info.away_team_name.clone(),
+            edge.match_info.match_date.to_rfc3339(),
+            edge.side.clone(),
+            format!("{:.4}", prob),
+            format!("{:.2}", odds),
+            format!("{:.2}", edge.edge_value * 100.0),
+            format!("{:.4}", prob * odds - 1.0),
+            format!("{:.4}", kelly_criterion(prob, odds)),
+            edge.bookmaker.clone().unwrap_or_default(),
+            edge.odds_fetched_at.clone().unwrap_or_default(),
+        ])?;
     }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
 
-    if let Err(e) = clear_all_data(&pool).await {
-        tracing::error!("Clear failed: {}", e);
-        return Err(StatusCode::INTERNAL_SERVER_ERROR);
-    }
+// GET /teams/:id/matches.csv?from=&to= - One team's home and away matches with pre-match probabilities
+#[derive(Deserialize)]
+struct DateRangeQuery {
+    /// Inclusive, YYYY-MM-DD
+    from: Option<chrono::NaiveDate>,
+    /// Inclusive, YYYY-MM-DD
+    to: Option<chrono::NaiveDate>,
+}
 
-    if let Err(e) = fetcher.fetch_all_data(&pool).await {
-        tracing::error!("Fetch failed: {}", e);
-        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+async fn get_team_matches_csv_handler(
+    State(pool): State<SqlitePool>,
+    Path(team_id): Path<String>,
+    Query(range): Query<DateRangeQuery>,
+) -> Result<([(header::HeaderName, String); 2], String), StatusCode> {
+    match team_matches_csv(&pool, &team_id, &range).await {
+        Ok(Some(body)) => Ok((
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}_matches.csv\"", team_id)),
+            ],
+            body,
+        )),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to export matches for {}: {}", team_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
     }
+}
 
-    rebuild_elo(&pool).await;
-    compute_season_stats(&pool).await;
-    refresh_predictions(&pool).await;
+// GET /matches/results.ndjson?from=&to=&sport= - Finished matches, one JSON object per line, streamed
+#[derive(Deserialize)]
+struct ResultsExportQuery {
+    /// Inclusive, YYYY-MM-DD
+    from: Option<chrono::NaiveDate>,
+    /// Inclusive, YYYY-MM-DD
+    to: Option<chrono::NaiveDate>,
+    sport: Option<String>,
+}
+
+async fn get_match_results_ndjson_handler(
+    State(pool): State<SqlitePool>,
+    Query(params): Query<ResultsExportQuery>,
+) -> Response {
+    let start_of = |d: chrono::NaiveDate| d.and_hms_opt(0, 0, 0).map(|t| t.and_utc());
+    let after = params.from.and_then(start_of);
+    let before = params.to.and_then(|d| d.succ_opt()).and_then(start_of);
 
-    let team_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM teams")
-        .fetch_one(&pool).await.unwrap_or(0);
-    let match_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM matches")
-        .fetch_one(&pool).await.unwrap_or(0);
+    // Rows go through a small bounded channel, so a slow client holds back the query
+    // instead of the server buffering the export
+    let (tx, rx) = tokio::sync::mpsc::channel::<std::io::Result<String>>(64);
+    tokio::spawn(async move {
+        let mut rows = stream_finished_matches(&pool, params.sport.as_deref(), after, before);
+        while let Some(row) = rows.next().await {
+            let line = row.map_err(std::io::Error::other)
+                .and_then(|m| serde_json::to_string(&m).map_err(std::io::Error::other))
+                .map(|json| json + "\n");
+            if let Err(e) = &line {
+                tracing::error!("NDJSON results export failed: {}", e);
+            }
+            let failed = line.is_err();
+            // A closed channel means the client went away
+            if tx.send(line).await.is_err() || failed {
+                break;
+            }
+        }
+    });
 
-    Ok(Json(ApiResponse::success(format!(
-        "Refreshed: {} teams, {} matches", team_count, match_count
-    ))))
+    let lines = stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|line| (line, rx)) });
+    ([(header::CONTENT_TYPE, "application/x-ndjson")], Body::from_stream(lines)).into_response()
 }
 
-// POST /predictions/generate - Generate predictions for upcoming matches
-async fn generate_predictions_handler(
+/// Oldest first, from the team's point of view. None when the team doesn't exist.
+async fn team_matches_csv(pool: &SqlitePool, team_id: &str, range: &DateRangeQuery) -> anyhow::Result<Option<String>> {
+    if get_team_by_id(pool, team_id).await?.is_none() {
+        return Ok(None);
+    }
+    let start_of = |d: chrono::NaiveDate| d.and_hms_opt(0, 0, 0).map(|t| t.and_utc());
+    let matches = get_matches_for_team(pool, team_id, &MatchFilter {
+        after: range.from.and_then(start_of),
+        before: range.to.and_then(|d| d.succ_opt()).and_then(start_of),
+        oldest_first: true,
+        ..Default::default()
+    })
+    .await?;
+
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer.write_record([
+        "match_id", "match_date", "venue", "opponent", "status", "team_score", "opponent_score",
+        "result", "win_probability", "draw_probability", "model_version",
+    ])?;
+    for m in matches {
+        let is_home = m.home_team_id == team_id;
+        let (own, other) = if is_home { (m.home_score, m.away_score) } else { (m.away_score, m.home_score) };
+        let result = m.result().map_or("", |r| r.for_side(is_home));
+        let prediction = get_prediction_by_match_id(pool, &m.id).await?;
+        let win_probability = prediction.as_ref()
+            .map(|p| if is_home { p.home_win_probability } else { p.away_win_probability });
+        let opt = |v: Option<String>| v.unwrap_or_default();
+        writer.write_record([
+            m.id.clone(),
+            m.match_date.to_rfc3339(),
+            (if is_home { "home" } else { "away" }).to_string(),
+            if is_home { m.away_team_name.clone() } else { m.home_team_name.clone() },
+            m.status.clone(),
+            opt(own.map(|v| v.to_string())),
+            opt(other.map(|v| v.to_string())),
+            result.to_string(),
+            opt(win_probability.map(|p| format!("{:.4}", p))),
+            opt(prediction.as_ref().and_then(|p| p.draw_probability).map(|p| format!("{:.4}", p))),
+            opt(prediction.map(|p| p.model_version)),
+        ])?;
+    }
+    Ok(Some(String::from_utf8(writer.into_inner()?)?))
+}
+
+// GET /predictions/edges/debug - Per-match reasons behind the edges list
+async fn get_edge_diagnostics_handler(
     State(pool): State<SqlitePool>,
-) -> Result<Json<ApiResponse<String>>, StatusCode> {
-    let prediction_engine = PredictionEngine::new();
-    
-    match get_upcoming_matches(&pool, None).await {
-        Ok(matches) => {
-            match prediction_engine.generate_predictions(&pool, &matches).await {
-                Ok(()) => Ok(Json(ApiResponse::success(format!("Generated predictions for {} matches", matches.len())))),
-                Err(e) => {
-                    tracing::error!("Failed to generate predictions: {}", e);
-                    Err(StatusCode::INTERNAL_SERVER_ERROR)
-                }
-            }
+) -> Result<Json<ApiResponse<Vec<EdgeDiagnostic>>>, StatusCode> {
+    match PredictionEngine::new().diagnose_market_edges(&pool).await {
+        Ok(diagnostics) => Ok(Json(ApiResponse::success(diagnostics))),
+        Err(e) => {
+            tracing::error!("Failed to diagnose market edges: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
+    }
+}
+
+// GET /odds/coverage?sport= - Which upcoming matches have market odds
+async fn get_odds_coverage_handler(
+    State(pool): State<SqlitePool>,
+    Query(params): Query<SportQuery>,
+) -> Result<Json<ApiResponse<Vec<OddsCoverage>>>, ApiError> {
+    match compute_odds_coverage(&pool, params.sport.as_deref()).await {
+        Ok(coverage) => Ok(Json(ApiResponse::success(coverage))),
+        Err(e) => Err(ApiError::internal("Failed to check odds coverage", e)),
+    }
+}
+
+async fn compute_odds_coverage(pool: &SqlitePool, sport: Option<&str>) -> anyhow::Result<Vec<OddsCoverage>> {
+    let now = chrono::Utc::now();
+    let mut coverage = Vec::new();
+    for m in get_upcoming_matches(pool, sport).await? {
+        let odds = get_market_odds(pool, &m.id).await?;
+        let odds_age_minutes = odds.as_ref()
+            .and_then(|o| chrono::DateTime::parse_from_rfc3339(&o.fetched_at).ok())
+            .map(|fetched| (now - fetched.with_timezone(&chrono::Utc)).num_minutes());
+        coverage.push(OddsCoverage {
+            match_id: m.id,
+            home_team_name: m.home_team_name,
+            away_team_name: m.away_team_name,
+            sport: m.sport,
+            league: m.league,
+            match_date: m.match_date,
+            covered: odds.is_some(),
+            bookmaker: odds.as_ref().map(|o| o.bookmaker.clone()),
+            odds_fetched_at: odds.map(|o| o.fetched_at),
+            odds_age_minutes,
+        });
+    }
+    Ok(coverage)
+}
+
+// GET /predictions?min_confidence=0.7&sport= - Upcoming picks at or above a confidence threshold
+#[derive(Deserialize)]
+struct ConfidenceQuery {
+    /// 0–1; defaults to 0 (every upcoming prediction)
+    min_confidence: Option<f64>,
+    sport: Option<String>,
+}
+
+async fn get_confident_predictions_handler(
+    State(pool): State<SqlitePool>,
+    Query(params): Query<ConfidenceQuery>,
+) -> Result<Json<ApiResponse<Vec<MatchPick>>>, StatusCode> {
+    let min_confidence = params.min_confidence.unwrap_or(0.0);
+    if !(0.0..=1.0).contains(&min_confidence) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    match get_confident_predictions(&pool, min_confidence, params.sport.as_deref()).await {
+        Ok(picks) => Ok(Json(ApiResponse::success(picks))),
         Err(e) => {
-            tracing::error!("Failed to fetch matches for prediction: {}", e);
+            tracing::error!("Failed to fetch predictions above {}: {}", min_confidence, e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
 
-// GET /matches/:id/analysis — per-component prediction breakdown
-async fn get_match_analysis_handler(
+// GET /predictions/divergence - All matches with odds, ranked by model-vs-market disagreement
+async fn get_market_divergence_handler(
     State(pool): State<SqlitePool>,
-    Path(match_id): Path<String>,
-) -> Result<Json<ApiResponse<MatchAnalysis>>, StatusCode> {
-    match compute_match_analysis(&pool, &match_id).await {
-        Ok(Some(a)) => Ok(Json(ApiResponse::success(a))),
-        Ok(None)    => Err(StatusCode::NOT_FOUND),
+) -> Result<Json<ApiResponse<Vec<MarketDivergence>>>, StatusCode> {
+    match PredictionEngine::new().find_market_divergence(&pool).await {
+        Ok(ranked) => Ok(Json(ApiResponse::success(ranked))),
         Err(e) => {
-            tracing::error!("Match analysis failed: {}", e);
+            tracing::error!("Failed to rank market divergence: {}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
 
-async fn compute_match_analysis(pool: &SqlitePool, match_id: &str) -> anyhow::Result<Option<MatchAnalysis>> {
-    use sqlx::Row;
+// GET /predictions/compare?a=&b= - Where two registry model configurations disagree on upcoming matches
+#[derive(Deserialize)]
+struct CompareQuery {
+    a: String,
+    b: String,
+}
 
-    let row = sqlx::query("SELECT * FROM matches WHERE id = ?")
-        .bind(match_id).fetch_optional(pool).await?;
-    let r = match row { Some(r) => r, None => return Ok(None) };
+async fn compare_models_handler(
+    State(pool): State<SqlitePool>,
+    Query(params): Query<CompareQuery>,
+) -> Result<Json<ApiResponse<Vec<ModelComparison>>>, StatusCode> {
+    let (Some(a), Some(b)) = (find_model_config(&params.a), find_model_config(&params.b)) else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+    match compare_models(&pool, &a, &b).await {
+        Ok(rows) => Ok(Json(ApiResponse::success(rows))),
+        Err(e) => {
+            tracing::error!("Failed to compare models: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
 
-    let home_id:   String = r.try_get("home_team_id")?;
-    let away_id:   String = r.try_get("away_team_id")?;
-    let home_name: String = r.try_get("home_team_name")?;
-    let away_name: String = r.try_get("away_team_name")?;
-    let sport:     String = r.try_get("sport")?;
-    let date_str:  String = r.try_get("match_date")?;
-    let match_date = chrono::DateTime::parse_from_rfc3339(&date_str)?.with_timezone(&chrono::Utc);
+/// Upcoming football matches both configurations have predicted, biggest disagreement first.
+async fn compare_models(pool: &SqlitePool, a: &ModelConfig, b: &ModelConfig) -> anyhow::Result<Vec<ModelComparison>> {
+    let mut rows = Vec::new();
+    for m in get_upcoming_matches(pool, Some("football")).await? {
+        let (Some(pa), Some(pb)) = (
+            get_prediction_for_version(pool, &m.id, &a.model_version()).await?,
+            get_prediction_for_version(pool, &m.id, &b.model_version()).await?,
+        ) else {
+            continue;
+        };
+        let max_divergence = [
+            (pa.home_win_probability - pb.home_win_probability).abs(),
+            (pa.away_win_probability - pb.away_win_probability).abs(),
+            (pa.draw_probability.unwrap_or(0.0) - pb.draw_probability.unwrap_or(0.0)).abs(),
+        ]
+        .into_iter()
+        .fold(0.0, f64::max);
+        rows.push(ModelComparison {
+            match_id: m.id,
+            home_team_name: m.home_team_name,
+            away_team_name: m.away_team_name,
+            match_date: m.match_date,
+            a: pa,
+            b: pb,
+            max_divergence,
+        });
+    }
+    rows.sort_by(|x, y| y.max_divergence.total_cmp(&x.max_divergence));
+    Ok(rows)
+}
 
-    // ── ELO ──────────────────────────────────────────────────────────────────
-    let home_elo: f64 = sqlx::query_scalar("SELECT elo_rating FROM teams WHERE id = ?")
-        .bind(&home_id).fetch_optional(pool).await?.unwrap_or(1200.0);
-    let away_elo: f64 = sqlx::query_scalar("SELECT elo_rating FROM teams WHERE id = ?")
-        .bind(&away_id).fetch_optional(pool).await?.unwrap_or(1200.0);
+// GET /leagues/:sport/:league/insights - League-wide prediction summary
+async fn get_league_insights_handler(
+    State(pool): State<SqlitePool>,
+    Path((sport, league)): Path<(String, String)>,
+) -> Result<Json<ApiResponse<LeagueInsights>>, StatusCode> {
+    match compute_league_insights(&pool, &sport, &league).await {
+        Ok(insights) => Ok(Json(ApiResponse::success(insights))),
+        Err(e) => {
+            tracing::error!("Failed to compute league insights: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
 
-    let hca = if sport == "basketball" { 75.0 } else { 100.0 };
-    let elo_diff = home_elo - away_elo;
-    let elo_home_prob = 1.0 / (1.0 + 10f64.powf((away_elo - (home_elo + hca)) / 400.0));
-    let elo_narrative = if elo_diff > 0.0 {
-        format!("{} carries a {:.0}-point ELO edge built from this season's results", home_name, elo_diff)
-    } else {
-        format!("{} is the stronger team by ELO, arriving with a {:.0}-point advantage", away_name, elo_diff.abs())
-    };
+// GET /leagues/:sport/:league/standings - League table with xPts comparison
+async fn get_standings_handler(
+    State(pool): State<SqlitePool>,
+    State(cache): State<ResponseCache>,
+    Path((sport, league)): Path<(String, String)>,
+    Query(params): Query<CacheQuery>,
+) -> Result<Json<ApiResponse<Vec<StandingsRow>>>, StatusCode> {
+    let key = format!("standings:{}:{}", sport, league);
+    match cache.get_or_load(&key, params.nocache, || get_standings(&pool, &sport, &league)).await {
+        Ok(table) => Ok(Json(ApiResponse::success(table))),
+        Err(e) => {
+            tracing::error!("Failed to build standings: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
 
-    // ── Form ─────────────────────────────────────────────────────────────────
-    struct FormResult { avg: f64, n: i64 }
+// GET /leagues/:sport/:league/leaders?metric=attack|defense|form|elo - Ranked teams by one metric
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum LeaderMetric {
+    /// Goals (football) or points (basketball) scored per game, highest first
+    Attack,
+    /// Conceded per game, lowest first
+    Defense,
+    /// Weighted recent points rate, highest first
+    Form,
+    Elo,
+}
 
-    async fn rolling_margin(pool: &SqlitePool, team_id: &str, is_home: bool, sport: &str) -> FormResult {
-        let (col, opp_col, filter_col) = if is_home {
-            ("home_score", "away_score", "home_team_id")
-        } else {
-            ("away_score", "home_score", "away_team_id")
-        };
-        let sql = format!(
-            "SELECT {col} - {opp_col} as margin FROM matches
-             WHERE {filter_col} = ? AND status = 'finished'
-               AND {col} IS NOT NULL AND sport = ?
-             ORDER BY match_date DESC LIMIT 15",
-        );
-        let rows = sqlx::query(&sql).bind(team_id).bind(sport).fetch_all(pool).await.unwrap_or_default();
-        let n = rows.len() as i64;
-        if n == 0 { return FormResult { avg: 0.0, n: 0 }; }
-        let sum: f64 = rows.iter().enumerate().map(|(i, row)| {
-            let margin: i32 = row.try_get("margin").unwrap_or(0);
-            let decay = 0.90_f64.powi(i as i32);
-            margin as f64 * decay
-        }).sum();
-        let weight: f64 = (0..n).map(|i| 0.90_f64.powi(i as i32)).sum();
-        FormResult { avg: sum / weight, n }
+#[derive(Deserialize)]
+struct LeadersQuery {
+    metric: LeaderMetric,
+}
+
+async fn get_league_leaders_handler(
+    State(pool): State<SqlitePool>,
+    Path((sport, league)): Path<(String, String)>,
+    Query(params): Query<LeadersQuery>,
+) -> Result<Json<ApiResponse<Vec<LeaderboardRow>>>, StatusCode> {
+    match compute_league_leaders(&pool, &sport, &league, params.metric).await {
+        Ok(rows) => Ok(Json(ApiResponse::success(rows))),
+        Err(e) => {
+            tracing::error!("Failed to build leaderboard: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
     }
+}
 
-    let home_form = rolling_margin(pool, &home_id, true,  &sport).await;
-    let away_form = rolling_margin(pool, &away_id, false, &sport).await;
+async fn compute_league_leaders(
+    pool: &SqlitePool,
+    sport: &str,
+    league: &str,
+    metric: LeaderMetric,
+) -> anyhow::Result<Vec<LeaderboardRow>> {
+    let engine = PredictionEngine::new();
+    let mut rows = Vec::new();
 
-    let hca_pts = if sport == "basketball" { 3.0_f64 } else { 0.0_f64 };
-    let coeff   = if sport == "basketball" { 0.10_f64 } else { 2.5_f64 };
-    let form_diff = home_form.avg - away_form.avg + hca_pts;
-    let form_home_prob = 1.0 / (1.0 + (-form_diff * coeff).exp());
+    for team in get_teams_by_league(pool, sport, league).await? {
+        let stats = get_team_current_stats(pool, &team.id).await?;
+        let played = stats.as_ref().map_or(0, |s| s.matches_played);
+        let per_game = |total: Option<i32>| total.map(|t| t as f64 / played as f64);
 
-    let form_narrative = if home_form.n > 0 && away_form.n > 0 {
-        format!(
-            "{} averages {:+.1} pts/gm at home (last {}). {} averages {:+.1} pts/gm away (last {}).",
-            home_name, home_form.avg, home_form.n,
-            away_name, away_form.avg, away_form.n
-        )
-    } else {
-        "Insufficient form data — using league average".into()
-    };
+        let value = match metric {
+            LeaderMetric::Elo => Some(team.elo_rating),
+            LeaderMetric::Form => Some(engine.team_form_rate(pool, &team.id, sport).await?),
+            // Attack/defense need at least one game of season stats
+            _ if played == 0 => None,
+            LeaderMetric::Attack => stats.as_ref().and_then(|s| {
+                per_game(if sport == "football" { s.goals_for } else { s.points_for })
+            }),
+            LeaderMetric::Defense => stats.as_ref().and_then(|s| {
+                per_game(if sport == "football" { s.goals_against } else { s.points_against })
+            }),
+        };
 
-    // ── H2H ──────────────────────────────────────────────────────────────────
-    let h2h_rows = sqlx::query(
-        "SELECT home_team_id, home_score, away_score FROM matches
-         WHERE ((home_team_id = ? AND away_team_id = ?)
-             OR (home_team_id = ? AND away_team_id = ?))
-           AND status = 'finished' AND sport = ?
-         ORDER BY match_date DESC LIMIT 10",
-    )
-    .bind(&home_id).bind(&away_id).bind(&away_id).bind(&home_id).bind(&sport)
-    .fetch_all(pool).await?;
+        if let Some(value) = value {
+            rows.push(LeaderboardRow {
+                rank: 0,
+                team_id: team.id,
+                team_name: team.name,
+                value,
+                matches_played: played,
+            });
+        }
+    }
 
-    let (mut hw, mut aw, mut draws) = (0i64, 0i64, 0i64);
-    for row in &h2h_rows {
-        let rhi: String = row.try_get("home_team_id").unwrap_or_default();
-        let hs: i32 = row.try_get("home_score").unwrap_or(0);
-        let aws: i32 = row.try_get("away_score").unwrap_or(0);
-        if hs > aws  { if rhi == home_id { hw += 1; } else { aw += 1; } }
-        else if hs < aws { if rhi == away_id { hw += 1; } else { aw += 1; } }
-        else { draws += 1; }
+    match metric {
+        LeaderMetric::Defense => rows.sort_by(|a, b| a.value.total_cmp(&b.value)),
+        _ => rows.sort_by(|a, b| b.value.total_cmp(&a.value)),
     }
-    let total_h2h = hw + aw + draws;
-    let h2h_home_prob = if total_h2h == 0 {
-        if sport == "basketball" { 0.55 } else { 0.46 }
-    } else {
-        let raw = hw as f64 / total_h2h as f64;
-        let cred = (total_h2h as f64 / 20.0).min(0.70);
-        raw * cred + 0.55 * (1.0 - cred)
-    };
-    let h2h_narrative = if total_h2h == 0 {
-        "No recent head-to-head history available".into()
-    } else {
-        format!(
-            "{home_name} won {hw} of the last {total_h2h} meetings (lost {aw}, drew {draws})"
-        )
-    };
+    for (i, row) in rows.iter_mut().enumerate() {
+        row.rank = i + 1;
+    }
+    Ok(rows)
+}
 
-    // ── Schedule ─────────────────────────────────────────────────────────────
-    async fn last_game_days(pool: &SqlitePool, team_id: &str, before: chrono::DateTime<chrono::Utc>) -> i64 {
-        let row = sqlx::query(
-            "SELECT match_date FROM matches WHERE (home_team_id = ? OR away_team_id = ?)
-             AND status = 'finished' AND match_date < ? ORDER BY match_date DESC LIMIT 1",
-        )
-        .bind(team_id).bind(team_id).bind(before.to_rfc3339())
-        .fetch_optional(pool).await.ok().flatten();
-        row.and_then(|r| {
-            let s: String = r.try_get("match_date").ok()?;
-            let d = chrono::DateTime::parse_from_rfc3339(&s).ok()?;
-            Some((before - d.with_timezone(&chrono::Utc)).num_days().max(0).saturating_sub(1).min(7))
-        }).unwrap_or(3) // well-rested if no prior game
+// GET /leagues/:sport/:league/form-table - Teams ranked by weighted recent form alone
+async fn get_form_table_handler(
+    State(pool): State<SqlitePool>,
+    Path((sport, league)): Path<(String, String)>,
+) -> Result<Json<ApiResponse<Vec<FormTableRow>>>, StatusCode> {
+    match compute_form_table(&pool, &sport, &league).await {
+        Ok(rows) => Ok(Json(ApiResponse::success(rows))),
+        Err(e) => {
+            tracing::error!("Failed to build form table: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
     }
+}
 
-    async fn consec_away(pool: &SqlitePool, team_id: &str, before: chrono::DateTime<chrono::Utc>) -> i64 {
-        let rows = sqlx::query(
-            "SELECT home_team_id FROM matches WHERE (home_team_id=? OR away_team_id=?)
-             AND status='finished' AND match_date < ? ORDER BY match_date DESC LIMIT 6",
-        )
-        .bind(team_id).bind(team_id).bind(before.to_rfc3339())
-        .fetch_all(pool).await.unwrap_or_default();
-        let mut n = 0i64;
-        for row in &rows {
-            let hid: String = row.try_get("home_team_id").unwrap_or_default();
-            if hid == team_id { break; }
-            n += 1;
+/// Teams without a finished game are left out rather than ranked at the neutral 0.5.
+async fn compute_form_table(pool: &SqlitePool, sport: &str, league: &str) -> anyhow::Result<Vec<FormTableRow>> {
+    let engine = PredictionEngine::new();
+    let mut rows = Vec::new();
+
+    for team in get_teams_by_league(pool, sport, league).await? {
+        let form = recent_form(pool, &team.id, sport == "football").await;
+        if form.is_empty() {
+            continue;
         }
-        n
+        rows.push(FormTableRow {
+            rank: 0,
+            form_rate: engine.team_form_rate(pool, &team.id, sport).await?,
+            team_id: team.id,
+            team_name: team.name,
+            form,
+            elo_rating: team.elo_rating,
+        });
     }
 
-    let home_rest = last_game_days(pool, &home_id, match_date).await;
-    let away_rest = last_game_days(pool, &away_id, match_date).await;
-    let away_road = consec_away(pool, &away_id, match_date).await;
+    rows.sort_by(|a, b| b.form_rate.total_cmp(&a.form_rate));
+    for (i, row) in rows.iter_mut().enumerate() {
+        row.rank = i + 1;
+    }
+    Ok(rows)
+}
 
-    let home_b2b = home_rest == 0;
-    let away_b2b = away_rest == 0;
-    let mut sched_adj = 0.0_f64;
-    if home_b2b { sched_adj -= 0.05; }
-    if away_b2b { sched_adj += 0.05; }
-    if away_road >= 3 { sched_adj += 0.015; }
+async fn compute_league_insights(pool: &SqlitePool, sport: &str, league: &str) -> anyhow::Result<LeagueInsights> {
+    let mut picks = Vec::new();
+    for m in get_upcoming_matches(pool, Some(sport)).await? {
+        if m.league != league || m.status != "scheduled" {
+            continue;
+        }
+        if let Some(p) = get_prediction_by_match_id(pool, &m.id).await? {
+            picks.push(MatchPick { match_info: m, prediction: p });
+        }
+    }
 
-    let sched_narrative = {
-        let mut parts = Vec::new();
-        if home_b2b { parts.push(format!("{} is on a back-to-back", home_name)); }
-        if away_b2b { parts.push(format!("{} is on a back-to-back", away_name)); }
-        if away_road >= 3 { parts.push(format!("{} is on game {} of a road trip", away_name, away_road + 1)); }
-        if parts.is_empty() { "No significant schedule factors".into() }
-        else { parts.join(". ") }
+    let avg_home_win_probability = if picks.is_empty() {
+        None
+    } else {
+        Some(picks.iter().map(|p| p.prediction.home_win_probability).sum::<f64>() / picks.len() as f64)
     };
 
-    // ── Existing prediction ──────────────────────────────────────────────────
-    let pred = sqlx::query(
-        "SELECT home_win_probability, away_win_probability, draw_probability,
-                confidence_score, model_version FROM predictions
-         WHERE match_id = ? ORDER BY created_at DESC LIMIT 1",
-    )
-    .bind(match_id).fetch_optional(pool).await?;
+    let highest_confidence = picks.iter()
+        .max_by(|a, b| a.prediction.confidence_score.total_cmp(&b.prediction.confidence_score))
+        .cloned();
 
-    let (final_home, final_away, draw_prob, confidence, model_version) = match pred {
-        Some(r) => (
-            r.try_get("home_win_probability").unwrap_or(elo_home_prob),
-            r.try_get("away_win_probability").unwrap_or(1.0 - elo_home_prob),
-            r.try_get("draw_probability").ok().flatten(),
-            r.try_get("confidence_score").unwrap_or(0.5),
-            r.try_get::<String, _>("model_version").unwrap_or_default(),
-        ),
-        None => (elo_home_prob, 1.0 - elo_home_prob, None, 0.5_f64, "none".into()),
-    };
+    let closeness = |p: &MatchPick| (p.prediction.home_win_probability - p.prediction.away_win_probability).abs();
+    let closest_matchup = picks.iter()
+        .min_by(|a, b| closeness(a).total_cmp(&closeness(b)))
+        .cloned();
 
-    let is_fallback = model_version.contains("fallback") || sport != "basketball";
-    let (w_elo, w_form, w_h2h) = if is_fallback { (0.40, 0.40, 0.20) } else { (0.20, 0.25, 0.10) };
+    // Edges come back sorted by value, so the first in this league is the biggest
+    let biggest_edge = PredictionEngine::new().find_market_edges(pool, false).await?
+        .into_iter()
+        .find(|e| e.match_info.sport == sport && e.match_info.league == league);
 
-    Ok(Some(MatchAnalysis {
-        match_id: match_id.into(), home_team_name: home_name, away_team_name: away_name, sport,
-        elo: EloComponent { home_elo, away_elo, diff: elo_diff, home_prob: elo_home_prob, weight: w_elo, narrative: elo_narrative },
-        form: FormComponent { home_avg_margin: home_form.avg, away_avg_margin: away_form.avg, home_games_used: home_form.n, away_games_used: away_form.n, home_prob: form_home_prob, weight: w_form, narrative: form_narrative },
-        h2h: H2hComponent { home_wins: hw, away_wins: aw, draws, total: total_h2h, home_prob: h2h_home_prob, weight: w_h2h, narrative: h2h_narrative },
-        schedule: ScheduleComponent { home_rest_days: home_rest, away_rest_days: away_rest, away_on_back_to_back: away_b2b, home_on_back_to_back: home_b2b, away_consecutive_road: away_road, adjustment: sched_adj, narrative: sched_narrative },
-        model_version, final_home_prob: final_home, final_away_prob: final_away, draw_prob, confidence,
-    }))
+    Ok(LeagueInsights {
+        sport: sport.to_string(),
+        league: league.to_string(),
+        predicted_matches: picks.len(),
+        avg_home_win_probability,
+        highest_confidence,
+        closest_matchup,
+        biggest_edge,
+    })
 }
 
-// Helper function to generate custom datasets
-async fn generate_custom_dataset(
-    pool: &SqlitePool,
-    request: DatasetRequest,
-) -> anyhow::Result<DatasetResponse> {
-    let mut query = String::from("SELECT ");
-    
-    // Build dynamic query based on requested stats categories
-    let mut columns = vec!["m.id", "m.home_team_name", "m.away_team_name", "m.match_date"];
-    
-    for category in &request.stats_categories {
-        match category.as_str() {
-            "basic" => {
-                columns.extend(&["m.home_score", "m.away_score", "m.status"]);
+// POST /datasets/generate - Generate custom dataset
+#[derive(Debug, Serialize, Deserialize)]
+struct DatasetResponse {
+    download_url: String,
+    format: String,
+    /// MIME type of the downloaded file: text/csv, application/json or application/gzip
+    #[serde(default)]
+    content_type: String,
+    #[serde(default)]
+    compressed: bool,
+    rows: usize,
+    generated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// How long an Idempotency-Key replays its original response (DATASET_IDEMPOTENCY_TTL_HOURS).
+fn dataset_idempotency_ttl_hours() -> i64 {
+    std::env::var("DATASET_IDEMPOTENCY_TTL_HOURS").ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(24)
+}
+
+/// Whether the file behind a dataset response's download URL is still on disk.
+async fn export_exists(response: &DatasetResponse) -> bool {
+    let file = response.download_url.trim_start_matches("/downloads/");
+    tokio::fs::try_exists(format!("../data/exports/{}", file)).await.unwrap_or(false)
+}
+
+async fn generate_dataset_handler(
+    State(pool): State<SqlitePool>,
+    headers: HeaderMap,
+    Json(request): Json<DatasetRequest>,
+) -> Result<Json<ApiResponse<DatasetResponse>>, ApiError> {
+    let key = headers.get("idempotency-key").and_then(|v| v.to_str().ok()).map(str::to_string);
+    let request_hash = sha256_hex(&serde_json::to_string(&request).unwrap_or_default());
+
+    // Same key + same body within the TTL → replay; same key + different body → reject.
+    // A replay whose export file has since been deleted is regenerated under the same key.
+    if let Some(key) = &key {
+        match get_idempotency_record(&pool, key, dataset_idempotency_ttl_hours()).await {
+            Ok(Some((hash, _))) if hash != request_hash => {
+                return Err(ApiError::new(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    format!("Idempotency-Key {} was already used for a different request body", key),
+                ));
             }
-            "teams" => {
-                columns.extend(&["ht.elo_rating as home_elo", "at.elo_rating as away_elo"]);
+            Ok(Some((_, stored))) => match serde_json::from_str::<DatasetResponse>(&stored) {
+                Ok(response) if export_exists(&response).await => return Ok(Json(ApiResponse::success(response))),
+                Ok(response) => tracing::info!("Export {} for Idempotency-Key {} is gone, regenerating", response.download_url, key),
+                Err(e) => tracing::warn!("Unreadable idempotent response for {}: {}", key, e),
+            },
+            Ok(None) => {}
+            Err(e) => return Err(ApiError::internal("Idempotency lookup failed", e)),
+        }
+    }
+
+    match generate_custom_dataset(&pool, request).await {
+        Ok(response) => {
+            if let Some(key) = &key {
+                let stored = serde_json::to_string(&response).unwrap_or_default();
+                if let Err(e) = save_idempotency_record(&pool, key, &request_hash, &stored).await {
+                    tracing::warn!("Could not store idempotency key {}: {}", key, e);
+                }
             }
-            "predictions" => {
-                columns.extend(&["p.home_win_probability", "p.away_win_probability", "p.draw_probability"]);
+            Ok(Json(ApiResponse::success(response)))
+        }
+        Err(e) => Err(ApiError::internal("Failed to generate dataset", e)),
+    }
+}
+
+// POST /data/fetch - Fetch sports data from APIs
+#[derive(Deserialize)]
+struct FetchDataRequest {
+    sport: Option<String>,
+    force_refresh: Option<bool>,
+}
+
+async fn fetch_data_handler(
+    State(pool): State<SqlitePool>,
+    State(fetcher): State<Arc<DataFetcher>>,
+    Json(request): Json<FetchDataRequest>,
+) -> (StatusCode, Json<ApiResponse<FetchSummary>>) {
+    let summary = match request.sport.as_deref() {
+        Some(sport @ "football") => {
+            let mut summary = FetchSummary::default();
+            summary.record_sport(sport, fetcher.fetch_epl(&pool).await);
+            summary
+        }
+        Some(sport @ "basketball") => {
+            let mut summary = FetchSummary::default();
+            summary.record_sport(sport, fetcher.fetch_nba(&pool).await);
+            summary
+        }
+        _ => fetcher.fetch_all_data(&pool).await,
+    };
+
+    // Partial success is still success; the per-sport statuses say what landed
+    if summary.all_failed() {
+        return all_sports_failed(summary);
+    }
+    (StatusCode::OK, Json(ApiResponse::success(summary)))
+}
+
+/// Every attempted sport failed: a 502 whose body still lists each sport's reason.
+fn all_sports_failed(summary: FetchSummary) -> (StatusCode, Json<ApiResponse<FetchSummary>>) {
+    (StatusCode::BAD_GATEWAY, Json(ApiResponse::failure(summary, "Fetch failed for every sport".to_string())))
+}
+
+// POST /data/refresh - Wipe and re-fetch all real data, then rebuild ELO + predictions
+async fn refresh_all_data_handler(
+    State(pool): State<SqlitePool>,
+    State(fetcher): State<Arc<DataFetcher>>,
+    State(cache): State<ResponseCache>,
+) -> Result<(StatusCode, Json<ApiResponse<FetchSummary>>), StatusCode> {
+    let Ok(_pipeline) = pipeline_lock().try_lock() else {
+        return Err(StatusCode::CONFLICT);
+    };
+    tracing::info!("Manual /data/refresh triggered");
+
+    if !fetcher.has_football_key() && !fetcher.has_nba_key() {
+        return Ok((StatusCode::OK, Json(ApiResponse::error(
+            "No API keys configured — set FOOTBALL_DATA_API_KEY and/or BALLDONTLIE_API_KEY".to_string()
+        ))));
+    }
+
+    if let Err(e) = clear_all_data(&pool).await {
+        tracing::error!("Clear failed: {}", e);
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    let summary = fetcher.fetch_all_data(&pool).await;
+    if summary.all_failed() {
+        return Ok(all_sports_failed(summary));
+    }
+
+    rebuild_elo(&pool).await;
+    compute_season_stats(&pool).await;
+    if fetcher.has_football_key() {
+        if let Err(e) = fetcher.fetch_epl_standings(&pool).await {
+            tracing::warn!("EPL standings fetch failed: {}", e);
+        }
+    }
+    refresh_predictions(&pool).await;
+    cache.bump_generation();
+    log_integrity_issues(&pool).await;
+
+    Ok((StatusCode::OK, Json(ApiResponse::success(summary))))
+}
+
+/// Run the integrity pass and log anything it finds; a refresh still succeeds either way.
+async fn log_integrity_issues(pool: &SqlitePool) {
+    match validate_data_integrity(pool).await {
+        Ok(issues) if issues.is_empty() => tracing::info!("Integrity check passed"),
+        Ok(issues) => {
+            tracing::warn!("Integrity check found {} issue(s) after refresh", issues.len());
+            for issue in issues.iter().take(20) {
+                tracing::warn!("  {} {}: {}", issue.kind, issue.entity_id, issue.detail);
             }
-            _ => {}
         }
+        Err(e) => tracing::error!("Integrity check failed: {}", e),
     }
-    
-    query.push_str(&columns.join(", "));
-    query.push_str(" FROM matches m ");
-    
-    if request.stats_categories.contains(&"teams".to_string()) {
-        query.push_str("LEFT JOIN teams ht ON m.home_team_id = ht.id ");
-        query.push_str("LEFT JOIN teams at ON m.away_team_id = at.id ");
+}
+
+// POST /admin/reseed - Wipe everything and restore the sample dataset, no API keys needed
+async fn reseed_handler(
+    State(pool): State<SqlitePool>,
+    State(cache): State<ResponseCache>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    let Ok(_pipeline) = pipeline_lock().try_lock() else {
+        return Err(StatusCode::CONFLICT);
+    };
+    tracing::info!("Manual /admin/reseed triggered");
+
+    if let Err(e) = clear_all_data(&pool).await {
+        tracing::error!("Clear failed: {}", e);
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
     }
-    
-    if request.stats_categories.contains(&"predictions".to_string()) {
-        query.push_str("LEFT JOIN predictions p ON m.id = p.match_id ");
+
+    if let Err(e) = seed_data(&pool).await {
+        tracing::error!("Seed failed: {}", e);
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
     }
-    
-    query.push_str("WHERE 1=1 ");
-    
-    // Add filters
-    if !request.sport.is_empty() {
-        query.push_str(&format!("AND m.sport = '{}' ", request.sport));
+
+    rebuild_elo(&pool).await;
+    compute_season_stats(&pool).await;
+    refresh_predictions(&pool).await;
+    cache.bump_generation();
+
+    let team_count = count_teams(&pool).await.unwrap_or(0);
+    let match_count = count_matches_by_status(&pool, None).await.unwrap_or(0);
+    let prediction_count = count_predictions(&pool).await.unwrap_or(0);
+
+    Ok(Json(ApiResponse::success(format!(
+        "Reseeded: {} teams, {} matches, {} predictions", team_count, match_count, prediction_count
+    ))))
+}
+
+// POST /admin/carryover - Start a new season from the previous one's regressed final ratings
+async fn carryover_handler(
+    State(pool): State<SqlitePool>,
+    State(cache): State<ResponseCache>,
+    Json(request): Json<CarryoverRequest>,
+) -> Result<Json<ApiResponse<CarryoverReport>>, StatusCode> {
+    if !(0.0..=1.0).contains(&request.regression) || request.from_season == request.to_season {
+        return Err(StatusCode::BAD_REQUEST);
     }
-    
-    if let Some(date_from) = request.date_from {
-        query.push_str(&format!("AND m.match_date >= '{}' ", date_from.to_rfc3339()));
+    let Ok(_pipeline) = pipeline_lock().try_lock() else {
+        return Err(StatusCode::CONFLICT);
+    };
+    match run_carryover(&pool, &request).await {
+        Ok(report) if report.teams_carried == 0 => {
+            tracing::warn!("No final ratings recorded for season {}", request.from_season);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Ok(report) => {
+            cache.bump_generation();
+            Ok(Json(ApiResponse::success(report)))
+        }
+        Err(e) => {
+            tracing::error!("Carryover failed: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
     }
-    
-    if let Some(date_to) = request.date_to {
-        query.push_str(&format!("AND m.match_date <= '{}' ", date_to.to_rfc3339()));
+}
+
+/// Carries the ratings over, then rebuilds ELO so teams without results start from them
+/// and reprices upcoming matches.
+async fn run_carryover(pool: &SqlitePool, request: &CarryoverRequest) -> anyhow::Result<CarryoverReport> {
+    let finals_recorded = if request.record_finals {
+        save_season_final_ratings(pool, &request.from_season).await?
+    } else {
+        0
+    };
+    let teams_carried = carry_over_ratings(pool, &request.from_season, &request.to_season, request.regression).await?;
+    if teams_carried > 0 {
+        rebuild_elo(pool).await;
+        refresh_predictions(pool).await;
     }
-    
-    query.push_str("ORDER BY m.match_date DESC LIMIT 1000");
-    
-    let rows = sqlx::query(&query).fetch_all(pool).await?;
-    
-    // Generate file based on format
-    let filename = format!("dataset_{}_{}.{}", 
-        request.sport, 
-        chrono::Utc::now().timestamp(), 
-        request.format
+    tracing::info!(
+        "Carried {} ratings from {} to {} (regression {:.2})",
+        teams_carried, request.from_season, request.to_season, request.regression
     );
-    
-    let file_path = format!("../data/exports/{}", filename);
-    
-    // Create exports directory if it doesn't exist
-    tokio::fs::create_dir_all("../data/exports").await?;
-    
-    // Strip SQL aliases from header names:
-    //   "m.home_team_name"        → "home_team_name"
-    //   "ht.elo_rating as home_elo" → "home_elo"
-    let headers: Vec<String> = columns.iter().map(|col| {
-        // Take the alias after " as " if present, otherwise use the raw column expression.
-        let col = if let Some(pos) = col.to_lowercase().find(" as ") {
-            col[pos + 4..].trim()
-        } else {
-            col.trim()
-        };
-        // Strip the "table." prefix from "table.column".
-        if let Some(dot) = col.rfind('.') { col[dot + 1..].to_string() } else { col.to_string() }
-    }).collect();
+    Ok(CarryoverReport {
+        from_season: request.from_season.clone(),
+        to_season: request.to_season.clone(),
+        regression: request.regression,
+        finals_recorded,
+        teams_carried,
+    })
+}
 
-    // Helper: read a row cell as a plain string regardless of its SQLite type.
-    let cell_to_string = |row: &sqlx::sqlite::SqliteRow, i: usize| -> String {
-        if let Ok(v) = row.try_get::<String, _>(i)  { return v; }
-        if let Ok(v) = row.try_get::<f64, _>(i)     { return v.to_string(); }
-        if let Ok(v) = row.try_get::<i64, _>(i)     { return v.to_string(); }
-        if let Ok(v) = row.try_get::<bool, _>(i)    { return v.to_string(); }
-        String::new() // NULL
+// POST /admin/recompute - Rebuild ELO, stats, predictions and odds in order, timing each phase
+async fn recompute_handler(
+    State(pool): State<SqlitePool>,
+    State(cache): State<ResponseCache>,
+) -> Result<Json<ApiResponse<RecomputeReport>>, StatusCode> {
+    // A scheduler cycle or another recompute is already running the chain
+    let Ok(_pipeline) = pipeline_lock().try_lock() else {
+        return Err(StatusCode::CONFLICT);
+    };
+    tracing::info!("Manual /admin/recompute triggered");
+
+    let started = std::time::Instant::now();
+    let mut phases = Vec::with_capacity(4);
+    let mut timed_phase = |phase: &str, since: std::time::Instant, count: usize, skipped: Option<String>| {
+        phases.push(RecomputePhase {
+            phase: phase.to_string(),
+            count,
+            duration_ms: since.elapsed().as_millis() as u64,
+            skipped,
+        });
+    };
+
+    let t = std::time::Instant::now();
+    let replayed = rebuild_elo(&pool).await;
+    timed_phase("elo", t, replayed, None);
+
+    let t = std::time::Instant::now();
+    let teams = compute_season_stats(&pool).await;
+    timed_phase("stats", t, teams, None);
+
+    let t = std::time::Instant::now();
+    let predicted = refresh_predictions(&pool).await;
+    cache.bump_generation();
+    timed_phase("predictions", t, predicted, None);
+
+    let t = std::time::Instant::now();
+    match refresh_odds(&pool).await {
+        Some(n) => timed_phase("odds", t, n as usize, None),
+        None => timed_phase("odds", t, 0, Some("ODDS_API_KEY not set".to_string())),
+    }
+
+    Ok(Json(ApiResponse::success(RecomputeReport {
+        phases,
+        total_ms: started.elapsed().as_millis() as u64,
+    })))
+}
+
+/// Pairwise correlation between parlay legs in the same league on the same day (PARLAY_CORRELATION).
+fn parlay_correlation() -> f64 {
+    std::env::var("PARLAY_CORRELATION").ok()
+        .and_then(|v| v.parse::<f64>().ok())
+        .unwrap_or(0.05)
+        .clamp(0.0, 0.5)
+}
+
+// POST /predict/parlay - Combined probability for a multi-leg ticket from stored predictions
+async fn predict_parlay_handler(
+    State(pool): State<SqlitePool>,
+    Json(legs): Json<Vec<ParlayLeg>>,
+) -> Result<Json<ApiResponse<ParlayQuote>>, StatusCode> {
+    if legs.is_empty() || legs.len() > MAX_BATCH_FIXTURES {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    match compute_parlay(&pool, &legs, parlay_correlation()).await {
+        Ok(Some(quote)) => Ok(Json(ApiResponse::success(quote))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Parlay pricing failed: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// GET /matches/:id/edge/timeline - Edge at every stored odds snapshot, against the prediction current at the time
+async fn get_edge_timeline_handler(
+    State(pool): State<SqlitePool>,
+    Path(match_id): Path<String>,
+) -> Result<Json<ApiResponse<Vec<EdgeTimelinePoint>>>, StatusCode> {
+    match compute_edge_timeline(&pool, &match_id).await {
+        Ok(Some(points)) => Ok(Json(ApiResponse::success(points))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to build edge timeline for {}: {}", match_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// None when the match doesn't exist; empty when it has no odds history or predictions.
+async fn compute_edge_timeline(pool: &SqlitePool, match_id: &str) -> anyhow::Result<Option<Vec<EdgeTimelinePoint>>> {
+    if get_match_status(pool, match_id).await?.is_none() {
+        return Ok(None);
+    }
+    let predictions = get_prediction_history(pool, match_id).await?;
+    let snapshots = get_market_odds_history(pool, match_id).await?;
+    Ok(Some(edge_timeline(&predictions, &snapshots)))
+}
+
+// GET /matches/:id - One match with its prediction, market odds, edge and both teams
+async fn get_match_detail_handler(
+    State(pool): State<SqlitePool>,
+    Path(match_id): Path<String>,
+) -> Result<Json<ApiResponse<MatchDetail>>, StatusCode> {
+    match compute_match_detail(&pool, &match_id).await {
+        Ok(Some(detail)) => Ok(Json(ApiResponse::success(detail))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to load match {}: {}", match_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// None when the match (or either of its teams) doesn't exist.
+async fn compute_match_detail(pool: &SqlitePool, match_id: &str) -> anyhow::Result<Option<MatchDetail>> {
+    let Some(m) = get_match_by_id(pool, match_id).await? else {
+        return Ok(None);
+    };
+    let (Some(home_team), Some(away_team)) = (
+        get_team_by_id(pool, &m.home_team_id).await?,
+        get_team_by_id(pool, &m.away_team_id).await?,
+    ) else {
+        return Ok(None);
+    };
+
+    let prediction = get_prediction_by_match_id(pool, &m.id).await?;
+    let market_odds = get_market_odds(pool, &m.id).await?;
+    let edge = prediction.as_ref().zip(market_odds.as_ref()).and_then(|(p, odds)| prediction_edge(p, odds));
+
+    Ok(Some(MatchDetail {
+        prediction,
+        market_odds,
+        edge_value: edge.map(|(_, value)| value),
+        edge_side: edge.map(|(side, _)| side.to_string()),
+        home_team_stats: get_team_current_stats(pool, &home_team.id).await?,
+        away_team_stats: get_team_current_stats(pool, &away_team.id).await?,
+        home_team,
+        away_team,
+        match_info: m,
+    }))
+}
+
+// GET /matches/:id/prediction?blend_market=0.5 - Current prediction, optionally blended with the devigged market
+#[derive(Deserialize)]
+struct MatchPredictionQuery {
+    /// Weight on the market (0 = pure model, 1 = market only); defaults to MARKET_BLEND_WEIGHT
+    blend_market: Option<f64>,
+}
+
+async fn get_match_prediction_handler(
+    State(pool): State<SqlitePool>,
+    Path(match_id): Path<String>,
+    Query(params): Query<MatchPredictionQuery>,
+) -> Result<Json<ApiResponse<Prediction>>, StatusCode> {
+    let weight = params.blend_market.unwrap_or_else(market_blend_weight);
+    if !(0.0..=1.0).contains(&weight) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    match compute_match_prediction(&pool, &match_id, weight).await {
+        Ok(Some(prediction)) => Ok(Json(ApiResponse::success(prediction))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to load prediction for {}: {}", match_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// None when the match has no prediction. With a non-zero weight and a complete market the
+/// consensus prediction is blended on the fly (only the refresh stores one); otherwise the
+/// pure-model one is returned.
+async fn compute_match_prediction(
+    pool: &SqlitePool,
+    match_id: &str,
+    weight: f64,
+) -> anyhow::Result<Option<Prediction>> {
+    let Some(prediction) = get_prediction_by_match_id(pool, match_id).await? else {
+        return Ok(None);
+    };
+    if weight == 0.0 {
+        return Ok(Some(prediction));
+    }
+    let Some(odds) = get_market_odds(pool, match_id).await? else {
+        return Ok(Some(prediction));
     };
+    Ok(Some(blend_with_market(&prediction, &odds, weight).unwrap_or(prediction)))
+}
+
+// GET /matches/:id/simulate?iterations=10000&seed= - Monte Carlo outcome distribution for one fixture
+const DEFAULT_SIMULATIONS: usize = 10_000;
+const MAX_SIMULATIONS: usize = 200_000;
+
+#[derive(Deserialize)]
+struct SimulateQuery {
+    iterations: Option<usize>,
+    /// Fixes the RNG so the same request returns the same numbers
+    seed: Option<u64>,
+}
+
+async fn simulate_match_handler(
+    State(pool): State<SqlitePool>,
+    Path(match_id): Path<String>,
+    Query(params): Query<SimulateQuery>,
+) -> Result<Json<ApiResponse<MatchSimulation>>, StatusCode> {
+    let iterations = params.iterations.unwrap_or(DEFAULT_SIMULATIONS);
+    if iterations == 0 || iterations > MAX_SIMULATIONS {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    match compute_match_simulation(&pool, &match_id, iterations, params.seed).await {
+        Ok(Some(sim)) => Ok(Json(ApiResponse::success(sim))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to simulate match {}: {}", match_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// None when the match doesn't exist. The simulation itself runs on the blocking pool.
+async fn compute_match_simulation(
+    pool: &SqlitePool,
+    match_id: &str,
+    iterations: usize,
+    seed: Option<u64>,
+) -> anyhow::Result<Option<MatchSimulation>> {
+    let Some(m) = get_match_by_id(pool, match_id).await? else {
+        return Ok(None);
+    };
+
+    let is_football = m.sport == "football";
+    let (home_mean, away_mean) = expected_scores(pool, &m).await?;
+    let model = if is_football {
+        ScoreModel::Poisson { home_rate: home_mean, away_rate: away_mean }
+    } else {
+        ScoreModel::Normal { home_mean, away_mean, sd: NBA_SCORE_SD }
+    };
+    let sim = tokio::task::spawn_blocking(move || model.simulate(iterations, seed)).await?;
+    let (analytic_home, analytic_draw, analytic_away) = model.analytic_1x2();
+
+    Ok(Some(MatchSimulation {
+        prediction: get_prediction_by_match_id(pool, &m.id).await?,
+        match_info: m,
+        model: if is_football { "poisson" } else { "normal" }.to_string(),
+        iterations,
+        seed,
+        expected_home_score: home_mean,
+        expected_away_score: away_mean,
+        home_win: sim.home_win,
+        draw: is_football.then_some(sim.draw),
+        away_win: sim.away_win,
+        most_likely_home_score: sim.most_likely_score.0,
+        most_likely_away_score: sim.most_likely_score.1,
+        most_likely_score_frequency: sim.most_likely_score_frequency,
+        avg_total: sim.avg_total,
+        totals: sim.totals.into_iter().map(|(line, over)| TotalLine { line, over, under: 1.0 - over }).collect(),
+        btts: is_football.then_some(sim.btts),
+        analytic_home_win: analytic_home,
+        analytic_draw: is_football.then_some(analytic_draw),
+        analytic_away_win: analytic_away,
+    }))
+}
+
+// GET /matches/:id/totals?line= - expected total points and over/under probabilities
+#[derive(Deserialize)]
+struct TotalsQuery {
+    /// Price just this line instead of the default ladder
+    line: Option<f64>,
+}
+
+async fn get_match_totals_handler(
+    State(pool): State<SqlitePool>,
+    Path(match_id): Path<String>,
+    Query(params): Query<TotalsQuery>,
+) -> Result<Json<ApiResponse<MatchTotals>>, StatusCode> {
+    if params.line.is_some_and(|l| !l.is_finite() || l < 0.0) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    match compute_match_totals(&pool, &match_id, params.line).await {
+        Ok(Some(totals)) => Ok(Json(ApiResponse::success(totals))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to price totals for match {}: {}", match_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Basketball uses the stored pace/efficiency estimate, computing it when the refresh
+/// hasn't yet; football uses Poisson goals from the same means as /simulate.
+async fn compute_match_totals(pool: &SqlitePool, match_id: &str, line: Option<f64>) -> anyhow::Result<Option<MatchTotals>> {
+    let Some(m) = get_match_by_id(pool, match_id).await? else {
+        return Ok(None);
+    };
+
+    let prediction = if m.sport == "basketball" {
+        match get_total_prediction(pool, &m.id).await? {
+            Some(stored) => stored,
+            None => NbaPredictor::new().predict_total_points(pool, &m).await?.to_prediction(&m.id),
+        }
+    } else {
+        let (home_rate, away_rate) = expected_scores(pool, &m).await?;
+        TotalsPrediction {
+            match_id: m.id.clone(),
+            model: "poisson".to_string(),
+            expected_home_score: home_rate,
+            expected_away_score: away_rate,
+            total_sd: None,
+            possessions: None,
+            created_at: chrono::Utc::now(),
+        }
+    };
+
+    let model = match prediction.total_sd {
+        // ScoreModel::Normal takes the per-side SD
+        Some(sd) => ScoreModel::Normal {
+            home_mean: prediction.expected_home_score,
+            away_mean: prediction.expected_away_score,
+            sd: sd / std::f64::consts::SQRT_2,
+        },
+        None => ScoreModel::Poisson { home_rate: prediction.expected_home_score, away_rate: prediction.expected_away_score },
+    };
+    let lines = line.map_or_else(|| model.total_lines(), |l| vec![l]);
+
+    Ok(Some(MatchTotals {
+        expected_total: prediction.expected_home_score + prediction.expected_away_score,
+        lines: lines.into_iter()
+            .map(|line| {
+                let over = model.over_probability(line);
+                TotalLine { line, over, under: 1.0 - over }
+            })
+            .collect(),
+        prediction,
+    }))
+}
+
+/// Per-team standard deviation of NBA points in a game.
+const NBA_SCORE_SD: f64 = 12.0;
+
+/// None when any leg names an unknown match.
+async fn compute_parlay(pool: &SqlitePool, legs: &[ParlayLeg], rho: f64) -> anyhow::Result<Option<ParlayQuote>> {
+    let mut quotes = Vec::new();
+    for leg in legs {
+        let Some(m) = get_match_by_id(pool, &leg.match_id).await? else {
+            return Ok(None);
+        };
+        let prediction = get_prediction_by_match_id(pool, &m.id).await?;
+        let outcome = leg.outcome.unwrap_or_else(|| match &prediction {
+            Some(p) => p.predicted_outcome(),
+            None => Outcome::Home,
+        });
+        let probability = prediction.and_then(|p| match outcome {
+            Outcome::Home => Some(p.home_win_probability),
+            Outcome::Away => Some(p.away_win_probability),
+            Outcome::Draw => p.draw_probability,
+        });
+        quotes.push(ParlayLegQuote { match_info: m, outcome, probability });
+    }
+
+    let missing_predictions: Vec<String> = quotes.iter()
+        .filter(|q| q.probability.is_none())
+        .map(|q| q.match_info.id.clone())
+        .collect();
+    let (naive_probability, correlated_probability) = if missing_predictions.is_empty() {
+        let priced: Vec<(f64, (String, chrono::NaiveDate))> = quotes.iter()
+            .map(|q| (q.probability.unwrap_or(0.0), (q.match_info.league.clone(), q.match_info.match_date.date_naive())))
+            .collect();
+        let (naive, correlated) = parlay_probability(&priced, rho);
+        (Some(naive), Some(correlated))
+    } else {
+        (None, None)
+    };
+
+    Ok(Some(ParlayQuote { legs: quotes, missing_predictions, naive_probability, correlated_probability, correlation: rho }))
+}
+
+// GET /admin/integrity - Data anomalies: unscored finished matches, orphaned rows, bad probabilities
+async fn get_integrity_handler(
+    State(pool): State<SqlitePool>,
+) -> Result<Json<ApiResponse<Vec<IntegrityIssue>>>, StatusCode> {
+    match validate_data_integrity(&pool).await {
+        Ok(issues) => Ok(Json(ApiResponse::success(issues))),
+        Err(e) => {
+            tracing::error!("Integrity check failed: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// GET /admin/schema - Applied schema migrations and any the database is missing
+async fn get_schema_status_handler(
+    State(pool): State<SqlitePool>,
+) -> Result<Json<ApiResponse<SchemaStatus>>, StatusCode> {
+    match get_schema_status(&pool).await {
+        Ok(status) => Ok(Json(ApiResponse::success(status))),
+        Err(e) => {
+            tracing::error!("Failed to read schema status: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// GET /admin/duplicates - Same-teams-same-day match rows under different ids
+async fn get_duplicate_matches_handler(
+    State(pool): State<SqlitePool>,
+) -> Result<Json<ApiResponse<Vec<DuplicateMatches>>>, StatusCode> {
+    match find_duplicate_matches(&pool).await {
+        Ok(dupes) => Ok(Json(ApiResponse::success(dupes))),
+        Err(e) => {
+            tracing::error!("Duplicate match scan failed: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// PUT /admin/teams/:id/home-advantage - Scale the default home bonus for one team's venue
+#[derive(Deserialize)]
+struct HomeAdvantageRequest {
+    /// 1.0 = league default, 0.0 = no home edge
+    multiplier: f64,
+}
+
+async fn set_home_advantage_handler(
+    State(pool): State<SqlitePool>,
+    Path(team_id): Path<String>,
+    Json(request): Json<HomeAdvantageRequest>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    if !(0.0..=3.0).contains(&request.multiplier) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    match get_team_by_id(&pool, &team_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Team lookup failed: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+    match set_home_advantage(&pool, &team_id, request.multiplier).await {
+        Ok(()) => Ok(Json(ApiResponse::success(format!(
+            "Home advantage for {} set to {:.2}x", team_id, request.multiplier
+        )))),
+        Err(e) => {
+            tracing::error!("Failed to set home advantage: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// GET /teams/:id/accuracy - Hit rate, Brier score and predicted vs actual win rate for one team
+async fn get_team_accuracy_handler(
+    State(pool): State<SqlitePool>,
+    Path(team_id): Path<String>,
+) -> Result<Json<ApiResponse<TeamAccuracy>>, StatusCode> {
+    match compute_team_accuracy(&pool, &team_id).await {
+        Ok(Some(accuracy)) => Ok(Json(ApiResponse::success(accuracy))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to score predictions for {}: {}", team_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// None when the team doesn't exist or has no resolved predictions yet.
+async fn compute_team_accuracy(pool: &SqlitePool, team_id: &str) -> anyhow::Result<Option<TeamAccuracy>> {
+    let Some(team) = get_team_by_id(pool, team_id).await? else {
+        return Ok(None);
+    };
+    let resolved = get_resolved_predictions_for_team(pool, team_id).await?;
+    if resolved.is_empty() {
+        return Ok(None);
+    }
+
+    let (mut hits, mut brier, mut predicted_win, mut wins) = (0usize, 0.0, 0.0, 0usize);
+    for MatchPick { match_info: m, prediction: p } in &resolved {
+        let Some(outcome) = m.result().map(|r| r.winner) else { continue };
+        let is_home = m.home_team_id == team_id;
+        hits += usize::from(p.predicted_outcome() == outcome);
+        brier += brier_score(p.home_win_probability, p.draw_probability, p.away_win_probability, outcome);
+        predicted_win += if is_home { p.home_win_probability } else { p.away_win_probability };
+        wins += usize::from(outcome == if is_home { Outcome::Home } else { Outcome::Away });
+    }
+    let n = resolved.len() as f64;
+
+    Ok(Some(TeamAccuracy {
+        team_id: team.id,
+        team_name: team.name,
+        matches: resolved.len(),
+        hit_rate: hits as f64 / n,
+        brier_score: brier / n,
+        avg_predicted_win_probability: predicted_win / n,
+        actual_win_rate: wins as f64 / n,
+    }))
+}
+
+// GET /teams/:id/elo/vs-average - Team ELO history next to the league average on each of its dates
+async fn get_elo_vs_average_handler(
+    State(pool): State<SqlitePool>,
+    Path(team_id): Path<String>,
+) -> Result<Json<ApiResponse<EloVsAverage>>, StatusCode> {
+    match compute_elo_vs_average(&pool, &team_id).await {
+        Ok(Some(series)) => Ok(Json(ApiResponse::success(series))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to compare ELO for {}: {}", team_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// None when the team doesn't exist; no points when it has no ELO history.
+async fn compute_elo_vs_average(pool: &SqlitePool, team_id: &str) -> anyhow::Result<Option<EloVsAverage>> {
+    let Some(team) = get_team_by_id(pool, team_id).await? else {
+        return Ok(None);
+    };
+    let history = get_elo_history(pool, team_id).await?;
+    let league = get_league_elo_history(pool, &team.sport, &team.league).await?;
+
+    // Per-team histories, oldest first; a team counts from its first point onwards
+    let mut by_team: HashMap<&str, Vec<&crate::models::EloHistoryPoint>> = HashMap::new();
+    for point in &league {
+        by_team.entry(point.team_id.as_str()).or_default().push(point);
+    }
+
+    let points = history
+        .iter()
+        .map(|point| {
+            let ratings: Vec<f64> = by_team
+                .values()
+                .filter_map(|team_history| {
+                    let seen = team_history.partition_point(|p| p.date <= point.date);
+                    seen.checked_sub(1).map(|i| team_history[i].elo_rating)
+                })
+                .collect();
+            let league_average = ratings.iter().sum::<f64>() / ratings.len().max(1) as f64;
+            EloVsAveragePoint {
+                date: point.date,
+                match_id: point.match_id.clone(),
+                elo_rating: point.elo_rating,
+                league_average,
+                relative: point.elo_rating - league_average,
+            }
+        })
+        .collect();
+
+    Ok(Some(EloVsAverage { team_id: team.id, team_name: team.name, league: team.league, points }))
+}
+
+// GET /teams/:id/projection?iterations= - Final-position, title, qualification and relegation odds
+#[derive(Deserialize)]
+struct ProjectionQuery {
+    iterations: Option<usize>,
+    #[serde(default)]
+    nocache: bool,
+}
+
+async fn get_team_projection_handler(
+    State(pool): State<SqlitePool>,
+    State(cache): State<ResponseCache>,
+    Path(team_id): Path<String>,
+    Query(params): Query<ProjectionQuery>,
+) -> Result<Json<ApiResponse<TeamProjection>>, StatusCode> {
+    let iterations = params.iterations.unwrap_or(DEFAULT_SIMULATIONS);
+    if iterations == 0 || iterations > MAX_SIMULATIONS {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    match compute_team_projection(&pool, &cache, &team_id, iterations, params.nocache).await {
+        Ok(Some(projection)) => Ok(Json(ApiResponse::success(projection))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to project season for {}: {}", team_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// One simulated season for a whole league, shared by every team's projection.
+#[derive(Clone)]
+struct LeagueSimulation {
+    team_ids: Vec<String>,
+    fixtures: usize,
+    sim: Arc<SeasonSimulation>,
+}
+
+/// Plays out the league's remaining fixtures from the current table. Each fixture uses its
+/// stored prediction, or the plain ELO probabilities when it has none.
+async fn simulate_league_season(pool: &SqlitePool, sport: &str, league: &str, iterations: usize) -> anyhow::Result<LeagueSimulation> {
+    let teams = get_teams_by_league(pool, sport, league).await?;
+    let standings: HashMap<String, StandingsRow> = get_standings(pool, sport, league).await?
+        .into_iter()
+        .map(|row| (row.team_id.clone(), row))
+        .collect();
+    let table: Vec<TableEntry> = teams.iter().map(|t| {
+        let row = standings.get(&t.id);
+        TableEntry {
+            points: row.map_or(0, |r| r.points),
+            score_diff: row.map_or(0, |r| r.score_diff),
+        }
+    }).collect();
+    let index: HashMap<&str, usize> = teams.iter().enumerate().map(|(i, t)| (t.id.as_str(), i)).collect();
+
+    let calc = EloCalculator::new();
+    let mut fixtures = Vec::new();
+    for m in get_remaining_league_fixtures(pool, sport, league).await? {
+        let (Some(&home), Some(&away)) = (index.get(m.home_team_id.as_str()), index.get(m.away_team_id.as_str())) else {
+            continue;
+        };
+        let (home_win, draw) = match get_prediction_by_match_id(pool, &m.id).await? {
+            Some(p) => (p.home_win_probability, p.draw_probability.unwrap_or(0.0)),
+            None => {
+                let (h, _, d) = calc.win_probability(teams[home].elo_rating, teams[away].elo_rating, sport, league);
+                (h, d.unwrap_or(0.0))
+            }
+        };
+        fixtures.push(RemainingFixture { home, away, home_win, draw });
+    }
+
+    let points = if sport == "football" { (3, 1) } else { (1, 0) };
+    let fixture_count = fixtures.len();
+    let sim = tokio::task::spawn_blocking(move || simulate_season(&table, &fixtures, points, iterations, None)).await?;
+    Ok(LeagueSimulation {
+        team_ids: teams.into_iter().map(|t| t.id).collect(),
+        fixtures: fixture_count,
+        sim: Arc::new(sim),
+    })
+}
+
+/// (qualifying, relegation) places in a league's final table, each None where the
+/// competition has no such places. The NBA's top 16 stands in for playoff/play-in spots,
+/// as conferences are not modelled.
+fn league_places(sport: &str, league: &str) -> (Option<usize>, Option<usize>) {
+    match (sport, league) {
+        ("football", "EPL") => (Some(4), Some(3)),
+        ("basketball", "NBA") => (Some(16), None),
+        _ => (None, None),
+    }
+}
+
+/// None when the team doesn't exist. The league simulation is cached, so only the first
+/// team asked about in a league pays for the iterations.
+async fn compute_team_projection(
+    pool: &SqlitePool,
+    cache: &ResponseCache,
+    team_id: &str,
+    iterations: usize,
+    bypass_cache: bool,
+) -> anyhow::Result<Option<TeamProjection>> {
+    let Some(team) = get_team_by_id(pool, team_id).await? else {
+        return Ok(None);
+    };
+    let key = format!("season_sim:{}:{}:{}", team.sport, team.league, iterations);
+    let league = cache.get_or_load(&key, bypass_cache, || simulate_league_season(pool, &team.sport, &team.league, iterations)).await?;
+    let Some(i) = league.team_ids.iter().position(|id| id == team_id) else {
+        return Ok(None);
+    };
+
+    let positions = league.sim.positions[i].clone();
+    let (qualifying, relegated) = league_places(&team.sport, &team.league);
+    Ok(Some(TeamProjection {
+        team_id: team.id,
+        team_name: team.name,
+        league: team.league,
+        iterations: league.sim.iterations,
+        remaining_fixtures: league.fixtures,
+        title_probability: league.sim.top(i, 1),
+        qualification_probability: qualifying.map(|n| league.sim.top(i, n)),
+        relegation_probability: relegated.map(|n| league.sim.bottom(i, n)),
+        expected_position: positions.iter().enumerate().map(|(p, share)| (p + 1) as f64 * share).sum(),
+        position_probabilities: positions,
+    }))
+}
+
+/// Longest run of hypothetical results one projection accepts (a full EPL season).
+const MAX_PROJECTED_GAMES: usize = 38;
+
+// GET /teams/:id/elo/project?results=&opponents= - Rating after a run of hypothetical results
+#[derive(Deserialize)]
+struct EloProjectQuery {
+    /// One W/D/L per game from the team's side, e.g. "WWW"
+    results: String,
+    /// Comma-separated opponent team ids, one per result; defaults to the team's next fixtures
+    opponents: Option<String>,
+}
+
+async fn project_team_elo_handler(
+    State(pool): State<SqlitePool>,
+    Path(team_id): Path<String>,
+    Query(params): Query<EloProjectQuery>,
+) -> Result<Json<ApiResponse<EloProjection>>, StatusCode> {
+    let results: Option<Vec<ProjectedResult>> = params.results.trim().chars()
+        .map(|c| match c.to_ascii_uppercase() {
+            'W' => Some(ProjectedResult::Win),
+            'D' => Some(ProjectedResult::Draw),
+            'L' => Some(ProjectedResult::Loss),
+            _ => None,
+        })
+        .collect();
+    let Some(results) = results.filter(|r| !r.is_empty() && r.len() <= MAX_PROJECTED_GAMES) else {
+        return Err(StatusCode::BAD_REQUEST);
+    };
+    let opponents: Option<Vec<String>> = params.opponents.as_deref().map(|ids| {
+        ids.split(',').map(str::trim).filter(|id| !id.is_empty()).map(String::from).collect()
+    });
+    if opponents.as_ref().is_some_and(|o| o.len() != results.len()) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    match compute_elo_projection(&pool, &team_id, &results, opponents).await {
+        Ok(Some(projection)) => Ok(Json(ApiResponse::success(projection))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to project ELO for {}: {}", team_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// None when the team or an opponent doesn't exist, or when `opponents` is omitted and the
+/// team has fewer upcoming fixtures than results.
+async fn compute_elo_projection(
+    pool: &SqlitePool,
+    team_id: &str,
+    results: &[ProjectedResult],
+    opponents: Option<Vec<String>>,
+) -> anyhow::Result<Option<EloProjection>> {
+    let Some(team) = get_team_by_id(pool, team_id).await? else {
+        return Ok(None);
+    };
+    let opponent_ids = match opponents {
+        Some(ids) => ids,
+        None => get_matches_for_team(pool, team_id, &MatchFilter {
+            status: Some("scheduled"),
+            after: Some(chrono::Utc::now()),
+            oldest_first: true,
+            limit: Some(results.len() as i64),
+            ..Default::default()
+        })
+        .await?
+        .into_iter()
+        .map(|m| if m.home_team_id == team_id { m.away_team_id } else { m.home_team_id })
+        .collect(),
+    };
+    if opponent_ids.len() < results.len() {
+        return Ok(None);
+    }
+
+    let mut opponents = Vec::with_capacity(opponent_ids.len());
+    for id in &opponent_ids {
+        let Some(opponent) = get_team_by_id(pool, id).await? else {
+            return Ok(None);
+        };
+        opponents.push(opponent);
+    }
+
+    let games: Vec<(f64, ProjectedResult)> = opponents.iter().map(|o| o.elo_rating).zip(results.iter().copied()).collect();
+    let calc = EloCalculator::new();
+    let projected_rating = calc.project_rating(team.elo_rating, &games, &team.sport);
+    let path = calc.project_path(team.elo_rating, &games, &team.sport);
+    let steps = opponents.into_iter().zip(games).zip(path)
+        .map(|((opponent, (opponent_rating, result)), rating_after)| EloProjectionStep {
+            opponent_id: opponent.id,
+            opponent_name: opponent.name,
+            opponent_rating,
+            result,
+            rating_after,
+        })
+        .collect();
+
+    Ok(Some(EloProjection {
+        team_id: team.id,
+        team_name: team.name,
+        current_rating: team.elo_rating,
+        projected_rating,
+        steps,
+    }))
+}
+
+/// Most fixtures accepted by one POST /predict/batch call.
+const MAX_BATCH_FIXTURES: usize = 100;
+/// Fixtures priced at once; each one runs several queries against the shared pool.
+const BATCH_CONCURRENCY: usize = 8;
+
+// POST /predict/batch - Price arbitrary fixtures without persisting them
+#[derive(Deserialize)]
+struct PredictBatchQuery {
+    /// Football H2H ensemble weight for this call only, clamped to [0, 1]
+    h2h_weight: Option<f64>,
+}
+
+async fn predict_batch_handler(
+    State(pool): State<SqlitePool>,
+    Query(params): Query<PredictBatchQuery>,
+    Json(fixtures): Json<Vec<HypotheticalFixture>>,
+) -> Result<Json<ApiResponse<Vec<BatchPredictionResult>>>, StatusCode> {
+    if fixtures.len() > MAX_BATCH_FIXTURES {
+        return Err(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+    if params.h2h_weight.is_some_and(|w| !w.is_finite()) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    match compute_batch_predictions(&pool, fixtures, params.h2h_weight).await {
+        Ok(results) => Ok(Json(ApiResponse::success(results))),
+        Err(e) => {
+            tracing::error!("Batch prediction failed: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn compute_batch_predictions(
+    pool: &SqlitePool,
+    fixtures: Vec<HypotheticalFixture>,
+    h2h_weight: Option<f64>,
+) -> anyhow::Result<Vec<BatchPredictionResult>> {
+    // Resolve every distinct team id once, before any pricing starts
+    let mut teams: HashMap<String, Option<Team>> = HashMap::new();
+    for id in fixtures.iter().flat_map(|f| [&f.home_id, &f.away_id]) {
+        if !teams.contains_key(id) {
+            teams.insert(id.clone(), get_team_by_id(pool, id).await?);
+        }
+    }
+
+    let engine = Arc::new(PredictionEngine::new());
+    let permits = Arc::new(tokio::sync::Semaphore::new(BATCH_CONCURRENCY));
+    let mut results: Vec<BatchPredictionResult> = fixtures.into_iter()
+        .map(|fixture| BatchPredictionResult { fixture, prediction: None, error: None })
+        .collect();
+    let mut tasks = tokio::task::JoinSet::new();
+
+    for (i, result) in results.iter_mut().enumerate() {
+        let f = &result.fixture;
+        let (home, away) = match (teams[&f.home_id].clone(), teams[&f.away_id].clone()) {
+            (Some(home), Some(away)) => (home, away),
+            (home, _) => {
+                let missing = if home.is_none() { &f.home_id } else { &f.away_id };
+                result.error = Some(format!("Unknown team id: {}", missing));
+                continue;
+            }
+        };
+        if home.id == away.id {
+            result.error = Some("A team cannot play itself".to_string());
+            continue;
+        }
+        if home.sport != f.sport || away.sport != f.sport {
+            result.error = Some(format!("Both teams must play {}", f.sport));
+            continue;
+        }
+
+        let (pool, engine, permits, neutral) = (pool.clone(), engine.clone(), permits.clone(), f.neutral);
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            (i, engine.predict_hypothetical(&pool, &home, &away, neutral, h2h_weight).await)
+        });
+    }
+
+    while let Some(joined) = tasks.join_next().await {
+        let (i, outcome) = joined?;
+        match outcome {
+            Ok(prediction) => results[i].prediction = Some(prediction),
+            Err(e) => results[i].error = Some(e.to_string()),
+        }
+    }
+    Ok(results)
+}
+
+// POST /admin/teams/merge - Fold a renamed/relocated team's old id into the canonical one
+#[derive(Deserialize)]
+struct MergeTeamsRequest {
+    from_id: String,
+    into_id: String,
+}
+
+async fn merge_teams_handler(
+    State(pool): State<SqlitePool>,
+    Json(request): Json<MergeTeamsRequest>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    if request.from_id == request.into_id {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let (from, into) = match (
+        get_team_by_id(&pool, &request.from_id).await,
+        get_team_by_id(&pool, &request.into_id).await,
+    ) {
+        (Ok(Some(from)), Ok(Some(into))) => (from, into),
+        (Ok(_), Ok(_)) => return Err(StatusCode::NOT_FOUND),
+        (Err(e), _) | (_, Err(e)) => {
+            tracing::error!("Team lookup failed: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    if from.sport != into.sport {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if let Err(e) = merge_teams(&pool, &from.id, &into.id).await {
+        tracing::error!("Team merge failed: {}", e);
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    // Ratings and season stats are derived from matches, so rebuild them for the merged history
+    rebuild_elo(&pool).await;
+    compute_season_stats(&pool).await;
+
+    Ok(Json(ApiResponse::success(format!("Merged {} into {}", from.name, into.name))))
+}
+
+// POST /predictions/generate - Generate predictions for upcoming matches
+async fn generate_predictions_handler(
+    State(pool): State<SqlitePool>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    let prediction_engine = PredictionEngine::new();
+    
+    match get_upcoming_matches(&pool, None).await {
+        Ok(matches) => {
+            match prediction_engine.generate_predictions(&pool, &matches).await {
+                Ok(()) => Ok(Json(ApiResponse::success(format!("Generated predictions for {} matches", matches.len())))),
+                Err(e) => {
+                    tracing::error!("Failed to generate predictions: {}", e);
+                    Err(StatusCode::INTERNAL_SERVER_ERROR)
+                }
+            }
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch matches for prediction: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// GET /matches/:id/analysis — per-component prediction breakdown
+async fn get_match_analysis_handler(
+    State(pool): State<SqlitePool>,
+    Path(match_id): Path<String>,
+) -> Result<Json<ApiResponse<MatchAnalysis>>, StatusCode> {
+    match compute_match_analysis(&pool, &match_id).await {
+        Ok(Some(a)) => Ok(Json(ApiResponse::success(a))),
+        Ok(None)    => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Match analysis failed: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn compute_match_analysis(pool: &SqlitePool, match_id: &str) -> anyhow::Result<Option<MatchAnalysis>> {
+    use sqlx::Row;
+
+    let row = sqlx::query("SELECT * FROM matches WHERE id = ?")
+        .bind(match_id).fetch_optional(pool).await?;
+    let r = match row { Some(r) => r, None => return Ok(None) };
+
+    let home_id:   String = r.try_get("home_team_id")?;
+    let away_id:   String = r.try_get("away_team_id")?;
+    let home_name: String = r.try_get("home_team_name")?;
+    let away_name: String = r.try_get("away_team_name")?;
+    let sport:     String = r.try_get("sport")?;
+    let league:    String = r.try_get("league")?;
+    let date_str:  String = r.try_get("match_date")?;
+    let match_date = chrono::DateTime::parse_from_rfc3339(&date_str)?.with_timezone(&chrono::Utc);
+
+    // ── ELO ──────────────────────────────────────────────────────────────────
+    let home_elo: f64 = sqlx::query_scalar("SELECT elo_rating FROM teams WHERE id = ?")
+        .bind(&home_id).fetch_optional(pool).await?.unwrap_or(1200.0);
+    let away_elo: f64 = sqlx::query_scalar("SELECT elo_rating FROM teams WHERE id = ?")
+        .bind(&away_id).fetch_optional(pool).await?.unwrap_or(1200.0);
+
+    let hca = home_advantage_for(&league);
+    let elo_diff = home_elo - away_elo;
+    let elo_home_prob = 1.0 / (1.0 + 10f64.powf((away_elo - (home_elo + hca)) / 400.0));
+    let elo_narrative = if elo_diff > 0.0 {
+        format!("{} carries a {:.0}-point ELO edge built from this season's results", home_name, elo_diff)
+    } else {
+        format!("{} is the stronger team by ELO, arriving with a {:.0}-point advantage", away_name, elo_diff.abs())
+    };
+
+    // ── Form ─────────────────────────────────────────────────────────────────
+    struct FormResult { avg: f64, n: i64 }
+
+    async fn rolling_margin(pool: &SqlitePool, team_id: &str, is_home: bool, sport: &str) -> FormResult {
+        let (col, opp_col, filter_col) = if is_home {
+            ("home_score", "away_score", "home_team_id")
+        } else {
+            ("away_score", "home_score", "away_team_id")
+        };
+        let sql = format!(
+            "SELECT {col} - {opp_col} as margin FROM matches
+             WHERE {filter_col} = ? AND status = 'finished'
+               AND {col} IS NOT NULL AND sport = ?
+             ORDER BY match_date DESC LIMIT 15",
+        );
+        let rows = sqlx::query(&sql).bind(team_id).bind(sport).fetch_all(pool).await.unwrap_or_default();
+        let n = rows.len() as i64;
+        if n == 0 { return FormResult { avg: 0.0, n: 0 }; }
+        let sum: f64 = rows.iter().enumerate().map(|(i, row)| {
+            let margin: i32 = row.try_get("margin").unwrap_or(0);
+            let decay = 0.90_f64.powi(i as i32);
+            margin as f64 * decay
+        }).sum();
+        let weight: f64 = (0..n).map(|i| 0.90_f64.powi(i as i32)).sum();
+        FormResult { avg: sum / weight, n }
+    }
+
+    let home_form = rolling_margin(pool, &home_id, true,  &sport).await;
+    let away_form = rolling_margin(pool, &away_id, false, &sport).await;
+
+    let hca_pts = if sport == "basketball" { 3.0_f64 } else { 0.0_f64 };
+    let coeff   = if sport == "basketball" { 0.10_f64 } else { 2.5_f64 };
+    let form_diff = home_form.avg - away_form.avg + hca_pts;
+    let form_home_prob = 1.0 / (1.0 + (-form_diff * coeff).exp());
+
+    let form_narrative = if home_form.n > 0 && away_form.n > 0 {
+        format!(
+            "{} averages {:+.1} pts/gm at home (last {}). {} averages {:+.1} pts/gm away (last {}).",
+            home_name, home_form.avg, home_form.n,
+            away_name, away_form.avg, away_form.n
+        )
+    } else {
+        "Insufficient form data — using league average".into()
+    };
+
+    // ── H2H ──────────────────────────────────────────────────────────────────
+    let h2h_rows = get_matches_for_team(pool, &home_id, &MatchFilter {
+        opponent: Some(&away_id),
+        status: Some("finished"),
+        sport: Some(&sport),
+        limit: Some(10),
+        ..Default::default()
+    })
+    .await?;
+
+    let (mut hw, mut aw, mut draws) = (0i64, 0i64, 0i64);
+    for game in &h2h_rows {
+        let Some(result) = game.result() else { continue };
+        match result.for_side(game.home_team_id == home_id) {
+            "W" => hw += 1,
+            "L" => aw += 1,
+            _ => draws += 1,
+        }
+    }
+    let total_h2h = hw + aw + draws;
+    let h2h_home_prob = if total_h2h == 0 {
+        if sport == "basketball" { 0.55 } else { 0.46 }
+    } else {
+        let raw = hw as f64 / total_h2h as f64;
+        let cred = (total_h2h as f64 / 20.0).min(0.70);
+        raw * cred + 0.55 * (1.0 - cred)
+    };
+    let h2h_narrative = if total_h2h == 0 {
+        "No recent head-to-head history available".into()
+    } else {
+        format!(
+            "{home_name} won {hw} of the last {total_h2h} meetings (lost {aw}, drew {draws})"
+        )
+    };
+
+    // ── Schedule ─────────────────────────────────────────────────────────────
+    async fn last_game_days(pool: &SqlitePool, team_id: &str, before: chrono::DateTime<chrono::Utc>) -> i64 {
+        let last = get_matches_for_team(pool, team_id, &MatchFilter {
+            status: Some("finished"),
+            before: Some(before),
+            limit: Some(1),
+            ..Default::default()
+        })
+        .await.ok().and_then(|mut games| games.pop());
+        last.map(|m| nights_between(m.match_date, before).max(0).saturating_sub(1).min(7))
+            .unwrap_or(3) // well-rested if no prior game
+    }
+
+    async fn consec_away(pool: &SqlitePool, team_id: &str, before: chrono::DateTime<chrono::Utc>) -> i64 {
+        let games = get_matches_for_team(pool, team_id, &MatchFilter {
+            status: Some("finished"),
+            before: Some(before),
+            limit: Some(6),
+            ..Default::default()
+        })
+        .await.unwrap_or_default();
+        let mut n = 0i64;
+        for game in &games {
+            if game.home_team_id == team_id { break; }
+            n += 1;
+        }
+        n
+    }
+
+    let home_rest = last_game_days(pool, &home_id, match_date).await;
+    let away_rest = last_game_days(pool, &away_id, match_date).await;
+    let away_road = consec_away(pool, &away_id, match_date).await;
+
+    let home_b2b = home_rest == 0;
+    let away_b2b = away_rest == 0;
+    let mut sched_adj = 0.0_f64;
+    if home_b2b { sched_adj -= 0.05; }
+    if away_b2b { sched_adj += 0.05; }
+    if away_road >= 3 { sched_adj += 0.015; }
+
+    let sched_narrative = {
+        let mut parts = Vec::new();
+        if home_b2b { parts.push(format!("{} is on a back-to-back", home_name)); }
+        if away_b2b { parts.push(format!("{} is on a back-to-back", away_name)); }
+        if away_road >= 3 { parts.push(format!("{} is on game {} of a road trip", away_name, away_road + 1)); }
+        if parts.is_empty() { "No significant schedule factors".into() }
+        else { parts.join(". ") }
+    };
+
+    // ── Existing prediction ──────────────────────────────────────────────────
+    let pred = sqlx::query(
+        "SELECT home_win_probability, away_win_probability, draw_probability,
+                confidence_score, model_version FROM predictions
+         WHERE match_id = ? ORDER BY created_at DESC LIMIT 1",
+    )
+    .bind(match_id).fetch_optional(pool).await?;
+
+    let (final_home, final_away, draw_prob, confidence, model_version) = match pred {
+        Some(r) => (
+            r.try_get("home_win_probability").unwrap_or(elo_home_prob),
+            r.try_get("away_win_probability").unwrap_or(1.0 - elo_home_prob),
+            r.try_get("draw_probability").ok().flatten(),
+            r.try_get("confidence_score").unwrap_or(0.5),
+            r.try_get::<String, _>("model_version").unwrap_or_default(),
+        ),
+        None => (elo_home_prob, 1.0 - elo_home_prob, None, 0.5_f64, "none".into()),
+    };
+
+    let is_fallback = model_version.contains("fallback") || sport != "basketball";
+    let (w_elo, w_form, w_h2h) = if is_fallback { (0.40, 0.40, 0.20) } else { (0.20, 0.25, 0.10) };
+
+    Ok(Some(MatchAnalysis {
+        match_id: match_id.into(), home_team_name: home_name, away_team_name: away_name, sport,
+        elo: EloComponent { home_elo, away_elo, diff: elo_diff, home_prob: elo_home_prob, weight: w_elo, narrative: elo_narrative },
+        form: FormComponent { home_avg_margin: home_form.avg, away_avg_margin: away_form.avg, home_games_used: home_form.n, away_games_used: away_form.n, home_prob: form_home_prob, weight: w_form, narrative: form_narrative },
+        h2h: H2hComponent { home_wins: hw, away_wins: aw, draws, total: total_h2h, home_prob: h2h_home_prob, weight: w_h2h, narrative: h2h_narrative },
+        schedule: ScheduleComponent { home_rest_days: home_rest, away_rest_days: away_rest, away_on_back_to_back: away_b2b, home_on_back_to_back: home_b2b, away_consecutive_road: away_road, adjustment: sched_adj, narrative: sched_narrative },
+        model_version, final_home_prob: final_home, final_away_prob: final_away, draw_prob, confidence,
+    }))
+}
+
+// Helper function to generate custom datasets
+async fn generate_custom_dataset(
+    pool: &SqlitePool,
+    request: DatasetRequest,
+) -> anyhow::Result<DatasetResponse> {
+    let mut query = String::from("SELECT ");
+    
+    // Build dynamic query based on requested stats categories
+    let mut columns = vec!["m.id", "m.home_team_name", "m.away_team_name", "m.match_date"];
+    
+    for category in &request.stats_categories {
+        match category.as_str() {
+            "basic" => {
+                columns.extend(&["m.home_score", "m.away_score", "m.status"]);
+            }
+            "teams" => {
+                columns.extend(&["ht.elo_rating as home_elo", "at.elo_rating as away_elo"]);
+            }
+            "predictions" => {
+                columns.extend(&["p.home_win_probability", "p.away_win_probability", "p.draw_probability"]);
+            }
+            _ => {}
+        }
+    }
+    
+    query.push_str(&columns.join(", "));
+    query.push_str(" FROM matches m ");
+    
+    if request.stats_categories.contains(&"teams".to_string()) {
+        query.push_str("LEFT JOIN teams ht ON m.home_team_id = ht.id ");
+        query.push_str("LEFT JOIN teams at ON m.away_team_id = at.id ");
+    }
+    
+    if request.stats_categories.contains(&"predictions".to_string()) {
+        query.push_str("LEFT JOIN predictions p ON m.id = p.match_id ");
+    }
+    
+    // Only the allowlisted column list above is spliced in; filter values are always bound
+    query.push_str(
+        "WHERE (?1 IS NULL OR m.sport = ?1) \
+         AND (?2 IS NULL OR m.match_date >= ?2) \
+         AND (?3 IS NULL OR m.match_date <= ?3) \
+         ORDER BY m.match_date DESC LIMIT 1000",
+    );
+
+    let rows = sqlx::query(&query)
+        .bind(Some(request.sport.as_str()).filter(|s| !s.is_empty()))
+        .bind(request.date_from.map(|d| d.to_rfc3339()))
+        .bind(request.date_to.map(|d| d.to_rfc3339()))
+        .fetch_all(pool)
+        .await?;
+    
+    // Generate file based on format; the sport is user input, so keep it to a safe file name
+    let sport_slug: String = request.sport.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let filename = format!("dataset_{}_{}.{}{}", 
+        sport_slug, 
+        chrono::Utc::now().timestamp(), 
+        request.format,
+        if request.compress { ".gz" } else { "" },
+    );
+    
+    let file_path = format!("../data/exports/{}", filename);
+    
+    // Create exports directory if it doesn't exist
+    tokio::fs::create_dir_all("../data/exports").await?;
+    
+    // Strip SQL aliases from header names:
+    //   "m.home_team_name"        → "home_team_name"
+    //   "ht.elo_rating as home_elo" → "home_elo"
+    let headers: Vec<String> = columns.iter().map(|col| {
+        // Take the alias after " as " if present, otherwise use the raw column expression.
+        let col = if let Some(pos) = col.to_lowercase().find(" as ") {
+            col[pos + 4..].trim()
+        } else {
+            col.trim()
+        };
+        // Strip the "table." prefix from "table.column".
+        if let Some(dot) = col.rfind('.') { col[dot + 1..].to_string() } else { col.to_string() }
+    }).collect();
+
+    // Helper: read a row cell as a plain string regardless of its SQLite type.
+    let cell_to_string = |row: &sqlx::sqlite::SqliteRow, i: usize| -> String {
+        if let Ok(v) = row.try_get::<String, _>(i)  { return v; }
+        if let Ok(v) = row.try_get::<f64, _>(i)     { return v.to_string(); }
+        if let Ok(v) = row.try_get::<i64, _>(i)     { return v.to_string(); }
+        if let Ok(v) = row.try_get::<bool, _>(i)    { return v.to_string(); }
+        String::new() // NULL
+    };
+
+    let (body, content_type) = match request.format.as_str() {
+        "csv" => {
+            let mut writer = csv::Writer::from_writer(vec![]);
+            writer.write_record(&headers)?;
+            for row in &rows {
+                let record: Vec<String> = (0..columns.len())
+                    .map(|i| cell_to_string(row, i))
+                    .collect();
+                writer.write_record(&record)?;
+            }
+            (writer.into_inner()?, "text/csv")
+        }
+        "json" => {
+            let data: Vec<HashMap<String, String>> = rows.iter().map(|row| {
+                headers.iter().enumerate()
+                    .map(|(i, h)| (h.clone(), cell_to_string(row, i)))
+                    .collect()
+            }).collect();
+            (serde_json::to_vec_pretty(&data)?, "application/json")
+        }
+        _ => return Err(anyhow::anyhow!("Unsupported format: {}", request.format)),
+    };
+
+    let (body, content_type) = if request.compress {
+        use std::io::Write;
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&body)?;
+        (encoder.finish()?, "application/gzip")
+    } else {
+        (body, content_type)
+    };
+    tokio::fs::write(&file_path, body).await?;
+    
+    Ok(DatasetResponse {
+        download_url: format!("/downloads/{}", filename),
+        format: request.format,
+        content_type: content_type.to_string(),
+        compressed: request.compress,
+        rows: rows.len(),
+        generated_at: chrono::Utc::now(),
+    })
+}
+
+// ── ML endpoints ──────────────────────────────────────────────────────────────
+
+/// POST /models/train — Trigger full ML retrain pipeline
+async fn trigger_train_handler(
+    State(pool): State<SqlitePool>,
+) -> Json<ApiResponse<String>> {
+    // Run training in background to avoid blocking the HTTP response
+    let pool_clone = pool.clone();
+    tokio::spawn(async move {
+        match train_and_save(&pool_clone).await {
+            Ok((version, state, _)) => {
+                set_ml_model(state).await;
+                tracing::info!("ML training complete — saved as version {}", version);
+            }
+            Err(e) => tracing::error!("ML training failed: {}", e),
+        }
+    });
+
+    Json(ApiResponse::success("Training started in background. Check /models/evaluate for results.".to_string()))
+}
+
+/// GET /models/evaluate — Return latest backtest results, plus a live score for each
+/// registry configuration over the football matches it predicted that have since finished
+async fn get_model_evaluations_handler(
+    State(pool): State<SqlitePool>,
+) -> Json<ApiResponse<Vec<MlEvaluation>>> {
+    let rows = sqlx::query(
+        "SELECT model_name, fold, brier_score, log_loss, accuracy, n_games, evaluated_at FROM backtest_results ORDER BY model_name, fold"
+    ).fetch_all(&pool).await;
+
+    match rows {
+        Ok(rows) => {
+            let mut evals: Vec<MlEvaluation> = rows.iter().map(|r| MlEvaluation {
+                model_name: r.get("model_name"),
+                fold: r.get("fold"),
+                year: 0,
+                n_games: r.get("n_games"),
+                brier_score: r.get("brier_score"),
+                log_loss: r.get("log_loss"),
+                accuracy: r.get("accuracy"),
+                evaluated_at: r.get("evaluated_at"),
+            }).collect();
+            for config in model_registry() {
+                match evaluate_model_config(&pool, &config).await {
+                    Ok(Some(eval)) => evals.push(eval),
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("Could not score model config {}: {}", config.name, e),
+                }
+            }
+            Json(ApiResponse::success(evals))
+        }
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+/// GET /matches/:id/explain — Feature importance for a prediction
+async fn explain_prediction_handler(
+    Path(match_id): Path<String>,
+    State(pool): State<SqlitePool>,
+) -> Json<ApiResponse<Vec<FeatureContribution>>> {
+    use crate::ml::MlPredictor;
+
+    // Look up the match
+    let m = match get_match_by_id(&pool, &match_id).await {
+        Ok(Some(m)) => m,
+        Ok(None) => return Json(ApiResponse::error("Match not found".to_string())),
+        Err(e) => return Json(ApiResponse::error(e.to_string())),
+    };
+
+    let mut predictor = MlPredictor::new();
+    let _ = predictor.load_from_db(&pool).await;
+    if !predictor.has_model() {
+        return Json(ApiResponse::error("No trained ML model available. Run POST /models/train first.".to_string()));
+    }
+
+    match predictor.feature_importance(&pool, &m).await {
+        Some(importances) => {
+            let contribs: Vec<FeatureContribution> = importances.iter().map(|(name, val, contrib)| {
+                FeatureContribution { feature_name: name.clone(), feature_value: *val, contribution: *contrib }
+            }).collect();
+            Json(ApiResponse::success(contribs))
+        }
+        None => Json(ApiResponse::error("Could not compute feature importance".to_string())),
+    }
+}
+
+/// GET /predictions/:id/explain — Inputs snapshot captured when the prediction was made
+async fn get_prediction_inputs_handler(
+    Path(prediction_id): Path<String>,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<ApiResponse<PredictionInputs>>, StatusCode> {
+    match get_prediction_inputs(&pool, &prediction_id).await {
+        Ok(Some(inputs)) => Ok(Json(ApiResponse::success(inputs))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to load prediction inputs: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// GET /predictions/:match_id/history?model_version= — Every prediction stored for a match, oldest first
+#[derive(Deserialize)]
+struct PredictionHistoryQuery {
+    /// Only this model's predictions, e.g. to follow one registry candidate
+    model_version: Option<String>,
+}
+
+async fn get_prediction_history_handler(
+    Path(match_id): Path<String>,
+    Query(params): Query<PredictionHistoryQuery>,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<ApiResponse<Vec<Prediction>>>, StatusCode> {
+    match compute_prediction_history(&pool, &match_id, params.model_version.as_deref()).await {
+        Ok(Some(history)) => Ok(Json(ApiResponse::success(history))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to load prediction history for {}: {}", match_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// None when the match doesn't exist. Predictions are appended on every generation that
+/// moved by more than PREDICTION_CHANGE_EPSILON, so this is the drift over time.
+async fn compute_prediction_history(
+    pool: &SqlitePool,
+    match_id: &str,
+    model_version: Option<&str>,
+) -> anyhow::Result<Option<Vec<Prediction>>> {
+    if get_match_status(pool, match_id).await?.is_none() {
+        return Ok(None);
+    }
+    let mut history = get_prediction_history(pool, match_id).await?;
+    if let Some(version) = model_version {
+        history.retain(|p| p.model_version == version);
+    }
+    Ok(Some(history))
+}
+
+/// GET /predictions/:id/distribution — Monte Carlo score distribution
+async fn get_score_distribution_handler(
+    Path(pred_id): Path<String>,
+    State(pool): State<SqlitePool>,
+) -> Json<ApiResponse<ScoreDistribution>> {
+    use crate::ml::MlPredictor;
+
+    // Get match_id from prediction
+    let pred_row = sqlx::query("SELECT match_id FROM predictions WHERE id = ?")
+        .bind(&pred_id).fetch_optional(&pool).await;
+
+    let match_id = match pred_row {
+        Ok(Some(r)) => r.get::<String, _>("match_id"),
+        Ok(None) => return Json(ApiResponse::error("Prediction not found".to_string())),
+        Err(e) => return Json(ApiResponse::error(e.to_string())),
+    };
+
+    let m = match get_match_by_id(&pool, &match_id).await {
+        Ok(Some(m)) => m,
+        Ok(None) => return Json(ApiResponse::error("Match not found".to_string())),
+        Err(e) => return Json(ApiResponse::error(e.to_string())),
+    };
+
+    let mut predictor = MlPredictor::new();
+    let _ = predictor.load_from_db(&pool).await;
+
+    match predictor.score_distribution(&pool, &m).await {
+        Some(buckets) => {
+            let p_home = buckets[40..].iter().sum::<f64>();
+            let expected: f64 = buckets.iter().enumerate()
+                .map(|(i, &p)| p * (i as f64 - 40.0)).sum();
+            Json(ApiResponse::success(ScoreDistribution {
+                match_id,
+                p_home_win: p_home,
+                expected_margin: expected,
+                buckets,
+            }))
+        }
+        None => Json(ApiResponse::error("No trained ML model available".to_string())),
+    }
+}
+
+/// GET /matches/history — paginated finished games with scores
+#[derive(Deserialize)]
+struct HistoryQuery {
+    sport: Option<String>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+#[derive(Serialize)]
+struct HistoryGame {
+    match_id: String,
+    home_team: String,
+    away_team: String,
+    match_date: String,
+    home_score: i32,
+    away_score: i32,
+    home_won: bool,
+    season: i32,
+}
+
+async fn get_match_history_handler(
+    State(pool): State<SqlitePool>,
+    Query(params): Query<HistoryQuery>,
+) -> Json<ApiResponse<Vec<HistoryGame>>> {
+    let sport = params.sport.as_deref().unwrap_or("basketball");
+    let limit = params.limit.unwrap_or(50).min(500);
+    let offset = params.offset.unwrap_or(0);
+
+    let rows = sqlx::query(
+        "SELECT id, home_team_name, away_team_name, match_date, home_score, away_score
+         FROM matches
+         WHERE sport = ? AND status = 'finished'
+           AND home_score IS NOT NULL AND away_score IS NOT NULL
+         ORDER BY match_date DESC LIMIT ? OFFSET ?"
+    )
+    .bind(sport).bind(limit).bind(offset)
+    .fetch_all(&pool).await;
+
+    match rows {
+        Ok(rows) => {
+            let games: Vec<HistoryGame> = rows.iter().filter_map(|r| {
+                let hs: i32 = r.try_get("home_score").ok()?;
+                let aws: i32 = r.try_get("away_score").ok()?;
+                let date_str: String = r.try_get("match_date").ok()?;
+                let season = chrono::DateTime::parse_from_rfc3339(&date_str)
+                    .map(|d| d.format("%Y").to_string().parse::<i32>().unwrap_or(2024))
+                    .unwrap_or(2024);
+                Some(HistoryGame {
+                    match_id: r.try_get("id").ok()?,
+                    home_team: r.try_get("home_team_name").ok()?,
+                    away_team: r.try_get("away_team_name").ok()?,
+                    match_date: date_str,
+                    home_score: hs,
+                    away_score: aws,
+                    home_won: hs > aws,
+                    season,
+                })
+            }).collect();
+            Json(ApiResponse::success(games))
+        }
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{insert_prediction, upsert_market_odds};
+    use crate::models::EdgeStatus;
+
+    /// Fresh in-memory database with the full schema and sample data.
+    async fn seeded_pool() -> SqlitePool {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        init_database_with_pool(&pool).await.unwrap();
+        seed_data(&pool).await.unwrap();
+        pool
+    }
+
+    fn no_cache() -> State<ResponseCache> {
+        State(ResponseCache::with_ttl(std::time::Duration::ZERO))
+    }
+
+    /// Held by tests that call handlers taking `pipeline_lock`, so parallel tests never see
+    /// each other's CONFLICT.
+    async fn pipeline_test_guard() -> tokio::sync::MutexGuard<'static, ()> {
+        static GUARD: OnceLock<tokio::sync::Mutex<()>> = OnceLock::new();
+        GUARD.get_or_init(|| tokio::sync::Mutex::new(())).lock().await
+    }
+
+    #[tokio::test]
+    async fn recent_form_credits_tiebreak_winner() {
+        let pool = seeded_pool().await;
+        let m = get_finished_matches_ordered(&pool).await.unwrap().into_iter()
+            .find(|m| m.sport == "football")
+            .unwrap();
+        // Level after extra time, won on penalties by the home side: the newest result
+        let shootout = Match {
+            id: "form_shootout".into(),
+            match_date: chrono::Utc::now() - chrono::Duration::hours(1),
+            home_score: Some(1),
+            away_score: Some(1),
+            tiebreak_winner: Some("home".into()),
+            ..m.clone()
+        };
+        crate::db::insert_match(&pool, &shootout).await.unwrap();
+
+        assert!(recent_form(&pool, &m.home_team_id, true).await.starts_with('W'));
+        assert!(recent_form(&pool, &m.away_team_id, true).await.starts_with('L'));
+    }
+
+    #[tokio::test]
+    async fn league_insights_pick_out_confident_and_close_matchups() {
+        let pool = seeded_pool().await;
+
+        let Json(resp) = get_league_insights_handler(
+            State(pool),
+            Path(("football".to_string(), "EPL".to_string())),
+        )
+        .await
+        .unwrap();
+        let insights = resp.data.unwrap();
+
+        assert_eq!(insights.predicted_matches, 8);
+        // Man City (1520) hosting Chelsea (1420) is the widest ELO gap on the card
+        let top = insights.highest_confidence.unwrap();
+        assert_eq!(top.match_info.home_team_name, "Manchester City");
+        assert_eq!(top.match_info.away_team_name, "Chelsea");
+        // Arsenal (1450) v Liverpool (1480) is near level once home advantage is applied
+        let close = insights.closest_matchup.unwrap();
+        assert_eq!(close.match_info.home_team_name, "Arsenal");
+        assert_eq!(close.match_info.away_team_name, "Liverpool");
+        // Seed data carries no market odds
+        assert!(insights.biggest_edge.is_none());
+        assert!(insights.avg_home_win_probability.unwrap() > 0.0);
+    }
+
+    #[tokio::test]
+    async fn app_state_shares_one_fetcher() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        let state = AppState::new(pool);
+
+        // Axum clones the state for every request; the scheduler gets its own handle
+        let per_request = state.clone();
+        let from_state = Arc::<DataFetcher>::from_ref(&per_request);
+        let scheduler_handle = state.fetcher.clone();
+
+        assert!(Arc::ptr_eq(&from_state, &state.fetcher));
+        assert!(Arc::ptr_eq(&scheduler_handle, &state.fetcher));
+    }
+
+    #[tokio::test]
+    async fn within_days_bounds_upcoming_horizon() {
+        let pool = seeded_pool().await;
+        let query = |within_days| UpcomingMatchesQuery { sport: Some("football".into()), limit: None, within_days, nocache: false, updated_since: None };
+
+        let Json(all) = get_upcoming_matches_handler(State(pool.clone()), no_cache(), Query(query(None))).await.unwrap();
+        let all = all.data.unwrap();
+        assert!(all.iter().any(|m| m.match_info.home_team_name == "Everton"));
+
+        // Seeded EPL fixtures sit 2, 5 and 7 days out within the week; Everton v Forest is 21 days out
+        let Json(week) = get_upcoming_matches_handler(State(pool), no_cache(), Query(query(Some(7)))).await.unwrap();
+        let week = week.data.unwrap();
+        assert_eq!(week.len(), 3);
+        assert!(week.iter().all(|m| m.match_info.home_team_name != "Everton"));
+        assert!(week.len() < all.len());
+    }
+
+    #[tokio::test]
+    async fn upset_win_earns_more_points_than_xpts() {
+        let pool = seeded_pool().await;
+        // Seeded result: Arsenal 2-1 Manchester City
+        let m = get_finished_matches_ordered(&pool).await.unwrap()
+            .into_iter()
+            .find(|m| m.home_team_id == "epl_1" && m.away_team_id == "epl_2")
+            .unwrap();
+        crate::db::insert_prediction(&pool, &crate::models::Prediction {
+            id: "upset".into(),
+            match_id: m.id.clone(),
+            home_win_probability: 0.20,
+            away_win_probability: 0.55,
+            draw_probability: Some(0.25),
+            model_version: "test".into(),
+            confidence_score: 0.6,
+            created_at: chrono::Utc::now(),
+            insufficient_data: false,
+            most_likely_score: None,
+        }).await.unwrap();
+
+        compute_season_stats(&pool).await;
+
+        let (xpts, actual): (f64, f64) = sqlx::query_as(
+            "SELECT xpts, actual_points FROM match_xpts WHERE match_id = ? AND team_id = 'epl_1'",
+        )
+        .bind(&m.id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert!((xpts - 0.85).abs() < 1e-9);
+        assert_eq!(actual, 3.0);
+        assert!(actual > xpts);
+
+        let Json(resp) = get_standings_handler(
+            State(pool),
+            no_cache(),
+            Path(("football".to_string(), "EPL".to_string())),
+            Query(CacheQuery { nocache: false }),
+        )
+        .await
+        .unwrap();
+        let arsenal = resp.data.unwrap().into_iter().find(|r| r.team_id == "epl_1").unwrap();
+        assert_eq!(arsenal.points, 3);
+        assert!(arsenal.xpts_diff.unwrap() > 0.0);
+    }
+
+    #[tokio::test]
+    async fn xpts_diff_covers_only_predicted_matches_this_season() {
+        let pool = seeded_pool().await;
+        // Seeded result: Arsenal 2-1 Manchester City
+        let m = get_finished_matches_ordered(&pool).await.unwrap()
+            .into_iter()
+            .find(|m| m.home_team_id == "epl_1")
+            .unwrap();
+        let (season_start, _) = season_bounds(&season_of(chrono::Utc::now())).unwrap();
+        let days = chrono::Duration::days;
+        for (id, match_date) in [("predicted", season_start + days(1)), ("unpredicted", season_start + days(2)), ("last_season", season_start - days(30))] {
+            crate::db::insert_match(&pool, &Match { id: id.into(), match_date, ..m.clone() }).await.unwrap();
+        }
+        for match_id in ["predicted", "last_season"] {
+            crate::db::insert_prediction(&pool, &crate::models::Prediction {
+                id: format!("{}_prediction", match_id),
+                match_id: match_id.into(),
+                home_win_probability: 0.20,
+                away_win_probability: 0.55,
+                draw_probability: Some(0.25),
+                model_version: "test".into(),
+                confidence_score: 0.6,
+                created_at: chrono::Utc::now(),
+                insufficient_data: false,
+                most_likely_score: None,
+            }).await.unwrap();
+        }
+        compute_season_stats(&pool).await;
+
+        let table = get_standings(&pool, "football", "EPL").await.unwrap();
+        let arsenal = table.iter().find(|r| r.team_id == "epl_1").unwrap();
+        assert_eq!(arsenal.points, 12);
+        // Only the predicted win this season: 3 − (3·0.20 + 0.25)
+        assert!((arsenal.xpts.unwrap() - 0.85).abs() < 1e-9);
+        assert!((arsenal.xpts_diff.unwrap() - 2.15).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn stats_overview_counts_seeded_data() {
+        let pool = seeded_pool().await;
+
+        let Json(resp) = get_stats_overview_handler(State(pool)).await.unwrap();
+        let overview = resp.data.unwrap();
+
+        let counts = |sport: &str| {
+            let c = overview.sports.iter().find(|c| c.sport == sport).unwrap();
+            (c.teams, c.matches)
+        };
+        assert_eq!(counts("football"), (20, 13));
+        assert_eq!(counts("basketball"), (30, 13));
+        // EPL fixtures 2/5/7 days out, NBA games 1/3/4/6 days out
+        assert_eq!(overview.upcoming_next_7_days, 7);
+        assert_eq!(overview.value_edges, 0);
+
+        let leader = |league: &str| overview.top_teams.iter().find(|t| t.league == league).unwrap().name.clone();
+        assert_eq!(leader("EPL"), "Manchester City");
+        assert_eq!(leader("NBA"), "Boston Celtics");
+        assert_eq!(overview.top_teams.len(), 2);
+        assert!(overview.last_data_refresh.is_some());
+    }
+
+    #[tokio::test]
+    async fn edge_debug_reports_missing_odds() {
+        let pool = seeded_pool().await;
+
+        let Json(resp) = get_edge_diagnostics_handler(State(pool)).await.unwrap();
+        let diagnostics = resp.data.unwrap();
+
+        assert!(!diagnostics.is_empty());
+        for d in &diagnostics {
+            assert!(d.has_prediction);
+            assert!(!d.has_odds);
+            assert_eq!(d.reason, "no odds");
+            assert!(d.edge_value.is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn postponed_match_is_not_upcoming() {
+        let pool = seeded_pool().await;
+        let postponed_id: String = sqlx::query_scalar(
+            "SELECT id FROM matches WHERE status = 'scheduled' AND sport = 'football' ORDER BY match_date LIMIT 1",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        sqlx::query("UPDATE matches SET status = 'postponed' WHERE id = ?")
+            .bind(&postponed_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let Json(resp) = get_upcoming_matches_handler(
+            State(pool.clone()),
+            no_cache(),
+            Query(UpcomingMatchesQuery { sport: Some("football".into()), limit: None, within_days: None, nocache: false, updated_since: None }),
+        )
+        .await
+        .unwrap();
+        let upcoming = resp.data.unwrap();
+        assert_eq!(upcoming.len(), 7);
+        assert!(upcoming.iter().all(|m| m.match_info.id != postponed_id));
+
+        let Json(resp) = get_postponed_matches_handler(State(pool), Query(SportQuery { sport: None }))
+            .await
+            .unwrap();
+        let postponed = resp.data.unwrap();
+        assert_eq!(postponed.len(), 1);
+        assert_eq!(postponed[0].id, postponed_id);
+    }
+
+    #[tokio::test]
+    async fn reseed_after_wipe_restores_sample_data() {
+        let _serial = pipeline_test_guard().await;
+        let pool = seeded_pool().await;
+        let seeded_teams: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM teams")
+            .fetch_one(&pool).await.unwrap();
+
+        clear_all_data(&pool).await.unwrap();
+        let wiped: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM teams")
+            .fetch_one(&pool).await.unwrap();
+        assert_eq!(wiped, 0);
+
+        let Json(resp) = reseed_handler(State(pool.clone()), no_cache()).await.unwrap();
+        assert!(resp.success);
+        let teams: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM teams")
+            .fetch_one(&pool).await.unwrap();
+        assert_eq!(teams, seeded_teams);
+
+        // Derived tables are rebuilt, not left empty
+        let stats: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM team_stats")
+            .fetch_one(&pool).await.unwrap();
+        let predictions: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM predictions")
+            .fetch_one(&pool).await.unwrap();
+        assert!(stats > 0);
+        assert!(predictions > 0);
+    }
+
+    #[tokio::test]
+    async fn divergence_ranks_disagreement_above_agreement() {
+        let pool = seeded_pool().await;
+        let games: Vec<(String, f64)> = sqlx::query_as(
+            r#"SELECT m.id, p.home_win_probability FROM matches m
+               JOIN predictions p ON p.match_id = m.id
+               WHERE m.sport = 'basketball' AND m.status = 'scheduled'
+               ORDER BY m.match_date LIMIT 2"#,
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        let (agree_id, agree_p) = &games[0];
+        let (disagree_id, disagree_p) = &games[1];
+
+        // Fair (no-overround) prices: one matches our number, one has the home side 20% lower
+        upsert_market_odds(&pool, agree_id, "Test", 1.0 / agree_p, None, 1.0 / (1.0 - agree_p))
+            .await.unwrap();
+        let market_home = disagree_p - 0.20;
+        upsert_market_odds(&pool, disagree_id, "Test", 1.0 / market_home, None, 1.0 / (1.0 - market_home))
+            .await.unwrap();
+
+        let Json(resp) = get_market_divergence_handler(State(pool)).await.unwrap();
+        let ranked = resp.data.unwrap();
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(&ranked[0].match_id, disagree_id);
+        assert_eq!(ranked[0].side, "home");
+        assert!((ranked[0].divergence - 0.20).abs() < 1e-6);
+        assert_eq!(&ranked[1].match_id, agree_id);
+        assert!(ranked[1].divergence.abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn prediction_inputs_snapshot_ratings_at_generation() {
+        let pool = seeded_pool().await;
+        // Goal records for the Poisson component
+        compute_season_stats(&pool).await;
+        refresh_predictions(&pool).await;
+
+        let (prediction_id, home_id, away_id): (String, String, String) = sqlx::query_as(
+            r#"SELECT p.id, m.home_team_id, m.away_team_id FROM predictions p
+               JOIN matches m ON m.id = p.match_id
+               WHERE m.sport = 'football' AND p.model_version <> 'seed_v1'
+               ORDER BY m.match_date LIMIT 1"#,
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let elo = |id: String| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query_scalar::<_, f64>("SELECT elo_rating FROM teams WHERE id = ?")
+                    .bind(id).fetch_one(&pool).await.unwrap()
+            }
+        };
+        let (home_elo, away_elo) = (elo(home_id.clone()).await, elo(away_id).await);
+
+        // Ratings move on after the prediction was made
+        sqlx::query("UPDATE teams SET elo_rating = elo_rating + 250 WHERE id = ?")
+            .bind(&home_id).execute(&pool).await.unwrap();
+
+        let Json(resp) = get_prediction_inputs_handler(Path(prediction_id.clone()), State(pool.clone()))
+            .await
+            .unwrap();
+        let inputs = resp.data.unwrap();
+        assert_eq!(inputs.prediction_id, prediction_id);
+        assert_eq!(inputs.home_elo, home_elo);
+        assert_eq!(inputs.away_elo, away_elo);
+        // ELO, H2H, form and Poisson goals
+        assert_eq!(inputs.weights.len(), 4);
+        assert!((inputs.weights.values().sum::<f64>() - 1.0).abs() < 1e-9);
+        // The H2H component's own output, not a raw tally
+        let h2h = [inputs.h2h_home_prob, inputs.h2h_away_prob, inputs.h2h_draw_prob];
+        assert!((h2h.iter().map(|p| p.unwrap()).sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(inputs.home_form_rate.is_none_or(|r| (0.0..=1.0).contains(&r)));
+        let score: Option<String> = sqlx::query_scalar("SELECT most_likely_score FROM predictions WHERE id = ?")
+            .bind(&prediction_id).fetch_one(&pool).await.unwrap();
+        assert!(score.is_some_and(|s| s.contains('-')));
+    }
+
+    #[tokio::test]
+    async fn predicted_scoreline_matches_the_simulator_model() {
+        let pool = seeded_pool().await;
+        refresh_predictions(&pool).await;
+
+        let (match_id, score): (String, Option<String>) = sqlx::query_as(
+            r#"SELECT p.match_id, p.most_likely_score FROM predictions p
+               JOIN matches m ON m.id = p.match_id
+               WHERE m.sport = 'football' AND p.model_version <> 'seed_v1' AND p.insufficient_data = 0
+               ORDER BY m.match_date LIMIT 1"#,
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let m = get_match_by_id(&pool, &match_id).await.unwrap().unwrap();
+        let (home_rate, away_rate) = expected_scores(&pool, &m).await.unwrap();
+        let (h, a) = ScoreModel::Poisson { home_rate, away_rate }.most_likely_score().unwrap();
+        assert_eq!(score, Some(format!("{}-{}", h, a)));
+    }
+
+    #[tokio::test]
+    async fn live_match_is_listed_live_not_upcoming() {
+        let pool = seeded_pool().await;
+        // Kickoff clocks can lag: a match already live may still carry a future match_date
+        let live_id: String = sqlx::query_scalar(
+            "SELECT id FROM matches WHERE status = 'scheduled' AND sport = 'football' ORDER BY match_date LIMIT 1",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        sqlx::query("UPDATE matches SET status = 'live', home_score = 1, away_score = 0 WHERE id = ?")
+            .bind(&live_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let Json(resp) = get_upcoming_matches_handler(
+            State(pool.clone()),
+            no_cache(),
+            Query(UpcomingMatchesQuery { sport: Some("football".into()), limit: None, within_days: None, nocache: false, updated_since: None }),
+        )
+        .await
+        .unwrap();
+        assert!(resp.data.unwrap().iter().all(|m| m.match_info.id != live_id));
+
+        let Json(resp) = get_live_matches_handler(State(pool), Query(SportQuery { sport: None }))
+            .await
+            .unwrap();
+        let live = resp.data.unwrap();
+        assert_eq!(live.len(), 1);
+        assert_eq!(live[0].match_info.id, live_id);
+        assert_eq!(live[0].match_info.home_score, Some(1));
+        // Leading at kickoff time: in-play home chance beats the pre-match number
+        let pre = live[0].prediction.as_ref().unwrap().home_win_probability;
+        assert!(live[0].in_play_home_probability.unwrap() > pre);
+    }
+
+    #[tokio::test]
+    async fn leaders_rank_by_elo_and_defense() {
+        let pool = seeded_pool().await;
+        compute_season_stats(&pool).await;
+        let leaders = |metric| {
+            let pool = pool.clone();
+            async move {
+                let Json(resp) = get_league_leaders_handler(
+                    State(pool),
+                    Path(("football".to_string(), "EPL".to_string())),
+                    Query(LeadersQuery { metric }),
+                )
+                .await
+                .unwrap();
+                resp.data.unwrap()
+            }
+        };
+
+        let by_elo: Vec<String> = leaders(LeaderMetric::Elo).await.into_iter().map(|r| r.team_id).collect();
+        let expected: Vec<String> = get_all_teams(&pool).await.unwrap().into_iter()
+            .filter(|t| t.sport == "football" && t.league == "EPL")
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(by_elo, expected);
+
+        let defense = leaders(LeaderMetric::Defense).await;
+        assert!(!defense.is_empty());
+        assert!(defense.windows(2).all(|w| w[0].value <= w[1].value));
+        assert_eq!(defense[0].rank, 1);
+    }
+
+    #[tokio::test]
+    async fn idempotency_key_replays_dataset_response() {
+        let pool = seeded_pool().await;
+        let request = || DatasetRequest {
+            sport: "basketball".into(),
+            teams: None,
+            date_from: None,
+            date_to: None,
+            stats_categories: vec!["basic".into()],
+            format: "json".into(),
+            compress: false,
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert("idempotency-key", "export-1".parse().unwrap());
+
+        let Json(first) = generate_dataset_handler(State(pool.clone()), headers.clone(), Json(request()))
+            .await
+            .unwrap();
+        let Json(second) = generate_dataset_handler(State(pool.clone()), headers.clone(), Json(request()))
+            .await
+            .unwrap();
+        let (first, second) = (first.data.unwrap(), second.data.unwrap());
+        assert_eq!(first.download_url, second.download_url);
+        // Replayed, not regenerated: same generation timestamp and one stored key
+        assert_eq!(first.generated_at, second.generated_at);
+        let keys: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM idempotency_keys")
+            .fetch_one(&pool).await.unwrap();
+        assert_eq!(keys, 1);
+
+        // Reusing the key for a different body is rejected
+        let other = DatasetRequest { format: "csv".into(), ..request() };
+        let err = generate_dataset_handler(State(pool.clone()), headers.clone(), Json(other)).await.unwrap_err();
+        assert_eq!(err.status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        // Once the export is deleted, a replay regenerates it instead of handing out a dead link
+        let file = first.download_url.trim_start_matches("/downloads/");
+        std::fs::remove_file(format!("../data/exports/{}", file)).unwrap();
+        let Json(third) = generate_dataset_handler(State(pool), headers, Json(request()))
+            .await
+            .unwrap();
+        let third = third.data.unwrap();
+        assert!(export_exists(&third).await);
+        assert_ne!(third.generated_at, first.generated_at);
+
+        let file = third.download_url.trim_start_matches("/downloads/");
+        let _ = std::fs::remove_file(format!("../data/exports/{}", file));
+    }
+
+    #[tokio::test]
+    async fn top_epl_team_is_elite_hundredth_percentile() {
+        let pool = seeded_pool().await;
+
+        let full = || Query(TeamStatsQuery { recent: None, elo_limit: None });
+        let Json(resp) = get_team_stats_handler(State(pool.clone()), Path("epl_2".to_string()), full())
+            .await
+            .unwrap();
+        let top = resp.data.unwrap().team;
+        assert_eq!(top.name, "Manchester City");
+        assert_eq!(top.elo_percentile, Some(100.0));
+        assert_eq!(top.elo_tier.as_deref(), Some("Elite"));
+
+        let Json(resp) = get_teams_by_league_handler(
+            State(pool),
+            Path(("football".to_string(), "EPL".to_string())),
+        )
+        .await
+        .unwrap();
+        let teams = resp.data.unwrap();
+        assert!(teams.iter().all(|t| t.elo_percentile.is_some()));
+        assert!(teams.iter().any(|t| t.elo_tier.as_deref() == Some("Weak")));
+    }
+
+    #[tokio::test]
+    async fn past_dated_scheduled_match_is_stale() {
+        let pool = seeded_pool().await;
+        let stale_id: String = sqlx::query_scalar(
+            "SELECT id FROM matches WHERE status = 'scheduled' AND sport = 'basketball' ORDER BY match_date LIMIT 1",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let two_days_ago = (chrono::Utc::now() - chrono::Duration::days(2)).to_rfc3339();
+        sqlx::query("UPDATE matches SET match_date = ? WHERE id = ?")
+            .bind(two_days_ago)
+            .bind(&stale_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let Json(resp) = get_stale_matches_handler(State(pool.clone()), Query(SportQuery { sport: None }))
+            .await
+            .unwrap();
+        let stale = resp.data.unwrap();
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].id, stale_id);
+        assert_eq!(count_stale_matches(&pool, None, stale_grace_hours()).await.unwrap(), 1);
+        assert_eq!(count_stale_matches(&pool, Some("football"), stale_grace_hours()).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn upcoming_regenerates_stale_prediction() {
+        let pool = seeded_pool().await;
+        let m = get_upcoming_matches(&pool, Some("football")).await.unwrap().remove(0);
+
+        // A newer-than-seed row that is nonetheless days old, as if the scheduler had stalled
+        let old = crate::models::Prediction {
+            id: "stale_pred".into(),
+            match_id: m.id.clone(),
+            home_win_probability: 0.4,
+            away_win_probability: 0.3,
+            draw_probability: Some(0.3),
+            model_version: "stale".into(),
+            confidence_score: 0.5,
+            created_at: chrono::Utc::now() - chrono::Duration::days(3),
+            insufficient_data: false,
+            most_likely_score: None,
+        };
+        sqlx::query("UPDATE predictions SET created_at = ? WHERE match_id = ?")
+            .bind((old.created_at - chrono::Duration::days(1)).to_rfc3339())
+            .bind(&m.id)
+            .execute(&pool).await.unwrap();
+        insert_prediction(&pool, &old).await.unwrap();
+
+        let Json(resp) = get_upcoming_matches_handler(
+            State(pool.clone()),
+            no_cache(),
+            Query(UpcomingMatchesQuery { sport: Some("football".into()), limit: Some(100), within_days: None, nocache: false, updated_since: None }),
+        )
+        .await
+        .unwrap();
+        let served = resp.data.unwrap().into_iter()
+            .find(|u| u.match_info.id == m.id)
+            .and_then(|u| u.prediction)
+            .unwrap();
+        assert_ne!(served.id, old.id);
+        assert!(chrono::Utc::now() - served.created_at < chrono::Duration::minutes(5));
+    }
+
+    #[tokio::test]
+    async fn batch_prediction_reports_unknown_teams_per_item() {
+        let pool = seeded_pool().await;
+        let fixture = |home: &str, away: &str, neutral| HypotheticalFixture {
+            home_id: home.into(),
+            away_id: away.into(),
+            sport: "football".into(),
+            neutral,
+        };
+
+        let Json(resp) = predict_batch_handler(
+            State(pool.clone()),
+            Query(PredictBatchQuery { h2h_weight: None }),
+            Json(vec![fixture("epl_1", "epl_2", false), fixture("epl_3", "no_such_team", false), fixture("epl_1", "epl_2", true)]),
+        )
+        .await
+        .unwrap();
+        let results = resp.data.unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results.iter().filter(|r| r.prediction.is_some()).count(), 2);
+        assert!(results[1].prediction.is_none());
+        assert!(results[1].error.as_deref().unwrap().contains("no_such_team"));
+
+        // Taking away Arsenal's home advantage can only help Man City
+        let home = results[0].prediction.as_ref().unwrap();
+        let neutral = results[2].prediction.as_ref().unwrap();
+        assert!(neutral.away_win_probability > home.away_win_probability);
+
+        let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM predictions WHERE match_id LIKE 'hypothetical_%'")
+            .fetch_one(&pool).await.unwrap();
+        assert_eq!(stored, 0);
+    }
+
+    #[tokio::test]
+    async fn edges_csv_has_header_and_seeded_edge_row() {
+        let pool = seeded_pool().await;
+        let (match_id, home_p): (String, f64) = sqlx::query_as(
+            r#"SELECT m.id, p.home_win_probability FROM matches m
+               JOIN predictions p ON p.match_id = m.id
+               WHERE m.sport = 'basketball' AND m.status = 'scheduled'
+               ORDER BY m.match_date LIMIT 1"#,
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        // Market rates the home side 15 points below our model
+        let market_home = home_p - 0.15;
+        upsert_market_odds(&pool, &match_id, "Test Book", 1.0 / market_home, None, 1.0 / (1.0 - market_home))
+            .await.unwrap();
+
+        let (headers, body) = get_prediction_edges_csv_handler(State(pool), Query(EdgesQuery { per_outcome: false })).await.unwrap();
+        assert_eq!(headers[0].1, "text/csv; charset=utf-8");
+        let mut lines = body.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            "match_id,home_team,away_team,match_date,side,our_probability,market_odds,edge_pct,expected_value,kelly_stake,bookmaker,odds_fetched_at"
+        );
+        let row: Vec<&str> = lines.find(|l| l.starts_with(&match_id)).unwrap().split(',').collect();
+        assert_eq!(row[4], "home");
+        assert!((row[7].parse::<f64>().unwrap() - 15.0).abs() < 0.01);
+        assert!(row[9].parse::<f64>().unwrap() > 0.0);
+        assert_eq!(row[10], "Test Book");
+    }
+
+    #[tokio::test]
+    async fn edges_without_odds_explain_missing_market_data() {
+        let pool = seeded_pool().await;
+
+        let Json(resp) = get_prediction_edges_handler(State(pool), Query(EdgesQuery { per_outcome: false })).await.unwrap();
+        let report = resp.data.unwrap();
+
+        assert!(report.edges.is_empty());
+        assert!(matches!(report.status, EdgeStatus::NoOddsKey | EdgeStatus::NoMarketOdds));
+        assert!(report.message.starts_with("No market odds available"));
+    }
+
+    #[tokio::test]
+    async fn parlay_without_correlation_is_product_of_legs() {
+        let pool = seeded_pool().await;
+        let ids: Vec<String> = sqlx::query_scalar(
+            "SELECT DISTINCT match_id FROM predictions p JOIN matches m ON m.id = p.match_id
+             WHERE m.status = 'scheduled' ORDER BY m.match_date LIMIT 3",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        let legs: Vec<ParlayLeg> = ids.iter()
+            .map(|id| ParlayLeg { match_id: id.clone(), outcome: Some(Outcome::Home) })
+            .collect();
+
+        let quote = compute_parlay(&pool, &legs, 0.0).await.unwrap().unwrap();
+        let product: f64 = quote.legs.iter().map(|l| l.probability.unwrap()).product();
+        assert_eq!(quote.legs.len(), 3);
+        assert!(quote.missing_predictions.is_empty());
+        assert!((quote.naive_probability.unwrap() - product).abs() < 1e-12);
+        assert!((quote.correlated_probability.unwrap() - product).abs() < 1e-12);
+
+        // Correlated legs on the same league and day can only raise the joint estimate
+        let same_day = [(0.5, "EPL"), (0.6, "EPL"), (0.4, "NBA")];
+        let (naive, correlated) = parlay_probability(&same_day, 0.1);
+        assert!(correlated > naive && correlated <= 0.4);
+    }
+
+    #[tokio::test]
+    async fn team_matches_csv_has_one_row_per_match_with_results() {
+        let pool = seeded_pool().await;
+        let all = get_matches_for_team(&pool, "epl_1", &MatchFilter::default()).await.unwrap();
+
+        let (_, body) = get_team_matches_csv_handler(
+            State(pool.clone()),
+            Path("epl_1".to_string()),
+            Query(DateRangeQuery { from: None, to: None }),
+        )
+        .await
+        .unwrap();
+        let mut rows = csv::Reader::from_reader(body.as_bytes());
+        let records: Vec<csv::StringRecord> = rows.records().map(|r| r.unwrap()).collect();
+        assert_eq!(records.len(), all.len());
+
+        let finished = all.iter().find(|m| m.status == "finished" && m.home_score.is_some()).unwrap();
+        let (own, other) = if finished.home_team_id == "epl_1" {
+            (finished.home_score.unwrap(), finished.away_score.unwrap())
+        } else {
+            (finished.away_score.unwrap(), finished.home_score.unwrap())
+        };
+        let expected = match own.cmp(&other) {
+            std::cmp::Ordering::Greater => "W",
+            std::cmp::Ordering::Less => "L",
+            std::cmp::Ordering::Equal => "D",
+        };
+        let row = records.iter().find(|r| &r[0] == finished.id.as_str()).unwrap();
+        assert_eq!(&row[7], expected);
+
+        // A range that excludes everything still returns the header
+        let (_, empty) = get_team_matches_csv_handler(
+            State(pool),
+            Path("epl_1".to_string()),
+            Query(DateRangeQuery { from: chrono::NaiveDate::from_ymd_opt(1990, 1, 1), to: chrono::NaiveDate::from_ymd_opt(1990, 1, 2) }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(empty.lines().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn version_reports_crate_and_model_versions() {
+        let pool = seeded_pool().await;
+        let info = get_version_handler(State(pool)).await.unwrap().0.data.unwrap();
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert!(!info.football_model_version.is_empty());
+        assert!(!info.basketball_model_version.is_empty());
+        assert!(!info.git_commit.is_empty());
+        assert_eq!(info.schema_version, Some(crate::db::SCHEMA_VERSION));
+    }
+
+    #[tokio::test]
+    async fn fresh_database_has_every_migration_applied() {
+        let pool = seeded_pool().await;
+        let status = get_schema_status_handler(State(pool)).await.unwrap().0.data.unwrap();
+        assert!(status.up_to_date);
+        assert!(status.pending.is_empty() && status.unknown.is_empty());
+        assert_eq!(status.applied.len(), crate::db::MIGRATIONS.len());
+        assert!(status.applied.iter().all(|m| m.name.is_some() && m.applied_at.is_some()));
+        assert_eq!(status.current_version, Some(status.expected_version));
+    }
+
+    #[tokio::test]
+    async fn confidence_threshold_filters_predictions() {
+        let pool = seeded_pool().await;
+        let upcoming = get_upcoming_matches(&pool, Some("football")).await.unwrap();
+        let pick = |id: &str, m: &Match, confidence: f64| crate::models::Prediction {
+            id: id.into(),
+            match_id: m.id.clone(),
+            home_win_probability: 0.5,
+            away_win_probability: 0.25,
+            draw_probability: Some(0.25),
+            model_version: "test".into(),
+            confidence_score: confidence,
+            created_at: chrono::Utc::now(),
+            insufficient_data: false,
+            most_likely_score: None,
+        };
+        insert_prediction(&pool, &pick("unsure", &upcoming[0], 0.65)).await.unwrap();
+        insert_prediction(&pool, &pick("sure", &upcoming[1], 0.85)).await.unwrap();
+
+        let Json(resp) = get_confident_predictions_handler(
+            State(pool.clone()),
+            Query(ConfidenceQuery { min_confidence: Some(0.8), sport: Some("football".into()) }),
+        )
+        .await
+        .unwrap();
+        let picks = resp.data.unwrap();
+        let ids: Vec<&str> = picks.iter().map(|p| p.prediction.id.as_str()).collect();
+        assert!(ids.contains(&"sure"));
+        assert!(!ids.contains(&"unsure"));
+        assert!(picks.iter().all(|p| p.prediction.confidence_score >= 0.8));
+        assert!(picks.windows(2).all(|w| w[0].prediction.confidence_score >= w[1].prediction.confidence_score));
+
+        let rejected = get_confident_predictions_handler(
+            State(pool),
+            Query(ConfidenceQuery { min_confidence: Some(1.5), sport: None }),
+        )
+        .await;
+        assert_eq!(rejected.err(), Some(StatusCode::BAD_REQUEST));
+    }
+
+    #[tokio::test]
+    async fn seeded_simulation_is_reproducible_and_tracks_analytic_odds() {
+        let pool = seeded_pool().await;
+        let m = get_upcoming_matches(&pool, Some("football")).await.unwrap().remove(0);
+        let run = |seed| simulate_match_handler(
+            State(pool.clone()),
+            Path(m.id.clone()),
+            Query(SimulateQuery { iterations: Some(50_000), seed }),
+        );
+
+        let first = run(Some(7)).await.unwrap().0.data.unwrap();
+        let second = run(Some(7)).await.unwrap().0.data.unwrap();
+        assert_eq!(first.home_win, second.home_win);
+        assert_eq!(first.draw, second.draw);
+        assert_eq!(first.btts, second.btts);
+        assert_eq!(
+            (first.most_likely_home_score, first.most_likely_away_score),
+            (second.most_likely_home_score, second.most_likely_away_score),
+        );
+
+        assert!((first.home_win - first.analytic_home_win).abs() < 0.015);
+        assert!((first.draw.unwrap() - first.analytic_draw.unwrap()).abs() < 0.015);
+        assert!((first.away_win - first.analytic_away_win).abs() < 0.015);
+        assert!((first.home_win + first.draw.unwrap() + first.away_win - 1.0).abs() < 1e-9);
+        let over_2_5 = first.totals.iter().find(|t| t.line == 2.5).unwrap();
+        assert!((over_2_5.over + over_2_5.under - 1.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn compressed_dataset_decompresses_to_plain_export() {
+        use std::io::Read;
+        let pool = seeded_pool().await;
+        let request: DatasetRequest = serde_json::from_value(serde_json::json!({
+            "sport": "football",
+            "stats_categories": ["basic", "teams"],
+            "compress": true,
+        }))
+        .unwrap();
+        assert_eq!(request.format, "csv");
+
+        let plain = generate_custom_dataset(&pool, DatasetRequest { compress: false, ..request.clone() }).await.unwrap();
+        let gz = generate_custom_dataset(&pool, request).await.unwrap();
+        assert!(gz.download_url.ends_with(".csv.gz"));
+        assert_eq!(gz.content_type, "application/gzip");
+        assert_eq!(plain.content_type, "text/csv");
+
+        let path = |r: &DatasetResponse| format!("../data/exports/{}", r.download_url.trim_start_matches("/downloads/"));
+        let expected = std::fs::read_to_string(path(&plain)).unwrap();
+        let mut decoded = String::new();
+        flate2::read::GzDecoder::new(std::fs::File::open(path(&gz)).unwrap())
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, expected);
+        assert_eq!(decoded.lines().count(), plain.rows + 1);
+
+        let _ = std::fs::remove_file(path(&plain));
+        let _ = std::fs::remove_file(path(&gz));
+    }
+
+    #[tokio::test]
+    async fn per_outcome_edges_list_home_and_draw_separately() {
+        let pool = seeded_pool().await;
+        let upcoming = get_upcoming_matches(&pool, Some("football")).await.unwrap();
+        let match_id = upcoming[0].id.clone();
+        insert_prediction(&pool, &crate::models::Prediction {
+            id: "two_sided".into(),
+            match_id: match_id.clone(),
+            home_win_probability: 0.55,
+            away_win_probability: 0.10,
+            draw_probability: Some(0.35),
+            model_version: "test".into(),
+            confidence_score: 0.7,
+            created_at: chrono::Utc::now(),
+            insufficient_data: false,
+            most_likely_score: None,
+        })
+        .await
+        .unwrap();
+        // Devigged market ≈ 35% / 22% / 43%: home and draw both beat the threshold
+        upsert_market_odds(&pool, &match_id, "Test Book", 2.5, Some(4.0), 2.0).await.unwrap();
+
+        let engine = PredictionEngine::new();
+        let collapsed: Vec<_> = engine.find_market_edges(&pool, false).await.unwrap()
+            .into_iter().filter(|e| e.match_id == match_id).collect();
+        assert_eq!(collapsed.len(), 1);
+        assert_eq!(collapsed[0].side, "home");
+
+        let per_outcome: Vec<_> = engine.find_market_edges(&pool, true).await.unwrap()
+            .into_iter().filter(|e| e.match_id == match_id).collect();
+        let sides: Vec<&str> = per_outcome.iter().map(|e| e.side.as_str()).collect();
+        assert_eq!(sides, ["home", "draw"]);
+        assert!(per_outcome[1].edge_value > 0.03 && per_outcome[1].edge_value < per_outcome[0].edge_value);
+    }
+
+    #[tokio::test]
+    async fn repeated_reads_hit_cache_until_refresh() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let pool = seeded_pool().await;
+        let cache = ResponseCache::with_ttl(std::time::Duration::from_secs(60));
+        let queries = AtomicUsize::new(0);
+        let load = || async {
+            queries.fetch_add(1, Ordering::SeqCst);
+            get_all_teams(&pool).await
+        };
+
+        let first = cache.get_or_load("teams", false, load).await.unwrap();
+        let second = cache.get_or_load("teams", false, load).await.unwrap();
+        assert_eq!(queries.load(Ordering::SeqCst), 1);
+        assert_eq!(first.len(), second.len());
+
+        cache.get_or_load("teams", true, load).await.unwrap();
+        assert_eq!(queries.load(Ordering::SeqCst), 2, "nocache goes to the database");
+
+        cache.bump_generation();
+        cache.get_or_load("teams", false, load).await.unwrap();
+        assert_eq!(queries.load(Ordering::SeqCst), 3, "a refresh invalidates earlier entries");
+
+        // Through the handler: a team added between ticks only shows up after the bump
+        let teams = |nocache| get_all_teams_handler(State(pool.clone()), State(cache.clone()), Query(CacheQuery { nocache }));
+        let before = teams(false).await.unwrap().0.data.unwrap().len();
+        let newcomer = Team { id: "cache_new".into(), name: "Newcomer".into(), ..first[0].clone() };
+        crate::db::insert_team(&pool, &newcomer).await.unwrap();
+        assert_eq!(teams(false).await.unwrap().0.data.unwrap().len(), before);
+        assert_eq!(teams(true).await.unwrap().0.data.unwrap().len(), before + 1);
+        cache.bump_generation();
+        assert_eq!(teams(false).await.unwrap().0.data.unwrap().len(), before + 1);
+    }
+
+    #[tokio::test]
+    async fn updated_since_returns_only_changed_matches() {
+        let pool = seeded_pool().await;
+        let upcoming = get_upcoming_matches(&pool, Some("football")).await.unwrap();
+        let (touched, untouched) = (&upcoming[0], &upcoming[1]);
+
+        let cutoff = chrono::Utc::now();
+        crate::db::insert_match(&pool, &Match { updated_at: chrono::Utc::now(), ..touched.clone() }).await.unwrap();
+
+        let Json(resp) = get_upcoming_matches_handler(
+            State(pool),
+            no_cache(),
+            Query(UpcomingMatchesQuery {
+                sport: Some("football".into()),
+                limit: None,
+                within_days: None,
+                nocache: false,
+                updated_since: Some(cutoff),
+            }),
+        )
+        .await
+        .unwrap();
+
+        let ids: Vec<String> = resp.data.unwrap().into_iter().map(|m| m.match_info.id).collect();
+        assert!(ids.contains(&touched.id));
+        assert!(!ids.contains(&untouched.id));
+        assert!(resp.timestamp > cutoff, "next poll starts from the server's timestamp");
+    }
+
+    #[tokio::test]
+    async fn results_ndjson_streams_one_match_per_line() {
+        let pool = seeded_pool().await;
+        let finished = count_matches_by_status(&pool, Some("finished")).await.unwrap();
+        let query = |sport: Option<&str>| ResultsExportQuery { from: None, to: None, sport: sport.map(String::from) };
+
+        let response = get_match_results_ndjson_handler(State(pool.clone()), Query(query(None))).await;
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/x-ndjson");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let matches: Vec<Match> = std::str::from_utf8(&body).unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(matches.len() as i64, finished);
+        assert!(matches.iter().all(|m| m.status == "finished" && m.home_score.is_some()));
+        assert!(matches.windows(2).all(|w| w[0].match_date <= w[1].match_date));
+
+        let response = get_match_results_ndjson_handler(State(pool), Query(query(Some("basketball")))).await;
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body.iter().filter(|&&b| b == b'\n').count(), 5);
+    }
+
+    #[tokio::test]
+    async fn upcoming_limit_above_default_is_honoured() {
+        let pool = seeded_pool().await;
+        let template = get_upcoming_matches(&pool, Some("football")).await.unwrap().remove(0);
+        for i in 0..80 {
+            let fixture = Match {
+                id: format!("bulk_{}", i),
+                match_date: template.match_date + chrono::Duration::hours(i),
+                ..template.clone()
+            };
+            crate::db::insert_match(&pool, &fixture).await.unwrap();
+        }
+
+        let query = UpcomingMatchesQuery { sport: Some("football".into()), limit: Some(75), within_days: None, nocache: false, updated_since: None };
+        let Json(resp) = get_upcoming_matches_handler(State(pool), no_cache(), Query(query)).await.unwrap();
+        assert_eq!(resp.data.unwrap().len(), 75);
+    }
+
+    #[tokio::test]
+    async fn edge_timeline_shrinks_as_odds_drift_toward_our_probability() {
+        let pool = seeded_pool().await;
+        let m = get_upcoming_matches(&pool, Some("football")).await.unwrap().remove(0);
+        let ours = get_prediction_by_match_id(&pool, &m.id).await.unwrap().unwrap();
+        let draw = ours.draw_probability.unwrap();
+
+        // The market starts 8 points short on the home side and closes the gap
+        for gap in [0.08, 0.04, 0.01] {
+            let home = ours.home_win_probability - gap;
+            crate::db::upsert_market_odds(
+                &pool, &m.id, "Test", 1.0 / home, Some(1.0 / (draw + gap / 2.0)), 1.0 / (ours.away_win_probability + gap / 2.0),
+            ).await.unwrap();
+        }
+
+        let Json(resp) = get_edge_timeline_handler(State(pool.clone()), Path(m.id.clone())).await.unwrap();
+        let points = resp.data.unwrap();
+        assert_eq!(points.len(), 3);
+        assert!(points.iter().all(|p| p.side == "home" && p.prediction_id == ours.id));
+        assert!((points[0].edge_value - 0.08).abs() < 1e-9);
+        assert!(points.windows(2).all(|w| w[1].edge_value < w[0].edge_value));
+
+        let missing = get_edge_timeline_handler(State(pool), Path("no_such_match".into())).await;
+        assert_eq!(missing.err(), Some(StatusCode::NOT_FOUND));
+    }
+
+    #[tokio::test]
+    async fn form_table_ranks_hot_streak_above_higher_elo_slump() {
+        let pool = seeded_pool().await;
+        let template = get_teams_by_league(&pool, "football", "EPL").await.unwrap().remove(0);
+        let hot = Team { id: "form_hot".into(), name: "Hot".into(), elo_rating: 1350.0, ..template.clone() };
+        let cold = Team { id: "form_cold".into(), name: "Cold".into(), elo_rating: 1750.0, ..template };
+        crate::db::insert_team(&pool, &hot).await.unwrap();
+        crate::db::insert_team(&pool, &cold).await.unwrap();
+
+        let result = get_finished_matches_ordered(&pool).await.unwrap().remove(0);
+        for day in 1..=4 {
+            let (home, away, home_score, away_score) = if day % 2 == 0 { (&hot, &cold, 2, 0) } else { (&cold, &hot, 0, 1) };
+            let m = Match {
+                id: format!("form_{}", day),
+                home_team_id: home.id.clone(),
+                away_team_id: away.id.clone(),
+                home_team_name: home.name.clone(),
+                away_team_name: away.name.clone(),
+                match_date: chrono::Utc::now() - chrono::Duration::days(day),
+                home_score: Some(home_score),
+                away_score: Some(away_score),
+                ..result.clone()
+            };
+            crate::db::insert_match(&pool, &m).await.unwrap();
+        }
+
+        let Json(resp) = get_form_table_handler(State(pool), Path(("football".into(), "EPL".into()))).await.unwrap();
+        let table = resp.data.unwrap();
+        let row = |id: &str| table.iter().find(|r| r.team_id == id).unwrap().clone();
+        let (hot, cold) = (row("form_hot"), row("form_cold"));
+        assert_eq!(hot.form, "WWWW");
+        assert_eq!(cold.form, "LLLL");
+        assert!(hot.rank < cold.rank);
+        assert!(hot.elo_rating < cold.elo_rating);
+        assert!(table.windows(2).all(|w| w[0].form_rate >= w[1].form_rate));
+    }
+
+    #[tokio::test]
+    async fn batched_elo_rebuild_matches_per_row_replay() {
+        async fn snapshot(pool: &SqlitePool) -> (Vec<(String, f64)>, Vec<(String, String, String, f64)>) {
+            let ratings = sqlx::query_as("SELECT id, elo_rating FROM teams ORDER BY id").fetch_all(pool).await.unwrap();
+            let history = sqlx::query_as("SELECT team_id, match_id, date, elo_rating FROM elo_history ORDER BY team_id, match_id")
+                .fetch_all(pool)
+                .await
+                .unwrap();
+            (ratings, history)
+        }
 
-    match request.format.as_str() {
-        "csv" => {
-            let mut writer = csv::Writer::from_path(&file_path)?;
-            writer.write_record(&headers)?;
-            for row in &rows {
-                let record: Vec<String> = (0..columns.len())
-                    .map(|i| cell_to_string(row, i))
-                    .collect();
-                writer.write_record(&record)?;
+        // Reference: one rating update and one history write per team per match, reading
+        // each rating back from the database
+        let pool = seeded_pool().await;
+        sqlx::query("DELETE FROM elo_history").execute(&pool).await.unwrap();
+        sqlx::query("UPDATE teams SET elo_rating = 1200.0").execute(&pool).await.unwrap();
+        let calc = EloCalculator::new();
+        let matches = get_finished_matches_ordered(&pool).await.unwrap();
+        for m in &matches {
+            let home = get_team_by_id(&pool, &m.home_team_id).await.unwrap().unwrap();
+            let away = get_team_by_id(&pool, &m.away_team_id).await.unwrap().unwrap();
+            let (hs, aws) = m.deciding_score().unwrap();
+            let hca = crate::db::get_home_advantage(&pool, &home.id).await.unwrap();
+            let (home_elo, away_elo) = calc.update_ratings_for_sport(
+                home.elo_rating, away.elo_rating, hs, aws,
+                crate::services::elo_calculator::home_advantage_for(&m.league) * hca, &m.sport,
+            );
+            for (team, elo_rating) in [(home, home_elo), (away, away_elo)] {
+                crate::db::insert_team(&pool, &Team { elo_rating, ..team.clone() }).await.unwrap();
+                crate::db::insert_elo_history(&pool, &[crate::models::EloHistoryPoint {
+                    team_id: team.id,
+                    date: m.match_date,
+                    elo_rating,
+                    match_id: Some(m.id.clone()),
+                }]).await.unwrap();
             }
-            writer.flush()?;
         }
-        "json" => {
-            let data: Vec<HashMap<String, String>> = rows.iter().map(|row| {
-                headers.iter().enumerate()
-                    .map(|(i, h)| (h.clone(), cell_to_string(row, i)))
-                    .collect()
-            }).collect();
-            let json_str = serde_json::to_string_pretty(&data)?;
-            tokio::fs::write(&file_path, json_str).await?;
+        let per_row = snapshot(&pool).await;
+        assert_eq!(per_row.1.len(), matches.len() * 2);
+
+        rebuild_elo(&pool).await;
+        assert_eq!(snapshot(&pool).await, per_row);
+    }
+
+    #[tokio::test]
+    async fn team_accuracy_separates_predictable_and_contrary_teams() {
+        let pool = seeded_pool().await;
+        let template = get_team_by_id(&pool, "epl_1").await.unwrap().unwrap();
+        let opponent = get_team_by_id(&pool, "epl_2").await.unwrap().unwrap();
+        let result = get_finished_matches_ordered(&pool).await.unwrap().remove(0);
+
+        for (id, score) in [("acc_good", (2, 0)), ("acc_bad", (0, 2))] {
+            let team = Team { id: id.into(), name: id.into(), ..template.clone() };
+            crate::db::insert_team(&pool, &team).await.unwrap();
+            for day in 1..=3 {
+                let m = Match {
+                    id: format!("{}_{}", id, day),
+                    home_team_id: team.id.clone(),
+                    away_team_id: opponent.id.clone(),
+                    home_team_name: team.name.clone(),
+                    away_team_name: opponent.name.clone(),
+                    match_date: chrono::Utc::now() - chrono::Duration::days(day),
+                    home_score: Some(score.0),
+                    away_score: Some(score.1),
+                    ..result.clone()
+                };
+                crate::db::insert_match(&pool, &m).await.unwrap();
+                // Both teams were tipped to win at home
+                crate::db::insert_prediction(&pool, &crate::models::Prediction {
+                    id: format!("{}_p", m.id),
+                    match_id: m.id.clone(),
+                    home_win_probability: 0.7,
+                    away_win_probability: 0.1,
+                    draw_probability: Some(0.2),
+                    model_version: "test".into(),
+                    confidence_score: 0.8,
+                    created_at: m.match_date - chrono::Duration::hours(2),
+                    insufficient_data: false,
+                    most_likely_score: None,
+                }).await.unwrap();
+            }
         }
-        _ => return Err(anyhow::anyhow!("Unsupported format: {}", request.format)),
+
+        let accuracy = |id: &'static str| {
+            let pool = pool.clone();
+            async move { get_team_accuracy_handler(State(pool), Path(id.into())).await.map(|Json(r)| r.data.unwrap()) }
+        };
+        let good = accuracy("acc_good").await.unwrap();
+        let bad = accuracy("acc_bad").await.unwrap();
+        assert_eq!((good.matches, bad.matches), (3, 3));
+        assert_eq!(good.hit_rate, 1.0);
+        assert_eq!(bad.hit_rate, 0.0);
+        assert!(good.brier_score < bad.brier_score);
+        assert!((good.avg_predicted_win_probability - 0.7).abs() < 1e-9);
+        assert_eq!((good.actual_win_rate, bad.actual_win_rate), (1.0, 0.0));
+        assert_eq!(accuracy("no_such_team").await.err(), Some(StatusCode::NOT_FOUND));
     }
-    
-    Ok(DatasetResponse {
-        download_url: format!("/downloads/{}", filename),
-        format: request.format,
-        rows: rows.len(),
-        generated_at: chrono::Utc::now(),
-    })
-}
 
-// ── ML endpoints ──────────────────────────────────────────────────────────────
+    #[tokio::test]
+    async fn market_blend_weight_moves_from_model_to_devigged_market() {
+        let pool = seeded_pool().await;
+        let m = get_upcoming_matches(&pool, Some("football")).await.unwrap().remove(0);
+        upsert_market_odds(&pool, &m.id, "Test", 2.0, Some(4.0), 4.0).await.unwrap();
+        let model = get_prediction_by_match_id(&pool, &m.id).await.unwrap().unwrap();
 
-/// POST /models/train — Trigger full ML retrain pipeline
-async fn trigger_train_handler(
-    State(pool): State<SqlitePool>,
-) -> Json<ApiResponse<String>> {
-    // Run training in background to avoid blocking the HTTP response
-    let pool_clone = pool.clone();
-    tokio::spawn(async move {
-        match train_and_evaluate(&pool_clone).await {
-            Ok((state, folds)) => {
-                match serde_json::to_string(&state) {
-                    Ok(json) => {
-                        let avg_b = if folds.is_empty() { None }
-                            else { Some(folds.iter().map(|f| f.brier_score).sum::<f64>() / folds.len() as f64) };
-                        let avg_l = if folds.is_empty() { None }
-                            else { Some(folds.iter().map(|f| f.log_loss).sum::<f64>() / folds.len() as f64) };
-                        if let Ok(v) = save_model_params(&pool_clone, "meta", &json, avg_b, avg_l).await {
-                            for f in &folds {
-                                let _ = save_backtest_result(&pool_clone, "meta", f.fold, f.brier_score, f.log_loss, f.accuracy, f.n_games).await;
-                            }
-                            set_ml_model(state).await;
-                            tracing::info!("ML training complete — saved as version {}", v);
-                        }
-                    }
-                    Err(e) => tracing::error!("Failed to serialise ML model: {}", e),
-                }
+        let blended = |weight| {
+            let pool = pool.clone();
+            let id = m.id.clone();
+            async move {
+                let query = Query(MatchPredictionQuery { blend_market: Some(weight) });
+                get_match_prediction_handler(State(pool), Path(id), query).await.map(|Json(r)| r.data.unwrap())
             }
-            Err(e) => tracing::error!("ML training failed: {}", e),
+        };
+
+        // 2.0 / 4.0 / 4.0 carries no margin: the market says 50% / 25% / 25%
+        let market = blended(1.0).await.unwrap();
+        assert!((market.home_win_probability - 0.5).abs() < 1e-9);
+        assert!((market.draw_probability.unwrap() - 0.25).abs() < 1e-9);
+        assert!((market.away_win_probability - 0.25).abs() < 1e-9);
+
+        let pure = blended(0.0).await.unwrap();
+        assert_eq!(pure.id, model.id);
+        assert_eq!(pure.home_win_probability, model.home_win_probability);
+        assert_eq!(pure.draw_probability, model.draw_probability);
+
+        // Reads blend on the fly: nothing is written and the served model prediction stands
+        let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM consensus_predictions WHERE match_id = ?")
+            .bind(&m.id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(stored, 0);
+        assert_eq!(get_prediction_by_match_id(&pool, &m.id).await.unwrap().unwrap().id, model.id);
+        assert_eq!(blended(1.5).await.err(), Some(StatusCode::BAD_REQUEST));
+    }
+
+    #[tokio::test]
+    async fn recompute_reports_counts_and_timings_for_every_phase() {
+        let _serial = pipeline_test_guard().await;
+        let pool = seeded_pool().await;
+
+        let Json(resp) = recompute_handler(State(pool.clone()), no_cache()).await.unwrap();
+        let report = resp.data.unwrap();
+
+        let names: Vec<&str> = report.phases.iter().map(|p| p.phase.as_str()).collect();
+        assert_eq!(names, ["elo", "stats", "predictions", "odds"]);
+        let count = |name: &str| report.phases.iter().find(|p| p.phase == name).unwrap().count;
+        assert!(count("elo") > 0);
+        assert!(count("stats") > 0);
+        assert!(count("predictions") > 0);
+        assert!(report.phases.iter().map(|p| p.duration_ms).sum::<u64>() <= report.total_ms);
+
+        // A second run can't start while the chain is already running
+        let _running = pipeline_lock().lock().await;
+        assert_eq!(recompute_handler(State(pool.clone()), no_cache()).await.err(), Some(StatusCode::CONFLICT));
+        // Nor can anything that wipes the tables the chain is writing to
+        assert_eq!(reseed_handler(State(pool.clone()), no_cache()).await.err(), Some(StatusCode::CONFLICT));
+        let refresh = refresh_all_data_handler(State(pool), State(Arc::new(DataFetcher::new())), no_cache()).await;
+        assert_eq!(refresh.err(), Some(StatusCode::CONFLICT));
+    }
+
+    #[tokio::test]
+    async fn match_detail_carries_prediction_and_null_odds_for_seeded_fixture() {
+        let pool = seeded_pool().await;
+        let m = get_upcoming_matches(&pool, Some("football")).await.unwrap().remove(0);
+
+        let Json(resp) = get_match_detail_handler(State(pool.clone()), Path(m.id.clone())).await.unwrap();
+        let detail = resp.data.unwrap();
+
+        assert_eq!(detail.match_info.id, m.id);
+        assert_eq!(detail.prediction.unwrap().match_id, m.id);
+        assert!(detail.market_odds.is_none());
+        assert!(detail.edge_value.is_none() && detail.edge_side.is_none());
+        assert_eq!(detail.home_team.id, m.home_team_id);
+        assert_eq!(detail.away_team.id, m.away_team_id);
+
+        let missing = get_match_detail_handler(State(pool), Path("no_such_match".into())).await;
+        assert_eq!(missing.err(), Some(StatusCode::NOT_FOUND));
+    }
+
+    #[tokio::test]
+    async fn elo_vs_average_aligns_team_and_league_series_by_date() {
+        let pool = seeded_pool().await;
+        rebuild_elo(&pool).await;
+        let history = get_elo_history(&pool, "epl_1").await.unwrap();
+        assert!(!history.is_empty());
+
+        let Json(resp) = get_elo_vs_average_handler(State(pool.clone()), Path("epl_1".into())).await.unwrap();
+        let series = resp.data.unwrap();
+
+        assert_eq!(series.points.len(), history.len());
+        let league = get_league_elo_history(&pool, "football", "EPL").await.unwrap();
+        for (point, own) in series.points.iter().zip(&history) {
+            assert_eq!(point.date, own.date);
+            assert_eq!(point.elo_rating, own.elo_rating);
+            assert!((point.relative - (point.elo_rating - point.league_average)).abs() < 1e-9);
+            // The average only draws on ratings the league had recorded by that date
+            let known: Vec<f64> = league.iter().filter(|p| p.date <= point.date).map(|p| p.elo_rating).collect();
+            let (lo, hi) = known.iter().fold((f64::MAX, f64::MIN), |(lo, hi), &r| (lo.min(r), hi.max(r)));
+            assert!(point.league_average >= lo - 1e-9 && point.league_average <= hi + 1e-9);
         }
-    });
 
-    Json(ApiResponse::success("Training started in background. Check /models/evaluate for results.".to_string()))
-}
+        let missing = get_elo_vs_average_handler(State(pool), Path("no_such_team".into())).await;
+        assert_eq!(missing.err(), Some(StatusCode::NOT_FOUND));
+    }
 
-/// GET /models/evaluate — Return latest backtest results
-async fn get_model_evaluations_handler(
-    State(pool): State<SqlitePool>,
-) -> Json<ApiResponse<Vec<MlEvaluation>>> {
-    let rows = sqlx::query(
-        "SELECT model_name, fold, brier_score, log_loss, accuracy, n_games, evaluated_at FROM backtest_results ORDER BY model_name, fold"
-    ).fetch_all(&pool).await;
+    #[test]
+    fn bind_address_is_parsed_and_invalid_ones_are_rejected() {
+        assert_eq!(listen_addr(Some("127.0.0.1"), 8080).unwrap().to_string(), "127.0.0.1:8080");
+        assert_eq!(listen_addr(Some("::1"), 3000).unwrap().to_string(), "[::1]:3000");
 
-    match rows {
-        Ok(rows) => {
-            let evals: Vec<MlEvaluation> = rows.iter().map(|r| MlEvaluation {
-                model_name: r.get("model_name"),
-                fold: r.get("fold"),
-                year: 0,
-                n_games: r.get("n_games"),
-                brier_score: r.get("brier_score"),
-                log_loss: r.get("log_loss"),
-                accuracy: r.get("accuracy"),
-                evaluated_at: r.get("evaluated_at"),
-            }).collect();
-            Json(ApiResponse::success(evals))
+        let err = listen_addr(Some("localhost:80"), 3000).unwrap_err().to_string();
+        assert!(err.contains("invalid bind address 'localhost:80'"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn season_projection_positions_sum_to_one_and_leader_is_title_favourite() {
+        let pool = seeded_pool().await;
+        compute_season_stats(&pool).await;
+        let cache = ResponseCache::with_ttl(std::time::Duration::from_secs(60));
+        let table = get_standings(&pool, "football", "EPL").await.unwrap();
+        let leader = &table[0].team_id;
+
+        let mut titles = Vec::new();
+        for row in &table {
+            let p = compute_team_projection(&pool, &cache, &row.team_id, DEFAULT_SIMULATIONS, false).await.unwrap().unwrap();
+            assert_eq!(p.position_probabilities.len(), 20);
+            assert!((p.position_probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-9, "{}", row.team_id);
+            assert!(p.relegation_probability.is_some());
+            assert!(p.qualification_probability.unwrap() >= p.title_probability);
+            titles.push((row.team_id.clone(), p.title_probability));
         }
-        Err(e) => Json(ApiResponse::error(e.to_string())),
+        let favourite = titles.iter().max_by(|a, b| a.1.total_cmp(&b.1)).unwrap();
+        assert_eq!(&favourite.0, leader);
+        assert!((titles.iter().map(|t| t.1).sum::<f64>() - 1.0).abs() < 1e-9);
+
+        assert!(compute_team_projection(&pool, &cache, "no_such_team", 100, false).await.unwrap().is_none());
+
+        // Neither EPL's top four nor its drop applies in the Champions League
+        sqlx::query("UPDATE teams SET league = 'Champions League' WHERE id = ?")
+            .bind(leader).execute(&pool).await.unwrap();
+        let ucl = compute_team_projection(&pool, &cache, leader, 100, false).await.unwrap().unwrap();
+        assert_eq!(ucl.league, "Champions League");
+        assert_eq!((ucl.qualification_probability, ucl.relegation_probability), (None, None));
     }
-}
 
-/// GET /matches/:id/explain — Feature importance for a prediction
-async fn explain_prediction_handler(
-    Path(match_id): Path<String>,
-    State(pool): State<SqlitePool>,
-) -> Json<ApiResponse<Vec<FeatureContribution>>> {
-    use crate::ml::MlPredictor;
+    #[tokio::test]
+    async fn advanced_stats_adjust_for_opponents_and_zero_out_without_matches() {
+        let pool = seeded_pool().await;
+        let finished = get_finished_matches_ordered(&pool).await.unwrap();
+        let team_id = finished[0].home_team_id.clone();
+        let played: Vec<&Match> = finished.iter().filter(|m| m.home_team_id == team_id || m.away_team_id == team_id).collect();
 
-    // Look up the match
-    let match_row = sqlx::query(
-        "SELECT id, home_team_id, away_team_id, home_team_name, away_team_name, sport, league, match_date, status, home_score, away_score, created_at, updated_at FROM matches WHERE id = ?"
-    ).bind(&match_id).fetch_optional(&pool).await;
+        let stats = compute_team_advanced_stats(&pool, &team_id).await.unwrap().unwrap();
+        assert_eq!(stats.matches_played, played.len());
+        let scored: i32 = played.iter().map(|m| if m.home_team_id == team_id { m.home_score.unwrap() } else { m.away_score.unwrap() }).sum();
+        assert!((stats.goals_for_per_game - scored as f64 / played.len() as f64).abs() < 1e-9);
+        assert!(stats.avg_opponent_elo > 1000.0);
+        assert!(stats.adjusted_goals_for_per_game > 0.0 || scored == 0);
+        assert!((stats.points_delta - (stats.actual_points - stats.expected_points)).abs() < 1e-9);
 
-    let m = match match_row {
-        Ok(Some(r)) => match parse_match_row(&r) {
-            Ok(m) => m,
-            Err(e) => return Json(ApiResponse::error(e.to_string())),
-        },
-        Ok(None) => return Json(ApiResponse::error("Match not found".to_string())),
-        Err(e) => return Json(ApiResponse::error(e.to_string())),
-    };
+        // A team with no finished games gets zeros, not an error
+        let fresh = Team { id: "expansion".into(), name: "Expansion".into(), ..get_team_by_id(&pool, &team_id).await.unwrap().unwrap() };
+        crate::db::insert_team(&pool, &fresh).await.unwrap();
+        let empty = compute_team_advanced_stats(&pool, "expansion").await.unwrap().unwrap();
+        assert_eq!(empty.matches_played, 0);
+        assert_eq!(empty.avg_opponent_elo, 0.0);
+        assert_eq!(empty.points_delta, 0.0);
 
-    let mut predictor = MlPredictor::new();
-    let _ = predictor.load_from_db(&pool).await;
-    if !predictor.has_model() {
-        return Json(ApiResponse::error("No trained ML model available. Run POST /models/train first.".to_string()));
+        assert!(compute_team_advanced_stats(&pool, "no_such_team").await.unwrap().is_none());
     }
 
-    match predictor.feature_importance(&pool, &m).await {
-        Some(importances) => {
-            let contribs: Vec<FeatureContribution> = importances.iter().map(|(name, val, contrib)| {
-                FeatureContribution { feature_name: name.clone(), feature_value: *val, contribution: *contrib }
-            }).collect();
-            Json(ApiResponse::success(contribs))
-        }
-        None => Json(ApiResponse::error("Could not compute feature importance".to_string())),
+    #[tokio::test]
+    async fn carryover_regresses_final_ratings_toward_the_mean() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        init_database_with_pool(&pool).await.unwrap();
+        let team = Team {
+            id: "contender".into(),
+            name: "Contender".into(),
+            sport: "football".into(),
+            league: "EPL".into(),
+            logo_url: None,
+            elo_rating: 1600.0,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+            elo_percentile: None,
+            elo_tier: None,
+        };
+        crate::db::insert_team(&pool, &team).await.unwrap();
+
+        let request = CarryoverRequest {
+            from_season: "2025-26".into(),
+            to_season: "2026-27".into(),
+            regression: 0.25,
+            record_finals: true,
+        };
+        let report = run_carryover(&pool, &request).await.unwrap();
+        assert_eq!((report.finals_recorded, report.teams_carried), (1, 1));
+        assert!((crate::db::get_preseason_ratings(&pool).await.unwrap()["contender"] - 1575.0).abs() < 1e-9);
+        // With no results yet, the rebuild starts the team from its carried-over rating
+        let rebuilt = get_team_by_id(&pool, "contender").await.unwrap().unwrap();
+        assert!((rebuilt.elo_rating - 1575.0).abs() < 1e-9);
+
+        // A season with no recorded finals carries nothing over
+        let missing = CarryoverRequest { from_season: "1999-00".into(), record_finals: false, ..request };
+        assert_eq!(run_carryover(&pool, &missing).await.unwrap().teams_carried, 0);
     }
-}
 
-/// GET /predictions/:id/distribution — Monte Carlo score distribution
-async fn get_score_distribution_handler(
-    Path(pred_id): Path<String>,
-    State(pool): State<SqlitePool>,
-) -> Json<ApiResponse<ScoreDistribution>> {
-    use crate::ml::MlPredictor;
+    #[tokio::test]
+    async fn carryover_restarts_teams_that_played_the_previous_season() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        init_database_with_pool(&pool).await.unwrap();
+        let team = |id: &str| Team {
+            id: id.into(),
+            name: id.into(),
+            sport: "football".into(),
+            league: "EPL".into(),
+            logo_url: None,
+            elo_rating: 1200.0,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+            elo_percentile: None,
+            elo_tier: None,
+        };
+        let (contender, rival) = (team("contender"), team("rival"));
+        for t in [&contender, &rival] {
+            crate::db::insert_team(&pool, t).await.unwrap();
+        }
+        let (to_start, _) = season_bounds("2026-27").unwrap();
+        let result = |id: &str, match_date, home: &Team, away: &Team, score: (i32, i32)| Match {
+            id: id.into(),
+            home_team_id: home.id.clone(),
+            away_team_id: away.id.clone(),
+            home_team_name: home.name.clone(),
+            away_team_name: away.name.clone(),
+            sport: "football".into(),
+            league: "EPL".into(),
+            match_date,
+            status: "finished".into(),
+            home_score: Some(score.0),
+            away_score: Some(score.1),
+            venue: None,
+            city: None,
+            tiebreak_winner: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+        // A 2025-26 win for the contender
+        let old = result("old", to_start - chrono::Duration::days(60), &contender, &rival, (3, 0));
+        crate::db::insert_match(&pool, &old).await.unwrap();
+        rebuild_elo(&pool).await;
+        let final_rating = get_team_by_id(&pool, "contender").await.unwrap().unwrap().elo_rating;
+        assert!(final_rating > 1200.0);
 
-    // Get match_id from prediction
-    let pred_row = sqlx::query("SELECT match_id FROM predictions WHERE id = ?")
-        .bind(&pred_id).fetch_optional(&pool).await;
+        let request = CarryoverRequest {
+            from_season: "2025-26".into(),
+            to_season: "2026-27".into(),
+            regression: 0.25,
+            record_finals: true,
+        };
+        run_carryover(&pool, &request).await.unwrap();
+        let regressed = final_rating - 0.25 * (final_rating - crate::db::CARRYOVER_MEAN);
+        let carried = |pool: SqlitePool| async move { get_team_by_id(&pool, "contender").await.unwrap().unwrap().elo_rating };
+        assert!((carried(pool.clone()).await - regressed).abs() < 1e-9);
 
-    let match_id = match pred_row {
-        Ok(Some(r)) => r.get::<String, _>("match_id"),
-        Ok(None) => return Json(ApiResponse::error("Prediction not found".to_string())),
-        Err(e) => return Json(ApiResponse::error(e.to_string())),
-    };
+        // A 2026-27 loss is replayed on top of the regressed rating, not the 1200 rebuild
+        let new = result("new", to_start + chrono::Duration::days(40), &rival, &contender, (1, 0));
+        crate::db::insert_match(&pool, &new).await.unwrap();
+        rebuild_elo(&pool).await;
+        let rival_rating = crate::db::get_preseason_ratings(&pool).await.unwrap()["rival"];
+        let bonus = home_advantage_for("EPL");
+        let (_, expected) = EloCalculator::new().update_ratings_for_sport(rival_rating, regressed, 1, 0, bonus, "football");
+        assert!((carried(pool).await - expected).abs() < 1e-9);
+    }
 
-    let match_row = sqlx::query(
-        "SELECT id, home_team_id, away_team_id, home_team_name, away_team_name, sport, league, match_date, status, home_score, away_score, created_at, updated_at FROM matches WHERE id = ?"
-    ).bind(&match_id).fetch_optional(&pool).await;
+    #[tokio::test]
+    async fn prediction_history_keeps_every_generation_oldest_first() {
+        let pool = seeded_pool().await;
+        let m = &get_upcoming_matches(&pool, Some("football")).await.unwrap()[0];
+        sqlx::query("DELETE FROM predictions WHERE match_id = ?").bind(&m.id).execute(&pool).await.unwrap();
+        let at = |id: &str, version: &str, home: f64, hours_ago: i64| crate::models::Prediction {
+            id: id.into(),
+            match_id: m.id.clone(),
+            home_win_probability: home,
+            away_win_probability: 0.8 - home,
+            draw_probability: Some(0.2),
+            model_version: version.into(),
+            confidence_score: 0.6,
+            created_at: chrono::Utc::now() - chrono::Duration::hours(hours_ago),
+            insufficient_data: false,
+            most_likely_score: None,
+        };
+        for p in [at("late", "v1", 0.5, 1), at("early", "v1", 0.4, 3), at("shadow", "candidate", 0.45, 2)] {
+            crate::db::insert_prediction(&pool, &p).await.unwrap();
+        }
 
-    let m = match match_row {
-        Ok(Some(r)) => match parse_match_row(&r) {
-            Ok(m) => m,
-            Err(e) => return Json(ApiResponse::error(e.to_string())),
-        },
-        Ok(None) => return Json(ApiResponse::error("Match not found".to_string())),
-        Err(e) => return Json(ApiResponse::error(e.to_string())),
-    };
+        let history = |version: Option<&str>| get_prediction_history_handler(
+            Path(m.id.clone()),
+            Query(PredictionHistoryQuery { model_version: version.map(str::to_string) }),
+            State(pool.clone()),
+        );
+        let all = history(None).await.unwrap().0.data.unwrap();
+        assert_eq!(all.iter().map(|p| p.id.as_str()).collect::<Vec<_>>(), ["early", "shadow", "late"]);
+        let v1 = history(Some("v1")).await.unwrap().0.data.unwrap();
+        assert_eq!(v1.iter().map(|p| p.id.as_str()).collect::<Vec<_>>(), ["early", "late"]);
 
-    let mut predictor = MlPredictor::new();
-    let _ = predictor.load_from_db(&pool).await;
+        let missing = get_prediction_history_handler(
+            Path("no_such_match".into()),
+            Query(PredictionHistoryQuery { model_version: None }),
+            State(pool.clone()),
+        ).await;
+        assert_eq!(missing.unwrap_err(), StatusCode::NOT_FOUND);
+    }
 
-    match predictor.score_distribution(&pool, &m).await {
-        Some(buckets) => {
-            let p_home = buckets[40..].iter().sum::<f64>();
-            let expected: f64 = buckets.iter().enumerate()
-                .map(|(i, &p)| p * (i as f64 - 40.0)).sum();
-            Json(ApiResponse::success(ScoreDistribution {
-                match_id,
-                p_home_win: p_home,
-                expected_margin: expected,
-                buckets,
-            }))
+    #[tokio::test]
+    async fn team_profile_honours_recent_and_elo_limits() {
+        let pool = seeded_pool().await;
+        let played = get_team_recent_matches(&pool, "epl_1", 1).await.unwrap().remove(0);
+        for week in 1..=5 {
+            let m = Match {
+                id: format!("profile_{}", week),
+                match_date: played.match_date - chrono::Duration::weeks(week),
+                ..played.clone()
+            };
+            crate::db::insert_match(&pool, &m).await.unwrap();
         }
-        None => Json(ApiResponse::error("No trained ML model available".to_string())),
+        rebuild_elo(&pool).await;
+        let profile = |recent, elo_limit| get_team_stats_handler(
+            State(pool.clone()),
+            Path("epl_1".to_string()),
+            Query(TeamStatsQuery { recent, elo_limit }),
+        );
+
+        let full = profile(None, None).await.unwrap().0.data.unwrap();
+        assert!(full.recent_matches.len() > 3);
+        assert!(full.elo_history.len() > 2);
+
+        let compact = profile(Some(3), Some(2)).await.unwrap().0.data.unwrap();
+        assert_eq!(compact.recent_matches.len(), 3);
+        assert_eq!(
+            compact.recent_matches.iter().map(|m| &m.id).collect::<Vec<_>>(),
+            full.recent_matches.iter().take(3).map(|m| &m.id).collect::<Vec<_>>(),
+        );
+        // The newest points, still oldest first
+        assert_eq!(compact.elo_history.len(), 2);
+        assert_eq!(compact.elo_history[1].date, full.elo_history.last().unwrap().date);
+        assert!(compact.elo_history[0].date <= compact.elo_history[1].date);
     }
-}
 
-/// GET /matches/history — paginated finished games with scores
-#[derive(Deserialize)]
-struct HistoryQuery {
-    sport: Option<String>,
-    limit: Option<i64>,
-    offset: Option<i64>,
-}
+    #[tokio::test]
+    async fn team_search_filters_by_league_and_rejects_blank_names() {
+        let pool = seeded_pool().await;
+        let mut arsenal = get_team_by_id(&pool, "epl_1").await.unwrap().unwrap();
+        arsenal.id = "ucl_arsenal".to_string();
+        arsenal.league = "Champions League".to_string();
+        crate::db::insert_team(&pool, &arsenal).await.unwrap();
 
-#[derive(Serialize)]
-struct HistoryGame {
-    match_id: String,
-    home_team: String,
-    away_team: String,
-    match_date: String,
-    home_score: i32,
-    away_score: i32,
-    home_won: bool,
-    season: i32,
-}
+        let search = |league: Option<&str>| TeamSearchQuery {
+            name: "Arsenal".to_string(),
+            sport: None,
+            league: league.map(str::to_string),
+        };
+        let Json(both) = search_teams_handler(State(pool.clone()), Query(search(None))).await.unwrap();
+        assert_eq!(both.data.unwrap().len(), 2);
+        let Json(ucl) = search_teams_handler(State(pool.clone()), Query(search(Some("Champions League")))).await.unwrap();
+        let ucl = ucl.data.unwrap();
+        assert_eq!(ucl.len(), 1);
+        assert_eq!(ucl[0].id, "ucl_arsenal");
 
-async fn get_match_history_handler(
-    State(pool): State<SqlitePool>,
-    Query(params): Query<HistoryQuery>,
-) -> Json<ApiResponse<Vec<HistoryGame>>> {
-    let sport = params.sport.as_deref().unwrap_or("basketball");
-    let limit = params.limit.unwrap_or(50).min(500);
-    let offset = params.offset.unwrap_or(0);
+        let blank = TeamSearchQuery { name: "  ".to_string(), sport: None, league: None };
+        assert_eq!(search_teams_handler(State(pool), Query(blank)).await.unwrap_err(), StatusCode::BAD_REQUEST);
+    }
 
-    let rows = sqlx::query(
-        "SELECT id, home_team_name, away_team_name, match_date, home_score, away_score
-         FROM matches
-         WHERE sport = ? AND status = 'finished'
-           AND home_score IS NOT NULL AND away_score IS NOT NULL
-         ORDER BY match_date DESC LIMIT ? OFFSET ?"
-    )
-    .bind(sport).bind(limit).bind(offset)
-    .fetch_all(&pool).await;
+    #[tokio::test]
+    async fn unknown_team_stats_return_a_json_error_body() {
+        let pool = seeded_pool().await;
+        let query = TeamStatsQuery { recent: None, elo_limit: None };
+        let err = get_team_stats_handler(State(pool), Path("no_such_team".to_string()), Query(query))
+            .await
+            .unwrap_err();
+        assert_eq!(err.status, StatusCode::NOT_FOUND);
 
-    match rows {
-        Ok(rows) => {
-            let games: Vec<HistoryGame> = rows.iter().filter_map(|r| {
-                let hs: i32 = r.try_get("home_score").ok()?;
-                let aws: i32 = r.try_get("away_score").ok()?;
-                let date_str: String = r.try_get("match_date").ok()?;
-                let season = chrono::DateTime::parse_from_rfc3339(&date_str)
-                    .map(|d| d.format("%Y").to_string().parse::<i32>().unwrap_or(2024))
-                    .unwrap_or(2024);
-                Some(HistoryGame {
-                    match_id: r.try_get("id").ok()?,
-                    home_team: r.try_get("home_team_name").ok()?,
-                    away_team: r.try_get("away_team_name").ok()?,
-                    match_date: date_str,
-                    home_score: hs,
-                    away_score: aws,
-                    home_won: hs > aws,
-                    season,
-                })
-            }).collect();
-            Json(ApiResponse::success(games))
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["success"], false);
+        assert_eq!(body["error"], "Team no_such_team not found");
+    }
+
+    #[tokio::test]
+    async fn odds_coverage_flags_matches_with_stored_odds() {
+        let pool = seeded_pool().await;
+        let upcoming = get_upcoming_matches(&pool, Some("football")).await.unwrap();
+        let (priced, unpriced) = (&upcoming[0], &upcoming[1]);
+        upsert_market_odds(&pool, &priced.id, "Pinnacle", 2.1, Some(3.4), 3.6).await.unwrap();
+
+        let coverage = compute_odds_coverage(&pool, Some("football")).await.unwrap();
+        assert_eq!(coverage.len(), upcoming.len());
+        let row = |id: &str| coverage.iter().find(|c| c.match_id == id).unwrap();
+
+        let covered = row(&priced.id);
+        assert!(covered.covered);
+        assert_eq!(covered.bookmaker.as_deref(), Some("Pinnacle"));
+        assert!(covered.odds_age_minutes.is_some_and(|m| (0..5).contains(&m)));
+
+        let missing = row(&unpriced.id);
+        assert!(!missing.covered);
+        assert!(missing.bookmaker.is_none() && missing.odds_age_minutes.is_none());
+    }
+
+    #[tokio::test]
+    async fn dataset_sport_filter_is_bound_not_spliced() {
+        let pool = seeded_pool().await;
+        let request = |sport: &str| DatasetRequest {
+            sport: sport.into(),
+            teams: None,
+            date_from: None,
+            date_to: None,
+            stats_categories: vec!["basic".into()],
+            format: "csv".into(),
+            compress: false,
+        };
+
+        let football = generate_custom_dataset(&pool, request("football")).await.unwrap();
+        let injected = generate_custom_dataset(&pool, request("x' OR '1'='1")).await.unwrap();
+        assert!(football.rows > 0);
+        assert_eq!(injected.rows, 0);
+        assert!(injected.download_url.starts_with("/downloads/dataset_x__OR__1___1_"), "{}", injected.download_url);
+
+        for response in [football, injected] {
+            let file = response.download_url.trim_start_matches("/downloads/");
+            let _ = std::fs::remove_file(format!("../data/exports/{}", file));
         }
-        Err(e) => Json(ApiResponse::error(e.to_string())),
     }
-}
 
-fn parse_match_row(r: &sqlx::sqlite::SqliteRow) -> anyhow::Result<crate::models::Match> {
-    Ok(crate::models::Match {
-        id: r.get("id"),
-        home_team_id: r.get("home_team_id"),
-        away_team_id: r.get("away_team_id"),
-        home_team_name: r.get("home_team_name"),
-        away_team_name: r.get("away_team_name"),
-        sport: r.get("sport"),
-        league: r.get("league"),
-        match_date: chrono::DateTime::parse_from_rfc3339(&r.get::<String, _>("match_date"))?.with_timezone(&chrono::Utc),
-        status: r.get("status"),
-        home_score: r.get("home_score"),
-        away_score: r.get("away_score"),
-        created_at: chrono::DateTime::parse_from_rfc3339(&r.get::<String, _>("created_at"))?.with_timezone(&chrono::Utc),
-        updated_at: chrono::DateTime::parse_from_rfc3339(&r.get::<String, _>("updated_at"))?.with_timezone(&chrono::Utc),
-    })
-}
\ No newline at end of file
+    #[test]
+    fn failed_fetch_reports_each_sport_reason() {
+        let mut summary = FetchSummary::default();
+        summary.record_sport("football", Err(anyhow::anyhow!("football-data.org returned 429")));
+        summary.skip_sport("basketball", "BALLDONTLIE_API_KEY not set");
+        assert!(summary.all_failed());
+
+        let (status, Json(body)) = all_sports_failed(summary);
+        assert_eq!(status, StatusCode::BAD_GATEWAY);
+        assert!(!body.success);
+        let sports = body.data.unwrap().sports;
+        assert_eq!(sports[0].reason.as_deref(), Some("football-data.org returned 429"));
+        assert_eq!(sports[1].reason.as_deref(), Some("BALLDONTLIE_API_KEY not set"));
+    }
+
+    /// Seeded pool with an (untrained) ML model saved, so the ML endpoints get past the model check.
+    async fn pool_with_ml_model() -> SqlitePool {
+        let pool = seeded_pool().await;
+        let state = serde_json::to_string(&crate::ml::meta_learner::MlModelState::new()).unwrap();
+        crate::db::save_model_params(&pool, "meta", &state, None, None).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn explain_prediction_loads_the_match() {
+        let pool = pool_with_ml_model().await;
+        let match_id: String = sqlx::query_scalar(
+            "SELECT id FROM matches WHERE sport = 'basketball' LIMIT 1",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let Json(resp) = explain_prediction_handler(Path(match_id), State(pool)).await;
+        assert!(resp.success, "{:?}", resp.error);
+        assert!(!resp.data.unwrap().is_empty());
+
+        let Json(missing) = explain_prediction_handler(Path("nope".into()), State(pool_with_ml_model().await)).await;
+        assert_eq!(missing.error.as_deref(), Some("Match not found"));
+    }
+
+    #[tokio::test]
+    async fn score_distribution_loads_the_predicted_match() {
+        let pool = pool_with_ml_model().await;
+        let (prediction_id, match_id): (String, String) = sqlx::query_as(
+            "SELECT p.id, p.match_id FROM predictions p JOIN matches m ON m.id = p.match_id WHERE m.sport = 'basketball' LIMIT 1",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let Json(resp) = get_score_distribution_handler(Path(prediction_id), State(pool)).await;
+        assert!(resp.success, "{:?}", resp.error);
+        let distribution = resp.data.unwrap();
+        assert_eq!(distribution.match_id, match_id);
+        assert!((distribution.buckets.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+    }
+}
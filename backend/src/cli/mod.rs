@@ -1,14 +1,23 @@
 use anyhow::Result;
 use sqlx::Row;
 
-use crate::db::{create_pool, get_upcoming_matches, init_database_with_pool, save_model_params, save_backtest_result};
+use crate::db::{
+    create_pool, find_teams_by_name, get_matches_for_team, get_upcoming_matches, init_database_with_pool,
+    MatchFilter,
+};
+use crate::models::{EdgeStatus, FetchOutcome};
 use crate::services::{DataFetcher, PredictionEngine};
 
-pub async fn fetch_data(sport: &str) -> Result<()> {
+/// Fetch one sport (or "all"); with `since`, only matches from that date on.
+pub async fn fetch_data(sport: &str, since: Option<chrono::NaiveDate>) -> Result<()> {
     let pool = create_pool().await?;
     let fetcher = DataFetcher::new();
+    let since = since.map(|d| d.and_time(chrono::NaiveTime::MIN).and_utc());
 
-    println!("🏈 Fetching {} data...", sport);
+    match since {
+        Some(d) => println!("🏈 Fetching {} data since {}...", sport, d.format("%Y-%m-%d")),
+        None => println!("🏈 Fetching {} data...", sport),
+    }
 
     match sport.to_lowercase().as_str() {
         "football" | "soccer" => {
@@ -16,7 +25,7 @@ pub async fn fetch_data(sport: &str) -> Result<()> {
             fetcher.fetch_epl_teams(&pool).await?;
 
             println!("📥 Fetching EPL matches...");
-            fetcher.fetch_epl_matches(&pool).await?;
+            fetcher.fetch_epl_matches_since(&pool, since).await?;
             
             println!("✅ Football data fetched successfully!");
         }
@@ -25,14 +34,23 @@ pub async fn fetch_data(sport: &str) -> Result<()> {
             fetcher.fetch_nba_teams(&pool).await?;
             
             println!("📥 Fetching NBA games...");
-            fetcher.fetch_nba_games(&pool).await?;
+            fetcher.fetch_nba_games_since(&pool, since).await?;
             
             println!("✅ Basketball data fetched successfully!");
         }
         "all" => {
             println!("📥 Fetching all sports data...");
-            fetcher.fetch_all_data(&pool).await?;
-            println!("✅ All sports data fetched successfully!");
+            let summary = fetcher.fetch_all_data_since(&pool, since).await;
+            for s in &summary.sports {
+                match s.outcome {
+                    FetchOutcome::Success => println!("✅ {} fetched", s.sport),
+                    FetchOutcome::Skipped => println!("⏭️  {} skipped: {}", s.sport, s.reason.as_deref().unwrap_or("")),
+                    FetchOutcome::Failed => println!("❌ {} failed: {}", s.sport, s.reason.as_deref().unwrap_or("")),
+                }
+            }
+            if summary.all_failed() {
+                anyhow::bail!("every sport failed to fetch");
+            }
         }
         _ => {
             println!("❌ Unsupported sport: {}. Use 'football', 'basketball', or 'all'", sport);
@@ -82,18 +100,15 @@ pub async fn generate_predictions() -> Result<()> {
     Ok(())
 }
 
-pub async fn query_team(team_name: &str) -> Result<()> {
+/// Show one team's details. `sport` / `league` narrow the name search when a club has a
+/// row in more than one competition.
+pub async fn query_team(team_name: &str, sport: Option<&str>, league: Option<&str>) -> Result<()> {
     let pool = create_pool().await?;
 
     println!("🔍 Searching for team: {}", team_name);
 
     // First try to find the team by name (case-insensitive search)
-    let teams = sqlx::query_as::<_, crate::models::Team>(
-        "SELECT * FROM teams WHERE LOWER(name) LIKE LOWER(?) ORDER BY name"
-    )
-    .bind(format!("%{}%", team_name))
-    .fetch_all(&pool)
-    .await?;
+    let teams = find_teams_by_name(&pool, team_name, sport, league).await?;
 
     if teams.is_empty() {
         println!("❌ No teams found matching '{}'", team_name);
@@ -118,7 +133,8 @@ pub async fn query_team(team_name: &str) -> Result<()> {
         for (i, team) in teams.iter().enumerate() {
             println!("{}. {} ({} - {})", i + 1, team.name, team.league, team.sport);
         }
-        println!("\n🔍 Showing details for first match:");
+        println!("\n💡 Narrow the search with a fuller name, --league or --sport");
+        return Ok(());
     }
 
     let team = &teams[0];
@@ -131,41 +147,20 @@ pub async fn query_team(team_name: &str) -> Result<()> {
 
     // Get recent matches
     println!("\n📅 Recent Matches:");
-    let recent_matches = sqlx::query_as::<_, crate::models::Match>(
-        r#"
-        SELECT * FROM matches 
-        WHERE (home_team_id = ? OR away_team_id = ?) 
-            AND status = 'finished'
-        ORDER BY match_date DESC 
-        LIMIT 5
-        "#
-    )
-    .bind(&team.id)
-    .bind(&team.id)
-    .fetch_all(&pool)
+    let recent_matches = get_matches_for_team(&pool, &team.id, &MatchFilter {
+        status: Some("finished"),
+        limit: Some(5),
+        ..Default::default()
+    })
     .await?;
 
     if recent_matches.is_empty() {
         println!("   No recent matches found");
     } else {
         for match_data in recent_matches {
-            let (opponent, is_home, result) = if match_data.home_team_id == team.id {
-                let result = match (match_data.home_score, match_data.away_score) {
-                    (Some(h), Some(a)) => {
-                        if h > a { "W" } else if h < a { "L" } else { "D" }
-                    }
-                    _ => "?"
-                };
-                (match_data.away_team_name, true, result)
-            } else {
-                let result = match (match_data.home_score, match_data.away_score) {
-                    (Some(h), Some(a)) => {
-                        if a > h { "W" } else if a < h { "L" } else { "D" }
-                    }
-                    _ => "?"
-                };
-                (match_data.home_team_name, false, result)
-            };
+            let is_home = match_data.home_team_id == team.id;
+            let result = match_data.result().map_or("?", |r| r.for_side(is_home));
+            let opponent = if is_home { &match_data.away_team_name } else { &match_data.home_team_name };
 
             let venue = if is_home { "vs" } else { "at" };
             let score = match (match_data.home_score, match_data.away_score) {
@@ -185,19 +180,13 @@ pub async fn query_team(team_name: &str) -> Result<()> {
 
     // Get upcoming matches
     println!("\n📅 Upcoming Matches:");
-    let upcoming_matches = sqlx::query_as::<_, crate::models::Match>(
-        r#"
-        SELECT * FROM matches 
-        WHERE (home_team_id = ? OR away_team_id = ?) 
-            AND status = 'scheduled'
-            AND match_date > datetime('now')
-        ORDER BY match_date ASC 
-        LIMIT 5
-        "#
-    )
-    .bind(&team.id)
-    .bind(&team.id)
-    .fetch_all(&pool)
+    let upcoming_matches = get_matches_for_team(&pool, &team.id, &MatchFilter {
+        status: Some("scheduled"),
+        after: Some(chrono::Utc::now()),
+        oldest_first: true,
+        limit: Some(5),
+        ..Default::default()
+    })
     .await?;
 
     if upcoming_matches.is_empty() {
@@ -279,11 +268,16 @@ pub async fn show_edges() -> Result<()> {
 
     println!("🎯 Finding market edges...\n");
 
-    let edges = prediction_engine.find_market_edges(&pool).await?;
+    let report = prediction_engine.edge_report(&pool, false).await?;
+    let edges = report.edges;
 
     if edges.is_empty() {
-        println!("📭 No significant edges found at the moment.");
-        println!("💡 Try running predictions first: oddsforge predict");
+        println!("📭 {}.", report.message);
+        match report.status {
+            EdgeStatus::NoOddsKey => println!("💡 Set ODDS_API_KEY (https://the-odds-api.com/) to sync bookmaker odds."),
+            EdgeStatus::NoMarketOdds => println!("💡 Start the server to run the odds sync, then check again."),
+            _ => println!("💡 Try running predictions first: oddsforge predict"),
+        }
         return Ok(());
     }
 
@@ -309,12 +303,15 @@ pub async fn show_edges() -> Result<()> {
             edge.market_draw_odds.map_or(String::new(), |d| format!(" | {:.2}", d))
         );
         
-        println!("   Edge value: {:.1}%", edge.edge_value * 100.0);
+        println!("   Edge value: {:.1}% on {} ({})",
+            edge.edge_value * 100.0,
+            edge.side,
+            edge.bookmaker.as_deref().unwrap_or("unknown bookmaker")
+        );
         println!("   Confidence: {:.1}%\n", edge.our_prediction.confidence_score * 100.0);
     }
 
-    println!("⚠️  Note: Market odds are simulated for demonstration purposes.");
-    println!("💡 In production, these would be fetched from betting APIs.");
+    println!("ℹ️  Market odds are the best available prices from The Odds API.");
 
     Ok(())
 }
@@ -345,19 +342,11 @@ pub async fn train_models() -> Result<()> {
     println!("Training ML models on historical NBA data...");
     println!("This may take a few minutes for large datasets.");
 
-    let (state, folds) = crate::ml::backtest::train_and_evaluate(&pool).await?;
-
-    let json = serde_json::to_string(&state)?;
-    let avg_brier = if folds.is_empty() { None }
-        else { Some(folds.iter().map(|f| f.brier_score).sum::<f64>() / folds.len() as f64) };
-    let avg_ll = if folds.is_empty() { None }
-        else { Some(folds.iter().map(|f| f.log_loss).sum::<f64>() / folds.len() as f64) };
-
-    let version = save_model_params(&pool, "meta", &json, avg_brier, avg_ll).await?;
-
-    for fold in &folds {
-        save_backtest_result(&pool, "meta", fold.fold, fold.brier_score, fold.log_loss, fold.accuracy, fold.n_games).await?;
-    }
+    let (version, state, folds) = crate::ml::retrain::train_and_save(&pool).await?;
+    let mean = |f: fn(&crate::ml::backtest::FoldResult) -> f64| {
+        (!folds.is_empty()).then(|| folds.iter().map(f).sum::<f64>() / folds.len() as f64)
+    };
+    let (avg_brier, avg_ll) = (mean(|f| f.brier_score), mean(|f| f.log_loss));
 
     println!("\nModel trained and saved as version {}", version);
     println!("Model: {}", state.model_version);
@@ -2,20 +2,32 @@ pub mod seed;
 pub use seed::seed_database as seed_data;
 
 pub async fn clear_all_data(pool: &SqlitePool) -> Result<()> {
-    sqlx::query("DELETE FROM predictions").execute(pool).await?;
-    sqlx::query("DELETE FROM matches").execute(pool).await?;
-    sqlx::query("DELETE FROM season_stats").execute(pool).await?;
-    sqlx::query("DELETE FROM elo_history").execute(pool).await?;
-    sqlx::query("DELETE FROM teams").execute(pool).await?;
+    // Ratings survive the wipe so the next season's opening predictions aren't all flat
+    let carried = save_preseason_ratings(pool).await?;
+    if carried > 0 {
+        tracing::info!("Carried {} team ratings over as preseason ratings", carried);
+    }
+    // Children first: foreign keys are enforced. Trained model params and preseason
+    // ratings are kept.
+    for table in [
+        "prediction_inputs", "consensus_predictions", "predictions", "total_predictions", "match_xpts", "market_odds", "market_odds_history", "odds_fetch_log", "ml_features",
+        "elo_history", "team_stats", "official_standings", "game_box_stats", "nba_advanced_stats",
+        "nba_player_stats", "matches", "team_aliases", "team_home_advantage", "teams",
+    ] {
+        sqlx::query(&format!("DELETE FROM {}", table)).execute(pool).await?;
+    }
     tracing::info!("All data cleared");
     Ok(())
 }
 
 use anyhow::Result;
 use chrono::Utc;
-use sqlx::{Row, SqlitePool, sqlite::SqliteConnectOptions};
+use futures_util::stream::BoxStream;
+use sqlx::{FromRow, Row, SqlitePool, sqlite::SqliteConnectOptions};
 use std::env;
+use std::future::Future;
 use std::str::FromStr;
+use std::time::{Duration, Instant};
 
 use crate::models::*;
 
@@ -43,6 +55,41 @@ pub async fn create_pool() -> Result<SqlitePool> {
     Ok(pool)
 }
 
+/// Budget for one statement on a hot path, including the wait for a pooled connection or a
+/// locked database (`DB_QUERY_TIMEOUT_MS`, default 5000).
+fn query_timeout() -> Duration {
+    Duration::from_millis(env::var("DB_QUERY_TIMEOUT_MS").ok().and_then(|v| v.parse().ok()).unwrap_or(5000))
+}
+
+/// Statements slower than this are logged at warn level (`DB_SLOW_QUERY_MS`, default 250).
+fn slow_query_threshold() -> Duration {
+    Duration::from_millis(env::var("DB_SLOW_QUERY_MS").ok().and_then(|v| v.parse().ok()).unwrap_or(250))
+}
+
+/// Run a statement under `query_timeout`, tracing how long it took.
+async fn timed<T>(name: &'static str, fetch: impl Future<Output = sqlx::Result<T>>) -> Result<T> {
+    with_timeout(name, query_timeout(), fetch).await
+}
+
+async fn with_timeout<T>(
+    name: &'static str,
+    limit: Duration,
+    fetch: impl Future<Output = sqlx::Result<T>>,
+) -> Result<T> {
+    let started = Instant::now();
+    let result = tokio::time::timeout(limit, fetch).await;
+    let elapsed = started.elapsed();
+    if elapsed >= slow_query_threshold() {
+        tracing::warn!(query = name, elapsed_ms = elapsed.as_millis() as u64, "slow query");
+    } else {
+        tracing::debug!(query = name, elapsed_ms = elapsed.as_millis() as u64, "query");
+    }
+    match result {
+        Ok(rows) => Ok(rows?),
+        Err(_) => anyhow::bail!("query {} timed out after {} ms", name, limit.as_millis()),
+    }
+}
+
 /// Called from the CLI where no pool exists yet.
 pub async fn init_database() -> Result<()> {
     let pool = create_pool().await?;
@@ -50,6 +97,22 @@ pub async fn init_database() -> Result<()> {
 }
 
 /// Called from the server so schema creation shares the main pool.
+/// Every schema change init_database_with_pool knows how to apply, oldest first. Append an
+/// entry when the schema changes; each is recorded in schema_migrations once applied.
+pub const MIGRATIONS: &[(i64, &str)] = &[
+    (1, "initial_schema"),
+    (2, "prediction_insufficient_data_flag"),
+    (3, "official_standings"),
+    (4, "match_venue_and_tiebreak_winner"),
+    (5, "odds_history_and_consensus_predictions"),
+    (6, "preseason_and_season_final_ratings"),
+    (7, "total_predictions"),
+    (8, "prediction_most_likely_score"),
+];
+
+/// Schema version this binary expects: the newest entry in MIGRATIONS.
+pub const SCHEMA_VERSION: i64 = MIGRATIONS[MIGRATIONS.len() - 1].0;
+
 pub async fn init_database_with_pool(pool: &SqlitePool) -> Result<()> {
     let pool = pool.clone(); // clone is cheap (Arc refcount) — gives us SqlitePool, not &SqlitePool
     
@@ -85,6 +148,8 @@ pub async fn init_database_with_pool(pool: &SqlitePool) -> Result<()> {
             status TEXT NOT NULL DEFAULT 'scheduled',
             home_score INTEGER,
             away_score INTEGER,
+            venue TEXT,
+            city TEXT,
             created_at TEXT NOT NULL,
             updated_at TEXT NOT NULL,
             FOREIGN KEY (home_team_id) REFERENCES teams (id),
@@ -113,6 +178,60 @@ pub async fn init_database_with_pool(pool: &SqlitePool) -> Result<()> {
     .execute(&pool)
     .await?;
 
+    // Model predictions blended with the devigged market, kept apart from the pure-model rows
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS consensus_predictions (
+            id TEXT PRIMARY KEY,
+            match_id TEXT NOT NULL,
+            prediction_id TEXT NOT NULL,
+            blend_weight REAL NOT NULL,
+            home_win_probability REAL NOT NULL,
+            away_win_probability REAL NOT NULL,
+            draw_probability REAL,
+            model_version TEXT NOT NULL,
+            confidence_score REAL NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (match_id) REFERENCES matches (id)
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await?;
+
+    // Latest expected score per match for over/under pricing (basketball pace model)
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS total_predictions (
+            match_id TEXT PRIMARY KEY,
+            model TEXT NOT NULL,
+            expected_home_score REAL NOT NULL,
+            expected_away_score REAL NOT NULL,
+            total_sd REAL,
+            possessions REAL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (match_id) REFERENCES matches (id)
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await?;
+
+    // Snapshot of what each prediction was based on (JSON, see PredictionInputs)
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS prediction_inputs (
+            prediction_id TEXT PRIMARY KEY,
+            match_id TEXT NOT NULL,
+            snapshot TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (prediction_id) REFERENCES predictions (id)
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await?;
+
     sqlx::query(
         r#"
         CREATE TABLE IF NOT EXISTS team_stats (
@@ -128,6 +247,7 @@ pub async fn init_database_with_pool(pool: &SqlitePool) -> Result<()> {
             points_for INTEGER,
             points_against INTEGER,
             form TEXT,
+            xpts REAL,
             updated_at TEXT NOT NULL,
             FOREIGN KEY (team_id) REFERENCES teams (id)
         )
@@ -135,6 +255,30 @@ pub async fn init_database_with_pool(pool: &SqlitePool) -> Result<()> {
     )
     .execute(&pool)
     .await?;
+    add_column_if_missing(&pool, "team_stats", "xpts", "REAL").await?;
+    add_column_if_missing(&pool, "predictions", "insufficient_data", "INTEGER NOT NULL DEFAULT 0").await?;
+    add_column_if_missing(&pool, "predictions", "most_likely_score", "TEXT").await?;
+    add_column_if_missing(&pool, "matches", "venue", "TEXT").await?;
+    add_column_if_missing(&pool, "matches", "city", "TEXT").await?;
+    add_column_if_missing(&pool, "matches", "tiebreak_winner", "TEXT").await?;
+
+    // match_xpts: expected vs actual points per team per finished match
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS match_xpts (
+            match_id      TEXT NOT NULL,
+            team_id       TEXT NOT NULL,
+            xpts          REAL NOT NULL,
+            actual_points REAL NOT NULL,
+            computed_at   TEXT NOT NULL,
+            PRIMARY KEY (match_id, team_id),
+            FOREIGN KEY (match_id) REFERENCES matches (id),
+            FOREIGN KEY (team_id) REFERENCES teams (id)
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await?;
 
     sqlx::query(
         r#"
@@ -170,6 +314,28 @@ pub async fn init_database_with_pool(pool: &SqlitePool) -> Result<()> {
     .execute(&pool)
     .await?;
 
+    // market_odds_history: every odds snapshot stored, so edges can be replayed over time
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS market_odds_history (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            match_id    TEXT NOT NULL,
+            bookmaker   TEXT NOT NULL,
+            home_odds   REAL NOT NULL,
+            draw_odds   REAL,
+            away_odds   REAL NOT NULL,
+            fetched_at  TEXT NOT NULL,
+            FOREIGN KEY (match_id) REFERENCES matches (id)
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_market_odds_history_match ON market_odds_history(match_id, fetched_at)")
+        .execute(&pool)
+        .await?;
+
     // odds_fetch_log: tracks last successful API call per sport_key to avoid burning quota
     sqlx::query(
         r#"
@@ -182,6 +348,82 @@ pub async fn init_database_with_pool(pool: &SqlitePool) -> Result<()> {
     .execute(&pool)
     .await?;
 
+    // team_aliases: retired team ids merged into a canonical team (renames, relocations)
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS team_aliases (
+            alias_id   TEXT PRIMARY KEY,
+            alias_name TEXT NOT NULL,
+            team_id    TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (team_id) REFERENCES teams (id)
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await?;
+
+    // team_home_advantage: per-team multiplier on the default home bonus (altitude, crowd, travel)
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS team_home_advantage (
+            team_id    TEXT PRIMARY KEY,
+            multiplier REAL NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (team_id) REFERENCES teams (id)
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await?;
+
+    // preseason_ratings: each team's starting rating (carried over from a season's finals, or
+    // its rating before the last wipe), used until it has finished games again. No foreign
+    // key, so it outlives the teams table rows.
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS preseason_ratings (
+            team_id    TEXT PRIMARY KEY,
+            rating     REAL NOT NULL,
+            updated_at TEXT NOT NULL
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await?;
+    // Season a carried-over rating starts; NULL for ratings saved by a wipe
+    add_column_if_missing(&pool, "preseason_ratings", "season", "TEXT").await?;
+
+    // season_final_ratings: every team's rating at the end of a season, kept across wipes
+    // for carryover and season-over-season comparison
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS season_final_ratings (
+            team_id     TEXT NOT NULL,
+            season      TEXT NOT NULL,
+            elo_rating  REAL NOT NULL,
+            recorded_at TEXT NOT NULL,
+            PRIMARY KEY (team_id, season)
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await?;
+
+    // idempotency_keys: Idempotency-Key header → stored response for replayed POSTs
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS idempotency_keys (
+            key          TEXT PRIMARY KEY,
+            request_hash TEXT NOT NULL,
+            response     TEXT NOT NULL,
+            created_at   TEXT NOT NULL
+        )
+        "#,
+    )
+    .execute(&pool)
+    .await?;
+
     // nba_advanced_stats: one row per team, refreshed from stats.nba.com every 6 hours
     sqlx::query(
         r#"
@@ -250,10 +492,42 @@ pub async fn init_database_with_pool(pool: &SqlitePool) -> Result<()> {
         .execute(&pool)
         .await?;
 
+    // League-wide windows (`get_matches_between`) filter on sport and status, then date
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_matches_sport_status_date ON matches(sport, status, match_date)")
+        .execute(&pool)
+        .await?;
+
+    // Per-team lookups (form, H2H, schedule) filter on one side and sort by date
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_matches_home_date ON matches(home_team_id, match_date)")
+        .execute(&pool)
+        .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_matches_away_date ON matches(away_team_id, match_date)")
+        .execute(&pool)
+        .await?;
+
     sqlx::query("CREATE INDEX IF NOT EXISTS idx_teams_sport_league ON teams(sport, league)")
         .execute(&pool)
         .await?;
 
+    // One ELO history point per team per match. Older databases may hold duplicates
+    // from before the constraint existed, so keep the newest row of each pair first.
+    sqlx::query(
+        r#"DELETE FROM elo_history
+           WHERE match_id IS NOT NULL
+             AND rowid NOT IN (
+                 SELECT MAX(rowid) FROM elo_history
+                 WHERE match_id IS NOT NULL
+                 GROUP BY team_id, match_id
+             )"#,
+    )
+    .execute(&pool)
+    .await?;
+
+    sqlx::query("CREATE UNIQUE INDEX IF NOT EXISTS idx_elo_history_team_match ON elo_history(team_id, match_id)")
+        .execute(&pool)
+        .await?;
+
     // ── ML tables ──────────────────────────────────────────────────────────────
 
     sqlx::query(
@@ -313,10 +587,98 @@ pub async fn init_database_with_pool(pool: &SqlitePool) -> Result<()> {
         "CREATE INDEX IF NOT EXISTS idx_gbs_team_date ON game_box_stats(team_id, game_date)"
     ).execute(&pool).await?;
 
+    sqlx::query(
+        r#"CREATE TABLE IF NOT EXISTS official_standings (
+            team_id       TEXT NOT NULL,
+            league        TEXT NOT NULL,
+            position      INTEGER NOT NULL,
+            played        INTEGER NOT NULL,
+            wins          INTEGER NOT NULL,
+            draws         INTEGER NOT NULL,
+            losses        INTEGER NOT NULL,
+            points        INTEGER NOT NULL,
+            goals_for     INTEGER NOT NULL,
+            goals_against INTEGER NOT NULL,
+            fetched_at    TEXT NOT NULL,
+            PRIMARY KEY (team_id, league)
+        )"#,
+    ).execute(&pool).await?;
+
+    sqlx::query(
+        r#"CREATE TABLE IF NOT EXISTS schema_migrations (
+            version    INTEGER PRIMARY KEY,
+            applied_at TEXT NOT NULL
+        )"#,
+    ).execute(&pool).await?;
+    add_column_if_missing(&pool, "schema_migrations", "name", "TEXT").await?;
+    // Every statement above is idempotent, so reaching here means every migration is in
+    // place. Rows recorded by older binaries keep their applied_at and gain a name.
+    let now = Utc::now().to_rfc3339();
+    for (version, name) in MIGRATIONS {
+        sqlx::query(
+            r#"INSERT INTO schema_migrations (version, name, applied_at) VALUES (?, ?, ?)
+               ON CONFLICT(version) DO UPDATE SET name = excluded.name"#,
+        )
+        .bind(version)
+        .bind(name)
+        .bind(&now)
+        .execute(&pool)
+        .await?;
+    }
+
     tracing::info!("Database initialized successfully");
     Ok(())
 }
 
+/// Highest schema version recorded in schema_migrations by this or an earlier binary.
+pub async fn get_schema_version(pool: &SqlitePool) -> Result<Option<i64>> {
+    Ok(sqlx::query_scalar("SELECT MAX(version) FROM schema_migrations").fetch_one(pool).await?)
+}
+
+/// Applied migrations next to the ones this binary expects but the database lacks.
+pub async fn get_schema_status(pool: &SqlitePool) -> Result<crate::models::SchemaStatus> {
+    use crate::models::{SchemaMigration, SchemaStatus};
+
+    let applied: Vec<SchemaMigration> = sqlx::query_as(
+        "SELECT version, name, applied_at FROM schema_migrations ORDER BY version",
+    )
+    .fetch_all(pool)
+    .await?;
+    let pending: Vec<SchemaMigration> = MIGRATIONS.iter()
+        .filter(|(version, _)| !applied.iter().any(|m| m.version == *version))
+        .map(|(version, name)| SchemaMigration { version: *version, name: Some(name.to_string()), applied_at: None })
+        .collect();
+    // Recorded by a newer binary: this one may be missing columns that data relies on
+    let unknown: Vec<i64> = applied.iter()
+        .map(|m| m.version)
+        .filter(|v| !MIGRATIONS.iter().any(|(known, _)| known == v))
+        .collect();
+
+    Ok(SchemaStatus {
+        expected_version: SCHEMA_VERSION,
+        current_version: applied.iter().map(|m| m.version).max(),
+        up_to_date: pending.is_empty() && unknown.is_empty(),
+        applied,
+        pending,
+        unknown,
+    })
+}
+
+/// Add a column to an existing table when the database predates it.
+async fn add_column_if_missing(pool: &SqlitePool, table: &str, column: &str, decl: &str) -> Result<()> {
+    let exists: bool = sqlx::query_scalar("SELECT COUNT(*) > 0 FROM pragma_table_info(?) WHERE name = ?")
+        .bind(table)
+        .bind(column)
+        .fetch_one(pool)
+        .await?;
+    if !exists {
+        sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, decl))
+            .execute(pool)
+            .await?;
+    }
+    Ok(())
+}
+
 // ── ML model persistence ──────────────────────────────────────────────────────
 
 pub async fn save_model_params(
@@ -341,6 +703,39 @@ pub async fn save_model_params(
     Ok(version)
 }
 
+/// Version and time of the most recent save under `model_name`, if it was ever trained.
+pub async fn get_latest_model_training(pool: &SqlitePool, model_name: &str) -> Result<Option<(i32, chrono::DateTime<Utc>)>> {
+    let row: Option<(i32, String)> = sqlx::query_as(
+        "SELECT version, trained_at FROM model_params WHERE model_name = ? ORDER BY version DESC LIMIT 1",
+    )
+    .bind(model_name)
+    .fetch_optional(pool)
+    .await?;
+
+    row.map(|(version, trained_at)| {
+        Ok((version, chrono::DateTime::parse_from_rfc3339(&trained_at)?.with_timezone(&Utc)))
+    })
+    .transpose()
+}
+
+/// Finished, scored matches in `sport` played after `since` (all of them when None).
+pub async fn count_finished_matches_since(
+    pool: &SqlitePool,
+    sport: &str,
+    since: Option<chrono::DateTime<Utc>>,
+) -> Result<i64> {
+    let count = sqlx::query_scalar(
+        r#"SELECT COUNT(*) FROM matches
+           WHERE sport = ?1 AND status = 'finished' AND home_score IS NOT NULL AND away_score IS NOT NULL
+             AND (?2 IS NULL OR match_date > ?2)"#,
+    )
+    .bind(sport)
+    .bind(since.map(|t| t.to_rfc3339()))
+    .fetch_one(pool)
+    .await?;
+    Ok(count)
+}
+
 pub async fn save_backtest_result(
     pool: &SqlitePool,
     model_name: &str,
@@ -363,11 +758,13 @@ pub async fn save_backtest_result(
 
 // Team operations
 pub async fn insert_team(pool: &SqlitePool, team: &Team) -> Result<()> {
+    // A merged-away id must not be resurrected by the next fetch
     sqlx::query(
         r#"
         INSERT OR REPLACE INTO teams 
         (id, name, sport, league, logo_url, elo_rating, created_at, updated_at)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8
+        WHERE NOT EXISTS (SELECT 1 FROM team_aliases WHERE alias_id = ?1)
         "#,
     )
     .bind(&team.id)
@@ -385,10 +782,10 @@ pub async fn insert_team(pool: &SqlitePool, team: &Team) -> Result<()> {
 }
 
 pub async fn get_team_by_id(pool: &SqlitePool, team_id: &str) -> Result<Option<Team>> {
-    let row = sqlx::query("SELECT * FROM teams WHERE id = ?")
+    let fetch = sqlx::query("SELECT * FROM teams WHERE id = ?")
         .bind(team_id)
-        .fetch_optional(pool)
-        .await?;
+        .fetch_optional(pool);
+    let row = timed("get_team_by_id", fetch).await?;
     
     if let Some(row) = row {
         Ok(Some(Team {
@@ -400,6 +797,8 @@ pub async fn get_team_by_id(pool: &SqlitePool, team_id: &str) -> Result<Option<T
             elo_rating: row.get("elo_rating"),
             created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<String, _>("created_at"))?.with_timezone(&Utc),
             updated_at: chrono::DateTime::parse_from_rfc3339(&row.get::<String, _>("updated_at"))?.with_timezone(&Utc),
+            elo_percentile: None,
+            elo_tier: None,
         }))
     } else {
         Ok(None)
@@ -424,20 +823,123 @@ pub async fn get_teams_by_league(pool: &SqlitePool, sport: &str, league: &str) -
             elo_rating: row.get("elo_rating"),
             created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<String, _>("created_at"))?.with_timezone(&Utc),
             updated_at: chrono::DateTime::parse_from_rfc3339(&row.get::<String, _>("updated_at"))?.with_timezone(&Utc),
+            elo_percentile: None,
+            elo_tier: None,
         });
     }
     
     Ok(teams)
 }
 
+/// Teams whose name contains `name` (case-insensitive), optionally narrowed to one sport
+/// and/or league. A club entered in several competitions has a row per league, so callers
+/// get every one back rather than whichever sorts first.
+pub async fn find_teams_by_name(
+    pool: &SqlitePool,
+    name: &str,
+    sport: Option<&str>,
+    league: Option<&str>,
+) -> Result<Vec<Team>> {
+    let teams = sqlx::query_as::<_, Team>(
+        r#"
+        SELECT * FROM teams
+        WHERE LOWER(name) LIKE LOWER(?1)
+          AND (?2 IS NULL OR sport = ?2)
+          AND (?3 IS NULL OR league = ?3)
+        ORDER BY name, sport, league
+        "#,
+    )
+    .bind(format!("%{}%", name))
+    .bind(sport)
+    .bind(league)
+    .fetch_all(pool)
+    .await?;
+    Ok(teams)
+}
+
+/// Fold `from_id` into `into_id`: every match, ELO point, xPts row, published table row
+/// and NBA stat moves to the surviving id, the old id is recorded in team_aliases and its team row is removed.
+/// team_stats for the old id are dropped; they are rebuilt from matches by the stats pass.
+pub async fn merge_teams(pool: &SqlitePool, from_id: &str, into_id: &str) -> Result<()> {
+    if from_id == into_id {
+        return Err(anyhow::anyhow!("Cannot merge a team into itself"));
+    }
+    let from = get_team_by_id(pool, from_id).await?
+        .ok_or_else(|| anyhow::anyhow!("Team {} not found", from_id))?;
+    let into = get_team_by_id(pool, into_id).await?
+        .ok_or_else(|| anyhow::anyhow!("Team {} not found", into_id))?;
+    if from.sport != into.sport {
+        return Err(anyhow::anyhow!("Cannot merge a {} team into a {} team", from.sport, into.sport));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("UPDATE matches SET home_team_id = ? WHERE home_team_id = ?")
+        .bind(into_id).bind(from_id).execute(&mut *tx).await?;
+    sqlx::query("UPDATE matches SET away_team_id = ? WHERE away_team_id = ?")
+        .bind(into_id).bind(from_id).execute(&mut *tx).await?;
+    // Old fixtures carry the canonical name so search and standings line up
+    sqlx::query("UPDATE matches SET home_team_name = ? WHERE home_team_id = ?")
+        .bind(&into.name).bind(into_id).execute(&mut *tx).await?;
+    sqlx::query("UPDATE matches SET away_team_name = ? WHERE away_team_id = ?")
+        .bind(&into.name).bind(into_id).execute(&mut *tx).await?;
+
+    // Tables keyed on team_id: move what doesn't collide, drop the rest
+    for table in ["elo_history", "match_xpts", "official_standings", "nba_advanced_stats", "team_home_advantage"] {
+        sqlx::query(&format!("UPDATE OR IGNORE {} SET team_id = ? WHERE team_id = ?", table))
+            .bind(into_id).bind(from_id).execute(&mut *tx).await?;
+        sqlx::query(&format!("DELETE FROM {} WHERE team_id = ?", table))
+            .bind(from_id).execute(&mut *tx).await?;
+    }
+    for table in ["nba_player_stats", "game_box_stats"] {
+        sqlx::query(&format!("UPDATE {} SET team_id = ? WHERE team_id = ?", table))
+            .bind(into_id).bind(from_id).execute(&mut *tx).await?;
+    }
+    sqlx::query("DELETE FROM team_stats WHERE team_id = ?")
+        .bind(from_id).execute(&mut *tx).await?;
+
+    // Aliases that pointed at the old id now point at the survivor
+    sqlx::query("UPDATE team_aliases SET team_id = ? WHERE team_id = ?")
+        .bind(into_id).bind(from_id).execute(&mut *tx).await?;
+    sqlx::query(
+        "INSERT OR REPLACE INTO team_aliases (alias_id, alias_name, team_id, created_at) VALUES (?, ?, ?, ?)",
+    )
+    .bind(from_id)
+    .bind(&from.name)
+    .bind(into_id)
+    .bind(Utc::now().to_rfc3339())
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("DELETE FROM teams WHERE id = ?")
+        .bind(from_id).execute(&mut *tx).await?;
+
+    tx.commit().await?;
+    tracing::info!("Merged team {} ({}) into {} ({})", from_id, from.name, into_id, into.name);
+    Ok(())
+}
+
 // Match operations
+/// A re-fetched fixture whose kickoff moved at least this far is treated as corrected:
+/// enough to change rest days, not just a kickoff-time tweak.
+const FIXTURE_MOVE_HOURS: i64 = 12;
+
 pub async fn insert_match(pool: &SqlitePool, match_data: &Match) -> Result<()> {
+    let before = fixture_identity(pool, &match_data.id).await?;
+
     sqlx::query(
         r#"
         INSERT OR REPLACE INTO matches 
         (id, home_team_id, away_team_id, home_team_name, away_team_name, sport, league, 
-         match_date, status, home_score, away_score, created_at, updated_at)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+         match_date, status, home_score, away_score, venue, city, tiebreak_winner, created_at, updated_at)
+        VALUES (?1,
+                COALESCE((SELECT team_id FROM team_aliases WHERE alias_id = ?2), ?2),
+                COALESCE((SELECT team_id FROM team_aliases WHERE alias_id = ?3), ?3),
+                ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11,
+                -- Keep a known venue when a later fetch comes from a source without one
+                COALESCE(?14, (SELECT venue FROM matches WHERE id = ?1)),
+                COALESCE(?15, (SELECT city FROM matches WHERE id = ?1)),
+                ?16, ?12, ?13)
         "#,
     )
     .bind(&match_data.id)
@@ -453,25 +955,105 @@ pub async fn insert_match(pool: &SqlitePool, match_data: &Match) -> Result<()> {
     .bind(match_data.away_score)
     .bind(match_data.created_at.to_rfc3339())
     .bind(match_data.updated_at.to_rfc3339())
+    .bind(&match_data.venue)
+    .bind(&match_data.city)
+    .bind(&match_data.tiebreak_winner)
     .execute(pool)
     .await?;
-    
+
+    // Compare after the upsert so team aliases are already resolved
+    if let (Some(old), Some(new)) = (before, fixture_identity(pool, &match_data.id).await?) {
+        let moved = chrono::DateTime::parse_from_rfc3339(&old.2).ok()
+            .zip(chrono::DateTime::parse_from_rfc3339(&new.2).ok())
+            .is_some_and(|(o, n)| (n - o).num_hours().abs() >= FIXTURE_MOVE_HOURS);
+        if old.0 != new.0 || old.1 != new.1 || moved {
+            tracing::warn!(
+                "Fixture {} corrected upstream: {} v {} @ {} -> {} v {} @ {}; clearing its predictions and odds",
+                match_data.id, old.0, old.1, old.2, new.0, new.1, new.2
+            );
+            clear_match_dependents(pool, &match_data.id).await?;
+        }
+    }
+
     Ok(())
 }
 
-pub async fn get_upcoming_matches(pool: &SqlitePool, sport: Option<&str>) -> Result<Vec<Match>> {
-    let query = if let Some(sport) = sport {
-        "SELECT * FROM matches WHERE match_date > datetime('now') AND sport = ? ORDER BY match_date LIMIT 50"
-    } else {
-        "SELECT * FROM matches WHERE match_date > datetime('now') ORDER BY match_date LIMIT 50"
-    };
-    
-    let mut query_builder = sqlx::query(query);
-    if let Some(sport) = sport {
-        query_builder = query_builder.bind(sport);
+/// (home_team_id, away_team_id, match_date) as currently stored.
+async fn fixture_identity(pool: &SqlitePool, match_id: &str) -> Result<Option<(String, String, String)>> {
+    Ok(sqlx::query_as("SELECT home_team_id, away_team_id, match_date FROM matches WHERE id = ?")
+        .bind(match_id)
+        .fetch_optional(pool)
+        .await?)
+}
+
+/// Drop everything derived from a match's teams and date. Predictions are regenerated by
+/// the next refresh, odds by the next odds fetch, ELO/xPts rows by the next rebuild.
+async fn clear_match_dependents(pool: &SqlitePool, match_id: &str) -> Result<()> {
+    for table in ["prediction_inputs", "consensus_predictions", "predictions", "total_predictions", "market_odds", "market_odds_history", "match_xpts", "elo_history", "ml_features"] {
+        sqlx::query(&format!("DELETE FROM {} WHERE match_id = ?", table))
+            .bind(match_id)
+            .execute(pool)
+            .await?;
     }
-    
-    let rows = query_builder.fetch_all(pool).await?;
+    Ok(())
+}
+
+pub async fn get_match_by_id(pool: &SqlitePool, match_id: &str) -> Result<Option<Match>> {
+    let fetch = sqlx::query_as::<_, Match>("SELECT * FROM matches WHERE id = ?")
+        .bind(match_id)
+        .fetch_optional(pool);
+    timed("get_match_by_id", fetch).await
+}
+
+/// Every scheduled, not yet played fixture left in a league, soonest first (no row limit).
+pub async fn get_remaining_league_fixtures(pool: &SqlitePool, sport: &str, league: &str) -> Result<Vec<Match>> {
+    let fetch = sqlx::query_as::<_, Match>(
+        "SELECT * FROM matches WHERE sport = ? AND league = ? AND status = 'scheduled' AND match_date > datetime('now') ORDER BY match_date",
+    )
+    .bind(sport)
+    .bind(league)
+    .fetch_all(pool);
+    timed("get_remaining_league_fixtures", fetch).await
+}
+
+pub async fn get_match_status(pool: &SqlitePool, match_id: &str) -> Result<Option<String>> {
+    let status = sqlx::query_scalar("SELECT status FROM matches WHERE id = ?")
+        .bind(match_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(status)
+}
+
+/// Rows `get_upcoming_matches` returns when the caller doesn't ask for a specific count.
+pub const DEFAULT_UPCOMING_LIMIT: usize = 50;
+
+pub async fn get_upcoming_matches(pool: &SqlitePool, sport: Option<&str>) -> Result<Vec<Match>> {
+    get_upcoming_matches_within(pool, sport, None, None, DEFAULT_UPCOMING_LIMIT).await
+}
+
+/// Upcoming matches, optionally bounded to kick off within the next `within_days` days,
+/// soonest first and at most `limit` of them.
+/// With `updated_since`, only matches whose row changed or that gained a prediction after
+/// that instant are returned, for clients polling incrementally.
+pub async fn get_upcoming_matches_within(
+    pool: &SqlitePool,
+    sport: Option<&str>,
+    within_days: Option<i64>,
+    updated_since: Option<chrono::DateTime<Utc>>,
+    limit: usize,
+) -> Result<Vec<Match>> {
+    let changed = "(?3 IS NULL OR updated_at > ?3 OR EXISTS (SELECT 1 FROM predictions p WHERE p.match_id = matches.id AND p.created_at > ?3))";
+    let query = format!("SELECT * FROM matches WHERE match_date > datetime('now') AND status NOT IN ('postponed', 'cancelled', 'live') AND (?1 IS NULL OR match_date < ?2) AND {} AND (?4 IS NULL OR sport = ?4) ORDER BY match_date LIMIT ?5", changed);
+
+    let horizon = within_days.map(|d| (Utc::now() + chrono::Duration::days(d)).to_rfc3339());
+    let fetch = sqlx::query(&query)
+        .bind(&horizon)
+        .bind(&horizon)
+        .bind(updated_since.map(|t| t.to_rfc3339()))
+        .bind(sport)
+        .bind(limit as i64)
+        .fetch_all(pool);
+    let rows = timed("get_upcoming_matches_within", fetch).await?;
     
     let mut matches = Vec::new();
     for row in rows {
@@ -487,6 +1069,9 @@ pub async fn get_upcoming_matches(pool: &SqlitePool, sport: Option<&str>) -> Res
             status: row.get("status"),
             home_score: row.get("home_score"),
             away_score: row.get("away_score"),
+            venue: row.get("venue"),
+            city: row.get("city"),
+            tiebreak_winner: row.get("tiebreak_winner"),
             created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<String, _>("created_at"))?.with_timezone(&Utc),
             updated_at: chrono::DateTime::parse_from_rfc3339(&row.get::<String, _>("updated_at"))?.with_timezone(&Utc),
         });
@@ -495,32 +1080,367 @@ pub async fn get_upcoming_matches(pool: &SqlitePool, sport: Option<&str>) -> Res
     Ok(matches)
 }
 
-pub async fn get_finished_matches_ordered(pool: &SqlitePool) -> Result<Vec<Match>> {
-    let rows = sqlx::query(
-        "SELECT * FROM matches WHERE status = 'finished' AND home_score IS NOT NULL ORDER BY match_date ASC"
+// ── Counts (COUNT(*) only, no rows loaded) ─────────────────────────────────────
+
+pub async fn count_teams(pool: &SqlitePool) -> Result<i64> {
+    Ok(sqlx::query_scalar("SELECT COUNT(*) FROM teams").fetch_one(pool).await?)
+}
+
+/// Matches with the given status, or all matches when `status` is None.
+pub async fn count_matches_by_status(pool: &SqlitePool, status: Option<&str>) -> Result<i64> {
+    Ok(sqlx::query_scalar("SELECT COUNT(*) FROM matches WHERE (? IS NULL OR status = ?)")
+        .bind(status)
+        .bind(status)
+        .fetch_one(pool)
+        .await?)
+}
+
+pub async fn count_predictions(pool: &SqlitePool) -> Result<i64> {
+    Ok(sqlx::query_scalar("SELECT COUNT(*) FROM predictions").fetch_one(pool).await?)
+}
+
+/// Same filter as `get_upcoming_matches_within`, without its row limit.
+pub async fn count_upcoming(pool: &SqlitePool, sport: Option<&str>, within_days: Option<i64>) -> Result<i64> {
+    let horizon = within_days.map(|d| (Utc::now() + chrono::Duration::days(d)).to_rfc3339());
+    Ok(sqlx::query_scalar(
+        r#"SELECT COUNT(*) FROM matches
+           WHERE match_date > datetime('now') AND status NOT IN ('postponed', 'cancelled', 'live')
+             AND (? IS NULL OR match_date < ?) AND (? IS NULL OR sport = ?)"#,
+    )
+    .bind(&horizon)
+    .bind(&horizon)
+    .bind(sport)
+    .bind(sport)
+    .fetch_one(pool)
+    .await?)
+}
+
+/// Postponed or cancelled matches, most recently scheduled first.
+pub async fn get_postponed_matches(pool: &SqlitePool, sport: Option<&str>) -> Result<Vec<Match>> {
+    let rows = sqlx::query_as::<_, Match>(
+        r#"SELECT * FROM matches
+           WHERE status IN ('postponed', 'cancelled') AND (? IS NULL OR sport = ?)
+           ORDER BY match_date DESC"#,
     )
+    .bind(sport)
+    .bind(sport)
     .fetch_all(pool)
     .await?;
+    Ok(rows)
+}
 
-    let mut matches = Vec::new();
-    for row in rows {
-        matches.push(Match {
-            id:             row.get("id"),
-            home_team_id:   row.get("home_team_id"),
-            away_team_id:   row.get("away_team_id"),
-            home_team_name: row.get("home_team_name"),
-            away_team_name: row.get("away_team_name"),
-            sport:          row.get("sport"),
-            league:         row.get("league"),
-            match_date:     chrono::DateTime::parse_from_rfc3339(&row.get::<String, _>("match_date"))?.with_timezone(&Utc),
-            status:         row.get("status"),
-            home_score:     row.get("home_score"),
-            away_score:     row.get("away_score"),
-            created_at:     chrono::DateTime::parse_from_rfc3339(&row.get::<String, _>("created_at"))?.with_timezone(&Utc),
-            updated_at:     chrono::DateTime::parse_from_rfc3339(&row.get::<String, _>("updated_at"))?.with_timezone(&Utc),
-        });
+/// Scheduled matches whose kickoff is more than `grace_hours` in the past: the result
+/// never arrived, so they have dropped out of upcoming without being resolved.
+pub async fn get_stale_matches(pool: &SqlitePool, sport: Option<&str>, grace_hours: i64) -> Result<Vec<Match>> {
+    get_matches_between(pool, &MatchDateFilter {
+        sport,
+        status: Some("scheduled"),
+        before: Some(Utc::now() - chrono::Duration::hours(grace_hours)),
+        oldest_first: true,
+        ..Default::default()
+    })
+    .await
+}
+
+/// How many matches `get_stale_matches` would return, without loading them.
+pub async fn count_stale_matches(pool: &SqlitePool, sport: Option<&str>, grace_hours: i64) -> Result<i64> {
+    Ok(sqlx::query_scalar(
+        r#"SELECT COUNT(*) FROM matches
+           WHERE status = 'scheduled' AND match_date < ?1 AND (?2 IS NULL OR sport = ?2)"#,
+    )
+    .bind((Utc::now() - chrono::Duration::hours(grace_hours)).to_rfc3339())
+    .bind(sport)
+    .fetch_one(pool)
+    .await?)
+}
+
+/// Upcoming matches whose latest prediction has at least `min_confidence`, most confident first.
+pub async fn get_confident_predictions(
+    pool: &SqlitePool,
+    min_confidence: f64,
+    sport: Option<&str>,
+) -> Result<Vec<MatchPick>> {
+    let fetch = sqlx::query(
+        r#"SELECT m.*, p.id AS prediction_id, p.home_win_probability, p.away_win_probability,
+                  p.draw_probability, p.model_version, p.confidence_score, p.created_at AS predicted_at,
+                  p.insufficient_data, p.most_likely_score
+           FROM matches m
+           JOIN predictions p ON p.id = (
+               SELECT id FROM predictions WHERE match_id = m.id ORDER BY created_at DESC LIMIT 1
+           )
+           WHERE m.match_date > ? AND m.status NOT IN ('postponed', 'cancelled', 'live')
+             AND p.confidence_score >= ? AND (? IS NULL OR m.sport = ?)
+           ORDER BY p.confidence_score DESC, m.match_date ASC"#,
+    )
+    .bind(Utc::now().to_rfc3339())
+    .bind(min_confidence)
+    .bind(sport)
+    .bind(sport)
+    .fetch_all(pool);
+    let rows = timed("get_confident_predictions", fetch).await?;
+
+    rows.iter().map(match_pick_from_row).collect()
+}
+
+/// A match row joined with one prediction's columns, the prediction id aliased to
+/// `prediction_id` and its timestamp to `predicted_at`.
+fn match_pick_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<MatchPick> {
+    Ok(MatchPick {
+        match_info: Match::from_row(row)?,
+        prediction: Prediction {
+            id: row.get("prediction_id"),
+            match_id: row.get("id"),
+            home_win_probability: row.get("home_win_probability"),
+            away_win_probability: row.get("away_win_probability"),
+            draw_probability: row.get("draw_probability"),
+            model_version: row.get("model_version"),
+            confidence_score: row.get("confidence_score"),
+            created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<String, _>("predicted_at"))?.with_timezone(&Utc),
+            insufficient_data: row.try_get("insufficient_data").unwrap_or(false),
+            most_likely_score: row.try_get("most_likely_score").unwrap_or(None),
+        },
+    })
+}
+
+/// A team's finished, scored matches paired with the last prediction made before
+/// kickoff, oldest first. Matches never predicted before kickoff are left out.
+pub async fn get_resolved_predictions_for_team(pool: &SqlitePool, team_id: &str) -> Result<Vec<MatchPick>> {
+    let rows = sqlx::query(
+        r#"SELECT m.*, p.id AS prediction_id, p.home_win_probability, p.away_win_probability,
+                  p.draw_probability, p.model_version, p.confidence_score, p.created_at AS predicted_at,
+                  p.insufficient_data, p.most_likely_score
+           FROM matches m
+           JOIN predictions p ON p.id = (
+               SELECT id FROM predictions
+               WHERE match_id = m.id AND created_at <= m.match_date
+               ORDER BY created_at DESC LIMIT 1
+           )
+           WHERE (m.home_team_id = ?1 OR m.away_team_id = ?1) AND m.status = 'finished'
+             AND m.home_score IS NOT NULL AND m.away_score IS NOT NULL
+           ORDER BY m.match_date"#,
+    )
+    .bind(team_id)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter().map(match_pick_from_row).collect()
+}
+
+/// Multiplier on the default home advantage for a team's home games (1.0 when unset).
+pub async fn get_home_advantage(pool: &SqlitePool, team_id: &str) -> Result<f64> {
+    let multiplier: Option<f64> = sqlx::query_scalar("SELECT multiplier FROM team_home_advantage WHERE team_id = ?")
+        .bind(team_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(multiplier.unwrap_or(1.0))
+}
+
+/// Every stored home-advantage multiplier, by team; teams without one default to 1.0.
+pub async fn get_home_advantages(pool: &SqlitePool) -> Result<std::collections::HashMap<String, f64>> {
+    let rows: Vec<(String, f64)> = sqlx::query_as("SELECT team_id, multiplier FROM team_home_advantage")
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().collect())
+}
+
+/// Store the current rating of every team that has finished a match as its preseason
+/// rating, replacing older ones. Returns how many were written.
+pub async fn save_preseason_ratings(pool: &SqlitePool) -> Result<u64> {
+    let result = sqlx::query(
+        r#"INSERT INTO preseason_ratings (team_id, rating, updated_at)
+           SELECT t.id, t.elo_rating, ? FROM teams t
+           WHERE EXISTS (SELECT 1 FROM matches m
+                         WHERE m.status = 'finished' AND (m.home_team_id = t.id OR m.away_team_id = t.id))
+           ON CONFLICT(team_id) DO UPDATE SET
+               rating = excluded.rating,
+               season = NULL,
+               updated_at = excluded.updated_at"#,
+    )
+    .bind(Utc::now().to_rfc3339())
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Rating carried-over ratings are regressed toward.
+pub const CARRYOVER_MEAN: f64 = 1500.0;
+
+/// Record every team's current rating as its final rating for `season`, replacing any
+/// earlier snapshot of that season. Returns how many were written.
+pub async fn save_season_final_ratings(pool: &SqlitePool, season: &str) -> Result<u64> {
+    let result = sqlx::query(
+        r#"INSERT INTO season_final_ratings (team_id, season, elo_rating, recorded_at)
+           SELECT id, ?, elo_rating, ? FROM teams
+           WHERE true -- lets SQLite parse the ON CONFLICT below as an upsert
+           ON CONFLICT(team_id, season) DO UPDATE SET
+               elo_rating = excluded.elo_rating,
+               recorded_at = excluded.recorded_at"#,
+    )
+    .bind(season)
+    .bind(Utc::now().to_rfc3339())
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Start `to_season` from `from_season`'s final ratings, each moved `regression` of the way
+/// toward `CARRYOVER_MEAN` (0.25 takes 1600 to 1575). Written as preseason ratings tagged
+/// with `to_season`; the ELO rebuild resets each team to it where that season begins.
+/// Returns how many teams were carried over.
+pub async fn carry_over_ratings(pool: &SqlitePool, from_season: &str, to_season: &str, regression: f64) -> Result<u64> {
+    let result = sqlx::query(
+        r#"INSERT INTO preseason_ratings (team_id, rating, season, updated_at)
+           SELECT team_id, elo_rating - ?1 * (elo_rating - ?2), ?3, ?4 FROM season_final_ratings
+           WHERE season = ?5
+           ON CONFLICT(team_id) DO UPDATE SET
+               rating = excluded.rating,
+               season = excluded.season,
+               updated_at = excluded.updated_at"#,
+    )
+    .bind(regression)
+    .bind(CARRYOVER_MEAN)
+    .bind(to_season)
+    .bind(Utc::now().to_rfc3339())
+    .bind(from_season)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Every preseason rating with the season it starts: (team_id, rating, season). The season
+/// is None for ratings saved by a wipe.
+pub async fn get_preseason_rating_seasons(pool: &SqlitePool) -> Result<Vec<(String, f64, Option<String>)>> {
+    let rows = sqlx::query_as("SELECT team_id, rating, season FROM preseason_ratings")
+        .fetch_all(pool)
+        .await?;
+    Ok(rows)
+}
+
+pub async fn get_preseason_ratings(pool: &SqlitePool) -> Result<std::collections::HashMap<String, f64>> {
+    let rows: Vec<(String, f64)> = sqlx::query_as("SELECT team_id, rating FROM preseason_ratings")
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().collect())
+}
+
+pub async fn set_home_advantage(pool: &SqlitePool, team_id: &str, multiplier: f64) -> Result<()> {
+    sqlx::query(
+        r#"INSERT INTO team_home_advantage (team_id, multiplier, updated_at) VALUES (?, ?, ?)
+           ON CONFLICT(team_id) DO UPDATE SET
+               multiplier = excluded.multiplier,
+               updated_at = excluded.updated_at"#,
+    )
+    .bind(team_id)
+    .bind(multiplier)
+    .bind(Utc::now().to_rfc3339())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Same home and away team on the same calendar day under different ids, e.g. a game
+/// re-issued upstream with a new id after a reschedule, or a genuine double-header.
+pub async fn find_duplicate_matches(pool: &SqlitePool) -> Result<Vec<DuplicateMatches>> {
+    let rows = sqlx::query_as::<_, Match>(
+        r#"SELECT m.* FROM matches m
+           JOIN (SELECT home_team_id, away_team_id, substr(match_date, 1, 10) AS day
+                 FROM matches
+                 GROUP BY home_team_id, away_team_id, day
+                 HAVING COUNT(*) > 1) d
+             ON m.home_team_id = d.home_team_id AND m.away_team_id = d.away_team_id
+            AND substr(m.match_date, 1, 10) = d.day
+           ORDER BY m.match_date ASC, m.id ASC"#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut groups: Vec<Vec<Match>> = Vec::new();
+    for m in rows {
+        let day = m.match_date.date_naive();
+        match groups.last_mut() {
+            Some(g) if g[0].home_team_id == m.home_team_id
+                && g[0].away_team_id == m.away_team_id
+                && g[0].match_date.date_naive() == day => g.push(m),
+            _ => groups.push(vec![m]),
+        }
     }
-    Ok(matches)
+
+    Ok(groups.into_iter().filter(|g| g.len() > 1).map(|mut g| {
+        // Prefer a row with a final score, then the freshest row
+        let keep_idx = g.iter().enumerate()
+            .max_by_key(|(_, m)| (m.status == "finished" && m.home_score.is_some(), m.updated_at))
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+        let keep = g.remove(keep_idx);
+        DuplicateMatches { keep, duplicates: g }
+    }).collect())
+}
+
+/// Rows that should not be possible after a clean fetch: finished matches without a score,
+/// negative scores, matches pointing at missing teams, and predictions whose outcome
+/// probabilities do not sum to ~1.
+pub async fn validate_data_integrity(pool: &SqlitePool) -> Result<Vec<IntegrityIssue>> {
+    let checks: [(&str, &str); 5] = [
+        (
+            "finished_without_score",
+            "SELECT id, 'finished but score is ' || COALESCE(home_score, 'null') || '-' || COALESCE(away_score, 'null')
+             FROM matches WHERE status = 'finished' AND (home_score IS NULL OR away_score IS NULL)",
+        ),
+        (
+            "negative_score",
+            "SELECT id, 'score is ' || home_score || '-' || away_score
+             FROM matches WHERE home_score < 0 OR away_score < 0",
+        ),
+        (
+            "missing_home_team",
+            "SELECT m.id, 'home team ' || m.home_team_id || ' does not exist'
+             FROM matches m LEFT JOIN teams t ON t.id = m.home_team_id WHERE t.id IS NULL",
+        ),
+        (
+            "missing_away_team",
+            "SELECT m.id, 'away team ' || m.away_team_id || ' does not exist'
+             FROM matches m LEFT JOIN teams t ON t.id = m.away_team_id WHERE t.id IS NULL",
+        ),
+        (
+            "probabilities_not_normalized",
+            "SELECT id, 'probabilities sum to ' ||
+                    ROUND(home_win_probability + away_win_probability + COALESCE(draw_probability, 0), 4)
+             FROM predictions
+             WHERE ABS(home_win_probability + away_win_probability + COALESCE(draw_probability, 0) - 1.0) > 0.01",
+        ),
+    ];
+
+    let mut issues = Vec::new();
+    for (kind, sql) in checks {
+        let rows: Vec<(String, String)> = sqlx::query_as(sql).fetch_all(pool).await?;
+        issues.extend(rows.into_iter().map(|(entity_id, detail)| IntegrityIssue {
+            kind: kind.to_string(),
+            entity_id,
+            detail,
+        }));
+    }
+    Ok(issues)
+}
+
+/// Matches currently in play, earliest kickoff first.
+pub async fn get_live_matches(pool: &SqlitePool, sport: Option<&str>) -> Result<Vec<Match>> {
+    get_matches_between(pool, &MatchDateFilter {
+        sport,
+        status: Some("live"),
+        oldest_first: true,
+        ..Default::default()
+    })
+    .await
+}
+
+pub async fn get_finished_matches_ordered(pool: &SqlitePool) -> Result<Vec<Match>> {
+    get_matches_between(pool, &MatchDateFilter {
+        status: Some("finished"),
+        scored_only: true,
+        oldest_first: true,
+        ..Default::default()
+    })
+    .await
 }
 
 // Prediction operations
@@ -529,8 +1449,8 @@ pub async fn insert_prediction(pool: &SqlitePool, prediction: &Prediction) -> Re
         r#"
         INSERT OR REPLACE INTO predictions 
         (id, match_id, home_win_probability, away_win_probability, draw_probability, 
-         model_version, confidence_score, created_at)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+         model_version, confidence_score, created_at, insufficient_data, most_likely_score)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#,
     )
     .bind(&prediction.id)
@@ -541,40 +1461,154 @@ pub async fn insert_prediction(pool: &SqlitePool, prediction: &Prediction) -> Re
     .bind(&prediction.model_version)
     .bind(prediction.confidence_score)
     .bind(prediction.created_at.to_rfc3339())
+    .bind(prediction.insufficient_data)
+    .bind(&prediction.most_likely_score)
     .execute(pool)
     .await?;
     
     Ok(())
 }
 
-pub async fn get_prediction_by_match_id(pool: &SqlitePool, match_id: &str) -> Result<Option<Prediction>> {
-    let row = sqlx::query("SELECT * FROM predictions WHERE match_id = ? ORDER BY created_at DESC LIMIT 1")
+/// Store a match's expected score, replacing the previous one.
+pub async fn upsert_total_prediction(pool: &SqlitePool, totals: &crate::models::TotalsPrediction) -> Result<()> {
+    sqlx::query(
+        r#"INSERT INTO total_predictions (match_id, model, expected_home_score, expected_away_score, total_sd, possessions, created_at)
+           VALUES (?, ?, ?, ?, ?, ?, ?)
+           ON CONFLICT(match_id) DO UPDATE SET
+               model               = excluded.model,
+               expected_home_score = excluded.expected_home_score,
+               expected_away_score = excluded.expected_away_score,
+               total_sd            = excluded.total_sd,
+               possessions         = excluded.possessions,
+               created_at          = excluded.created_at"#,
+    )
+    .bind(&totals.match_id)
+    .bind(&totals.model)
+    .bind(totals.expected_home_score)
+    .bind(totals.expected_away_score)
+    .bind(totals.total_sd)
+    .bind(totals.possessions)
+    .bind(totals.created_at.to_rfc3339())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn get_total_prediction(pool: &SqlitePool, match_id: &str) -> Result<Option<crate::models::TotalsPrediction>> {
+    Ok(sqlx::query_as("SELECT * FROM total_predictions WHERE match_id = ?")
         .bind(match_id)
         .fetch_optional(pool)
+        .await?)
+}
+
+/// Store a consensus (model + market) prediction, replacing the last one for the same
+/// match and blend weight. `prediction_id` is the pure-model prediction it was blended from.
+pub async fn insert_consensus_prediction(
+    pool: &SqlitePool,
+    prediction_id: &str,
+    blend_weight: f64,
+    consensus: &Prediction,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT OR REPLACE INTO consensus_predictions
+        (id, match_id, prediction_id, blend_weight, home_win_probability, away_win_probability,
+         draw_probability, model_version, confidence_score, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&consensus.id)
+    .bind(&consensus.match_id)
+    .bind(prediction_id)
+    .bind(blend_weight)
+    .bind(consensus.home_win_probability)
+    .bind(consensus.away_win_probability)
+    .bind(consensus.draw_probability)
+    .bind(&consensus.model_version)
+    .bind(consensus.confidence_score)
+    .bind(consensus.created_at.to_rfc3339())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn insert_prediction_inputs(pool: &SqlitePool, inputs: &PredictionInputs) -> Result<()> {
+    sqlx::query(
+        "INSERT OR REPLACE INTO prediction_inputs (prediction_id, match_id, snapshot, created_at) VALUES (?, ?, ?, ?)",
+    )
+    .bind(&inputs.prediction_id)
+    .bind(&inputs.match_id)
+    .bind(serde_json::to_string(inputs)?)
+    .bind(inputs.captured_at.to_rfc3339())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_prediction_inputs(pool: &SqlitePool, prediction_id: &str) -> Result<Option<PredictionInputs>> {
+    let snapshot: Option<String> = sqlx::query_scalar("SELECT snapshot FROM prediction_inputs WHERE prediction_id = ?")
+        .bind(prediction_id)
+        .fetch_optional(pool)
         .await?;
+
+    Ok(snapshot.map(|s| serde_json::from_str(&s)).transpose()?)
+}
+
+pub async fn get_prediction_by_match_id(pool: &SqlitePool, match_id: &str) -> Result<Option<Prediction>> {
+    let fetch = sqlx::query("SELECT * FROM predictions WHERE match_id = ? ORDER BY created_at DESC LIMIT 1")
+        .bind(match_id)
+        .fetch_optional(pool);
+    let row = timed("get_prediction_by_match_id", fetch).await?;
     
-    if let Some(row) = row {
-        Ok(Some(Prediction {
-            id: row.get("id"),
-            match_id: row.get("match_id"),
-            home_win_probability: row.get("home_win_probability"),
-            away_win_probability: row.get("away_win_probability"),
-            draw_probability: row.get("draw_probability"),
-            model_version: row.get("model_version"),
-            confidence_score: row.get("confidence_score"),
-            created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<String, _>("created_at"))?.with_timezone(&Utc),
-        }))
-    } else {
-        Ok(None)
-    }
+    row.map(|r| prediction_from_row(&r)).transpose()
+}
+
+/// Every prediction stored for a match, oldest first.
+pub async fn get_prediction_history(pool: &SqlitePool, match_id: &str) -> Result<Vec<Prediction>> {
+    let rows = sqlx::query("SELECT * FROM predictions WHERE match_id = ? ORDER BY created_at")
+        .bind(match_id)
+        .fetch_all(pool)
+        .await?;
+
+    rows.iter().map(prediction_from_row).collect()
+}
+
+/// Latest prediction for a match stored under one model version, e.g. a registry candidate.
+pub async fn get_prediction_for_version(pool: &SqlitePool, match_id: &str, model_version: &str) -> Result<Option<Prediction>> {
+    let row = sqlx::query(
+        "SELECT * FROM predictions WHERE match_id = ? AND model_version = ? ORDER BY created_at DESC LIMIT 1",
+    )
+    .bind(match_id)
+    .bind(model_version)
+    .fetch_optional(pool)
+    .await?;
+
+    row.map(|r| prediction_from_row(&r)).transpose()
+}
+
+fn prediction_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<Prediction> {
+    Ok(Prediction {
+        id: row.get("id"),
+        match_id: row.get("match_id"),
+        home_win_probability: row.get("home_win_probability"),
+        away_win_probability: row.get("away_win_probability"),
+        draw_probability: row.get("draw_probability"),
+        model_version: row.get("model_version"),
+        confidence_score: row.get("confidence_score"),
+        created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<String, _>("created_at"))?.with_timezone(&Utc),
+        insufficient_data: row.try_get("insufficient_data").unwrap_or(false),
+        most_likely_score: row.try_get("most_likely_score").unwrap_or(None),
+    })
 }
 
 // Additional query helpers
 
 pub async fn get_all_teams(pool: &SqlitePool) -> Result<Vec<Team>> {
-    let rows = sqlx::query("SELECT * FROM teams ORDER BY sport, league, elo_rating DESC")
-        .fetch_all(pool)
-        .await?;
+    let fetch = sqlx::query("SELECT * FROM teams ORDER BY sport, league, elo_rating DESC")
+        .fetch_all(pool);
+    let rows = timed("get_all_teams", fetch).await?;
 
     let mut teams = Vec::new();
     for row in rows {
@@ -587,6 +1621,8 @@ pub async fn get_all_teams(pool: &SqlitePool) -> Result<Vec<Team>> {
             elo_rating: row.get("elo_rating"),
             created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<String, _>("created_at"))?.with_timezone(&Utc),
             updated_at: chrono::DateTime::parse_from_rfc3339(&row.get::<String, _>("updated_at"))?.with_timezone(&Utc),
+            elo_percentile: None,
+            elo_tier: None,
         });
     }
     Ok(teams)
@@ -614,6 +1650,7 @@ pub async fn get_team_current_stats(pool: &SqlitePool, team_id: &str) -> Result<
             points_for: row.get("points_for"),
             points_against: row.get("points_against"),
             form: row.get::<Option<String>, _>("form").unwrap_or_default(),
+            xpts: row.get("xpts"),
             updated_at: chrono::DateTime::parse_from_rfc3339(&row.get::<String, _>("updated_at"))?.with_timezone(&Utc),
         }))
     } else {
@@ -622,36 +1659,438 @@ pub async fn get_team_current_stats(pool: &SqlitePool, team_id: &str) -> Result<
 }
 
 pub async fn get_team_recent_matches(pool: &SqlitePool, team_id: &str, limit: i64) -> Result<Vec<Match>> {
-    let rows = sqlx::query(
+    get_matches_for_team(pool, team_id, &MatchFilter {
+        status: Some("finished"),
+        limit: Some(limit),
+        ..Default::default()
+    })
+    .await
+}
+
+/// Which side of the fixture the team was on.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Venue {
+    #[default]
+    Any,
+    Home,
+    Away,
+}
+
+/// Filters for `get_matches_for_team`. The default is every match the team played
+/// or will play, newest first.
+#[derive(Debug, Clone, Default)]
+pub struct MatchFilter<'a> {
+    pub venue: Venue,
+    /// Only meetings with this team (head-to-head)
+    pub opponent: Option<&'a str>,
+    pub status: Option<&'a str>,
+    pub sport: Option<&'a str>,
+    /// Inclusive lower bound on match_date
+    pub after: Option<chrono::DateTime<Utc>>,
+    /// Exclusive upper bound on match_date
+    pub before: Option<chrono::DateTime<Utc>>,
+    /// Skip rows without both scores
+    pub scored_only: bool,
+    pub oldest_first: bool,
+    pub limit: Option<i64>,
+}
+
+/// One team's matches, served by the (home_team_id, match_date) and
+/// (away_team_id, match_date) indexes.
+pub async fn get_matches_for_team(pool: &SqlitePool, team_id: &str, filter: &MatchFilter<'_>) -> Result<Vec<Match>> {
+    let team_clause = match (filter.venue, filter.opponent.is_some()) {
+        (Venue::Any, false) => "(home_team_id = ?1 OR away_team_id = ?1)",
+        (Venue::Home, false) => "home_team_id = ?1",
+        (Venue::Away, false) => "away_team_id = ?1",
+        (Venue::Any, true) => "((home_team_id = ?1 AND away_team_id = ?2) OR (home_team_id = ?2 AND away_team_id = ?1))",
+        (Venue::Home, true) => "(home_team_id = ?1 AND away_team_id = ?2)",
+        (Venue::Away, true) => "(away_team_id = ?1 AND home_team_id = ?2)",
+    };
+    let scored = if filter.scored_only { "AND home_score IS NOT NULL AND away_score IS NOT NULL" } else { "" };
+    let order = if filter.oldest_first { "ASC" } else { "DESC" };
+
+    let sql = format!(
+        r#"SELECT * FROM matches
+           WHERE {team_clause}
+             AND (?3 IS NULL OR status = ?3)
+             AND (?4 IS NULL OR sport = ?4)
+             AND (?5 IS NULL OR match_date >= ?5)
+             AND (?6 IS NULL OR match_date < ?6)
+             {scored}
+           ORDER BY match_date {order}
+           LIMIT ?7"#
+    );
+    let fetch = sqlx::query_as::<_, Match>(&sql)
+    .bind(team_id)
+    .bind(filter.opponent)
+    .bind(filter.status)
+    .bind(filter.sport)
+    .bind(filter.after.map(|d| d.to_rfc3339()))
+    .bind(filter.before.map(|d| d.to_rfc3339()))
+    .bind(filter.limit.unwrap_or(-1))
+    .fetch_all(pool);
+    let rows = timed("get_matches_for_team", fetch).await?;
+    Ok(rows)
+}
+
+/// Filters for `get_matches_between`. The default is every match, newest first.
+#[derive(Debug, Clone, Default)]
+pub struct MatchDateFilter<'a> {
+    pub sport: Option<&'a str>,
+    pub league: Option<&'a str>,
+    pub status: Option<&'a str>,
+    /// Inclusive lower bound on match_date
+    pub after: Option<chrono::DateTime<Utc>>,
+    /// Exclusive upper bound on match_date
+    pub before: Option<chrono::DateTime<Utc>>,
+    /// Skip rows without both scores
+    pub scored_only: bool,
+    pub oldest_first: bool,
+    pub limit: Option<i64>,
+}
+
+/// League-wide matches in a date window, the counterpart of `get_matches_for_team` for
+/// analytics that are not about one team. Served by the (sport, status, match_date) index.
+pub async fn get_matches_between(pool: &SqlitePool, filter: &MatchDateFilter<'_>) -> Result<Vec<Match>> {
+    let scored = if filter.scored_only { "AND home_score IS NOT NULL AND away_score IS NOT NULL" } else { "" };
+    let order = if filter.oldest_first { "ASC" } else { "DESC" };
+
+    let sql = format!(
         r#"SELECT * FROM matches
-           WHERE (home_team_id = ? OR away_team_id = ?) AND status = 'finished'
-           ORDER BY match_date DESC LIMIT ?"#
+           WHERE (?1 IS NULL OR sport = ?1)
+             AND (?2 IS NULL OR league = ?2)
+             AND (?3 IS NULL OR status = ?3)
+             AND (?4 IS NULL OR match_date >= ?4)
+             AND (?5 IS NULL OR match_date < ?5)
+             {scored}
+           ORDER BY match_date {order}
+           LIMIT ?6"#
+    );
+    let fetch = sqlx::query_as::<_, Match>(&sql)
+    .bind(filter.sport)
+    .bind(filter.league)
+    .bind(filter.status)
+    .bind(filter.after.map(|d| d.to_rfc3339()))
+    .bind(filter.before.map(|d| d.to_rfc3339()))
+    .bind(filter.limit.unwrap_or(-1))
+    .fetch_all(pool);
+    let rows = timed("get_matches_between", fetch).await?;
+    Ok(rows)
+}
+
+/// Every finished, scored match in a date window, oldest first, as a row stream so large
+/// exports never hold the whole result set in memory.
+pub fn stream_finished_matches<'a>(
+    pool: &'a SqlitePool,
+    sport: Option<&'a str>,
+    after: Option<chrono::DateTime<Utc>>,
+    before: Option<chrono::DateTime<Utc>>,
+) -> BoxStream<'a, sqlx::Result<Match>> {
+    sqlx::query_as::<_, Match>(
+        r#"SELECT * FROM matches
+           WHERE status = 'finished' AND home_score IS NOT NULL AND away_score IS NOT NULL
+             AND (?1 IS NULL OR sport = ?1)
+             AND (?2 IS NULL OR match_date >= ?2)
+             AND (?3 IS NULL OR match_date < ?3)
+           ORDER BY match_date ASC"#,
+    )
+    .bind(sport)
+    .bind(after.map(|d| d.to_rfc3339()))
+    .bind(before.map(|d| d.to_rfc3339()))
+    .fetch(pool)
+}
+
+// ── Expected points & standings ──────────────────────────────────────────────
+
+/// Total xPts credited to a team for matches dated in [after, before); None without any.
+pub async fn get_team_xpts_between(
+    pool: &SqlitePool,
+    team_id: &str,
+    after: chrono::DateTime<Utc>,
+    before: chrono::DateTime<Utc>,
+) -> Result<Option<f64>> {
+    Ok(sqlx::query_scalar(
+        r#"SELECT SUM(mx.xpts) FROM match_xpts mx JOIN matches m ON m.id = mx.match_id
+           WHERE mx.team_id = ? AND m.match_date >= ? AND m.match_date < ?"#,
     )
     .bind(team_id)
+    .bind(after.to_rfc3339())
+    .bind(before.to_rfc3339())
+    .fetch_one(pool)
+    .await?)
+}
+
+pub async fn upsert_match_xpts(
+    pool: &SqlitePool,
+    match_id: &str,
+    team_id: &str,
+    xpts: f64,
+    actual_points: f64,
+) -> Result<()> {
+    sqlx::query(
+        r#"INSERT INTO match_xpts (match_id, team_id, xpts, actual_points, computed_at)
+           VALUES (?, ?, ?, ?, ?)
+           ON CONFLICT(match_id, team_id) DO UPDATE SET
+               xpts          = excluded.xpts,
+               actual_points = excluded.actual_points,
+               computed_at   = excluded.computed_at"#,
+    )
+    .bind(match_id)
     .bind(team_id)
-    .bind(limit)
+    .bind(xpts)
+    .bind(actual_points)
+    .bind(Utc::now().to_rfc3339())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Tiebreak criteria, applied in order until teams separate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Tiebreak {
+    Points,
+    WinPct,
+    /// Goal difference (football) or point differential (basketball)
+    ScoreDiff,
+    GoalsFor,
+    /// Points (football) or wins (basketball) in games among the tied teams only
+    HeadToHead,
+}
+
+/// Competition rules per league; unknown leagues fall back to their sport's rules.
+fn tiebreak_rules(sport: &str, league: &str) -> &'static [Tiebreak] {
+    use Tiebreak::*;
+    match (sport, league) {
+        ("football", "EPL") => &[Points, ScoreDiff, GoalsFor, HeadToHead],
+        ("basketball", "NBA") => &[WinPct, HeadToHead, ScoreDiff],
+        ("basketball", _) => &[WinPct, HeadToHead, ScoreDiff],
+        _ => &[Points, ScoreDiff, GoalsFor, HeadToHead],
+    }
+}
+
+/// League table for the latest season in team_stats, ordered by the league's tiebreak rules.
+/// xPts and its delta both come from the season's match_xpts rows, so they cover the same
+/// (predicted) matches.
+pub async fn get_standings(pool: &SqlitePool, sport: &str, league: &str) -> Result<Vec<StandingsRow>> {
+    let season: Option<String> = sqlx::query_scalar("SELECT MAX(season) FROM team_stats")
+        .fetch_one(pool)
+        .await?;
+    let bounds = season.as_deref().and_then(crate::utils::season_bounds);
+    let rows = sqlx::query(
+        r#"SELECT t.id AS team_id, t.name AS team_name, ts.matches_played, ts.wins, ts.draws, ts.losses,
+                  ts.goals_for, ts.goals_against, ts.points_for, ts.points_against, ts.form,
+                  x.xpts, x.xpts_diff
+           FROM team_stats ts
+           JOIN teams t ON t.id = ts.team_id
+           LEFT JOIN (
+               SELECT mx.team_id, SUM(mx.xpts) AS xpts, SUM(mx.actual_points - mx.xpts) AS xpts_diff
+               FROM match_xpts mx
+               JOIN matches m ON m.id = mx.match_id
+               WHERE (?4 IS NULL OR m.match_date >= ?4) AND (?5 IS NULL OR m.match_date < ?5)
+               GROUP BY mx.team_id
+           ) x ON x.team_id = t.id
+           WHERE t.sport = ?1 AND t.league = ?2 AND ts.season = ?3"#,
+    )
+    .bind(sport)
+    .bind(league)
+    .bind(&season)
+    .bind(bounds.map(|(start, _)| start.to_rfc3339()))
+    .bind(bounds.map(|(_, end)| end.to_rfc3339()))
     .fetch_all(pool)
     .await?;
 
-    let mut matches = Vec::new();
-    for row in rows {
-        matches.push(Match {
-            id: row.get("id"),
-            home_team_id: row.get("home_team_id"),
-            away_team_id: row.get("away_team_id"),
-            home_team_name: row.get("home_team_name"),
-            away_team_name: row.get("away_team_name"),
-            sport: row.get("sport"),
-            league: row.get("league"),
-            match_date: chrono::DateTime::parse_from_rfc3339(&row.get::<String, _>("match_date"))?.with_timezone(&Utc),
-            status: row.get("status"),
-            home_score: row.get("home_score"),
-            away_score: row.get("away_score"),
-            created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<String, _>("created_at"))?.with_timezone(&Utc),
-            updated_at: chrono::DateTime::parse_from_rfc3339(&row.get::<String, _>("updated_at"))?.with_timezone(&Utc),
-        });
+    // (row, goals or points scored)
+    let table: Vec<(StandingsRow, i32)> = rows.iter().map(|row| {
+        let wins: i32 = row.get("wins");
+        let draws: Option<i32> = row.get("draws");
+        let points = if sport == "football" { wins * 3 + draws.unwrap_or(0) } else { wins };
+        let (scored, conceded) = if sport == "football" {
+            (row.get::<Option<i32>, _>("goals_for").unwrap_or(0), row.get::<Option<i32>, _>("goals_against").unwrap_or(0))
+        } else {
+            (row.get::<Option<i32>, _>("points_for").unwrap_or(0), row.get::<Option<i32>, _>("points_against").unwrap_or(0))
+        };
+        (StandingsRow {
+            position: 0,
+            team_id: row.get("team_id"),
+            team_name: row.get("team_name"),
+            played: row.get("matches_played"),
+            wins,
+            draws,
+            losses: row.get("losses"),
+            points,
+            score_diff: scored - conceded,
+            xpts: row.get("xpts"),
+            xpts_diff: row.get("xpts_diff"),
+            form: row.get::<Option<String>, _>("form").unwrap_or_default(),
+        }, scored)
+    }).collect();
+
+    // Where the competition publishes its own table, show its numbers: our stored
+    // matches can miss whole matchdays. team_stats stays as computed for the model, and
+    // xPts and its delta stay on the stored matches they were computed from.
+    let official: std::collections::HashMap<String, OfficialStanding> = if sport == "football" {
+        get_official_standings(pool, league, OFFICIAL_STANDINGS_MAX_AGE_HOURS).await?
+            .into_iter()
+            .map(|o| (o.team_id.clone(), o))
+            .collect()
+    } else {
+        Default::default()
+    };
+    let table: Vec<(StandingsRow, i32)> = table.into_iter().map(|(row, scored)| match official.get(&row.team_id) {
+        Some(o) => (StandingsRow {
+            played: o.played,
+            wins: o.wins,
+            draws: Some(o.draws),
+            losses: o.losses,
+            points: o.points,
+            score_diff: o.goals_for - o.goals_against,
+            ..row
+        }, o.goals_for),
+        None => (row, scored),
+    }).collect();
+
+    // This season's league results between teams in this table, for head-to-head tiebreaks
+    let in_table: std::collections::HashSet<&str> = table.iter().map(|(r, _)| r.team_id.as_str()).collect();
+    let results: Vec<Match> = get_matches_between(pool, &MatchDateFilter {
+        sport: Some(sport),
+        league: Some(league),
+        status: Some("finished"),
+        after: bounds.map(|(start, _)| start),
+        before: bounds.map(|(_, end)| end),
+        scored_only: true,
+        ..Default::default()
+    })
+    .await?
+    .into_iter()
+    .filter(|m| in_table.contains(m.home_team_id.as_str()) && in_table.contains(m.away_team_id.as_str()))
+    .collect();
+
+    let mut table = apply_tiebreaks(table, tiebreak_rules(sport, league), &results, sport);
+    for (i, row) in table.iter_mut().enumerate() {
+        row.position = i + 1;
     }
-    Ok(matches)
+    Ok(table)
+}
+
+/// Orders rows by the first rule, then re-ranks each group still level with the
+/// remaining rules. Head-to-head only counts games among the group being split.
+fn apply_tiebreaks(rows: Vec<(StandingsRow, i32)>, rules: &[Tiebreak], results: &[Match], sport: &str) -> Vec<StandingsRow> {
+    let Some((&rule, rest)) = rules.split_first().filter(|_| rows.len() > 1) else {
+        let mut rows: Vec<StandingsRow> = rows.into_iter().map(|(r, _)| r).collect();
+        rows.sort_by(|a, b| a.team_name.cmp(&b.team_name));
+        return rows;
+    };
+
+    let h2h_points = |team_id: &str| -> f64 {
+        let group: Vec<&str> = rows.iter().map(|(r, _)| r.team_id.as_str()).collect();
+        results.iter()
+            .filter(|m| group.contains(&m.home_team_id.as_str()) && group.contains(&m.away_team_id.as_str()))
+            .filter_map(|m| {
+                let (own, other) = if m.home_team_id == team_id {
+                    (m.home_score?, m.away_score?)
+                } else if m.away_team_id == team_id {
+                    (m.away_score?, m.home_score?)
+                } else {
+                    return None;
+                };
+                Some(match own.cmp(&other) {
+                    std::cmp::Ordering::Greater => if sport == "football" { 3.0 } else { 1.0 },
+                    std::cmp::Ordering::Equal if sport == "football" => 1.0,
+                    _ => 0.0,
+                })
+            })
+            .sum()
+    };
+    let mut keyed: Vec<(f64, (StandingsRow, i32))> = rows.iter().map(|(r, scored)| {
+        let key = match rule {
+            Tiebreak::Points => r.points as f64,
+            Tiebreak::WinPct => if r.played > 0 { r.wins as f64 / r.played as f64 } else { 0.0 },
+            Tiebreak::ScoreDiff => r.score_diff as f64,
+            Tiebreak::GoalsFor => *scored as f64,
+            Tiebreak::HeadToHead => h2h_points(&r.team_id),
+        };
+        (key, (r.clone(), *scored))
+    }).collect();
+    keyed.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut ordered = Vec::with_capacity(keyed.len());
+    let mut group: Vec<(StandingsRow, i32)> = Vec::new();
+    let mut group_key = f64::NAN;
+    for (key, entry) in keyed {
+        if key != group_key && !group.is_empty() {
+            ordered.extend(apply_tiebreaks(std::mem::take(&mut group), rest, results, sport));
+        }
+        group_key = key;
+        group.push(entry);
+    }
+    ordered.extend(apply_tiebreaks(group, rest, results, sport));
+    ordered
+}
+
+/// Store the published table row for a team, replacing the previous fetch.
+pub async fn upsert_official_standing(pool: &SqlitePool, row: &OfficialStanding) -> Result<()> {
+    sqlx::query(
+        r#"INSERT OR REPLACE INTO official_standings
+           (team_id, league, position, played, wins, draws, losses, points, goals_for, goals_against, fetched_at)
+           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+    )
+    .bind(&row.team_id)
+    .bind(&row.league)
+    .bind(row.position)
+    .bind(row.played)
+    .bind(row.wins)
+    .bind(row.draws)
+    .bind(row.losses)
+    .bind(row.points)
+    .bind(row.goals_for)
+    .bind(row.goals_against)
+    .bind(&row.fetched_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// A published table not refreshed for this long is no longer laid over our own standings.
+pub const OFFICIAL_STANDINGS_MAX_AGE_HOURS: i64 = 72;
+
+/// The league's published table, if fetched within `max_age_hours`.
+pub async fn get_official_standings(pool: &SqlitePool, league: &str, max_age_hours: i64) -> Result<Vec<OfficialStanding>> {
+    let cutoff = (Utc::now() - chrono::Duration::hours(max_age_hours)).to_rfc3339();
+    Ok(sqlx::query_as::<_, OfficialStanding>(
+        "SELECT * FROM official_standings WHERE league = ? AND fetched_at >= ? ORDER BY position ASC",
+    )
+    .bind(league)
+    .bind(cutoff)
+    .fetch_all(pool)
+    .await?)
+}
+
+// Idempotency key operations
+
+/// (request_hash, response JSON) stored under `key`, if newer than `ttl_hours`.
+pub async fn get_idempotency_record(pool: &SqlitePool, key: &str, ttl_hours: i64) -> Result<Option<(String, String)>> {
+    let cutoff = (Utc::now() - chrono::Duration::hours(ttl_hours)).to_rfc3339();
+    let row = sqlx::query_as::<_, (String, String)>(
+        "SELECT request_hash, response FROM idempotency_keys WHERE key = ? AND created_at >= ?",
+    )
+    .bind(key)
+    .bind(cutoff)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row)
+}
+
+pub async fn save_idempotency_record(pool: &SqlitePool, key: &str, request_hash: &str, response: &str) -> Result<()> {
+    sqlx::query(
+        "INSERT OR REPLACE INTO idempotency_keys (key, request_hash, response, created_at) VALUES (?, ?, ?, ?)",
+    )
+    .bind(key)
+    .bind(request_hash)
+    .bind(response)
+    .bind(Utc::now().to_rfc3339())
+    .execute(pool)
+    .await?;
+    Ok(())
 }
 
 // Market odds operations
@@ -683,16 +2122,49 @@ pub async fn upsert_market_odds(
     .bind(&now)
     .execute(pool)
     .await?;
+
+    sqlx::query(
+        r#"INSERT INTO market_odds_history (match_id, bookmaker, home_odds, draw_odds, away_odds, fetched_at)
+           VALUES (?, ?, ?, ?, ?, ?)"#,
+    )
+    .bind(match_id)
+    .bind(bookmaker)
+    .bind(home_odds)
+    .bind(draw_odds)
+    .bind(away_odds)
+    .bind(&now)
+    .execute(pool)
+    .await?;
     Ok(())
 }
 
+/// Every odds snapshot stored for a match, oldest first.
+pub async fn get_market_odds_history(pool: &SqlitePool, match_id: &str) -> Result<Vec<crate::models::MarketOdds>> {
+    let rows = sqlx::query(
+        "SELECT match_id, bookmaker, home_odds, draw_odds, away_odds, fetched_at FROM market_odds_history
+         WHERE match_id = ? ORDER BY fetched_at, id",
+    )
+    .bind(match_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(|r| crate::models::MarketOdds {
+        match_id:   r.get("match_id"),
+        bookmaker:  r.get("bookmaker"),
+        home_odds:  r.get("home_odds"),
+        draw_odds:  r.get("draw_odds"),
+        away_odds:  r.get("away_odds"),
+        fetched_at: r.get("fetched_at"),
+    }).collect())
+}
+
 pub async fn get_market_odds(pool: &SqlitePool, match_id: &str) -> Result<Option<crate::models::MarketOdds>> {
-    let row = sqlx::query(
+    let fetch = sqlx::query(
         "SELECT match_id, bookmaker, home_odds, draw_odds, away_odds, fetched_at FROM market_odds WHERE match_id = ?"
     )
     .bind(match_id)
-    .fetch_optional(pool)
-    .await?;
+    .fetch_optional(pool);
+    let row = timed("get_market_odds", fetch).await?;
 
     Ok(row.map(|r| crate::models::MarketOdds {
         match_id:   r.get("match_id"),
@@ -704,24 +2176,64 @@ pub async fn get_market_odds(pool: &SqlitePool, match_id: &str) -> Result<Option
     }))
 }
 
-pub async fn insert_elo_history(
+/// Rows per multi-row elo_history INSERT: five binds each, well under SQLite's variable limit.
+const ELO_HISTORY_CHUNK: usize = 200;
+
+/// Record teams' ratings after matches; re-recording the same team/match overwrites it.
+/// Points are written in chunks inside one transaction. Rebuilds go through
+/// `replace_elo_state`; this is the per-row reference the tests compare it against.
+#[cfg(test)]
+pub async fn insert_elo_history(pool: &SqlitePool, points: &[EloHistoryPoint]) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    insert_elo_history_chunks(&mut tx, points).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+async fn insert_elo_history_chunks(conn: &mut sqlx::SqliteConnection, points: &[EloHistoryPoint]) -> Result<()> {
+    for chunk in points.chunks(ELO_HISTORY_CHUNK) {
+        let mut insert = sqlx::QueryBuilder::<sqlx::Sqlite>::new(
+            "INSERT INTO elo_history (id, team_id, date, elo_rating, match_id) ",
+        );
+        insert.push_values(chunk, |mut row, point| {
+            row.push_bind(uuid::Uuid::new_v4().to_string())
+                .push_bind(&point.team_id)
+                .push_bind(point.date.to_rfc3339())
+                .push_bind(point.elo_rating)
+                .push_bind(&point.match_id);
+        });
+        insert.push(
+            r#" ON CONFLICT(team_id, match_id) DO UPDATE SET
+                   date       = excluded.date,
+                   elo_rating = excluded.elo_rating"#,
+        );
+        insert.build().execute(&mut *conn).await?;
+    }
+    Ok(())
+}
+
+/// Swap in a rebuilt ELO state in one transaction: each team's rating in `ratings`, and
+/// `history` in place of the whole elo_history table.
+pub async fn replace_elo_state(
     pool: &SqlitePool,
-    team_id: &str,
-    date: chrono::DateTime<Utc>,
-    elo_rating: f64,
-    match_id: &str,
+    ratings: &std::collections::HashMap<String, f64>,
+    history: &[EloHistoryPoint],
 ) -> Result<()> {
-    let id = uuid::Uuid::new_v4().to_string();
-    sqlx::query(
-        "INSERT OR IGNORE INTO elo_history (id, team_id, date, elo_rating, match_id) VALUES (?, ?, ?, ?, ?)"
-    )
-    .bind(id)
-    .bind(team_id)
-    .bind(date.to_rfc3339())
-    .bind(elo_rating)
-    .bind(match_id)
-    .execute(pool)
-    .await?;
+    let now = Utc::now().to_rfc3339();
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM elo_history").execute(&mut *tx).await?;
+    for (team_id, rating) in ratings {
+        sqlx::query("UPDATE teams SET elo_rating = ?, updated_at = ? WHERE id = ?")
+            .bind(rating)
+            .bind(&now)
+            .bind(team_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    insert_elo_history_chunks(&mut tx, history).await?;
+
+    tx.commit().await?;
     Ok(())
 }
 
@@ -840,6 +2352,25 @@ pub async fn get_elo_history(pool: &SqlitePool, team_id: &str) -> Result<Vec<Elo
     .fetch_all(pool)
     .await?;
 
+    elo_history_from_rows(&rows)
+}
+
+/// A team's newest `limit` ELO history points, oldest first.
+pub async fn get_recent_elo_history(pool: &SqlitePool, team_id: &str, limit: i64) -> Result<Vec<EloHistoryPoint>> {
+    let rows = sqlx::query(
+        r#"SELECT * FROM (
+               SELECT * FROM elo_history WHERE team_id = ? ORDER BY date DESC LIMIT ?
+           ) ORDER BY date ASC"#,
+    )
+    .bind(team_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    elo_history_from_rows(&rows)
+}
+
+fn elo_history_from_rows(rows: &[sqlx::sqlite::SqliteRow]) -> Result<Vec<EloHistoryPoint>> {
     let mut history = Vec::new();
     for row in rows {
         history.push(EloHistoryPoint {
@@ -850,4 +2381,531 @@ pub async fn get_elo_history(pool: &SqlitePool, team_id: &str) -> Result<Vec<Elo
         });
     }
     Ok(history)
-}
\ No newline at end of file
+}
+
+/// ELO history of every team in a league, ordered by team then date.
+pub async fn get_league_elo_history(pool: &SqlitePool, sport: &str, league: &str) -> Result<Vec<EloHistoryPoint>> {
+    let rows = sqlx::query(
+        r#"SELECT h.team_id, h.date, h.elo_rating, h.match_id FROM elo_history h
+           JOIN teams t ON t.id = h.team_id
+           WHERE t.sport = ? AND t.league = ?
+           ORDER BY h.team_id, h.date ASC"#,
+    )
+    .bind(sport)
+    .bind(league)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(EloHistoryPoint {
+                team_id: row.get("team_id"),
+                date: chrono::DateTime::parse_from_rfc3339(&row.get::<String, _>("date"))?.with_timezone(&Utc),
+                elo_rating: row.get("elo_rating"),
+                match_id: row.get("match_id"),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn seeded_pool() -> SqlitePool {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        init_database_with_pool(&pool).await.unwrap();
+        seed_data(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn elo_history_upserts_per_team_and_match() {
+        let pool = seeded_pool().await;
+        let m = get_finished_matches_ordered(&pool).await.unwrap().remove(0);
+        let before = get_elo_history(&pool, &m.home_team_id).await.unwrap().len();
+
+        let point = |elo_rating| EloHistoryPoint {
+            team_id: m.home_team_id.clone(),
+            date: m.match_date,
+            elo_rating,
+            match_id: Some(m.id.clone()),
+        };
+        insert_elo_history(&pool, &[point(1210.0)]).await.unwrap();
+        insert_elo_history(&pool, &[point(1225.5)]).await.unwrap();
+
+        let history = get_elo_history(&pool, &m.home_team_id).await.unwrap();
+        let for_match: Vec<_> = history.iter().filter(|p| p.match_id.as_deref() == Some(m.id.as_str())).collect();
+        assert_eq!(history.len(), before + 1);
+        assert_eq!(for_match.len(), 1);
+        assert_eq!(for_match[0].elo_rating, 1225.5);
+    }
+
+    #[tokio::test]
+    async fn clear_all_data_empties_every_match_and_odds_table() {
+        let pool = seeded_pool().await;
+        let m = get_upcoming_matches(&pool, None).await.unwrap().remove(0);
+        upsert_market_odds(&pool, &m.id, "Test", 2.0, None, 1.9).await.unwrap();
+        sqlx::query("INSERT INTO odds_fetch_log (sport_key, last_fetched) VALUES ('basketball_nba', ?)")
+            .bind(Utc::now().to_rfc3339())
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            r#"INSERT INTO team_stats (id, team_id, season, matches_played, wins, draws, losses, form, updated_at)
+               VALUES ('stats', ?, '2025-26', 1, 1, 0, 0, 'W', ?)"#,
+        )
+        .bind(&m.home_team_id)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&pool)
+        .await
+        .unwrap();
+        insert_elo_history(&pool, &[EloHistoryPoint {
+            team_id: m.home_team_id.clone(),
+            date: Utc::now(),
+            elo_rating: 1210.0,
+            match_id: None,
+        }])
+        .await
+        .unwrap();
+
+        let tables = ["teams", "matches", "predictions", "team_stats", "elo_history", "market_odds", "odds_fetch_log"];
+        let count = |table: &'static str| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) FROM {}", table))
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap()
+            }
+        };
+        for table in tables {
+            assert!(count(table).await > 0, "{} empty before the clear", table);
+        }
+        clear_all_data(&pool).await.unwrap();
+        for table in tables {
+            assert_eq!(count(table).await, 0, "{} not cleared", table);
+        }
+    }
+
+    #[tokio::test]
+    async fn merge_teams_repoints_history_to_survivor() {
+        let pool = seeded_pool().await;
+        let arsenal = get_team_by_id(&pool, "epl_1").await.unwrap().unwrap();
+        let chelsea = get_team_by_id(&pool, "epl_4").await.unwrap().unwrap();
+
+        // The same club under an old id and name, with its own match and ELO point
+        let old = Team { id: "epl_old_1".into(), name: "Woolwich Arsenal".into(), ..arsenal.clone() };
+        insert_team(&pool, &old).await.unwrap();
+        let m = Match {
+            id: "epl_old_m1".into(),
+            home_team_id: old.id.clone(),
+            away_team_id: chelsea.id.clone(),
+            home_team_name: old.name.clone(),
+            away_team_name: chelsea.name.clone(),
+            sport: "football".into(),
+            league: "EPL".into(),
+            match_date: Utc::now() - chrono::Duration::days(30),
+            status: "finished".into(),
+            home_score: Some(1),
+            away_score: Some(0),
+            venue: None,
+            city: None,
+            tiebreak_winner: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        insert_match(&pool, &m).await.unwrap();
+        insert_elo_history(&pool, &[EloHistoryPoint {
+            team_id: old.id.clone(),
+            date: m.match_date,
+            elo_rating: 1460.0,
+            match_id: Some(m.id.clone()),
+        }]).await.unwrap();
+        let teams_before = get_all_teams(&pool).await.unwrap().len();
+
+        merge_teams(&pool, &old.id, &arsenal.id).await.unwrap();
+
+        assert_eq!(get_all_teams(&pool).await.unwrap().len(), teams_before - 1);
+        assert!(get_team_by_id(&pool, &old.id).await.unwrap().is_none());
+        let moved: (String, String) = sqlx::query_as("SELECT home_team_id, home_team_name FROM matches WHERE id = ?")
+            .bind(&m.id).fetch_one(&pool).await.unwrap();
+        assert_eq!(moved, (arsenal.id.clone(), arsenal.name.clone()));
+        let orphans: i64 = sqlx::query_scalar(
+            r#"SELECT (SELECT COUNT(*) FROM matches WHERE home_team_id = ?1 OR away_team_id = ?1)
+                    + (SELECT COUNT(*) FROM elo_history WHERE team_id = ?1)"#,
+        )
+        .bind(&old.id).fetch_one(&pool).await.unwrap();
+        assert_eq!(orphans, 0);
+        assert!(get_elo_history(&pool, &arsenal.id).await.unwrap().iter()
+            .any(|p| p.match_id.as_deref() == Some(m.id.as_str())));
+
+        // A re-fetch under the old id lands on the survivor instead of recreating it
+        insert_team(&pool, &old).await.unwrap();
+        insert_match(&pool, &m).await.unwrap();
+        assert!(get_team_by_id(&pool, &old.id).await.unwrap().is_none());
+        let home: String = sqlx::query_scalar("SELECT home_team_id FROM matches WHERE id = ?")
+            .bind(&m.id).fetch_one(&pool).await.unwrap();
+        assert_eq!(home, arsenal.id);
+    }
+
+    #[tokio::test]
+    async fn merge_teams_moves_published_table_row() {
+        let pool = seeded_pool().await;
+        let arsenal = get_team_by_id(&pool, "epl_1").await.unwrap().unwrap();
+        let old = Team { id: "epl_old_1".into(), name: "Woolwich Arsenal".into(), ..arsenal.clone() };
+        insert_team(&pool, &old).await.unwrap();
+        upsert_official_standing(&pool, &official_row(&old.id, 40, Utc::now())).await.unwrap();
+
+        merge_teams(&pool, &old.id, &arsenal.id).await.unwrap();
+
+        let table = get_official_standings(&pool, "EPL", OFFICIAL_STANDINGS_MAX_AGE_HOURS).await.unwrap();
+        assert_eq!(table.len(), 1);
+        assert_eq!((table[0].team_id.as_str(), table[0].points), ("epl_1", 40));
+    }
+
+    #[tokio::test]
+    async fn same_teams_same_day_flagged_as_duplicates() {
+        let pool = seeded_pool().await;
+        let home = get_team_by_id(&pool, "epl_1").await.unwrap().unwrap();
+        let away = get_team_by_id(&pool, "epl_4").await.unwrap().unwrap();
+        let kickoff = Utc::now() - chrono::Duration::days(40);
+        let fixture = |id: &str, status: &str, score: Option<(i32, i32)>| Match {
+            id: id.into(),
+            home_team_id: home.id.clone(),
+            away_team_id: away.id.clone(),
+            home_team_name: home.name.clone(),
+            away_team_name: away.name.clone(),
+            sport: "football".into(),
+            league: "EPL".into(),
+            match_date: kickoff,
+            status: status.into(),
+            home_score: score.map(|s| s.0),
+            away_score: score.map(|s| s.1),
+            venue: None,
+            city: None,
+            tiebreak_winner: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        // The original id never got its result; the re-issued id did
+        insert_match(&pool, &fixture("dup_old", "scheduled", None)).await.unwrap();
+        insert_match(&pool, &fixture("dup_new", "finished", Some((2, 1)))).await.unwrap();
+
+        let dupes = find_duplicate_matches(&pool).await.unwrap();
+        let group = dupes.iter().find(|d| d.keep.id.starts_with("dup_")).unwrap();
+        assert_eq!(group.keep.id, "dup_new");
+        assert_eq!(group.duplicates.iter().map(|m| m.id.as_str()).collect::<Vec<_>>(), vec!["dup_old"]);
+    }
+
+    #[tokio::test]
+    async fn matches_for_team_agrees_with_recent_matches_query() {
+        let pool = seeded_pool().await;
+
+        let legacy = sqlx::query_as::<_, Match>(
+            r#"SELECT * FROM matches
+               WHERE (home_team_id = ? OR away_team_id = ?) AND status = 'finished'
+               ORDER BY match_date DESC LIMIT ?"#,
+        )
+        .bind("epl_1").bind("epl_1").bind(5)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        let consolidated = get_matches_for_team(&pool, "epl_1", &MatchFilter {
+            status: Some("finished"),
+            limit: Some(5),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        assert!(!legacy.is_empty());
+        let ids = |ms: &[Match]| ms.iter().map(|m| m.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&consolidated), ids(&legacy));
+        assert_eq!(ids(&get_team_recent_matches(&pool, "epl_1", 5).await.unwrap()), ids(&legacy));
+
+        // Venue and opponent narrow the same index-backed query
+        let home_vs_city = get_matches_for_team(&pool, "epl_1", &MatchFilter {
+            venue: Venue::Home,
+            opponent: Some("epl_2"),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        assert!(home_vs_city.iter().all(|m| m.home_team_id == "epl_1" && m.away_team_id == "epl_2"));
+    }
+
+    /// Two fresh EPL teams with hand-set stats for the current season.
+    async fn level_pair(pool: &SqlitePool, a: (i32, i32), b: (i32, i32)) -> (Team, Team) {
+        let template = get_team_by_id(pool, "epl_1").await.unwrap().unwrap();
+        let mut pair = Vec::new();
+        for (id, name, (gf, ga)) in [("tb_a", "Alpha", a), ("tb_b", "Beta", b)] {
+            let t = Team { id: id.into(), name: name.into(), league: "EPL".into(), ..template.clone() };
+            insert_team(pool, &t).await.unwrap();
+            // Level on points: 5 wins, 2 draws, 3 losses = 17
+            sqlx::query(
+                r#"INSERT INTO team_stats (id, team_id, season, matches_played, wins, draws, losses,
+                       goals_for, goals_against, form, updated_at)
+                   VALUES (?, ?, ?, 10, 5, 2, 3, ?, ?, '', ?)"#,
+            )
+            .bind(format!("{}_stats", id)).bind(id).bind(crate::utils::season_of(Utc::now())).bind(gf).bind(ga).bind(Utc::now().to_rfc3339())
+            .execute(pool).await.unwrap();
+            pair.push(t);
+        }
+        (pair.remove(0), pair.remove(0))
+    }
+
+    fn position(table: &[StandingsRow], team_id: &str) -> usize {
+        table.iter().find(|r| r.team_id == team_id).unwrap().position
+    }
+
+    /// A finished EPL meeting between the pair, `days` after the current season's start
+    /// (negative for last season).
+    fn meeting(id: &str, home: &Team, away: &Team, score: (i32, i32), days: i64) -> Match {
+        let (season_start, _) = crate::utils::season_bounds(&crate::utils::season_of(Utc::now())).unwrap();
+        Match {
+            id: id.into(),
+            home_team_id: home.id.clone(),
+            away_team_id: away.id.clone(),
+            home_team_name: home.name.clone(),
+            away_team_name: away.name.clone(),
+            sport: "football".into(),
+            league: "EPL".into(),
+            match_date: season_start + chrono::Duration::days(days),
+            status: "finished".into(),
+            home_score: Some(score.0),
+            away_score: Some(score.1),
+            venue: None,
+            city: None,
+            tiebreak_winner: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn standings_break_points_ties_on_goal_difference() {
+        let pool = seeded_pool().await;
+        // Alpha sorts first by name but Beta has the better goal difference
+        level_pair(&pool, (12, 10), (15, 9)).await;
+
+        let table = get_standings(&pool, "football", "EPL").await.unwrap();
+        assert!(position(&table, "tb_b") < position(&table, "tb_a"));
+    }
+
+    #[tokio::test]
+    async fn standings_fall_back_to_head_to_head() {
+        let pool = seeded_pool().await;
+        // Identical points, goal difference and goals scored
+        let (alpha, beta) = level_pair(&pool, (14, 10), (14, 10)).await;
+        insert_match(&pool, &meeting("tb_meeting", &alpha, &beta, (0, 1), 0)).await.unwrap();
+
+        let table = get_standings(&pool, "football", "EPL").await.unwrap();
+        assert_eq!(position(&table, "tb_b") + 1, position(&table, "tb_a"));
+    }
+
+    fn official_row(team_id: &str, points: i32, fetched_at: chrono::DateTime<Utc>) -> OfficialStanding {
+        OfficialStanding {
+            team_id: team_id.into(),
+            league: "EPL".into(),
+            position: 1,
+            played: 20,
+            wins: points / 3,
+            draws: points % 3,
+            losses: 20 - points / 3 - points % 3,
+            points,
+            goals_for: 30,
+            goals_against: 20,
+            fetched_at: fetched_at.to_rfc3339(),
+        }
+    }
+
+    #[tokio::test]
+    async fn stale_published_table_is_not_applied() {
+        let pool = seeded_pool().await;
+        level_pair(&pool, (14, 10), (14, 10)).await;
+        upsert_official_standing(&pool, &official_row("tb_a", 40, Utc::now())).await.unwrap();
+        upsert_official_standing(&pool, &official_row("tb_b", 40, Utc::now() - chrono::Duration::days(10))).await.unwrap();
+
+        let table = get_standings(&pool, "football", "EPL").await.unwrap();
+        let points = |id: &str| table.iter().find(|r| r.team_id == id).unwrap().points;
+        assert_eq!(points("tb_a"), 40);
+        // Beta's published row is ten days old: its computed 17 points stand
+        assert_eq!(points("tb_b"), 17);
+    }
+
+    #[tokio::test]
+    async fn standings_head_to_head_ignores_last_season() {
+        let pool = seeded_pool().await;
+        let (alpha, beta) = level_pair(&pool, (14, 10), (14, 10)).await;
+        // Beta won their only meeting, but that was last season: the tie stays on name
+        insert_match(&pool, &meeting("tb_last_season", &alpha, &beta, (0, 1), -30)).await.unwrap();
+
+        let table = get_standings(&pool, "football", "EPL").await.unwrap();
+        assert_eq!(position(&table, "tb_a") + 1, position(&table, "tb_b"));
+    }
+
+    #[tokio::test]
+    async fn counts_match_seeded_rows() {
+        let pool = seeded_pool().await;
+        assert_eq!(count_teams(&pool).await.unwrap(), get_all_teams(&pool).await.unwrap().len() as i64);
+        assert_eq!(count_teams(&pool).await.unwrap(), 50);
+
+        let all = count_matches_by_status(&pool, None).await.unwrap();
+        let finished = count_matches_by_status(&pool, Some("finished")).await.unwrap();
+        let scheduled = count_matches_by_status(&pool, Some("scheduled")).await.unwrap();
+        assert_eq!(finished, get_finished_matches_ordered(&pool).await.unwrap().len() as i64);
+        assert!(finished > 0 && scheduled > 0 && finished + scheduled <= all);
+
+        let upcoming = get_upcoming_matches(&pool, None).await.unwrap();
+        assert_eq!(count_upcoming(&pool, None, None).await.unwrap(), upcoming.len() as i64);
+        let football = upcoming.iter().filter(|m| m.sport == "football").count() as i64;
+        assert_eq!(count_upcoming(&pool, Some("football"), None).await.unwrap(), football);
+
+        let predictions: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM predictions").fetch_one(&pool).await.unwrap();
+        assert!(predictions > 0);
+        assert_eq!(count_predictions(&pool).await.unwrap(), predictions);
+    }
+
+    #[tokio::test]
+    async fn integrity_pass_reports_finished_match_without_score() {
+        let pool = seeded_pool().await;
+        assert!(validate_data_integrity(&pool).await.unwrap().is_empty());
+
+        let arsenal = get_team_by_id(&pool, "epl_1").await.unwrap().unwrap();
+        let chelsea = get_team_by_id(&pool, "epl_4").await.unwrap().unwrap();
+        insert_match(&pool, &Match {
+            id: "epl_unscored".into(),
+            home_team_id: arsenal.id.clone(),
+            away_team_id: chelsea.id.clone(),
+            home_team_name: arsenal.name.clone(),
+            away_team_name: chelsea.name.clone(),
+            sport: "football".into(),
+            league: "EPL".into(),
+            match_date: Utc::now() - chrono::Duration::days(2),
+            status: "finished".into(),
+            home_score: None,
+            away_score: None,
+            venue: None,
+            city: None,
+            tiebreak_winner: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        })
+        .await
+        .unwrap();
+
+        let issues = validate_data_integrity(&pool).await.unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, "finished_without_score");
+        assert_eq!(issues[0].entity_id, "epl_unscored");
+    }
+
+    #[tokio::test]
+    async fn fixture_correction_clears_stale_prediction() {
+        let pool = seeded_pool().await;
+        let m = get_upcoming_matches(&pool, Some("football")).await.unwrap().remove(0);
+        assert!(get_prediction_by_match_id(&pool, &m.id).await.unwrap().is_some());
+        upsert_market_odds(&pool, &m.id, "Test", 2.0, Some(3.4), 3.8).await.unwrap();
+
+        // Re-fetching the fixture unchanged keeps everything
+        insert_match(&pool, &Match { updated_at: Utc::now(), ..m.clone() }).await.unwrap();
+        assert!(get_prediction_by_match_id(&pool, &m.id).await.unwrap().is_some());
+
+        let replacement = get_teams_by_league(&pool, "football", "EPL").await.unwrap().into_iter()
+            .find(|t| t.id != m.home_team_id && t.id != m.away_team_id)
+            .unwrap();
+        insert_match(&pool, &Match {
+            away_team_id: replacement.id.clone(),
+            away_team_name: replacement.name.clone(),
+            updated_at: Utc::now(),
+            ..m.clone()
+        })
+        .await
+        .unwrap();
+
+        assert!(get_prediction_by_match_id(&pool, &m.id).await.unwrap().is_none());
+        assert!(get_market_odds(&pool, &m.id).await.unwrap().is_none());
+        // ...so the next refresh predicts the corrected fixture afresh
+        let upcoming = get_upcoming_matches(&pool, Some("football")).await.unwrap();
+        crate::services::PredictionEngine::new().generate_predictions(&pool, &upcoming).await.unwrap();
+        let regenerated = get_prediction_by_match_id(&pool, &m.id).await.unwrap().unwrap();
+        assert!(regenerated.created_at > m.updated_at);
+    }
+
+    #[tokio::test]
+    async fn matches_between_honours_date_window_and_status() {
+        let pool = seeded_pool().await;
+        let now = Utc::now();
+        let days = chrono::Duration::days;
+
+        // Seeded EPL results are 7, 14, 21, 28 and 35 days old
+        let recent = get_matches_between(&pool, &MatchDateFilter {
+            sport: Some("football"),
+            status: Some("finished"),
+            after: Some(now - days(25)),
+            before: Some(now),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let homes: Vec<&str> = recent.iter().map(|m| m.home_team_id.as_str()).collect();
+        assert_eq!(homes, ["epl_1", "epl_3", "epl_5"]);
+
+        // Around today the NBA has one result (3 days ago) and fixtures 1, 3 and 4 days out
+        let window = MatchDateFilter {
+            sport: Some("basketball"),
+            league: Some("NBA"),
+            after: Some(now - days(5)),
+            before: Some(now + days(5)),
+            oldest_first: true,
+            ..Default::default()
+        };
+        let all = get_matches_between(&pool, &window).await.unwrap();
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[0].status, "finished");
+        assert!(all.windows(2).all(|w| w[0].match_date <= w[1].match_date));
+
+        let scheduled = get_matches_between(&pool, &MatchDateFilter { status: Some("scheduled"), ..window.clone() })
+            .await
+            .unwrap();
+        assert_eq!(scheduled.len(), 3);
+        assert!(scheduled.iter().all(|m| m.status == "scheduled" && m.match_date >= now));
+    }
+
+    #[tokio::test]
+    async fn blocked_query_times_out_instead_of_hanging() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        // Holding the only connection leaves the next statement waiting on the pool
+        let _held = pool.acquire().await.unwrap();
+
+        let started = Instant::now();
+        let err = with_timeout("blocked", Duration::from_millis(50), sqlx::query_scalar::<_, i64>("SELECT 1").fetch_one(&pool))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{}", err);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn club_in_two_leagues_is_found_in_both_unless_filtered() {
+        let pool = seeded_pool().await;
+        let mut arsenal = get_team_by_id(&pool, "epl_1").await.unwrap().unwrap();
+        arsenal.id = "ucl_arsenal".to_string();
+        arsenal.league = "Champions League".to_string();
+        insert_team(&pool, &arsenal).await.unwrap();
+
+        let both = find_teams_by_name(&pool, "arsenal", None, None).await.unwrap();
+        let leagues: Vec<_> = both.iter().map(|t| t.league.as_str()).collect();
+        assert_eq!(leagues, ["Champions League", "EPL"]);
+
+        let epl = find_teams_by_name(&pool, "Arsenal", Some("football"), Some("EPL")).await.unwrap();
+        assert_eq!(epl.len(), 1);
+        assert_eq!(epl[0].id, "epl_1");
+        assert!(find_teams_by_name(&pool, "Arsenal", Some("basketball"), None).await.unwrap().is_empty());
+    }
+}
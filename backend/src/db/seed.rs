@@ -1,4 +1,4 @@
-use crate::models::*;
+use crate::services::EloCalculator;
 use sqlx::SqlitePool;
 use chrono::{Utc, Duration};
 use uuid::Uuid;
@@ -7,9 +7,8 @@ use rand;
 pub async fn seed_database(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     println!("🌱 Seeding database with sample data...");
     
-    // Create tables first
-    create_tables(pool).await?;
-    
+    // Schema is owned by init_database_with_pool; seeding only inserts rows.
+
     // Seed teams
     seed_teams(pool).await?;
     
@@ -26,97 +25,12 @@ pub async fn seed_database(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     Ok(())
 }
 
-async fn create_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS teams (
-            id TEXT PRIMARY KEY,
-            name TEXT NOT NULL,
-            sport TEXT NOT NULL,
-            league TEXT NOT NULL,
-            elo_rating REAL NOT NULL DEFAULT 1200.0,
-            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
-        )
-        "#,
-    )
-    .execute(pool)
-    .await?;
-
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS matches (
-            id TEXT PRIMARY KEY,
-            home_team_id TEXT NOT NULL,
-            away_team_id TEXT NOT NULL,
-            sport TEXT NOT NULL,
-            league TEXT NOT NULL,
-            match_date DATETIME NOT NULL,
-            home_score INTEGER,
-            away_score INTEGER,
-            status TEXT NOT NULL DEFAULT 'scheduled',
-            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (home_team_id) REFERENCES teams (id),
-            FOREIGN KEY (away_team_id) REFERENCES teams (id)
-        )
-        "#,
-    )
-    .execute(pool)
-    .await?;
-
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS predictions (
-            id TEXT PRIMARY KEY,
-            match_id TEXT NOT NULL,
-            home_win_prob REAL NOT NULL,
-            draw_prob REAL,
-            away_win_prob REAL NOT NULL,
-            confidence REAL NOT NULL,
-            model_version TEXT NOT NULL DEFAULT 'v1.0',
-            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (match_id) REFERENCES matches (id)
-        )
-        "#,
-    )
-    .execute(pool)
-    .await?;
-
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS market_odds (
-            id TEXT PRIMARY KEY,
-            match_id TEXT NOT NULL,
-            home_odds REAL NOT NULL,
-            draw_odds REAL,
-            away_odds REAL NOT NULL,
-            source TEXT NOT NULL DEFAULT 'sample',
-            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (match_id) REFERENCES matches (id)
-        )
-        "#,
-    )
-    .execute(pool)
-    .await?;
-
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS elo_history (
-            id TEXT PRIMARY KEY,
-            team_id TEXT NOT NULL,
-            elo_rating REAL NOT NULL,
-            date DATETIME NOT NULL,
-            match_id TEXT,
-            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (team_id) REFERENCES teams (id),
-            FOREIGN KEY (match_id) REFERENCES matches (id)
-        )
-        "#,
-    )
-    .execute(pool)
-    .await?;
-
-    Ok(())
+/// Name and current ELO for a seeded team.
+async fn team_info(pool: &SqlitePool, team_id: &str) -> Result<(String, f64), sqlx::Error> {
+    sqlx::query_as::<_, (String, f64)>("SELECT name, elo_rating FROM teams WHERE id = ?")
+        .bind(team_id)
+        .fetch_one(pool)
+        .await
 }
 
 async fn seed_teams(pool: &SqlitePool) -> Result<(), sqlx::Error> {
@@ -144,16 +58,20 @@ async fn seed_teams(pool: &SqlitePool) -> Result<(), sqlx::Error> {
         ("epl_20", "Luton Town", 1160.0),
     ];
 
+    let now = Utc::now().to_rfc3339();
+
     for (id, name, elo) in epl_teams {
         sqlx::query(
             r#"
-            INSERT OR REPLACE INTO teams (id, name, sport, league, elo_rating)
-            VALUES (?, ?, 'football', 'EPL', ?)
+            INSERT OR REPLACE INTO teams (id, name, sport, league, elo_rating, created_at, updated_at)
+            VALUES (?, ?, 'football', 'EPL', ?, ?, ?)
             "#,
         )
         .bind(id)
         .bind(name)
         .bind(elo)
+        .bind(&now)
+        .bind(&now)
         .execute(pool)
         .await?;
     }
@@ -195,13 +113,15 @@ async fn seed_teams(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     for (id, name, elo) in nba_teams {
         sqlx::query(
             r#"
-            INSERT OR REPLACE INTO teams (id, name, sport, league, elo_rating)
-            VALUES (?, ?, 'basketball', 'NBA', ?)
+            INSERT OR REPLACE INTO teams (id, name, sport, league, elo_rating, created_at, updated_at)
+            VALUES (?, ?, 'basketball', 'NBA', ?, ?, ?)
             "#,
         )
         .bind(id)
         .bind(name)
         .bind(elo)
+        .bind(&now)
+        .bind(&now)
         .execute(pool)
         .await?;
     }
@@ -233,21 +153,28 @@ async fn seed_historical_matches(pool: &SqlitePool) -> Result<(), sqlx::Error> {
         let sport = if home_id.starts_with("epl") { "football" } else { "basketball" };
         let league = if home_id.starts_with("epl") { "EPL" } else { "NBA" };
         let match_date = now + Duration::days(days_ago);
+        let (home_name, _) = team_info(pool, home_id).await?;
+        let (away_name, _) = team_info(pool, away_id).await?;
 
         sqlx::query(
             r#"
-            INSERT INTO matches (id, home_team_id, away_team_id, sport, league, match_date, home_score, away_score, status)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'completed')
+            INSERT INTO matches (id, home_team_id, away_team_id, home_team_name, away_team_name, sport, league,
+                                 match_date, status, home_score, away_score, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'finished', ?, ?, ?, ?)
             "#,
         )
         .bind(&match_id)
         .bind(home_id)
         .bind(away_id)
+        .bind(&home_name)
+        .bind(&away_name)
         .bind(sport)
         .bind(league)
-        .bind(match_date)
+        .bind(match_date.to_rfc3339())
         .bind(home_score)
         .bind(away_score)
+        .bind(now.to_rfc3339())
+        .bind(now.to_rfc3339())
         .execute(pool)
         .await?;
     }
@@ -257,6 +184,7 @@ async fn seed_historical_matches(pool: &SqlitePool) -> Result<(), sqlx::Error> {
 
 async fn seed_upcoming_matches(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     let now = Utc::now();
+    let elo = EloCalculator::new();
     
     // Upcoming matches
     let upcoming_matches = vec![
@@ -285,65 +213,50 @@ async fn seed_upcoming_matches(pool: &SqlitePool) -> Result<(), sqlx::Error> {
         let sport = if home_id.starts_with("epl") { "football" } else { "basketball" };
         let league = if home_id.starts_with("epl") { "EPL" } else { "NBA" };
         let match_date = now + Duration::days(days_ahead);
+        let (home_name, home_elo) = team_info(pool, home_id).await?;
+        let (away_name, away_elo) = team_info(pool, away_id).await?;
 
         sqlx::query(
             r#"
-            INSERT INTO matches (id, home_team_id, away_team_id, sport, league, match_date, status)
-            VALUES (?, ?, ?, ?, ?, ?, 'scheduled')
+            INSERT INTO matches (id, home_team_id, away_team_id, home_team_name, away_team_name, sport, league,
+                                 match_date, status, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'scheduled', ?, ?)
             "#,
         )
         .bind(&match_id)
         .bind(home_id)
         .bind(away_id)
+        .bind(&home_name)
+        .bind(&away_name)
         .bind(sport)
         .bind(league)
-        .bind(match_date)
+        .bind(match_date.to_rfc3339())
+        .bind(now.to_rfc3339())
+        .bind(now.to_rfc3339())
         .execute(pool)
         .await?;
 
-        // Generate predictions for each match
-        let home_win_prob = 0.4 + (rand::random::<f64>() * 0.4);
-        let away_win_prob = if sport == "football" {
-            0.6 - home_win_prob - 0.25 // Reserve 0.25 for draw
-        } else {
-            1.0 - home_win_prob
-        };
-        let draw_prob = if sport == "football" { Some(0.25) } else { None };
-        let confidence = 0.6 + (rand::random::<f64>() * 0.3);
+        // Deterministic ELO-only prediction; the engine replaces it on the first refresh.
+        // Confidence scales with the favourite's margin so sample data has a clear ordering.
+        let (home_win_prob, away_win_prob, draw_prob) =
+            elo.win_probability(home_elo, away_elo, sport, league);
+        let confidence = crate::utils::prediction_confidence(home_win_prob, draw_prob, away_win_prob, 1.0);
 
         let prediction_id = Uuid::new_v4().to_string();
         sqlx::query(
             r#"
-            INSERT INTO predictions (id, match_id, home_win_prob, draw_prob, away_win_prob, confidence)
-            VALUES (?, ?, ?, ?, ?, ?)
+            INSERT INTO predictions (id, match_id, home_win_probability, away_win_probability, draw_probability,
+                                     model_version, confidence_score, created_at)
+            VALUES (?, ?, ?, ?, ?, 'seed_v1', ?, ?)
             "#,
         )
         .bind(&prediction_id)
         .bind(&match_id)
         .bind(home_win_prob)
-        .bind(draw_prob)
         .bind(away_win_prob)
+        .bind(draw_prob)
         .bind(confidence)
-        .execute(pool)
-        .await?;
-
-        // Generate market odds
-        let home_odds = 1.0 / (home_win_prob + 0.05); // Add bookmaker margin
-        let away_odds = 1.0 / (away_win_prob + 0.05);
-        let draw_odds = if sport == "football" { Some(1.0 / (0.25 + 0.05)) } else { None };
-
-        let odds_id = Uuid::new_v4().to_string();
-        sqlx::query(
-            r#"
-            INSERT INTO market_odds (id, match_id, home_odds, draw_odds, away_odds)
-            VALUES (?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(&odds_id)
-        .bind(&match_id)
-        .bind(home_odds)
-        .bind(draw_odds)
-        .bind(away_odds)
+        .bind(now.to_rfc3339())
         .execute(pool)
         .await?;
     }
@@ -381,7 +294,7 @@ async fn seed_elo_history(pool: &SqlitePool) -> Result<(), sqlx::Error> {
             .bind(&history_id)
             .bind(team_id)
             .bind(historical_elo)
-            .bind(history_date)
+            .bind(history_date.to_rfc3339())
             .execute(pool)
             .await?;
         }
@@ -7,12 +7,17 @@ mod services;
 mod utils;
 
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{ArgAction, Parser, Subcommand};
+use tracing_subscriber::filter::{EnvFilter, LevelFilter};
 
 #[derive(Parser)]
 #[command(name = "oddsforge")]
 #[command(about = "A sports analytics platform for prediction markets")]
 struct Cli {
+    /// Increase log verbosity (-v info, -vv debug, -vvv trace); overrides RUST_LOG
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    verbose: u8,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -23,11 +28,17 @@ enum Commands {
     Serve {
         #[arg(short, long, default_value = "3000")]
         port: u16,
+        /// Interface to listen on (default BIND_ADDR, else 0.0.0.0 — every interface)
+        #[arg(short, long)]
+        bind: Option<String>,
     },
     /// Fetch sports data
     Fetch {
         #[arg(short, long)]
         sport: String,
+        /// Only fetch matches from this date on (default: the whole season)
+        #[arg(long, value_name = "YYYY-MM-DD", value_parser = parse_since_date)]
+        since: Option<chrono::NaiveDate>,
     },
     /// Generate predictions for upcoming matches
     Predict,
@@ -35,6 +46,12 @@ enum Commands {
     Team {
         #[arg(short, long)]
         name: String,
+        /// Only match teams in this sport
+        #[arg(long)]
+        sport: Option<String>,
+        /// Only match teams in this league (e.g. "EPL", "Champions League")
+        #[arg(long)]
+        league: Option<String>,
     },
     /// Initialize the database
     InitDb,
@@ -48,32 +65,57 @@ enum Commands {
     Train,
 }
 
+/// `--since` value: a calendar date, rejected up front rather than falling back to a full fetch.
+fn parse_since_date(value: &str) -> Result<chrono::NaiveDate, String> {
+    chrono::NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .map_err(|_| format!("expected a date in YYYY-MM-DD form, e.g. 2026-01-31 (got '{}')", value))
+}
+
+/// Build the tracing filter for one invocation from the `-v` count and RUST_LOG.
+fn log_filter(verbose: u8, rust_log: Option<String>) -> EnvFilter {
+    let level = match verbose {
+        0 => match rust_log.filter(|s| !s.trim().is_empty()) {
+            Some(directives) => return EnvFilter::new(directives),
+            None => LevelFilter::WARN,
+        },
+        1 => LevelFilter::INFO,
+        2 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    };
+    EnvFilter::default().add_directive(level.into())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Load .env before tracing so RUST_LOG is available
     dotenv::dotenv().ok();
 
-    // Initialize tracing (reads RUST_LOG from env)
-    tracing_subscriber::fmt::init();
-    
     let cli = Cli::parse();
 
+    // -v flags win over RUST_LOG; without either, only warnings and errors are shown
+    tracing_subscriber::fmt()
+        .with_env_filter(log_filter(cli.verbose, std::env::var("RUST_LOG").ok()))
+        .init();
+    // Printed rather than logged so it shows under the default warn-level filter
+    eprintln!("ELO K-factor: {}", services::EloCalculator::new().k_factor());
+
     match cli.command {
-        Some(Commands::Serve { port }) => {
-            tracing::info!("Starting OddsForge API server on port {}", port);
-            api::serve(port).await?;
+        Some(Commands::Serve { port, bind }) => {
+            let addr = api::listen_addr(bind.as_deref(), port)?;
+            tracing::info!("Starting OddsForge API server on {}", addr);
+            api::serve(addr).await?;
         }
-        Some(Commands::Fetch { sport }) => {
+        Some(Commands::Fetch { sport, since }) => {
             tracing::info!("Fetching data for sport: {}", sport);
-            cli::fetch_data(&sport).await?;
+            cli::fetch_data(&sport, since).await?;
         }
         Some(Commands::Predict) => {
             tracing::info!("Generating predictions...");
             cli::generate_predictions().await?;
         }
-        Some(Commands::Team { name }) => {
+        Some(Commands::Team { name, sport, league }) => {
             tracing::info!("Querying team: {}", name);
-            cli::query_team(&name).await?;
+            cli::query_team(&name, sport.as_deref(), league.as_deref()).await?;
         }
         Some(Commands::InitDb) => {
             tracing::info!("Initializing database...");
@@ -89,10 +131,42 @@ async fn main() -> Result<()> {
         }
         None => {
             // Default to serving
-            tracing::info!("Starting OddsForge API server on port 3000");
-            api::serve(3000).await?;
+            let addr = api::listen_addr(None, 3000)?;
+            tracing::info!("Starting OddsForge API server on {}", addr);
+            api::serve(addr).await?;
         }
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter_for(args: &[&str], rust_log: Option<&str>) -> Option<LevelFilter> {
+        let cli = Cli::try_parse_from(args).unwrap();
+        log_filter(cli.verbose, rust_log.map(String::from)).max_level_hint()
+    }
+
+    #[test]
+    fn double_verbose_configures_debug_logging() {
+        assert_eq!(filter_for(&["oddsforge", "-vv", "predict"], None), Some(LevelFilter::DEBUG));
+        // Flags after the subcommand count too, and override RUST_LOG
+        assert_eq!(filter_for(&["oddsforge", "predict", "-vv"], Some("error")), Some(LevelFilter::DEBUG));
+        assert_eq!(filter_for(&["oddsforge", "-vvvv"], None), Some(LevelFilter::TRACE));
+        // Without flags RUST_LOG applies, falling back to warn
+        assert_eq!(filter_for(&["oddsforge"], Some("error")), Some(LevelFilter::ERROR));
+        assert_eq!(filter_for(&["oddsforge"], None), Some(LevelFilter::WARN));
+    }
+
+    #[test]
+    fn fetch_since_accepts_dates_and_rejects_anything_else() {
+        let cli = Cli::try_parse_from(["oddsforge", "fetch", "--sport", "nba", "--since", "2026-01-15"]).unwrap();
+        let Some(Commands::Fetch { since, .. }) = cli.command else { panic!("not a fetch") };
+        assert_eq!(since, chrono::NaiveDate::from_ymd_opt(2026, 1, 15));
+
+        let err = Cli::try_parse_from(["oddsforge", "fetch", "--sport", "nba", "--since", "15/01/2026"]).err().unwrap();
+        assert!(err.to_string().contains("YYYY-MM-DD"));
+    }
+}
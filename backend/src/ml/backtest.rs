@@ -7,8 +7,9 @@
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use sqlx::{Row, SqlitePool};
+use sqlx::SqlitePool;
 
+use crate::db::{get_matches_between, MatchDateFilter};
 use crate::models::Match;
 use super::{
     feature_store::{build_features, N_FEATURES},
@@ -22,6 +23,9 @@ use super::{
     rapm::RapmModel,
 };
 
+/// Fewest finished NBA games worth training on; below this an untrained model is returned.
+pub const MIN_TRAINING_GAMES: usize = 50;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FoldResult {
     pub fold: i32,
@@ -32,24 +36,6 @@ pub struct FoldResult {
     pub accuracy: f64,
 }
 
-fn parse_match(row: &sqlx::sqlite::SqliteRow) -> Result<Match> {
-    Ok(Match {
-        id: row.get("id"),
-        home_team_id: row.get("home_team_id"),
-        away_team_id: row.get("away_team_id"),
-        home_team_name: row.get("home_team_name"),
-        away_team_name: row.get("away_team_name"),
-        sport: row.get("sport"),
-        league: row.get("league"),
-        match_date: chrono::DateTime::parse_from_rfc3339(&row.get::<String, _>("match_date"))?.with_timezone(&chrono::Utc),
-        status: row.get("status"),
-        home_score: row.get("home_score"),
-        away_score: row.get("away_score"),
-        created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<String, _>("created_at"))?.with_timezone(&chrono::Utc),
-        updated_at: chrono::DateTime::parse_from_rfc3339(&row.get::<String, _>("updated_at"))?.with_timezone(&chrono::Utc),
-    })
-}
-
 fn base_probs(
     m: &Match,
     f: &[f64; N_FEATURES],
@@ -97,37 +83,31 @@ fn eval_fold(
 pub async fn train_and_evaluate(pool: &SqlitePool) -> Result<(MlModelState, Vec<FoldResult>)> {
     tracing::info!("Starting ML training pipeline...");
 
-    let rows = sqlx::query(
-        r#"SELECT id, home_team_id, away_team_id, home_team_name, away_team_name,
-                  sport, league, match_date, status, home_score, away_score,
-                  created_at, updated_at
-           FROM matches
-           WHERE sport = 'basketball' AND status = 'finished'
-             AND home_score IS NOT NULL AND away_score IS NOT NULL
-           ORDER BY match_date ASC"#
-    ).fetch_all(pool).await?;
+    let rows = get_matches_between(pool, &MatchDateFilter {
+        sport: Some("basketball"),
+        status: Some("finished"),
+        scored_only: true,
+        oldest_first: true,
+        ..Default::default()
+    })
+    .await?;
 
     tracing::info!("Found {} finished NBA games", rows.len());
 
-    if rows.len() < 50 {
-        tracing::warn!("Not enough data to train (<50 games). Returning untrained model.");
+    if rows.len() < MIN_TRAINING_GAMES {
+        tracing::warn!("Not enough data to train (<{} games). Returning untrained model.", MIN_TRAINING_GAMES);
         return Ok((MlModelState::new(), vec![]));
     }
 
     // Build features for all games
     let mut all_games: Vec<(Match, [f64; N_FEATURES], bool)> = Vec::new();
-    for row in &rows {
-        match parse_match(row) {
-            Ok(m) => {
-                match build_features(pool, &m).await {
-                    Ok(feat) => {
-                        let hw = m.home_score.unwrap_or(0) > m.away_score.unwrap_or(0);
-                        all_games.push((m, feat.0, hw));
-                    }
-                    Err(e) => tracing::debug!("Feature build failed for {}: {}", row.get::<String, _>("id"), e),
-                }
+    for m in rows.iter().cloned() {
+        match build_features(pool, &m).await {
+            Ok(feat) => {
+                let hw = m.home_score.unwrap_or(0) > m.away_score.unwrap_or(0);
+                all_games.push((m, feat.0, hw));
             }
-            Err(e) => tracing::debug!("Parse failed: {}", e),
+            Err(e) => tracing::debug!("Feature build failed for {}: {}", m.id, e),
         }
     }
 
@@ -0,0 +1,206 @@
+//! Single-Fixture Monte Carlo Simulator
+//!
+//! Draws full-time scores for one match from a simple score model:
+//!   Football:   independent Poisson goals per side
+//!   Basketball: independent normal points per side, ties settled by overtime
+//!
+//! Returns 1X2 frequencies, the modal scoreline, over/under and BTTS rates,
+//! plus the closed-form 1X2 under the same model as a cross-check.
+
+use statrs::distribution::{ContinuousCDF, Normal};
+use std::collections::BTreeMap;
+
+use super::monte_carlo::Xr64;
+
+/// Regulation minutes vs one overtime period, for scaling basketball means.
+const OT_SHARE: f64 = 5.0 / 48.0;
+
+#[derive(Debug, Clone, Copy)]
+pub enum ScoreModel {
+    Poisson { home_rate: f64, away_rate: f64 },
+    Normal { home_mean: f64, away_mean: f64, sd: f64 },
+}
+
+#[derive(Debug, Clone)]
+pub struct SimulatedOutcomes {
+    pub home_win: f64,
+    pub draw: f64,
+    pub away_win: f64,
+    pub most_likely_score: (u32, u32),
+    pub most_likely_score_frequency: f64,
+    pub avg_total: f64,
+    /// (line, share of simulations going over it)
+    pub totals: Vec<(f64, f64)>,
+    /// Share of simulations where both sides scored
+    pub btts: f64,
+}
+
+impl ScoreModel {
+    /// Over/under lines: the usual goal lines for football, and the expected total
+    /// ±5 and ±10 points for basketball.
+    pub fn total_lines(&self) -> Vec<f64> {
+        match *self {
+            Self::Poisson { .. } => vec![0.5, 1.5, 2.5, 3.5, 4.5],
+            Self::Normal { home_mean, away_mean, .. } => {
+                let centre = (home_mean + away_mean).floor() + 0.5;
+                [-10.0, -5.0, 0.0, 5.0, 10.0].iter().map(|d| centre + d).collect()
+            }
+        }
+    }
+
+    /// Run `iterations` simulated matches; the same seed always yields the same outcomes.
+    pub fn simulate(&self, iterations: usize, seed: Option<u64>) -> SimulatedOutcomes {
+        let mut rng = seed.map_or_else(Xr64::new, Xr64::from_seed);
+        let lines = self.total_lines();
+        let n = iterations.max(1);
+
+        let (mut home_wins, mut draws, mut btts) = (0usize, 0usize, 0usize);
+        let mut total_sum = 0u64;
+        let mut overs = vec![0usize; lines.len()];
+        let mut scores: BTreeMap<(u32, u32), usize> = BTreeMap::new();
+
+        for _ in 0..n {
+            let (h, a) = self.sample(&mut rng);
+            match h.cmp(&a) {
+                std::cmp::Ordering::Greater => home_wins += 1,
+                std::cmp::Ordering::Equal => draws += 1,
+                std::cmp::Ordering::Less => {}
+            }
+            if h > 0 && a > 0 {
+                btts += 1;
+            }
+            let total = h + a;
+            total_sum += total as u64;
+            for (count, line) in overs.iter_mut().zip(&lines) {
+                if total as f64 > *line {
+                    *count += 1;
+                }
+            }
+            *scores.entry((h, a)).or_default() += 1;
+        }
+
+        // BTreeMap order makes the first of several equally common scorelines win
+        let (most_likely_score, mode_count) = scores.iter()
+            .fold(((0, 0), 0), |best, (&s, &c)| if c > best.1 { (s, c) } else { best });
+        let share = |c: usize| c as f64 / n as f64;
+
+        SimulatedOutcomes {
+            home_win: share(home_wins),
+            draw: share(draws),
+            away_win: share(n - home_wins - draws),
+            most_likely_score,
+            most_likely_score_frequency: share(mode_count),
+            avg_total: total_sum as f64 / n as f64,
+            totals: lines.into_iter().zip(overs).map(|(line, c)| (line, share(c))).collect(),
+            btts: share(btts),
+        }
+    }
+
+    fn sample(&self, rng: &mut Xr64) -> (u32, u32) {
+        match *self {
+            Self::Poisson { home_rate, away_rate } => (poisson(home_rate, rng), poisson(away_rate, rng)),
+            Self::Normal { home_mean, away_mean, sd } => {
+                let mut h = normal(home_mean, sd, rng);
+                let mut a = normal(away_mean, sd, rng);
+                // Play overtime until someone leads; give up (coin flip) after 10 periods
+                for _ in 0..10 {
+                    if h != a {
+                        break;
+                    }
+                    h += normal(home_mean * OT_SHARE, sd * OT_SHARE.sqrt(), rng);
+                    a += normal(away_mean * OT_SHARE, sd * OT_SHARE.sqrt(), rng);
+                }
+                if h == a {
+                    if rng.f64() < 0.5 { h += 1 } else { a += 1 }
+                }
+                (h, a)
+            }
+        }
+    }
+
+    /// Closed-form likeliest scoreline: each side's modal score, since the two are
+    /// independent. None for basketball, where no single scoreline is meaningfully likely.
+    pub fn most_likely_score(&self) -> Option<(u32, u32)> {
+        match *self {
+            Self::Poisson { home_rate, away_rate } => {
+                Some((home_rate.max(0.0).floor() as u32, away_rate.max(0.0).floor() as u32))
+            }
+            Self::Normal { .. } => None,
+        }
+    }
+
+    /// Closed-form probability that the combined score goes over `line`.
+    pub fn over_probability(&self, line: f64) -> f64 {
+        match *self {
+            Self::Poisson { home_rate, away_rate } => {
+                // The sum of independent Poissons is Poisson in the summed rate
+                let under: f64 = poisson_pmf(home_rate + away_rate, line.max(0.0).floor() as usize).iter().sum();
+                if line < 0.0 { 1.0 } else { (1.0 - under).clamp(0.0, 1.0) }
+            }
+            Self::Normal { home_mean, away_mean, sd } => {
+                Normal::new(home_mean + away_mean, sd * std::f64::consts::SQRT_2)
+                    .map(|d| 1.0 - d.cdf(line))
+                    .unwrap_or(0.5)
+            }
+        }
+    }
+
+    /// Closed-form (home, draw, away) under the same model. Basketball has no draw:
+    /// the tie mass is split evenly, which is what overtime roughly does.
+    pub fn analytic_1x2(&self) -> (f64, f64, f64) {
+        match *self {
+            Self::Poisson { home_rate, away_rate } => {
+                let (hp, ap) = (poisson_pmf(home_rate, 15), poisson_pmf(away_rate, 15));
+                let (mut home, mut draw, mut away) = (0.0, 0.0, 0.0);
+                for (i, ph) in hp.iter().enumerate() {
+                    for (j, pa) in ap.iter().enumerate() {
+                        let p = ph * pa;
+                        match i.cmp(&j) {
+                            std::cmp::Ordering::Greater => home += p,
+                            std::cmp::Ordering::Equal => draw += p,
+                            std::cmp::Ordering::Less => away += p,
+                        }
+                    }
+                }
+                let total = home + draw + away;
+                (home / total, draw / total, away / total)
+            }
+            Self::Normal { home_mean, away_mean, sd } => {
+                let margin = Normal::new(home_mean - away_mean, sd * std::f64::consts::SQRT_2)
+                    .map(|d| 1.0 - d.cdf(0.0))
+                    .unwrap_or(0.5);
+                (margin, 0.0, 1.0 - margin)
+            }
+        }
+    }
+}
+
+/// Knuth's method; fine for the small rates of football scores.
+fn poisson(rate: f64, rng: &mut Xr64) -> u32 {
+    let limit = (-rate.max(0.0)).exp();
+    let mut k = 0;
+    let mut p = rng.f64();
+    while p > limit {
+        k += 1;
+        p *= rng.f64();
+    }
+    k
+}
+
+/// Box–Muller, rounded to whole points and floored at zero.
+fn normal(mean: f64, sd: f64, rng: &mut Xr64) -> u32 {
+    let u1 = rng.f64().max(f64::MIN_POSITIVE);
+    let u2 = rng.f64();
+    let z = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+    (mean + sd * z).round().max(0.0) as u32
+}
+
+fn poisson_pmf(rate: f64, max_k: usize) -> Vec<f64> {
+    let mut pmf = Vec::with_capacity(max_k + 1);
+    let mut p = (-rate).exp();
+    for k in 0..=max_k {
+        pmf.push(p);
+        p *= rate / (k + 1) as f64;
+    }
+    pmf
+}
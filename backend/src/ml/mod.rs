@@ -2,9 +2,12 @@ pub mod backtest;
 pub mod feature_store;
 pub mod gradient_boosted;
 pub mod kaggle_ingest;
+pub mod match_simulator;
 pub mod meta_learner;
 pub mod monte_carlo;
 pub mod poisson_model;
 pub mod rapm;
+pub mod retrain;
+pub mod season_simulator;
 
 pub use meta_learner::MlPredictor;
@@ -9,16 +9,20 @@
 use serde::{Deserialize, Serialize};
 
 /// Minimal xorshift64 RNG — no external API surface to break across rand versions.
-struct Xr64(u64);
+pub(crate) struct Xr64(u64);
 impl Xr64 {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         // Seed from system nanos for reasonable entropy
         let seed = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.subsec_nanos() as u64 ^ (d.as_secs() * 6364136223846793005))
+            .map(|d| d.subsec_nanos() as u64 ^ d.as_secs().wrapping_mul(6364136223846793005))
             .unwrap_or(98765432109);
         Self(seed | 1) // ensure non-zero
     }
+    /// Reproducible stream; the seed is mixed so small seeds don't start in a weak state.
+    pub(crate) fn from_seed(seed: u64) -> Self {
+        Self((seed ^ 0x9E37_79B9_7F4A_7C15).wrapping_mul(0xBF58_476D_1CE4_E5B9) | 1)
+    }
     #[inline]
     fn next(&mut self) -> u64 {
         let mut x = self.0;
@@ -30,7 +34,7 @@ impl Xr64 {
     }
     /// Uniform [0, 1)
     #[inline]
-    fn f64(&mut self) -> f64 {
+    pub(crate) fn f64(&mut self) -> f64 {
         (self.next() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
     }
     #[inline]
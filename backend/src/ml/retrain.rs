@@ -0,0 +1,164 @@
+//! Rolling retraining
+//!
+//! The NBA ML stack is retrained (and its isotonic calibration refitted) once enough
+//! games have finished since the last saved version, or once it is old enough.
+//! Each retrain saves a new `meta` model_params version.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+use crate::db::{count_finished_matches_since, get_latest_model_training, save_backtest_result, save_model_params};
+use super::{
+    backtest::{train_and_evaluate, FoldResult, MIN_TRAINING_GAMES},
+    meta_learner::MlModelState,
+};
+
+/// When the scheduler retrains.
+#[derive(Debug, Clone, Copy)]
+pub struct RetrainPolicy {
+    /// New finished games since the last training that trigger a retrain (0 = never on count)
+    pub after_results: i64,
+    /// Age of the last training that triggers a retrain, given at least one new game
+    pub every_hours: Option<i64>,
+}
+
+impl RetrainPolicy {
+    /// RETRAIN_AFTER_RESULTS (default 50) and RETRAIN_EVERY_HOURS (unset = off).
+    pub fn from_env() -> Self {
+        let read = |key: &str| std::env::var(key).ok().and_then(|v| v.parse::<i64>().ok()).filter(|v| *v >= 0);
+        Self {
+            after_results: read("RETRAIN_AFTER_RESULTS").unwrap_or(50),
+            every_hours: read("RETRAIN_EVERY_HOURS").filter(|h| *h > 0),
+        }
+    }
+
+    /// True when `status` calls for a retrain at `now`. Never with too few games to train on.
+    pub fn is_due(&self, status: &RetrainStatus, now: DateTime<Utc>) -> bool {
+        if status.finished_games < MIN_TRAINING_GAMES as i64 {
+            return false;
+        }
+        let Some(trained_at) = status.last_trained_at else {
+            return true;
+        };
+        let by_count = self.after_results > 0 && status.results_since_training >= self.after_results;
+        let by_age = self.every_hours.is_some_and(|h| now - trained_at >= chrono::Duration::hours(h))
+            && status.results_since_training > 0;
+        by_count || by_age
+    }
+}
+
+/// Where the saved NBA model stands against the results stored since.
+#[derive(Debug, Clone)]
+pub struct RetrainStatus {
+    pub last_trained_at: Option<DateTime<Utc>>,
+    pub version: Option<i32>,
+    pub results_since_training: i64,
+    pub finished_games: i64,
+}
+
+pub async fn retrain_status(pool: &SqlitePool) -> Result<RetrainStatus> {
+    let latest = get_latest_model_training(pool, "meta").await?;
+    let last_trained_at = latest.map(|(_, at)| at);
+    Ok(RetrainStatus {
+        last_trained_at,
+        version: latest.map(|(v, _)| v),
+        results_since_training: count_finished_matches_since(pool, "basketball", last_trained_at).await?,
+        finished_games: count_finished_matches_since(pool, "basketball", None).await?,
+    })
+}
+
+/// Train on every finished game and save the model plus its walk-forward folds as the
+/// next `meta` version. The caller decides whether to load the returned state.
+pub async fn train_and_save(pool: &SqlitePool) -> Result<(i32, MlModelState, Vec<FoldResult>)> {
+    let (state, folds) = train_and_evaluate(pool).await?;
+    let json = serde_json::to_string(&state)?;
+    let mean = |f: fn(&FoldResult) -> f64| {
+        (!folds.is_empty()).then(|| folds.iter().map(f).sum::<f64>() / folds.len() as f64)
+    };
+    let version = save_model_params(pool, "meta", &json, mean(|f| f.brier_score), mean(|f| f.log_loss)).await?;
+    for f in &folds {
+        save_backtest_result(pool, "meta", f.fold, f.brier_score, f.log_loss, f.accuracy, f.n_games).await?;
+    }
+    Ok((version, state, folds))
+}
+
+/// Retrain and save when `policy` says it's due; None when it isn't.
+pub async fn retrain_if_due(pool: &SqlitePool, policy: &RetrainPolicy) -> Result<Option<(i32, MlModelState)>> {
+    let status = retrain_status(pool).await?;
+    if !policy.is_due(&status, Utc::now()) {
+        return Ok(None);
+    }
+    tracing::info!(
+        "Retraining ML model: {} new results since {}",
+        status.results_since_training,
+        status.last_trained_at.map_or("never".to_string(), |t| t.to_rfc3339())
+    );
+    let (version, state, _) = train_and_save(pool).await?;
+    Ok(Some((version, state)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{init_database_with_pool, insert_match, seed_data};
+    use crate::models::Match;
+
+    async fn add_games(pool: &SqlitePool, prefix: &str, n: i64, days_ago: i64) {
+        let teams = ["nba_1", "nba_2", "nba_3", "nba_4"];
+        for i in 0..n {
+            let (home, away) = (teams[i as usize % 4], teams[(i as usize + 1) % 4]);
+            let at = Utc::now() - chrono::Duration::days(days_ago) + chrono::Duration::hours(i);
+            insert_match(pool, &Match {
+                id: format!("{}_{}", prefix, i),
+                home_team_id: home.into(),
+                away_team_id: away.into(),
+                home_team_name: home.into(),
+                away_team_name: away.into(),
+                sport: "basketball".into(),
+                league: "NBA".into(),
+                match_date: at,
+                status: "finished".into(),
+                home_score: Some(100 + (i % 7) as i32 * 3),
+                away_score: Some(104 - (i % 5) as i32 * 2),
+                venue: None,
+                city: None,
+                tiebreak_winner: None,
+                created_at: at,
+                updated_at: at,
+            }).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn retrain_fires_after_enough_new_results_and_bumps_the_version() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        init_database_with_pool(&pool).await.unwrap();
+        seed_data(&pool).await.unwrap();
+        let policy = RetrainPolicy { after_results: 10, every_hours: None };
+
+        // The guard holds off until there are enough games, then the first training runs
+        let status = retrain_status(&pool).await.unwrap();
+        assert!(status.finished_games < MIN_TRAINING_GAMES as i64);
+        assert!(retrain_if_due(&pool, &policy).await.unwrap().is_none());
+        add_games(&pool, "season", 60, 120).await;
+        let (first, first_state) = retrain_if_due(&pool, &policy).await.unwrap().unwrap();
+        assert_eq!(first, 1);
+
+        // Nothing new yet, so nothing to do
+        assert!(retrain_if_due(&pool, &policy).await.unwrap().is_none());
+
+        // Results played after the last training trigger the next one
+        sqlx::query("UPDATE model_params SET trained_at = ?")
+            .bind((Utc::now() - chrono::Duration::days(30)).to_rfc3339())
+            .execute(&pool)
+            .await
+            .unwrap();
+        add_games(&pool, "recent", 12, 10).await;
+        assert!(retrain_status(&pool).await.unwrap().results_since_training >= 12);
+        let (second, second_state) = retrain_if_due(&pool, &policy).await.unwrap().unwrap();
+        assert_eq!(second, 2);
+        assert_ne!(serde_json::to_string(&first_state).unwrap(), serde_json::to_string(&second_state).unwrap());
+        assert_eq!(retrain_status(&pool).await.unwrap().version, Some(2));
+    }
+}
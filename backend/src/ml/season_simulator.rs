@@ -0,0 +1,92 @@
+//! Season Monte Carlo Simulator
+//!
+//! Plays out a league's remaining fixtures from the current table, drawing each result
+//! from its home/draw/away probabilities, and counts where every team finishes.
+//! Ties on points go to score difference, then a coin flip.
+
+use super::monte_carlo::Xr64;
+
+/// A team's place in the table before the remaining fixtures are played.
+#[derive(Debug, Clone)]
+pub struct TableEntry {
+    pub points: i32,
+    pub score_diff: i32,
+}
+
+/// One unplayed fixture, by index into the table.
+#[derive(Debug, Clone, Copy)]
+pub struct RemainingFixture {
+    pub home: usize,
+    pub away: usize,
+    pub home_win: f64,
+    /// 0 for basketball
+    pub draw: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SeasonSimulation {
+    pub iterations: usize,
+    /// Per table entry: share of simulations finishing in each position (index 0 = first)
+    pub positions: Vec<Vec<f64>>,
+}
+
+impl SeasonSimulation {
+    /// Share of simulations in which `team` finished in the top `n`.
+    pub fn top(&self, team: usize, n: usize) -> f64 {
+        self.positions[team].iter().take(n).sum()
+    }
+
+    /// Share of simulations in which `team` finished in the bottom `n`.
+    pub fn bottom(&self, team: usize, n: usize) -> f64 {
+        self.positions[team].iter().rev().take(n).sum()
+    }
+}
+
+/// Simulate the rest of the season `iterations` times. `points` is (win, draw) — (3, 1)
+/// for football, (1, 0) for basketball, where points are wins. The same seed always
+/// yields the same result.
+pub fn simulate_season(
+    table: &[TableEntry],
+    fixtures: &[RemainingFixture],
+    points: (i32, i32),
+    iterations: usize,
+    seed: Option<u64>,
+) -> SeasonSimulation {
+    let mut rng = seed.map_or_else(Xr64::new, Xr64::from_seed);
+    let n = iterations.max(1);
+    let teams = table.len();
+    let mut counts = vec![vec![0usize; teams]; teams];
+    let mut order: Vec<usize> = (0..teams).collect();
+
+    for _ in 0..n {
+        let mut final_points: Vec<i32> = table.iter().map(|t| t.points).collect();
+        for f in fixtures {
+            let draw = rng.f64();
+            if draw < f.home_win {
+                final_points[f.home] += points.0;
+            } else if draw < f.home_win + f.draw {
+                final_points[f.home] += points.1;
+                final_points[f.away] += points.1;
+            } else {
+                final_points[f.away] += points.0;
+            }
+        }
+
+        let coin: Vec<f64> = (0..teams).map(|_| rng.f64()).collect();
+        order.sort_by(|&a, &b| {
+            final_points[b].cmp(&final_points[a])
+                .then(table[b].score_diff.cmp(&table[a].score_diff))
+                .then(coin[a].total_cmp(&coin[b]))
+        });
+        for (position, &team) in order.iter().enumerate() {
+            counts[team][position] += 1;
+        }
+    }
+
+    SeasonSimulation {
+        iterations: n,
+        positions: counts.into_iter()
+            .map(|row| row.into_iter().map(|c| c as f64 / n as f64).collect())
+            .collect(),
+    }
+}
@@ -1,7 +1,27 @@
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
 use sqlx::FromRow;
 
+use crate::utils::{match_outcome, model_pick, probability_decimals, round_to};
+
+/// Serializes a probability rounded to PROBABILITY_DECIMALS; the struct keeps full precision.
+fn serialize_probability<S: Serializer>(p: &f64, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_f64(round_to(*p, probability_decimals()))
+}
+
+fn serialize_probability_opt<S: Serializer>(p: &Option<f64>, s: S) -> Result<S::Ok, S::Error> {
+    match p {
+        Some(p) => serialize_probability(p, s),
+        None => s.serialize_none(),
+    }
+}
+
+fn serialize_probabilities<S: Serializer>(ps: &[f64], s: S) -> Result<S::Ok, S::Error> {
+    let decimals = probability_decimals();
+    s.collect_seq(ps.iter().map(|p| round_to(*p, decimals)))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Team {
     pub id: String,
@@ -12,9 +32,17 @@ pub struct Team {
     pub elo_rating: f64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Rank of elo_rating within the league, 0 (lowest) to 100 (highest). Set by the API layer.
+    #[sqlx(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elo_percentile: Option<f64>,
+    /// "Elite", "Strong", "Average" or "Weak", derived from elo_percentile
+    #[sqlx(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elo_tier: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Deserialize, FromRow)]
 pub struct Match {
     pub id: String,
     pub home_team_id: String,
@@ -24,14 +52,94 @@ pub struct Match {
     pub sport: String,
     pub league: String,
     pub match_date: DateTime<Utc>,
-    pub status: String, // "scheduled", "live", "finished"
+    pub status: String, // "scheduled", "live", "finished", "postponed", "cancelled"
     pub home_score: Option<i32>,
     pub away_score: Option<i32>,
+    /// Stadium or arena, when the source provides one
+    #[serde(default)]
+    pub venue: Option<String>,
+    #[serde(default)]
+    pub city: Option<String>,
+    /// "home" or "away" when a knockout tie level after regulation was settled in extra
+    /// time or on penalties; the scores above stay the regulation scoreline
+    #[serde(default)]
+    #[sqlx(default)]
+    pub tiebreak_winner: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+impl Match {
+    /// Score that decides the result for ELO and head-to-head: the regulation scoreline,
+    /// with a tiebreak winner credited one extra goal. None until both scores are known.
+    pub fn deciding_score(&self) -> Option<(i32, i32)> {
+        let (home, away) = (self.home_score?, self.away_score?);
+        Some(match self.tiebreak_winner.as_deref() {
+            Some("home") if home == away => (home + 1, away),
+            Some("away") if home == away => (home, away + 1),
+            _ => (home, away),
+        })
+    }
+
+    /// Winner and scoreline of a finished match; None for anything not yet finished.
+    /// A tiebreak decides the winner while the score stays the regulation one.
+    pub fn result(&self) -> Option<MatchResult> {
+        if self.status != "finished" {
+            return None;
+        }
+        let (home, away) = self.deciding_score()?;
+        Some(MatchResult {
+            winner: match_outcome(home, away),
+            score: format!("{}-{}", self.home_score?, self.away_score?),
+        })
+    }
+}
+
+/// Written out by hand to add the derived `result`.
+impl Serialize for Match {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let mut out = s.serialize_struct("Match", 17)?;
+        out.serialize_field("id", &self.id)?;
+        out.serialize_field("home_team_id", &self.home_team_id)?;
+        out.serialize_field("away_team_id", &self.away_team_id)?;
+        out.serialize_field("home_team_name", &self.home_team_name)?;
+        out.serialize_field("away_team_name", &self.away_team_name)?;
+        out.serialize_field("sport", &self.sport)?;
+        out.serialize_field("league", &self.league)?;
+        out.serialize_field("match_date", &self.match_date)?;
+        out.serialize_field("status", &self.status)?;
+        out.serialize_field("home_score", &self.home_score)?;
+        out.serialize_field("away_score", &self.away_score)?;
+        out.serialize_field("venue", &self.venue)?;
+        out.serialize_field("city", &self.city)?;
+        out.serialize_field("tiebreak_winner", &self.tiebreak_winner)?;
+        out.serialize_field("result", &self.result())?;
+        out.serialize_field("created_at", &self.created_at)?;
+        out.serialize_field("updated_at", &self.updated_at)?;
+        out.end()
+    }
+}
+
+/// How a finished match ended, as served in `Match.result`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchResult {
+    pub winner: Outcome,
+    /// Home score first, e.g. "2-1"
+    pub score: String,
+}
+
+impl MatchResult {
+    /// "W", "D" or "L" from one side's point of view.
+    pub fn for_side(&self, home: bool) -> &'static str {
+        match (self.winner, home) {
+            (Outcome::Draw, _) => "D",
+            (Outcome::Home, true) | (Outcome::Away, false) => "W",
+            _ => "L",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, FromRow)]
 pub struct Prediction {
     pub id: String,
     pub match_id: String,
@@ -41,6 +149,74 @@ pub struct Prediction {
     pub model_version: String,
     pub confidence_score: f64,
     pub created_at: DateTime<Utc>,
+    /// A team had no finished matches, so this is a low-confidence league baseline
+    #[serde(default)]
+    #[sqlx(default)]
+    pub insufficient_data: bool,
+    /// Likeliest scoreline under the football Poisson goals model, e.g. "2-1"
+    #[serde(default)]
+    #[sqlx(default)]
+    pub most_likely_score: Option<String>,
+}
+
+impl Prediction {
+    /// The model's pick, as served in `predicted_outcome`.
+    pub fn predicted_outcome(&self) -> Outcome {
+        model_pick(self.home_win_probability, self.draw_probability, self.away_win_probability)
+    }
+}
+
+/// Written out by hand to add the derived `predicted_outcome`; probabilities are rounded
+/// like every other served probability.
+impl Serialize for Prediction {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let decimals = probability_decimals();
+        let mut out = s.serialize_struct("Prediction", 11)?;
+        out.serialize_field("id", &self.id)?;
+        out.serialize_field("match_id", &self.match_id)?;
+        out.serialize_field("home_win_probability", &round_to(self.home_win_probability, decimals))?;
+        out.serialize_field("away_win_probability", &round_to(self.away_win_probability, decimals))?;
+        out.serialize_field("draw_probability", &self.draw_probability.map(|d| round_to(d, decimals)))?;
+        out.serialize_field("model_version", &self.model_version)?;
+        out.serialize_field("confidence_score", &self.confidence_score)?;
+        out.serialize_field("created_at", &self.created_at)?;
+        out.serialize_field("insufficient_data", &self.insufficient_data)?;
+        out.serialize_field("most_likely_score", &self.most_likely_score)?;
+        out.serialize_field("predicted_outcome", &self.predicted_outcome())?;
+        out.end()
+    }
+}
+
+/// Inputs captured when a prediction was generated, so it can be explained after
+/// ratings move on. Stored as JSON in prediction_inputs, keyed by prediction id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PredictionInputs {
+    pub prediction_id: String,
+    pub match_id: String,
+    pub model_version: String,
+    pub home_elo: f64,
+    pub away_elo: f64,
+    /// Blended contextual form (home games for the home side, away games for the away side)
+    /// the form model used; None when it fell back to the league average, and for basketball
+    pub home_form_rate: Option<f64>,
+    pub away_form_rate: Option<f64>,
+    /// Meetings within the H2H lookback behind the H2H component
+    #[serde(default)]
+    pub h2h_meetings: usize,
+    /// Decayed, venue-adjusted H2H component output (the league average without meetings);
+    /// None for basketball
+    #[serde(serialize_with = "serialize_probability_opt")]
+    pub h2h_home_prob: Option<f64>,
+    #[serde(serialize_with = "serialize_probability_opt")]
+    pub h2h_away_prob: Option<f64>,
+    #[serde(serialize_with = "serialize_probability_opt")]
+    pub h2h_draw_prob: Option<f64>,
+    pub home_rest_days: Option<u32>,
+    pub away_rest_days: Option<u32>,
+    /// Weights of the components actually blended, by name (`poisson` only when it was);
+    /// empty for ML and league-baseline predictions
+    pub weights: std::collections::BTreeMap<String, f64>,
+    pub captured_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -57,9 +233,73 @@ pub struct TeamStats {
     pub points_for: Option<i32>, // Basketball
     pub points_against: Option<i32>, // Basketball
     pub form: String, // Last 5 games: "WLWDW" etc
+    /// Season expected points summed from pre-match predictions
+    pub xpts: Option<f64>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// A row of the league table as published by the competition (football-data.org),
+/// kept alongside our computed team_stats for display and cross-checking.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct OfficialStanding {
+    pub team_id: String,
+    pub league: String,
+    pub position: i32,
+    pub played: i32,
+    pub wins: i32,
+    pub draws: i32,
+    pub losses: i32,
+    pub points: i32,
+    pub goals_for: i32,
+    pub goals_against: i32,
+    pub fetched_at: String,
+}
+
+/// One row of a league table built from team_stats.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StandingsRow {
+    pub position: usize,
+    pub team_id: String,
+    pub team_name: String,
+    pub played: i32,
+    pub wins: i32,
+    pub draws: Option<i32>,
+    pub losses: i32,
+    /// Football: 3 per win + 1 per draw. Basketball: wins.
+    pub points: i32,
+    /// Goal difference (football) or point differential (basketball)
+    pub score_diff: i32,
+    /// Expected points over the season's matches that had a prediction
+    pub xpts: Option<f64>,
+    /// Actual − expected points over those same matches: positive = over-performing the model
+    pub xpts_diff: Option<f64>,
+    pub form: String,
+}
+
+/// One entry of a /leagues/:sport/:league/leaders ranking.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardRow {
+    pub rank: usize,
+    pub team_id: String,
+    pub team_name: String,
+    /// Per-game for attack/defense, 0–1 rate for form, rating for elo
+    pub value: f64,
+    pub matches_played: i32,
+}
+
+/// One entry of a /leagues/:sport/:league/form-table ranking.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FormTableRow {
+    pub rank: usize,
+    pub team_id: String,
+    pub team_name: String,
+    /// Last 5 results, newest first, e.g. "WWDLW"
+    pub form: String,
+    /// Weighted recent points rate over home and away games, 0–1
+    pub form_rate: f64,
+    pub elo_rating: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpcomingMatchWithPrediction {
     pub match_info: Match,
@@ -68,6 +308,237 @@ pub struct UpcomingMatchWithPrediction {
     pub away_team_stats: Option<TeamStats>,
 }
 
+/// Everything known about one match, for GET /matches/{id}.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchDetail {
+    pub match_info: Match,
+    pub prediction: Option<Prediction>,
+    pub market_odds: Option<MarketOdds>,
+    /// Our probability minus the devigged market's on the best side; None without complete odds
+    pub edge_value: Option<f64>,
+    /// "home", "away" or "draw"
+    pub edge_side: Option<String>,
+    pub home_team: Team,
+    pub away_team: Team,
+    pub home_team_stats: Option<TeamStats>,
+    pub away_team_stats: Option<TeamStats>,
+}
+
+/// A fixture priced on request by POST /predict/batch; nothing is persisted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HypotheticalFixture {
+    pub home_id: String,
+    pub away_id: String,
+    pub sport: String,
+    /// Neutral venue: no home advantage for either side
+    #[serde(default)]
+    pub neutral: bool,
+}
+
+/// A hypothetical result from one team's side, for ELO projections.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectedResult {
+    Win,
+    Draw,
+    Loss,
+}
+
+/// GET /teams/:id/accuracy: how well the model has predicted one team's finished matches.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamAccuracy {
+    pub team_id: String,
+    pub team_name: String,
+    /// Finished matches with a prediction made before kickoff
+    pub matches: usize,
+    /// Share of those where the model's pick was the result
+    pub hit_rate: f64,
+    pub brier_score: f64,
+    /// Mean probability the model gave this team to win
+    #[serde(serialize_with = "serialize_probability")]
+    pub avg_predicted_win_probability: f64,
+    /// Share of those matches the team actually won
+    pub actual_win_rate: f64,
+}
+
+/// GET /teams/:id/elo/project: where a team's rating lands after a run of hypothetical results.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EloProjection {
+    pub team_id: String,
+    pub team_name: String,
+    pub current_rating: f64,
+    pub projected_rating: f64,
+    pub steps: Vec<EloProjectionStep>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EloProjectionStep {
+    pub opponent_id: String,
+    pub opponent_name: String,
+    pub opponent_rating: f64,
+    pub result: ProjectedResult,
+    pub rating_after: f64,
+}
+
+/// One entry of a batch response, in request order: a prediction or the reason there isn't one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchPredictionResult {
+    #[serde(flatten)]
+    pub fixture: HypotheticalFixture,
+    pub prediction: Option<Prediction>,
+    pub error: Option<String>,
+}
+
+/// Rows that look like the same fixture (same teams, same day, different ids).
+/// `keep` is the row to retain: the one with a result, else the most recently updated.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateMatches {
+    pub keep: Match,
+    pub duplicates: Vec<Match>,
+}
+
+/// One anomaly found by the post-fetch integrity pass, e.g. a finished match with no score.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityIssue {
+    pub kind: String,
+    pub entity_id: String,
+    pub detail: String,
+}
+
+/// A match result from the home side's view: a parlay leg's pick, or the model's pick
+/// (see `utils::model_pick`).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Home,
+    Away,
+    Draw,
+}
+
+/// One leg of a POST /predict/parlay request; `outcome` defaults to the model's pick.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParlayLeg {
+    pub match_id: String,
+    pub outcome: Option<Outcome>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParlayLegQuote {
+    pub match_info: Match,
+    pub outcome: Outcome,
+    /// None when the match has no stored prediction (or no draw price for a draw pick)
+    #[serde(serialize_with = "serialize_probability_opt")]
+    pub probability: Option<f64>,
+}
+
+/// Combined probability of a multi-leg ticket. Both combined figures are None when any
+/// leg lacks a probability.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParlayQuote {
+    pub legs: Vec<ParlayLegQuote>,
+    /// Match ids of legs without a usable prediction
+    pub missing_predictions: Vec<String>,
+    /// Product of leg probabilities, assuming independence
+    #[serde(serialize_with = "serialize_probability_opt")]
+    pub naive_probability: Option<f64>,
+    /// Approximate: adds pairwise correlation between legs in the same league on the same day
+    #[serde(serialize_with = "serialize_probability_opt")]
+    pub correlated_probability: Option<f64>,
+    pub correlation: f64,
+}
+
+/// One over/under line from GET /matches/:id/simulate or /matches/:id/totals.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TotalLine {
+    pub line: f64,
+    #[serde(serialize_with = "serialize_probability")]
+    pub over: f64,
+    #[serde(serialize_with = "serialize_probability")]
+    pub under: f64,
+}
+
+/// Expected score behind a fixture's over/under prices. Basketball rows are stored by the
+/// prediction refresh; football is priced on request from the Poisson score means.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TotalsPrediction {
+    pub match_id: String,
+    /// "pace_efficiency" (league averages stand in as "pace_efficiency_league_avg" when a
+    /// side has no advanced stats) or "poisson"
+    pub model: String,
+    pub expected_home_score: f64,
+    pub expected_away_score: f64,
+    /// Standard deviation of the combined score; None for Poisson, where the mean fixes it
+    pub total_sd: Option<f64>,
+    /// Expected possessions per side (basketball)
+    pub possessions: Option<f64>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Expected total and over/under probabilities, returned by /matches/:id/totals.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchTotals {
+    #[serde(flatten)]
+    pub prediction: TotalsPrediction,
+    pub expected_total: f64,
+    pub lines: Vec<TotalLine>,
+}
+
+/// Monte Carlo outcome frequencies for one fixture, returned by /matches/:id/simulate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchSimulation {
+    pub match_info: Match,
+    /// "poisson" (football goals) or "normal" (basketball points)
+    pub model: String,
+    pub iterations: usize,
+    /// Echoed back when given, so a run can be reproduced
+    pub seed: Option<u64>,
+    pub expected_home_score: f64,
+    pub expected_away_score: f64,
+    #[serde(serialize_with = "serialize_probability")]
+    pub home_win: f64,
+    /// None for basketball, where ties go to overtime
+    #[serde(serialize_with = "serialize_probability_opt")]
+    pub draw: Option<f64>,
+    #[serde(serialize_with = "serialize_probability")]
+    pub away_win: f64,
+    pub most_likely_home_score: u32,
+    pub most_likely_away_score: u32,
+    #[serde(serialize_with = "serialize_probability")]
+    pub most_likely_score_frequency: f64,
+    pub avg_total: f64,
+    pub totals: Vec<TotalLine>,
+    /// Both teams to score; football only
+    #[serde(serialize_with = "serialize_probability_opt")]
+    pub btts: Option<f64>,
+    /// Closed-form 1X2 under the same score model, to cross-check the simulation
+    #[serde(serialize_with = "serialize_probability")]
+    pub analytic_home_win: f64,
+    #[serde(serialize_with = "serialize_probability_opt")]
+    pub analytic_draw: Option<f64>,
+    #[serde(serialize_with = "serialize_probability")]
+    pub analytic_away_win: f64,
+    /// Latest stored ensemble prediction, for comparison
+    pub prediction: Option<Prediction>,
+}
+
+/// An in-play match with its current score, returned by /matches/live.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveMatch {
+    /// home_score / away_score hold the current partial score
+    pub match_info: Match,
+    /// Pre-match prediction, unchanged
+    pub prediction: Option<Prediction>,
+    /// Share of regulation time left, estimated from kickoff time
+    pub fraction_remaining: f64,
+    /// Pre-match prediction updated for the current score and time left
+    #[serde(serialize_with = "serialize_probability_opt")]
+    pub in_play_home_probability: Option<f64>,
+    #[serde(serialize_with = "serialize_probability_opt")]
+    pub in_play_away_probability: Option<f64>,
+    #[serde(serialize_with = "serialize_probability_opt")]
+    pub in_play_draw_probability: Option<f64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Edge {
     pub match_id: String,
@@ -77,12 +548,316 @@ pub struct Edge {
     pub market_away_odds: f64,
     pub market_draw_odds: Option<f64>,
     pub edge_value: f64,
+    /// Outcome carrying the edge: "home", "away" or "draw"
+    pub side: String,
     /// True when odds come from The Odds API, false when simulated
     pub is_live_odds: bool,
     pub bookmaker: Option<String>,
     pub odds_fetched_at: Option<String>,
 }
 
+/// Why /predictions/edges has (or lacks) rows, so an empty list is never ambiguous.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeStatus {
+    EdgesFound,
+    /// No odds stored and ODDS_API_KEY is not set, so none will be fetched
+    NoOddsKey,
+    /// ODDS_API_KEY is set but no odds have been stored for upcoming matches yet
+    NoMarketOdds,
+    /// Odds exist but no prediction beats the market by the threshold
+    NoEdges,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EdgeReport {
+    pub edges: Vec<Edge>,
+    pub status: EdgeStatus,
+    pub message: String,
+}
+
+/// One odds snapshot priced against the prediction that was current when it was fetched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EdgeTimelinePoint {
+    pub fetched_at: String,
+    pub bookmaker: String,
+    pub home_odds: f64,
+    pub draw_odds: Option<f64>,
+    pub away_odds: f64,
+    pub prediction_id: String,
+    pub model_version: String,
+    /// Best side at this snapshot: "home", "away" or "draw"
+    pub side: String,
+    /// Our probability − devigged market probability on `side`
+    pub edge_value: f64,
+}
+
+/// Why an upcoming match did or did not surface in /predictions/edges.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EdgeDiagnostic {
+    pub match_id: String,
+    pub home_team_name: String,
+    pub away_team_name: String,
+    pub has_prediction: bool,
+    pub has_odds: bool,
+    /// Computed even when below threshold; None without both a prediction and odds
+    pub edge_value: Option<f64>,
+    pub threshold: f64,
+    /// "no prediction", "no odds", "incomplete odds", "below threshold" or "edge"
+    pub reason: String,
+}
+
+/// Whether an upcoming match has market odds, and how old they are, for /odds/coverage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OddsCoverage {
+    pub match_id: String,
+    pub home_team_name: String,
+    pub away_team_name: String,
+    pub sport: String,
+    pub league: String,
+    pub match_date: DateTime<Utc>,
+    /// A market_odds row exists, so the match can show edges
+    pub covered: bool,
+    pub bookmaker: Option<String>,
+    pub odds_fetched_at: Option<String>,
+    /// Minutes since the odds were fetched
+    pub odds_age_minutes: Option<i64>,
+}
+
+/// Two registry configurations' latest predictions for one upcoming match, for /predictions/compare.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelComparison {
+    pub match_id: String,
+    pub home_team_name: String,
+    pub away_team_name: String,
+    pub match_date: DateTime<Utc>,
+    pub a: Prediction,
+    pub b: Prediction,
+    /// Largest absolute gap between the two on any outcome
+    pub max_divergence: f64,
+}
+
+/// How far our prediction sits from the devigged market on one match, for /predictions/divergence.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketDivergence {
+    pub match_id: String,
+    pub home_team_name: String,
+    pub away_team_name: String,
+    pub sport: String,
+    pub match_date: DateTime<Utc>,
+    /// Outcome with the largest disagreement: "home", "away" or "draw"
+    pub side: String,
+    #[serde(serialize_with = "serialize_probability")]
+    pub our_probability: f64,
+    #[serde(serialize_with = "serialize_probability")]
+    pub market_probability: f64,
+    /// our_probability − market_probability: positive = we rate that side higher
+    pub divergence: f64,
+    pub bookmaker: String,
+}
+
+/// A single upcoming match paired with its latest prediction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchPick {
+    pub match_info: Match,
+    pub prediction: Prediction,
+}
+
+/// League-wide "what's interesting this week" summary for /leagues/:sport/:league/insights.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeagueInsights {
+    pub sport: String,
+    pub league: String,
+    /// Upcoming matches that have a prediction
+    pub predicted_matches: usize,
+    #[serde(serialize_with = "serialize_probability_opt")]
+    pub avg_home_win_probability: Option<f64>,
+    pub highest_confidence: Option<MatchPick>,
+    /// Matchup where home and away win probabilities are closest
+    pub closest_matchup: Option<MatchPick>,
+    pub biggest_edge: Option<Edge>,
+}
+
+/// Build and model metadata returned by /version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionInfo {
+    pub version: String,
+    /// Short git commit the binary was built from, or "unknown"
+    pub git_commit: String,
+    pub football_model_version: String,
+    pub basketball_model_version: String,
+    /// None only if the database was never initialised by a build that records it
+    pub schema_version: Option<i64>,
+}
+
+/// One entry of the schema_migrations log. `applied_at` is None for a pending migration;
+/// `name` is None for rows recorded before migrations were named.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SchemaMigration {
+    pub version: i64,
+    pub name: Option<String>,
+    pub applied_at: Option<String>,
+}
+
+/// Returned by /admin/schema.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaStatus {
+    /// Newest migration this binary knows
+    pub expected_version: i64,
+    /// Newest migration recorded in the database
+    pub current_version: Option<i64>,
+    pub applied: Vec<SchemaMigration>,
+    /// Known to this binary but not recorded in the database
+    pub pending: Vec<SchemaMigration>,
+    /// Recorded in the database by a newer binary than this one
+    pub unknown: Vec<i64>,
+    pub up_to_date: bool,
+}
+
+/// Returned by /config.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeConfig {
+    pub basketball_model_version: String,
+    /// New finished NBA games that trigger a scheduled retrain (0 = off)
+    pub retrain_after_results: i64,
+    /// Hours after which the model is retrained if any new game has finished
+    pub retrain_every_hours: Option<i64>,
+    pub last_trained_at: Option<DateTime<Utc>>,
+    /// model_params version of the last training
+    pub trained_model_version: Option<i32>,
+    pub results_since_training: i64,
+}
+
+/// Landing-page summary returned by /stats/overview.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsOverview {
+    pub sports: Vec<SportCounts>,
+    pub upcoming_next_7_days: i64,
+    pub value_edges: usize,
+    /// Highest-ELO team in each league
+    pub top_teams: Vec<Team>,
+    /// Most recent match insert/update, i.e. the last successful data refresh
+    pub last_data_refresh: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SportCounts {
+    pub sport: String,
+    pub teams: i64,
+    pub matches: i64,
+}
+
+/// One timed step of POST /admin/recompute.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecomputePhase {
+    /// "elo", "stats", "predictions" or "odds"
+    pub phase: String,
+    /// Matches replayed, team stat rows written, matches predicted, or odds rows updated
+    pub count: usize,
+    pub duration_ms: u64,
+    /// Why the phase did nothing, e.g. ODDS_API_KEY not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecomputeReport {
+    pub phases: Vec<RecomputePhase>,
+    pub total_ms: u64,
+}
+
+/// POST /admin/carryover: body and result of starting a season from the last one's ratings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CarryoverRequest {
+    pub from_season: String,
+    pub to_season: String,
+    /// Share of the way back to the mean each rating moves, 0 to 1
+    #[serde(default = "default_carryover_regression")]
+    pub regression: f64,
+    /// Snapshot current ratings as `from_season`'s finals first
+    #[serde(default)]
+    pub record_finals: bool,
+}
+
+fn default_carryover_regression() -> f64 {
+    0.25
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CarryoverReport {
+    pub from_season: String,
+    pub to_season: String,
+    pub regression: f64,
+    /// Final ratings recorded for `from_season` by this request (0 unless record_finals)
+    pub finals_recorded: u64,
+    pub teams_carried: u64,
+}
+
+/// What a data fetch actually changed, returned by every `DataFetcher::fetch_*`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FetchSummary {
+    pub teams_upserted: usize,
+    pub matches_upserted: usize,
+    /// Matches stored as finished that were not finished (or not stored) before
+    pub newly_finished: usize,
+    /// Rows skipped along the way; the fetch itself still succeeded
+    pub errors: Vec<String>,
+    /// How each sport's fetch went, when several were attempted together
+    #[serde(default)]
+    pub sports: Vec<SportFetchStatus>,
+}
+
+impl FetchSummary {
+    pub fn merge(&mut self, other: FetchSummary) {
+        self.teams_upserted += other.teams_upserted;
+        self.matches_upserted += other.matches_upserted;
+        self.newly_finished += other.newly_finished;
+        self.errors.extend(other.errors);
+        self.sports.extend(other.sports);
+    }
+
+    /// Fold in one sport's fetch: its counts on success, its error as the reason otherwise.
+    pub fn record_sport(&mut self, sport: &str, result: anyhow::Result<FetchSummary>) {
+        let (outcome, reason) = match result {
+            Ok(summary) => {
+                self.merge(summary);
+                (FetchOutcome::Success, None)
+            }
+            Err(e) => {
+                tracing::error!("{} fetch failed: {}", sport, e);
+                (FetchOutcome::Failed, Some(e.to_string()))
+            }
+        };
+        self.sports.push(SportFetchStatus { sport: sport.to_string(), outcome, reason });
+    }
+
+    pub fn skip_sport(&mut self, sport: &str, reason: &str) {
+        self.sports.push(SportFetchStatus { sport: sport.to_string(), outcome: FetchOutcome::Skipped, reason: Some(reason.to_string()) });
+    }
+
+    /// At least one sport was attempted and none of them succeeded.
+    pub fn all_failed(&self) -> bool {
+        self.sports.iter().any(|s| s.outcome == FetchOutcome::Failed)
+            && !self.sports.iter().any(|s| s.outcome == FetchOutcome::Success)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FetchOutcome {
+    Success,
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SportFetchStatus {
+    pub sport: String,
+    pub outcome: FetchOutcome,
+    /// Why the sport was skipped or what made it fail
+    pub reason: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketOdds {
     pub match_id: String,
@@ -100,7 +875,19 @@ pub struct DatasetRequest {
     pub date_from: Option<DateTime<Utc>>,
     pub date_to: Option<DateTime<Utc>>,
     pub stats_categories: Vec<String>, // "basic", "advanced", "form", etc.
+    #[serde(default = "default_dataset_format")]
     pub format: String, // "csv" or "json"
+    /// Gzip the generated file (served as .gz)
+    #[serde(default)]
+    pub compress: bool,
+}
+
+/// Format used when a dataset request omits one (DATASET_DEFAULT_FORMAT, default csv).
+fn default_dataset_format() -> String {
+    std::env::var("DATASET_DEFAULT_FORMAT").ok()
+        .map(|f| f.trim().to_lowercase())
+        .filter(|f| f == "csv" || f == "json")
+        .unwrap_or_else(|| "csv".to_string())
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -111,6 +898,30 @@ pub struct TeamProfile {
     pub elo_history: Vec<EloHistoryPoint>,
 }
 
+/// GET /teams/:id/advanced: a team's finished matches with strength-of-schedule context.
+/// Goals are points for basketball. All zero when the team has no finished matches.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdvancedTeamStats {
+    pub team_id: String,
+    pub team_name: String,
+    pub matches_played: usize,
+    /// Mean pre-match rating of the opponents faced
+    pub avg_opponent_elo: f64,
+    /// Mean current rating across the league, the reference for the adjusted rates
+    pub league_average_elo: f64,
+    pub goals_for_per_game: f64,
+    pub goals_against_per_game: f64,
+    /// Goals for scaled by opponent ELO / league average: scoring on strong sides counts more
+    pub adjusted_goals_for_per_game: f64,
+    /// Goals against scaled by league average / opponent ELO: conceding to weak sides counts more
+    pub adjusted_goals_against_per_game: f64,
+    /// From the stored pre-match prediction, or pre-match ELO when there is none
+    pub expected_points: f64,
+    pub actual_points: f64,
+    /// actual − expected: positive = beating the model
+    pub points_delta: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct EloHistoryPoint {
     pub team_id: String,
@@ -119,6 +930,49 @@ pub struct EloHistoryPoint {
     pub match_id: Option<String>,
 }
 
+/// One of a team's ELO history points next to the league average on the same date.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EloVsAveragePoint {
+    pub date: DateTime<Utc>,
+    pub match_id: Option<String>,
+    pub elo_rating: f64,
+    /// Mean latest rating, as of `date`, over league teams with history by then
+    pub league_average: f64,
+    /// elo_rating − league_average
+    pub relative: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EloVsAverage {
+    pub team_id: String,
+    pub team_name: String,
+    pub league: String,
+    pub points: Vec<EloVsAveragePoint>,
+}
+
+/// Where a team is projected to finish, from simulating the league's remaining fixtures.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamProjection {
+    pub team_id: String,
+    pub team_name: String,
+    pub league: String,
+    pub iterations: usize,
+    pub remaining_fixtures: usize,
+    /// Index 0 = probability of finishing first
+    #[serde(serialize_with = "serialize_probabilities")]
+    pub position_probabilities: Vec<f64>,
+    #[serde(serialize_with = "serialize_probability")]
+    pub title_probability: f64,
+    /// Top 4 (EPL Champions League places) or top 16 (NBA playoff/play-in, conferences not
+    /// modelled); None for competitions without qualifying places
+    #[serde(serialize_with = "serialize_probability_opt")]
+    pub qualification_probability: Option<f64>,
+    /// Bottom 3 in the EPL; None for competitions without relegation
+    #[serde(serialize_with = "serialize_probability_opt")]
+    pub relegation_probability: Option<f64>,
+    pub expected_position: f64,
+}
+
 /// Advanced per-team NBA stats fetched from stats.nba.com.
 /// Stores Bayesian-friendly raw values; shrinkage is applied at prediction time.
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -160,8 +1014,11 @@ pub struct MatchAnalysis {
     pub h2h: H2hComponent,
     pub schedule: ScheduleComponent,
     pub model_version: String,
+    #[serde(serialize_with = "serialize_probability")]
     pub final_home_prob: f64,
+    #[serde(serialize_with = "serialize_probability")]
     pub final_away_prob: f64,
+    #[serde(serialize_with = "serialize_probability_opt")]
     pub draw_prob: Option<f64>,
     pub confidence: f64,
 }
@@ -171,6 +1028,7 @@ pub struct EloComponent {
     pub home_elo: f64,
     pub away_elo: f64,
     pub diff: f64,
+    #[serde(serialize_with = "serialize_probability")]
     pub home_prob: f64,
     pub weight: f64,
     pub narrative: String,
@@ -182,6 +1040,7 @@ pub struct FormComponent {
     pub away_avg_margin: f64,
     pub home_games_used: i64,
     pub away_games_used: i64,
+    #[serde(serialize_with = "serialize_probability")]
     pub home_prob: f64,
     pub weight: f64,
     pub narrative: String,
@@ -193,6 +1052,7 @@ pub struct H2hComponent {
     pub away_wins: i64,
     pub draws: i64,
     pub total: i64,
+    #[serde(serialize_with = "serialize_probability")]
     pub home_prob: f64,
     pub weight: f64,
     pub narrative: String,
@@ -258,6 +1118,15 @@ impl<T> ApiResponse<T> {
             timestamp: Utc::now(),
         }
     }
+    /// A failure that still carries its data, e.g. the per-sport reasons behind a failed fetch.
+    pub fn failure(data: T, message: String) -> Self {
+        Self {
+            success: false,
+            data: Some(data),
+            error: Some(message),
+            timestamp: Utc::now(),
+        }
+    }
 }
 
 // ── ML model types ────────────────────────────────────────────────────────────
@@ -288,4 +1157,124 @@ pub struct ScoreDistribution {
     pub expected_margin: f64,
     /// 80 probability buckets: index i → margin = i-40
     pub buckets: Vec<f64>,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probabilities_serialize_rounded_and_still_sum_to_one() {
+        let prediction = Prediction {
+            id: "p".into(),
+            match_id: "m".into(),
+            home_win_probability: 0.463829104,
+            away_win_probability: 0.287640392,
+            draw_probability: Some(1.0 - 0.463829104 - 0.287640392),
+            model_version: "test".into(),
+            confidence_score: 0.5,
+            created_at: Utc::now(),
+            insufficient_data: false,
+            most_likely_score: None,
+        };
+        let decimals = probability_decimals();
+        let json = serde_json::to_value(&prediction).unwrap();
+        let served: Vec<f64> = ["home_win_probability", "away_win_probability", "draw_probability"]
+            .iter()
+            .map(|k| json[k].as_f64().unwrap())
+            .collect();
+
+        assert_eq!(served[0], round_to(0.463829104, decimals));
+        for p in &served {
+            let shifted = p * 10f64.powi(decimals);
+            assert!((shifted - shifted.round()).abs() < 1e-6, "{} has more than {} decimals", p, decimals);
+        }
+        // Each outcome is off by at most half a unit in the last place
+        let tolerance = 1.5 * 10f64.powi(-decimals) + 1e-12;
+        assert!((served.iter().sum::<f64>() - 1.0).abs() <= tolerance);
+        // Full precision is untouched in memory
+        assert_eq!(prediction.home_win_probability, 0.463829104);
+        assert_eq!(round_to(0.463829104, 2), 0.46);
+    }
+
+    #[test]
+    fn predicted_outcome_is_served_with_the_probabilities() {
+        let prediction = |home: f64, draw: Option<f64>, away: f64| Prediction {
+            id: "p".into(),
+            match_id: "m".into(),
+            home_win_probability: home,
+            away_win_probability: away,
+            draw_probability: draw,
+            model_version: "test".into(),
+            confidence_score: 0.5,
+            created_at: Utc::now(),
+            insufficient_data: false,
+            most_likely_score: None,
+        };
+        let served = |p: Prediction| serde_json::to_value(&p).unwrap()["predicted_outcome"].clone();
+
+        assert_eq!(served(prediction(0.72, Some(0.18), 0.10)), "home");
+        assert_eq!(served(prediction(0.31, Some(0.38), 0.31)), "draw");
+        assert_eq!(served(prediction(0.35, None, 0.65)), "away");
+    }
+
+    #[test]
+    fn finished_match_serializes_winner_and_score() {
+        let now = Utc::now();
+        let mut m = Match {
+            id: "m".into(),
+            home_team_id: "h".into(),
+            away_team_id: "a".into(),
+            home_team_name: "Home".into(),
+            away_team_name: "Away".into(),
+            sport: "football".into(),
+            league: "EPL".into(),
+            match_date: now,
+            status: "finished".into(),
+            home_score: Some(2),
+            away_score: Some(1),
+            venue: None,
+            city: None,
+            tiebreak_winner: None,
+            created_at: now,
+            updated_at: now,
+        };
+        let json = serde_json::to_value(&m).unwrap();
+        assert_eq!(json["result"]["winner"], "home");
+        assert_eq!(json["result"]["score"], "2-1");
+        assert_eq!(json["home_score"], 2);
+
+        // Round-trips: the derived field is ignored on the way back in
+        let back: Match = serde_json::from_value(json).unwrap();
+        assert_eq!(back.result().unwrap().for_side(false), "L");
+
+        m.status = "scheduled".into();
+        (m.home_score, m.away_score) = (None, None);
+        assert!(serde_json::to_value(&m).unwrap()["result"].is_null());
+    }
+
+    #[test]
+    fn season_projection_serializes_rounded_probabilities() {
+        let projection = TeamProjection {
+            team_id: "t".into(),
+            team_name: "Team".into(),
+            league: "EPL".into(),
+            iterations: 3,
+            remaining_fixtures: 2,
+            position_probabilities: vec![1.0 / 3.0, 2.0 / 3.0],
+            title_probability: 1.0 / 3.0,
+            qualification_probability: Some(2.0 / 3.0),
+            relegation_probability: None,
+            expected_position: 1.0 + 2.0 / 3.0,
+        };
+        let decimals = probability_decimals();
+        let json = serde_json::to_value(&projection).unwrap();
+
+        assert_eq!(json["position_probabilities"][0], round_to(1.0 / 3.0, decimals));
+        assert_eq!(json["position_probabilities"][1], round_to(2.0 / 3.0, decimals));
+        assert_eq!(json["title_probability"], round_to(1.0 / 3.0, decimals));
+        assert_eq!(json["qualification_probability"], round_to(2.0 / 3.0, decimals));
+        assert!(json["relegation_probability"].is_null());
+        assert_eq!(projection.title_probability, 1.0 / 3.0);
+    }
+}
@@ -5,8 +5,9 @@ use serde::Deserialize;
 use sqlx::SqlitePool;
 use std::env;
 
-use crate::db::{insert_match, insert_team};
-use crate::models::{Match, Team};
+use crate::db::{get_match_status, get_team_current_stats, insert_match, insert_team, upsert_official_standing};
+use crate::models::{FetchSummary, Match, OfficialStanding, Team};
+use crate::services::HttpConfig;
 
 // ── football-data.org structures ────────────────────────────────────────────
 
@@ -36,6 +37,51 @@ pub struct FootballMatch {
     pub home_team: MatchTeam,
     pub away_team: MatchTeam,
     pub score: MatchScore,
+    /// Stadium name; football-data.org leaves it null for some fixtures
+    #[serde(default)]
+    pub venue: Option<String>,
+    /// REGULAR_SEASON, LEAGUE_STAGE, GROUP_STAGE, or a knockout round such as LAST_16 or FINAL
+    #[serde(default)]
+    pub stage: Option<String>,
+}
+
+impl FootballMatch {
+    /// Knockout rounds go to extra time and penalties; league and group games end at full time.
+    pub fn is_knockout(&self) -> bool {
+        self.stage.as_deref().is_some_and(|s| !matches!(s, "REGULAR_SEASON" | "LEAGUE_STAGE" | "GROUP_STAGE"))
+    }
+
+    /// Our match row for this fixture, with ids prefixed by `prefix` (e.g. "epl").
+    pub fn into_match(self, prefix: &str, league: &str, match_date: DateTime<Utc>) -> Match {
+        let status = match self.status.as_str() {
+            "FINISHED"                => "finished",
+            "IN_PLAY" | "PAUSED"      => "live",
+            "POSTPONED" | "SUSPENDED" => "postponed",
+            "CANCELLED"               => "cancelled",
+            _                         => "scheduled",   // SCHEDULED, TIMED …
+        };
+        let (home_score, away_score) = self.score.regulation();
+        let tiebreak_winner = if self.is_knockout() { self.score.tiebreak_winner() } else { None };
+
+        Match {
+            id:              format!("{}_{}", prefix, self.id),
+            home_team_id:    format!("{}_{}", prefix, self.home_team.id),
+            away_team_id:    format!("{}_{}", prefix, self.away_team.id),
+            home_team_name:  self.home_team.name,
+            away_team_name:  self.away_team.name,
+            sport:           "football".to_string(),
+            league:          league.to_string(),
+            match_date,
+            status:          status.to_string(),
+            home_score,
+            away_score,
+            venue:           self.venue.filter(|v| !v.trim().is_empty()),
+            city:            None,
+            tiebreak_winner,
+            created_at:      Utc::now(),
+            updated_at:      Utc::now(),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -47,7 +93,40 @@ pub struct MatchTeam {
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MatchScore {
+    /// HOME_TEAM, AWAY_TEAM or DRAW, after extra time and penalties
+    #[serde(default)]
+    pub winner: Option<String>,
+    /// REGULAR, EXTRA_TIME or PENALTY_SHOOTOUT
+    #[serde(default)]
+    pub duration: Option<String>,
+    /// Includes extra-time goals (and, for shootouts, the penalties) when there were any
     pub full_time: Option<Score>,
+    /// The 90-minute score; only sent when the match went beyond it
+    #[serde(default)]
+    pub regular_time: Option<Score>,
+}
+
+impl MatchScore {
+    /// The 90-minute scoreline, which goal-based metrics use whatever happened after it.
+    pub fn regulation(&self) -> (Option<i32>, Option<i32>) {
+        let score = self.regular_time.as_ref().or(self.full_time.as_ref());
+        (
+            score.and_then(|s| s.home.map(|v| v as i32)),
+            score.and_then(|s| s.away.map(|v| v as i32)),
+        )
+    }
+
+    /// "home" / "away" when a game level after regulation was won in extra time or on penalties.
+    pub fn tiebreak_winner(&self) -> Option<String> {
+        if self.duration.as_deref().is_none_or(|d| d == "REGULAR") {
+            return None;
+        }
+        match (self.regulation(), self.winner.as_deref()) {
+            ((Some(h), Some(a)), Some("HOME_TEAM")) if h == a => Some("home".to_string()),
+            ((Some(h), Some(a)), Some("AWAY_TEAM")) if h == a => Some("away".to_string()),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -56,6 +135,33 @@ pub struct Score {
     pub away: Option<u32>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct FootballDataStandings {
+    pub standings: Vec<StandingsTable>,
+}
+
+/// One of the TOTAL / HOME / AWAY tables in a standings response.
+#[derive(Debug, Deserialize)]
+pub struct StandingsTable {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub table: Vec<StandingsEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StandingsEntry {
+    pub position: i32,
+    pub team: MatchTeam,
+    pub played_games: i32,
+    pub won: i32,
+    pub draw: i32,
+    pub lost: i32,
+    pub points: i32,
+    pub goals_for: i32,
+    pub goals_against: i32,
+}
+
 // ── balldontlie.io structures ────────────────────────────────────────────────
 
 #[derive(Debug, Deserialize)]
@@ -85,6 +191,9 @@ pub struct NbaGamesResponse {
 pub struct NbaGame {
     pub id: u32,
     pub date: String,
+    /// Tip-off in UTC when scheduled; null for TBD games and some older rows
+    #[serde(default)]
+    pub datetime: Option<String>,
     pub home_team: NbaTeam,
     pub visitor_team: NbaTeam,
     pub home_team_score: Option<u32>,
@@ -94,34 +203,121 @@ pub struct NbaGame {
 
 // ── DataFetcher ──────────────────────────────────────────────────────────────
 
+/// A league the fetcher can sync from an external API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetchLeague {
+    Epl,
+    Nba,
+}
+
+impl FetchLeague {
+    pub const ALL: [FetchLeague; 2] = [FetchLeague::Epl, FetchLeague::Nba];
+
+    pub fn sport(self) -> &'static str {
+        match self {
+            FetchLeague::Epl => "football",
+            FetchLeague::Nba => "basketball",
+        }
+    }
+
+    /// Env flag that turns the league off when set to false / 0 / no.
+    pub fn enable_flag(self) -> &'static str {
+        match self {
+            FetchLeague::Epl => "ENABLE_EPL",
+            FetchLeague::Nba => "ENABLE_NBA",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "epl" | "football" => Some(FetchLeague::Epl),
+            "nba" | "basketball" => Some(FetchLeague::Nba),
+            _ => None,
+        }
+    }
+
+    /// Leagues in FETCH_ORDER (comma-separated, e.g. "nba,epl"; default "epl,nba"), minus
+    /// any switched off by its ENABLE_<LEAGUE> flag. Leagues left out of FETCH_ORDER are
+    /// appended in their default position; unknown names are ignored.
+    pub fn enabled_from_env() -> Vec<FetchLeague> {
+        let enabled = |league: FetchLeague| {
+            !matches!(
+                env::var(league.enable_flag()).ok().as_deref().map(|v| v.trim().to_ascii_lowercase()).as_deref(),
+                Some("false" | "0" | "no" | "off")
+            )
+        };
+        let mut order: Vec<FetchLeague> = Vec::new();
+        let listed = env::var("FETCH_ORDER").unwrap_or_default();
+        for league in listed.split(',').filter_map(Self::parse).chain(Self::ALL) {
+            if !order.contains(&league) {
+                order.push(league);
+            }
+        }
+        order.retain(|&league| enabled(league));
+        order
+    }
+}
+
 pub struct DataFetcher {
     client: Client,
     football_api_key: Option<String>,
     nba_api_key: Option<String>,
+    football_base_url: String,
+    nba_base_url: String,
+    /// Sleep between calls to stay inside the free-tier rate limits
+    pace_requests: bool,
+    /// Enabled leagues in fetch order (FETCH_ORDER / ENABLE_<LEAGUE>)
+    leagues: Vec<FetchLeague>,
 }
 
 impl DataFetcher {
     pub fn new() -> Self {
         Self {
-            client: Client::new(),
+            client: HttpConfig::from_env().build_client().unwrap_or_else(|e| {
+                tracing::warn!("HTTP client config rejected ({}), using defaults", e);
+                Client::new()
+            }),
             football_api_key: env::var("FOOTBALL_DATA_API_KEY").ok(),
             nba_api_key: env::var("BALLDONTLIE_API_KEY").ok(),
+            football_base_url: "https://api.football-data.org/v4".to_string(),
+            nba_base_url: "https://api.balldontlie.io/v1".to_string(),
+            pace_requests: true,
+            leagues: FetchLeague::enabled_from_env(),
+        }
+    }
+
+    async fn pause(&self, secs: u64) {
+        if self.pace_requests {
+            tokio::time::sleep(tokio::time::Duration::from_secs(secs)).await;
         }
     }
 
     pub fn has_football_key(&self) -> bool { self.football_api_key.is_some() }
     pub fn has_nba_key(&self)      -> bool { self.nba_api_key.is_some() }
 
+    /// Enabled leagues, in the order they are fetched.
+    pub fn leagues(&self) -> &[FetchLeague] { &self.leagues }
+
+    pub fn is_enabled(&self, league: FetchLeague) -> bool { self.leagues.contains(&league) }
+
+    /// Enabled and keyed, so the scheduler will call its API.
+    pub fn will_fetch(&self, league: FetchLeague) -> bool {
+        self.is_enabled(league) && match league {
+            FetchLeague::Epl => self.has_football_key(),
+            FetchLeague::Nba => self.has_nba_key(),
+        }
+    }
+
     // ── EPL ─────────────────────────────────────────────────────────────────
 
-    pub async fn fetch_epl_teams(&self, pool: &SqlitePool) -> Result<()> {
+    pub async fn fetch_epl_teams(&self, pool: &SqlitePool) -> Result<FetchSummary> {
         let api_key = self.football_api_key.as_ref()
             .ok_or_else(|| anyhow!("FOOTBALL_DATA_API_KEY not set"))?;
 
         tracing::info!("Fetching EPL teams from football-data.org…");
 
         let response = self.client
-            .get("https://api.football-data.org/v4/competitions/PL/teams")
+            .get(format!("{}/competitions/PL/teams", self.football_base_url))
             .header("X-Auth-Token", api_key)
             .send().await?;
 
@@ -132,9 +328,11 @@ impl DataFetcher {
         }
 
         let data: FootballDataTeams = response.json().await?;
+        let mut summary = FetchSummary::default();
         for t in data.teams {
-            insert_team(pool, &Team {
-                id:         format!("epl_{}", t.id),
+            let id = format!("epl_{}", t.id);
+            let result = insert_team(pool, &Team {
+                id:         id.clone(),
                 name:       t.name,
                 sport:      "football".to_string(),
                 league:     "EPL".to_string(),
@@ -142,22 +340,33 @@ impl DataFetcher {
                 elo_rating: 1200.0,
                 created_at: Utc::now(),
                 updated_at: Utc::now(),
-            }).await?;
+                elo_percentile: None,
+                elo_tier: None,
+            }).await;
+            record_team(&mut summary, &id, result);
         }
 
-        tracing::info!("EPL teams stored");
-        Ok(())
+        tracing::info!("Stored {} EPL teams", summary.teams_upserted);
+        Ok(summary)
     }
 
     /// Fetch all EPL matches for the current season (finished + scheduled).
-    pub async fn fetch_epl_matches(&self, pool: &SqlitePool) -> Result<()> {
+    pub async fn fetch_epl_matches(&self, pool: &SqlitePool) -> Result<FetchSummary> {
+        self.fetch_epl_matches_since(pool, None).await
+    }
+
+    /// EPL matches kicking off at or after `since` (None = the whole season).
+    /// football-data.org returns the season in a single response, so earlier matches are
+    /// skipped here rather than re-stored.
+    pub async fn fetch_epl_matches_since(&self, pool: &SqlitePool, since: Option<chrono::DateTime<Utc>>) -> Result<FetchSummary> {
         let api_key = self.football_api_key.as_ref()
             .ok_or_else(|| anyhow!("FOOTBALL_DATA_API_KEY not set"))?;
 
-        tracing::info!("Fetching EPL matches from football-data.org…");
+        let label = since.map_or("full season".to_string(), |d| format!("since {}", d.format("%Y-%m-%d")));
+        tracing::info!("Fetching EPL matches ({}) from football-data.org…", label);
 
         let response = self.client
-            .get("https://api.football-data.org/v4/competitions/PL/matches")
+            .get(format!("{}/competitions/PL/matches", self.football_base_url))
             .header("X-Auth-Token", api_key)
             .send().await?;
 
@@ -168,58 +377,106 @@ impl DataFetcher {
         }
 
         let data: FootballDataMatches = response.json().await?;
-        let mut stored = 0usize;
+        let mut summary = FetchSummary::default();
 
         for m in data.matches {
             let match_date = match DateTime::parse_from_rfc3339(&m.utc_date) {
                 Ok(d) => d.with_timezone(&Utc),
                 Err(e) => {
                     tracing::warn!("Bad date '{}': {}", m.utc_date, e);
+                    summary.errors.push(format!("epl_{}: bad date '{}'", m.id, m.utc_date));
                     continue;
                 }
             };
-
-            let status = match m.status.as_str() {
-                "FINISHED"            => "finished",
-                "IN_PLAY" | "PAUSED"  => "live",
-                _                     => "scheduled",   // SCHEDULED, TIMED, POSTPONED …
-            };
+            if since.is_some_and(|since| match_date < since) {
+                continue;
+            }
 
             // Only store matches with valid team IDs already in the DB
-            let match_obj = Match {
-                id:              format!("epl_{}", m.id),
-                home_team_id:    format!("epl_{}", m.home_team.id),
-                away_team_id:    format!("epl_{}", m.away_team.id),
-                home_team_name:  m.home_team.name,
-                away_team_name:  m.away_team.name,
-                sport:           "football".to_string(),
-                league:          "EPL".to_string(),
-                match_date,
-                status:          status.to_string(),
-                home_score:      m.score.full_time.as_ref().and_then(|s| s.home.map(|v| v as i32)),
-                away_score:      m.score.full_time.as_ref().and_then(|s| s.away.map(|v| v as i32)),
-                created_at:      Utc::now(),
-                updated_at:      Utc::now(),
-            };
+            let match_obj = m.into_match("epl", "EPL", match_date);
+
+            store_match(pool, &match_obj, &mut summary).await;
+        }
+
+        tracing::info!("Stored {} EPL matches ({} newly finished)", summary.matches_upserted, summary.newly_finished);
+        Ok(summary)
+    }
+
+    /// Fetch the official EPL table, store it, and compare it with our computed
+    /// team_stats. Returns one line per team whose numbers disagree (also logged);
+    /// teams we have no computed stats for yet are not compared.
+    pub async fn fetch_epl_standings(&self, pool: &SqlitePool) -> Result<Vec<String>> {
+        let api_key = self.football_api_key.as_ref()
+            .ok_or_else(|| anyhow!("FOOTBALL_DATA_API_KEY not set"))?;
+
+        tracing::info!("Fetching EPL standings from football-data.org…");
+
+        let response = self.client
+            .get(format!("{}/competitions/PL/standings", self.football_base_url))
+            .header("X-Auth-Token", api_key)
+            .send().await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!("EPL standings API error {}: {}", status, body));
+        }
 
-            insert_match(pool, &match_obj).await?;
-            stored += 1;
+        let data: FootballDataStandings = response.json().await?;
+        let total = data.standings.into_iter()
+            .find(|t| t.kind == "TOTAL")
+            .ok_or_else(|| anyhow!("EPL standings response has no TOTAL table"))?;
+
+        let fetched_at = Utc::now().to_rfc3339();
+        let mut discrepancies = Vec::new();
+        for e in total.table {
+            let official = OfficialStanding {
+                team_id:       format!("epl_{}", e.team.id),
+                league:        "EPL".to_string(),
+                position:      e.position,
+                played:        e.played_games,
+                wins:          e.won,
+                draws:         e.draw,
+                losses:        e.lost,
+                points:        e.points,
+                goals_for:     e.goals_for,
+                goals_against: e.goals_against,
+                fetched_at:    fetched_at.clone(),
+            };
+            upsert_official_standing(pool, &official).await?;
+
+            if let Some(ours) = get_team_current_stats(pool, &official.team_id).await? {
+                let computed = (ours.matches_played, ours.wins, ours.draws.unwrap_or(0), ours.losses,
+                                ours.goals_for.unwrap_or(0), ours.goals_against.unwrap_or(0));
+                let published = (official.played, official.wins, official.draws, official.losses,
+                                 official.goals_for, official.goals_against);
+                if computed != published {
+                    let line = format!(
+                        "{} ({}): official P{} W{} D{} L{} GF{} GA{}, computed P{} W{} D{} L{} GF{} GA{}",
+                        official.team_id, e.team.name,
+                        published.0, published.1, published.2, published.3, published.4, published.5,
+                        computed.0, computed.1, computed.2, computed.3, computed.4, computed.5,
+                    );
+                    tracing::warn!("Standings mismatch: {}", line);
+                    discrepancies.push(line);
+                }
+            }
         }
 
-        tracing::info!("Stored {} EPL matches", stored);
-        Ok(())
+        tracing::info!("Stored EPL standings ({} discrepancies with computed stats)", discrepancies.len());
+        Ok(discrepancies)
     }
 
     // ── NBA ──────────────────────────────────────────────────────────────────
 
-    pub async fn fetch_nba_teams(&self, pool: &SqlitePool) -> Result<()> {
+    pub async fn fetch_nba_teams(&self, pool: &SqlitePool) -> Result<FetchSummary> {
         let api_key = self.nba_api_key.as_ref()
             .ok_or_else(|| anyhow!("BALLDONTLIE_API_KEY not set"))?;
 
         tracing::info!("Fetching NBA teams from balldontlie.io…");
 
         let response = self.client
-            .get("https://api.balldontlie.io/v1/teams?per_page=100")
+            .get(format!("{}/teams?per_page=100", self.nba_base_url))
             .header("Authorization", api_key.as_str())
             .send().await?;
 
@@ -230,9 +487,11 @@ impl DataFetcher {
         }
 
         let data: NbaTeamsResponse = response.json().await?;
+        let mut summary = FetchSummary::default();
         for t in data.data {
-            insert_team(pool, &Team {
-                id:         format!("nba_{}", t.id),
+            let id = format!("nba_{}", t.id);
+            let result = insert_team(pool, &Team {
+                id:         id.clone(),
                 name:       t.full_name,
                 sport:      "basketball".to_string(),
                 league:     "NBA".to_string(),
@@ -240,25 +499,29 @@ impl DataFetcher {
                 elo_rating: 1200.0,
                 created_at: Utc::now(),
                 updated_at: Utc::now(),
-            }).await?;
+                elo_percentile: None,
+                elo_tier: None,
+            }).await;
+            record_team(&mut summary, &id, result);
         }
 
-        tracing::info!("NBA teams stored");
-        Ok(())
+        tracing::info!("Stored {} NBA teams", summary.teams_upserted);
+        Ok(summary)
     }
 
     /// Fetch all NBA games for the 2025-26 season, paginating through all results.
-    pub async fn fetch_nba_games(&self, pool: &SqlitePool) -> Result<()> {
+    pub async fn fetch_nba_games(&self, pool: &SqlitePool) -> Result<FetchSummary> {
         self.fetch_nba_games_since(pool, None).await
     }
 
     /// Fetch only NBA games from the last `days` days (for incremental background refreshes).
-    pub async fn fetch_recent_nba_games(&self, pool: &SqlitePool, days: i64) -> Result<()> {
+    pub async fn fetch_recent_nba_games(&self, pool: &SqlitePool, days: i64) -> Result<FetchSummary> {
         let since = chrono::Utc::now() - chrono::Duration::days(days);
         self.fetch_nba_games_since(pool, Some(since)).await
     }
 
-    async fn fetch_nba_games_since(&self, pool: &SqlitePool, since: Option<chrono::DateTime<Utc>>) -> Result<()> {
+    /// NBA games on or after `since`'s date (None = the whole season).
+    pub async fn fetch_nba_games_since(&self, pool: &SqlitePool, since: Option<chrono::DateTime<Utc>>) -> Result<FetchSummary> {
         let api_key = self.nba_api_key.as_ref()
             .ok_or_else(|| anyhow!("BALLDONTLIE_API_KEY not set"))?;
 
@@ -266,14 +529,12 @@ impl DataFetcher {
         tracing::info!("Fetching NBA 2025-26 games ({}) from balldontlie.io…", label);
 
         let mut cursor: Option<u64> = None;
-        let mut total = 0usize;
+        let mut summary = FetchSummary::default();
         let mut page = 0u32;
 
         loop {
             page += 1;
-            let mut url = format!(
-                "https://api.balldontlie.io/v1/games?seasons[]=2025&per_page=100"
-            );
+            let mut url = format!("{}/games?seasons[]=2025&per_page=100", self.nba_base_url);
             if let Some(d) = since {
                 url.push_str(&format!("&start_date={}", d.format("%Y-%m-%d")));
             }
@@ -315,19 +576,30 @@ impl DataFetcher {
             let batch_len = data.data.len();
 
             for g in data.data {
-                // balldontlie dates are "YYYY-MM-DD"
-                let date_str = format!("{}T00:00:00Z", g.date.trim_end_matches('Z').trim());
-                let match_date = match DateTime::parse_from_rfc3339(&date_str) {
-                    Ok(d) => d.with_timezone(&Utc),
-                    Err(_) => Utc::now(),
+                let match_date = nba_kickoff(&g).unwrap_or_else(Utc::now);
+
+                let upstream = g.status.to_lowercase();
+                // In-progress statuses look like "2nd Qtr", "Halftime" or "OT"
+                let in_play = !upstream.contains("final")
+                    && (upstream.contains("qtr") || upstream.contains("half") || upstream.contains("ot"));
+
+                let finished = !in_play
+                    && (g.home_team_score.is_some() && g.visitor_team_score.is_some()
+                        && g.home_team_score != Some(0) && g.visitor_team_score != Some(0)
+                        || upstream.contains("final"));
+
+                let status = if finished {
+                    "finished"
+                } else if in_play {
+                    "live"
+                } else if upstream.contains("postponed") {
+                    "postponed"
+                } else if upstream.contains("cancel") {
+                    "cancelled"
+                } else {
+                    "scheduled"
                 };
 
-                let finished = g.home_team_score.is_some() && g.visitor_team_score.is_some()
-                    && g.home_team_score != Some(0) && g.visitor_team_score != Some(0)
-                    || g.status.to_lowercase().contains("final");
-
-                let status = if finished { "finished" } else { "scheduled" };
-
                 let match_obj = Match {
                     id:             format!("nba_{}", g.id),
                     home_team_id:   format!("nba_{}", g.home_team.id),
@@ -338,14 +610,17 @@ impl DataFetcher {
                     league:         "NBA".to_string(),
                     match_date,
                     status:         status.to_string(),
-                    home_score:     if finished { g.home_team_score.map(|s| s as i32) } else { None },
-                    away_score:     if finished { g.visitor_team_score.map(|s| s as i32) } else { None },
+                    home_score:     if finished || in_play { g.home_team_score.map(|s| s as i32) } else { None },
+                    away_score:     if finished || in_play { g.visitor_team_score.map(|s| s as i32) } else { None },
+                    // balldontlie has no arena data
+                    venue:          None,
+                    city:           None,
+                    tiebreak_winner: None,
                     created_at:     Utc::now(),
                     updated_at:     Utc::now(),
                 };
 
-                insert_match(pool, &match_obj).await?;
-                total += 1;
+                store_match(pool, &match_obj, &mut summary).await;
             }
 
             // Advance cursor — stop when next_cursor is None or batch was empty
@@ -358,31 +633,403 @@ impl DataFetcher {
             tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
         }
 
-        tracing::info!("Stored {} NBA games across {} pages", total, page);
-        Ok(())
+        tracing::info!(
+            "Stored {} NBA games across {} pages ({} newly finished)",
+            summary.matches_upserted, page, summary.newly_finished
+        );
+        Ok(summary)
     }
 
     // ── Combined fetch ───────────────────────────────────────────────────────
 
-    pub async fn fetch_all_data(&self, pool: &SqlitePool) -> Result<()> {
-        if self.has_football_key() {
-            self.fetch_epl_teams(pool).await?;
-            // football-data.org free tier: 10 req/min — wait between calls
-            tokio::time::sleep(tokio::time::Duration::from_secs(6)).await;
-            self.fetch_epl_matches(pool).await?;
-            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-        } else {
-            tracing::warn!("FOOTBALL_DATA_API_KEY not set — skipping EPL");
+    /// EPL teams then matches.
+    pub async fn fetch_epl(&self, pool: &SqlitePool) -> Result<FetchSummary> {
+        self.fetch_epl_since(pool, None).await
+    }
+
+    async fn fetch_epl_since(&self, pool: &SqlitePool, since: Option<chrono::DateTime<Utc>>) -> Result<FetchSummary> {
+        let mut summary = self.fetch_epl_teams(pool).await?;
+        // football-data.org free tier: 10 req/min — wait between calls
+        self.pause(6).await;
+        summary.merge(self.fetch_epl_matches_since(pool, since).await?);
+        Ok(summary)
+    }
+
+    /// NBA teams then games.
+    pub async fn fetch_nba(&self, pool: &SqlitePool) -> Result<FetchSummary> {
+        self.fetch_nba_since(pool, None).await
+    }
+
+    async fn fetch_nba_since(&self, pool: &SqlitePool, since: Option<chrono::DateTime<Utc>>) -> Result<FetchSummary> {
+        let mut summary = self.fetch_nba_teams(pool).await?;
+        self.pause(1).await;
+        summary.merge(self.fetch_nba_games_since(pool, since).await?);
+        Ok(summary)
+    }
+
+    /// Fetch every enabled league with a key configured, in FETCH_ORDER. One sport failing
+    /// does not stop or hide the other: `sports` records success / skipped / failed for each.
+    pub async fn fetch_all_data(&self, pool: &SqlitePool) -> FetchSummary {
+        self.fetch_all_data_since(pool, None).await
+    }
+
+    /// `fetch_all_data` limited to matches from `since` on (None = whole seasons).
+    pub async fn fetch_all_data_since(&self, pool: &SqlitePool, since: Option<chrono::DateTime<Utc>>) -> FetchSummary {
+        let mut summary = FetchSummary::default();
+
+        for (i, &league) in self.leagues.iter().enumerate() {
+            if i > 0 {
+                self.pause(2).await;
+            }
+            match league {
+                FetchLeague::Epl if self.has_football_key() => {
+                    summary.record_sport("football", self.fetch_epl_since(pool, since).await);
+                }
+                FetchLeague::Epl => {
+                    tracing::warn!("FOOTBALL_DATA_API_KEY not set — skipping EPL");
+                    summary.skip_sport("football", "FOOTBALL_DATA_API_KEY not set");
+                }
+                FetchLeague::Nba if self.has_nba_key() => {
+                    summary.record_sport("basketball", self.fetch_nba_since(pool, since).await);
+                }
+                FetchLeague::Nba => {
+                    tracing::warn!("BALLDONTLIE_API_KEY not set — skipping NBA");
+                    summary.skip_sport("basketball", "BALLDONTLIE_API_KEY not set");
+                }
+            }
+        }
+
+        for league in FetchLeague::ALL.into_iter().filter(|&l| !self.is_enabled(l)) {
+            tracing::info!("{} disabled — skipping {:?}", league.enable_flag(), league);
+            summary.skip_sport(league.sport(), &format!("{}=false", league.enable_flag()));
         }
 
-        if self.has_nba_key() {
-            self.fetch_nba_teams(pool).await?;
-            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-            self.fetch_nba_games(pool).await?;
-        } else {
-            tracing::warn!("BALLDONTLIE_API_KEY not set — skipping NBA");
+        summary
+    }
+}
+
+fn record_team(summary: &mut FetchSummary, id: &str, result: Result<()>) {
+    match result {
+        Ok(()) => summary.teams_upserted += 1,
+        Err(e) => {
+            tracing::warn!("Team {} not stored: {}", id, e);
+            summary.errors.push(format!("{}: {}", id, e));
+        }
+    }
+}
+
+/// Upsert one match, counting it as newly finished when it was not finished before.
+/// A failed row is recorded in the summary rather than aborting the whole fetch.
+async fn store_match(pool: &SqlitePool, m: &Match, summary: &mut FetchSummary) {
+    let previous = get_match_status(pool, &m.id).await.ok().flatten();
+    match insert_match(pool, m).await {
+        Ok(()) => {
+            summary.matches_upserted += 1;
+            if m.status == "finished" && previous.as_deref() != Some("finished") {
+                summary.newly_finished += 1;
+            }
+        }
+        Err(e) => {
+            tracing::warn!("Match {} not stored: {}", m.id, e);
+            summary.errors.push(format!("{}: {}", m.id, e));
         }
+    }
+}
+
+/// Tip-off time for a balldontlie game.
+///
+/// `date` is the local game date, not a UTC one, so pinning it to midnight UTC
+/// puts an evening game on the previous US evening. Prefer the explicit UTC
+/// tip-off (`datetime`, or `status` which holds it for unplayed games); failing
+/// that, assume a 7pm local start in the home team's time zone. Standard-time
+/// offsets are used year-round, so the estimate can be an hour off under DST,
+/// which never moves a game across a local date boundary.
+fn nba_kickoff(g: &NbaGame) -> Option<DateTime<Utc>> {
+    let explicit = [g.datetime.as_deref(), Some(g.status.as_str())]
+        .into_iter()
+        .flatten()
+        .find_map(|s| DateTime::parse_from_rfc3339(s.trim()).ok());
+    if let Some(d) = explicit {
+        return Some(d.with_timezone(&Utc));
+    }
+
+    // balldontlie dates are "YYYY-MM-DD", sometimes with a midnight time attached
+    let day = chrono::NaiveDate::parse_from_str(g.date.get(..10)?, "%Y-%m-%d").ok()?;
+    let local_tipoff = day.and_hms_opt(19, 0, 0)?;
+    Some((local_tipoff - chrono::Duration::hours(nba_utc_offset_hours(&g.home_team.full_name))).and_utc())
+}
+
+/// Standard-time UTC offset of an NBA team's home arena (Eastern unless listed).
+fn nba_utc_offset_hours(team_name: &str) -> i64 {
+    const PACIFIC: [&str; 4] = ["Los Angeles", "Golden State", "Sacramento", "Portland"];
+    const MOUNTAIN: [&str; 3] = ["Denver", "Utah", "Phoenix"];
+    const CENTRAL: [&str; 8] = [
+        "Chicago", "Milwaukee", "Minnesota", "New Orleans", "Houston", "Dallas", "San Antonio", "Memphis",
+    ];
+    let in_zone = |cities: &[&str]| cities.iter().any(|c| team_name.starts_with(c));
+    if in_zone(&PACIFIC) {
+        -8
+    } else if in_zone(&MOUNTAIN) {
+        -7
+    } else if in_zone(&CENTRAL) || team_name.starts_with("Oklahoma City") {
+        -6
+    } else {
+        -5
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::init_database_with_pool;
+    use crate::models::FetchOutcome;
+    use axum::{routing::get, Json, Router};
+    use serde_json::json;
+
+    /// Serves canned football-data.org responses on a local port.
+    async fn mock_football_api() -> String {
+        let app = Router::new()
+            .route("/competitions/PL/teams", get(|| async {
+                Json(json!({ "teams": [
+                    { "id": 57, "name": "Arsenal FC", "crest": null },
+                    { "id": 61, "name": "Chelsea FC", "crest": null },
+                ]}))
+            }))
+            .route("/competitions/PL/standings", get(|| async {
+                let row = |pos: i32, id: u32, name: &str, (p, w, d, l, gf, ga): (i32, i32, i32, i32, i32, i32)| json!({
+                    "position": pos, "team": { "id": id, "name": name }, "playedGames": p,
+                    "won": w, "draw": d, "lost": l, "points": w * 3 + d, "goalsFor": gf, "goalsAgainst": ga,
+                });
+                Json(json!({ "standings": [
+                    { "type": "TOTAL", "table": [
+                        row(1, 57, "Arsenal FC", (2, 2, 0, 0, 5, 1)),
+                        row(2, 61, "Chelsea FC", (1, 0, 0, 1, 1, 2)),
+                    ]},
+                    { "type": "HOME", "table": [] },
+                ]}))
+            }))
+            .route("/competitions/PL/matches", get(|| async {
+                let team = |id: u32, name: &str| json!({ "id": id, "name": name });
+                Json(json!({ "matches": [
+                    { "id": 1, "utcDate": "2025-08-16T14:00:00Z", "status": "FINISHED",
+                      "homeTeam": team(57, "Arsenal FC"), "awayTeam": team(61, "Chelsea FC"),
+                      "score": { "fullTime": { "home": 2, "away": 1 } }, "venue": "Emirates Stadium" },
+                    { "id": 2, "utcDate": "2026-01-10T15:00:00Z", "status": "TIMED",
+                      "homeTeam": team(61, "Chelsea FC"), "awayTeam": team(57, "Arsenal FC"),
+                      "score": { "fullTime": null } },
+                    { "id": 3, "utcDate": "not a date", "status": "SCHEDULED",
+                      "homeTeam": team(57, "Arsenal FC"), "awayTeam": team(61, "Chelsea FC"),
+                      "score": { "fullTime": null } },
+                ]}))
+            }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    /// Unpaced fetcher with a football key, pointed at `football_url`. NBA calls go to the
+    /// same server, which serves no NBA routes.
+    fn test_fetcher(football_url: String, nba_key: Option<&str>, leagues: Vec<FetchLeague>) -> DataFetcher {
+        DataFetcher {
+            client: Client::new(),
+            football_api_key: Some("test".into()),
+            nba_api_key: nba_key.map(String::from),
+            football_base_url: football_url.clone(),
+            nba_base_url: football_url,
+            pace_requests: false,
+            leagues,
+        }
+    }
+
+    #[tokio::test]
+    async fn fetch_summary_counts_mocked_rows() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        init_database_with_pool(&pool).await.unwrap();
+        let fetcher = test_fetcher(mock_football_api().await, None, FetchLeague::ALL.to_vec());
+
+        let mut summary = fetcher.fetch_epl_teams(&pool).await.unwrap();
+        summary.merge(fetcher.fetch_epl_matches(&pool).await.unwrap());
+        assert_eq!(summary.teams_upserted, 2);
+        assert_eq!(summary.matches_upserted, 2);
+        assert_eq!(summary.newly_finished, 1);
+        // The unparseable date is reported, not silently dropped
+        assert_eq!(summary.errors.len(), 1);
+
+        // Re-fetching the same data upserts again but nothing is newly finished
+        let again = fetcher.fetch_epl_matches(&pool).await.unwrap();
+        assert_eq!(again.matches_upserted, 2);
+        assert_eq!(again.newly_finished, 0);
+
+        // A --since cutoff after the finished match only re-stores the later fixture
+        let since = DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        let recent = fetcher.fetch_epl_matches_since(&pool, Some(since)).await.unwrap();
+        assert_eq!(recent.matches_upserted, 1);
+    }
+
+    fn nba_game(date: &str, datetime: Option<&str>, status: &str, home: &str) -> NbaGame {
+        NbaGame {
+            id: 1,
+            date: date.into(),
+            datetime: datetime.map(String::from),
+            home_team: NbaTeam { id: 1, full_name: home.into() },
+            visitor_team: NbaTeam { id: 2, full_name: "Boston Celtics".into() },
+            home_team_score: None,
+            visitor_team_score: None,
+            status: status.into(),
+        }
+    }
+
+    #[test]
+    fn back_to_back_games_keep_local_date_order() {
+        // Night one in Los Angeles, night two in Boston: 7pm PST then 7pm EST
+        let first = nba_kickoff(&nba_game("2026-01-10", None, "Final", "Los Angeles Lakers")).unwrap();
+        let second = nba_kickoff(&nba_game("2026-01-11", None, "Final", "Boston Celtics")).unwrap();
+        assert!(first < second);
+        assert_eq!(first.to_rfc3339(), "2026-01-11T03:00:00+00:00");
+        assert_eq!(second.to_rfc3339(), "2026-01-12T00:00:00+00:00");
+        // 21 hours apart across time zones is still one night, i.e. a back-to-back
+        assert_eq!(crate::utils::nights_between(first, second), 1);
+
+        // An explicit tip-off wins over the heuristic, from either field
+        let scheduled = nba_game("2026-01-12", None, "2026-01-13T00:30:00Z", "Boston Celtics");
+        assert_eq!(nba_kickoff(&scheduled).unwrap().to_rfc3339(), "2026-01-13T00:30:00+00:00");
+        let dated = nba_game("2026-01-12", Some("2026-01-13T01:00:00.000Z"), "Final", "Boston Celtics");
+        assert_eq!(nba_kickoff(&dated).unwrap().to_rfc3339(), "2026-01-13T01:00:00+00:00");
+    }
+
+    #[tokio::test]
+    async fn official_standings_are_stored_and_mismatches_reported() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        init_database_with_pool(&pool).await.unwrap();
+        let fetcher = test_fetcher(mock_football_api().await, None, FetchLeague::ALL.to_vec());
+        fetcher.fetch_epl_teams(&pool).await.unwrap();
+        // Computed from the one stored result (Arsenal 2-1 Chelsea); the official
+        // table has a matchday for Arsenal that we never fetched
+        for (team, w, l, gf, ga) in [("epl_57", 1, 0, 2, 1), ("epl_61", 0, 1, 1, 2)] {
+            sqlx::query(
+                r#"INSERT INTO team_stats (id, team_id, season, matches_played, wins, draws, losses,
+                       goals_for, goals_against, form, updated_at)
+                   VALUES (?, ?, '2025-26', 1, ?, 0, ?, ?, ?, '', ?)"#,
+            )
+            .bind(format!("{}_stats", team)).bind(team).bind(w).bind(l).bind(gf).bind(ga)
+            .bind(Utc::now().to_rfc3339())
+            .execute(&pool).await.unwrap();
+        }
+
+        let discrepancies = fetcher.fetch_epl_standings(&pool).await.unwrap();
+
+        let stored = crate::db::get_official_standings(&pool, "EPL", crate::db::OFFICIAL_STANDINGS_MAX_AGE_HOURS).await.unwrap();
+        assert_eq!(stored.len(), 2);
+        assert_eq!((stored[0].team_id.as_str(), stored[0].played, stored[0].points), ("epl_57", 2, 6));
+        assert_eq!(discrepancies.len(), 1);
+        assert!(discrepancies[0].starts_with("epl_57"));
+
+        // Display prefers the published numbers
+        let table = crate::db::get_standings(&pool, "football", "EPL").await.unwrap();
+        let arsenal = table.iter().find(|r| r.team_id == "epl_57").unwrap();
+        assert_eq!((arsenal.played, arsenal.points, arsenal.score_diff), (2, 6, 4));
+    }
+
+    #[tokio::test]
+    async fn nba_failure_does_not_hide_epl_success() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        init_database_with_pool(&pool).await.unwrap();
+        // The mock serves no NBA routes, so every NBA call 404s
+        let fetcher = test_fetcher(mock_football_api().await, Some("test"), FetchLeague::ALL.to_vec());
+
+        let summary = fetcher.fetch_all_data(&pool).await;
+
+        assert_eq!(summary.teams_upserted, 2);
+        assert_eq!(summary.matches_upserted, 2);
+        let outcome = |sport: &str| summary.sports.iter().find(|s| s.sport == sport).unwrap();
+        assert_eq!(outcome("football").outcome, FetchOutcome::Success);
+        assert_eq!(outcome("basketball").outcome, FetchOutcome::Failed);
+        assert!(outcome("basketball").reason.as_deref().unwrap().contains("NBA teams API error"));
+        assert!(!summary.all_failed());
+    }
+
+    #[tokio::test]
+    async fn fetched_epl_match_stores_its_venue() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        init_database_with_pool(&pool).await.unwrap();
+        let fetcher = test_fetcher(mock_football_api().await, None, FetchLeague::ALL.to_vec());
+        fetcher.fetch_epl_teams(&pool).await.unwrap();
+        fetcher.fetch_epl_matches(&pool).await.unwrap();
+
+        let venue = |id: &str| {
+            let pool = pool.clone();
+            let id = id.to_string();
+            async move {
+                sqlx::query_scalar::<_, Option<String>>("SELECT venue FROM matches WHERE id = ?")
+                    .bind(id).fetch_one(&pool).await.unwrap()
+            }
+        };
+        assert_eq!(venue("epl_1").await.as_deref(), Some("Emirates Stadium"));
+        // Fixtures without a venue upstream stay NULL rather than an empty string
+        assert_eq!(venue("epl_2").await, None);
+    }
+
+    #[tokio::test]
+    async fn disabled_epl_is_not_fetched_even_with_a_key() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        init_database_with_pool(&pool).await.unwrap();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counted = calls.clone();
+        let app = Router::new().fallback(move || {
+            counted.fetch_add(1, Ordering::SeqCst);
+            async { Json(json!({ "teams": [], "matches": [] })) }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let fetcher = test_fetcher(format!("http://{}", addr), None, vec![FetchLeague::Nba]);
+        assert!(fetcher.has_football_key());
+        assert!(!fetcher.will_fetch(FetchLeague::Epl));
+
+        let summary = fetcher.fetch_all_data(&pool).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        let football = summary.sports.iter().find(|s| s.sport == "football").unwrap();
+        assert_eq!(football.outcome, FetchOutcome::Skipped);
+        assert_eq!(football.reason.as_deref(), Some("ENABLE_EPL=false"));
+    }
 
-        Ok(())
+    #[test]
+    fn knockout_won_on_penalties_keeps_regulation_score_but_credits_the_winner() {
+        let final_json = |stage: &str| json!({
+            "id": 9001, "utcDate": "2026-05-30T19:00:00Z", "status": "FINISHED", "stage": stage,
+            "homeTeam": { "id": 5, "name": "FC Bayern München" },
+            "awayTeam": { "id": 66, "name": "Manchester United FC" },
+            "score": {
+                "winner": "AWAY_TEAM", "duration": "PENALTY_SHOOTOUT",
+                "fullTime": { "home": 5, "away": 6 },
+                "regularTime": { "home": 1, "away": 1 },
+                "extraTime": { "home": 0, "away": 0 },
+                "penalties": { "home": 4, "away": 5 },
+            },
+        });
+        let date = Utc::now();
+        let m = serde_json::from_value::<FootballMatch>(final_json("FINAL")).unwrap().into_match("cl", "CL", date);
+
+        assert_eq!((m.home_score, m.away_score), (Some(1), Some(1)));
+        assert_eq!(m.tiebreak_winner.as_deref(), Some("away"));
+        assert_eq!(m.deciding_score(), Some((1, 2)));
+
+        let elo = crate::services::EloCalculator::new();
+        let mut ratings = std::collections::HashMap::from([
+            (m.home_team_id.clone(), 1500.0),
+            (m.away_team_id.clone(), 1500.0),
+        ]);
+        elo.replay(std::slice::from_ref(&m), &mut ratings, &Default::default());
+        assert!(ratings[&m.away_team_id] > 1500.0);
+        assert!(ratings[&m.home_team_id] < ratings[&m.away_team_id]);
+
+        // Outside a knockout round the same payload is just a draw at full time
+        let league = serde_json::from_value::<FootballMatch>(final_json("LEAGUE_STAGE")).unwrap().into_match("cl", "CL", date);
+        assert_eq!(league.tiebreak_winner, None);
     }
 }
@@ -1,21 +1,77 @@
 use anyhow::Result;
 use sqlx::SqlitePool;
-use chrono::Utc;
+use std::collections::HashMap;
+
+use crate::db::{get_home_advantage, get_team_by_id};
+use crate::models::{EloHistoryPoint, Match, ProjectedResult};
+
+/// How the margin of victory scales the K-factor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarginMultiplier {
+    /// FiveThirtyEight: ln(|margin| + 1) · 2.2 / (winner_elo_diff · 0.001 + 2.2).
+    /// The second term damps blowouts by favorites (autocorrelation correction).
+    Autocorrelated,
+    /// Original behavior: football 1 / 1.5 / (11+gd)/8 steps, basketball `NbaPredictor::mov_multiplier`
+    Legacy,
+}
 
-use crate::db::{get_team_by_id, insert_team};
-use crate::models::{Team, Match};
+impl MarginMultiplier {
+    /// Reads `key` ("legacy" or "538"), falling back to Autocorrelated.
+    fn from_env(key: &str) -> Self {
+        match std::env::var(key).ok().as_deref().map(str::trim) {
+            Some(v) if v.eq_ignore_ascii_case("legacy") => Self::Legacy,
+            _ => Self::Autocorrelated,
+        }
+    }
+}
 
 pub struct EloCalculator {
     k_factor: f64,
+    football_margin: MarginMultiplier,
+    basketball_margin: MarginMultiplier,
+}
+
+/// Standard K-factor, used when ELO_K_FACTOR is unset or invalid.
+pub const DEFAULT_K_FACTOR: f64 = 32.0;
+
+/// Home bonus in ELO points for a league, before any per-team multiplier. NBA home-court
+/// edge is smaller than football's: +65 gives equal teams ~59%, close to the observed rate.
+pub fn home_advantage_for(league: &str) -> f64 {
+    match league {
+        "EPL" | "Champions League" => 100.0,
+        "NBA" => 65.0,
+        _ => 100.0,
+    }
+}
+
+/// ELO_K_FACTOR when it parses as a positive number, else `DEFAULT_K_FACTOR`.
+pub fn k_factor_from_env() -> f64 {
+    std::env::var("ELO_K_FACTOR").ok()
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .filter(|k| k.is_finite() && *k > 0.0)
+        .unwrap_or(DEFAULT_K_FACTOR)
 }
 
 impl EloCalculator {
+    /// K-factor from ELO_K_FACTOR (see `k_factor_from_env`).
     pub fn new() -> Self {
+        Self::with_k_factor(k_factor_from_env())
+    }
+
+    /// Explicit K-factor, ignoring ELO_K_FACTOR. Margin modes still come from the environment.
+    pub fn with_k_factor(k_factor: f64) -> Self {
         Self {
-            k_factor: 32.0, // Standard K-factor, can be adjusted
+            k_factor,
+            football_margin: MarginMultiplier::from_env("ELO_MARGIN_FOOTBALL"),
+            basketball_margin: MarginMultiplier::from_env("ELO_MARGIN_BASKETBALL"),
         }
     }
 
+    /// K-factor applied to football updates (basketball uses a fixed 20).
+    pub fn k_factor(&self) -> f64 {
+        self.k_factor
+    }
+
     /// Calculate expected score based on ELO ratings
     pub fn expected_score(rating_a: f64, rating_b: f64) -> f64 {
         1.0 / (1.0 + 10f64.powf((rating_b - rating_a) / 400.0))
@@ -24,8 +80,11 @@ impl EloCalculator {
     /// Update ELO ratings after a match.
     ///
     /// Sport-specific tuning:
-    /// - Football: K=32, +100 HCA, soccer goal-difference multiplier
-    /// - Basketball: K=20, +75 HCA, logarithmic point-margin multiplier
+    /// - Football: K=ELO_K_FACTOR (default 32)
+    /// - Basketball: K=20
+    ///
+    /// The home bonus comes from `home_advantage_for` the league. Both scale K by the
+    /// margin multiplier configured for the sport.
     pub fn update_ratings(&self,
         home_rating: f64,
         away_rating: f64,
@@ -33,26 +92,24 @@ impl EloCalculator {
         away_score: i32,
         is_neutral_venue: bool,
     ) -> (f64, f64) {
-        self.update_ratings_for_sport(home_rating, away_rating, home_score, away_score, is_neutral_venue, "football")
+        let home_bonus = if is_neutral_venue { 0.0 } else { home_advantage_for("EPL") };
+        self.update_ratings_for_sport(home_rating, away_rating, home_score, away_score, home_bonus, "football")
     }
 
-    /// Sport-aware ELO update used internally and by the NBA path.
+    /// Sport-aware ELO update used internally and by the NBA path. `home_bonus` is the
+    /// home side's edge in ELO points: `home_advantage_for` its league scaled by any
+    /// per-team multiplier, or 0 at a neutral venue.
     pub fn update_ratings_for_sport(
         &self,
         home_rating: f64,
         away_rating: f64,
         home_score: i32,
         away_score: i32,
-        is_neutral_venue: bool,
+        home_bonus: f64,
         sport: &str,
     ) -> (f64, f64) {
-        let (k, hca) = if sport == "basketball" {
-            (20.0_f64, if is_neutral_venue { 0.0 } else { 75.0 })
-        } else {
-            (self.k_factor, if is_neutral_venue { 0.0 } else { 100.0 })
-        };
-
-        let adjusted_home = home_rating + hca;
+        let k = if sport == "basketball" { 20.0_f64 } else { self.k_factor };
+        let adjusted_home = home_rating + home_bonus;
         let expected_home = Self::expected_score(adjusted_home, away_rating);
         let expected_away = 1.0 - expected_home;
 
@@ -63,25 +120,90 @@ impl EloCalculator {
         };
         let actual_away = 1.0 - actual_home;
 
-        let margin = (home_score - away_score).abs();
-        let mov_mult = if sport == "basketball" {
-            // Logarithmic: captures meaningful margins without over-rewarding blowouts
-            crate::services::NbaPredictor::mov_multiplier(margin)
-        } else {
-            // Soccer goal-difference multiplier (original formula)
-            let gd = margin as f64;
-            if gd <= 1.0 { 1.0 } else if gd == 2.0 { 1.5 } else { (11.0 + gd) / 8.0 }
+        let winner_elo_diff = match home_score.cmp(&away_score) {
+            std::cmp::Ordering::Greater => adjusted_home - away_rating,
+            std::cmp::Ordering::Less    => away_rating - adjusted_home,
+            std::cmp::Ordering::Equal   => 0.0,
         };
+        let mov_mult = self.margin_multiplier(sport, (home_score - away_score).abs(), winner_elo_diff);
 
         let new_home = home_rating + k * mov_mult * (actual_home - expected_home);
         let new_away = away_rating + k * mov_mult * (actual_away - expected_away);
         (new_home, new_away)
     }
 
-    /// Calculate win probability based on ELO ratings
-    pub fn win_probability(&self, home_rating: f64, away_rating: f64, sport: &str) -> (f64, f64, Option<f64>) {
-        let home_advantage = 100.0; // Home advantage bonus
-        let adjusted_home_rating = home_rating + home_advantage;
+    /// Rating after each of `results` (opponent rating, result) in turn, starting from
+    /// `start_rating`. Nothing is stored: each game is a one-goal (or one-point) result at a
+    /// neutral venue, and opponents keep their given rating throughout.
+    pub fn project_path(&self, start_rating: f64, results: &[(f64, ProjectedResult)], sport: &str) -> Vec<f64> {
+        results
+            .iter()
+            .scan(start_rating, |rating, &(opponent, result)| {
+                let (own, other) = match result {
+                    ProjectedResult::Win => (1, 0),
+                    ProjectedResult::Draw => (0, 0),
+                    ProjectedResult::Loss => (0, 1),
+                };
+                *rating = self.update_ratings_for_sport(*rating, opponent, own, other, 0.0, sport).0;
+                Some(*rating)
+            })
+            .collect()
+    }
+
+    /// Final rating of `project_path`; `start_rating` when there are no results.
+    pub fn project_rating(&self, start_rating: f64, results: &[(f64, ProjectedResult)], sport: &str) -> f64 {
+        self.project_path(start_rating, results, sport).last().copied().unwrap_or(start_rating)
+    }
+
+    /// K-factor scaling for a result decided by `margin`, where `winner_elo_diff` is the
+    /// winner's (HCA-adjusted) rating minus the loser's. Draws count as a margin of one
+    /// so they still move ratings.
+    pub fn margin_multiplier(&self, sport: &str, margin: i32, winner_elo_diff: f64) -> f64 {
+        let mode = if sport == "basketball" { self.basketball_margin } else { self.football_margin };
+        match mode {
+            MarginMultiplier::Autocorrelated => {
+                let m = margin.unsigned_abs().max(1) as f64;
+                m.ln_1p() * (2.2 / (winner_elo_diff * 0.001 + 2.2))
+            }
+            MarginMultiplier::Legacy if sport == "basketball" => {
+                crate::services::NbaPredictor::mov_multiplier(margin)
+            }
+            MarginMultiplier::Legacy => {
+                match margin.unsigned_abs() {
+                    0 | 1 => 1.0,
+                    2 => 1.5,
+                    gd => (11.0 + gd as f64) / 8.0,
+                }
+            }
+        }
+    }
+
+    /// Calculate win probability based on ELO ratings, with the league's home bonus
+    pub fn win_probability(&self, home_rating: f64, away_rating: f64, sport: &str, league: &str) -> (f64, f64, Option<f64>) {
+        self.win_probability_with_hca(home_rating, away_rating, sport, league, 1.0)
+    }
+
+    /// Win probability with the league's home bonus scaled by the home team's override multiplier.
+    pub fn win_probability_with_hca(
+        &self,
+        home_rating: f64,
+        away_rating: f64,
+        sport: &str,
+        league: &str,
+        hca_multiplier: f64,
+    ) -> (f64, f64, Option<f64>) {
+        self.win_probability_with_bonus(home_rating, away_rating, sport, home_advantage_for(league) * hca_multiplier)
+    }
+
+    /// Win probability with `home_bonus` ELO points added to the home side's rating.
+    fn win_probability_with_bonus(
+        &self,
+        home_rating: f64,
+        away_rating: f64,
+        sport: &str,
+        home_bonus: f64,
+    ) -> (f64, f64, Option<f64>) {
+        let adjusted_home_rating = home_rating + home_bonus;
         
         let home_expected = Self::expected_score(adjusted_home_rating, away_rating);
         
@@ -107,63 +229,61 @@ impl EloCalculator {
         }
     }
 
-    /// Update team ELO ratings in database after match results
-    pub async fn update_team_ratings(&self, pool: &SqlitePool, match_data: &Match) -> Result<()> {
-        if match_data.status != "finished" || match_data.home_score.is_none() || match_data.away_score.is_none() {
-            return Ok(()); // Skip if match not finished or scores not available
-        }
-
-        let home_score = match_data.home_score.unwrap();
-        let away_score = match_data.away_score.unwrap();
-
-        // Get current team ratings
-        let home_team = get_team_by_id(pool, &match_data.home_team_id).await?
-            .ok_or_else(|| anyhow::anyhow!("Home team not found"))?;
-        let away_team = get_team_by_id(pool, &match_data.away_team_id).await?
-            .ok_or_else(|| anyhow::anyhow!("Away team not found"))?;
-
-        // Calculate new ratings (sport-aware: NBA uses K=20 and +75 HCA)
-        let (new_home_rating, new_away_rating) = self.update_ratings_for_sport(
-            home_team.elo_rating,
-            away_team.elo_rating,
-            home_score,
-            away_score,
-            false,
-            &match_data.sport,
-        );
-
-        let home_team_name = home_team.name.clone();
-        let away_team_name = away_team.name.clone();
-        let old_home_rating = home_team.elo_rating;
-        let old_away_rating = away_team.elo_rating;
-
-        // Update home team
-        let updated_home_team = Team {
-            elo_rating: new_home_rating,
-            updated_at: Utc::now(),
-            ..home_team
-        };
-        insert_team(pool, &updated_home_team).await?;
-
-        // Update away team
-        let updated_away_team = Team {
-            elo_rating: new_away_rating,
-            updated_at: Utc::now(),
-            ..away_team
-        };
-        insert_team(pool, &updated_away_team).await?;
-
-        tracing::info!(
-            "Updated ELO ratings: {} ({:.1} -> {:.1}), {} ({:.1} -> {:.1})",
-            home_team_name,
-            old_home_rating,
-            new_home_rating,
-            away_team_name,
-            old_away_rating,
-            new_away_rating
-        );
+    /// Win probability with each side's rating shifted by its recent form (see
+    /// `team_strength`) before the expected score is taken. `None` form leaves the
+    /// rating as is, so passing `None` for both matches `win_probability_with_hca`.
+    /// `home_bonus` is in ELO points, as for `update_ratings_for_sport`.
+    pub fn win_probability_with_form(
+        &self,
+        home_rating: f64,
+        away_rating: f64,
+        home_form: Option<&str>,
+        away_form: Option<&str>,
+        sport: &str,
+        home_bonus: f64,
+    ) -> (f64, f64, Option<f64>) {
+        self.win_probability_with_bonus(
+            self.team_strength(home_rating, home_form),
+            self.team_strength(away_rating, away_form),
+            sport,
+            home_bonus,
+        )
+    }
 
-        Ok(())
+    /// Replay finished matches in order against in-memory `ratings`, applying each result
+    /// with `update_ratings_for_sport`, returning both teams' rating after each match.
+    /// Matches naming a team missing from `ratings` are skipped; `home_advantage` falls
+    /// back to 1.0 for teams without a multiplier.
+    pub fn replay(
+        &self,
+        matches: &[Match],
+        ratings: &mut HashMap<String, f64>,
+        home_advantage: &HashMap<String, f64>,
+    ) -> Vec<EloHistoryPoint> {
+        let mut history = Vec::with_capacity(matches.len() * 2);
+        for m in matches {
+            let (Some(&home), Some(&away)) = (ratings.get(&m.home_team_id), ratings.get(&m.away_team_id)) else {
+                continue;
+            };
+            let (home, away) = match (m.status.as_str(), m.deciding_score()) {
+                ("finished", Some((hs, aws))) => {
+                    let hca = home_advantage.get(&m.home_team_id).copied().unwrap_or(1.0);
+                    self.update_ratings_for_sport(home, away, hs, aws, home_advantage_for(&m.league) * hca, &m.sport)
+                }
+                _ => (home, away),
+            };
+            ratings.insert(m.home_team_id.clone(), home);
+            ratings.insert(m.away_team_id.clone(), away);
+            for (team_id, elo_rating) in [(&m.home_team_id, home), (&m.away_team_id, away)] {
+                history.push(EloHistoryPoint {
+                    team_id: team_id.clone(),
+                    date: m.match_date,
+                    elo_rating,
+                    match_id: Some(m.id.clone()),
+                });
+            }
+        }
+        history
     }
 
     /// Calculate ELO-based predictions for upcoming matches
@@ -179,10 +299,12 @@ impl EloCalculator {
             let away_team = get_team_by_id(pool, &match_data.away_team_id).await?;
 
             if let (Some(home_team), Some(away_team)) = (home_team, away_team) {
-                let (home_win_prob, away_win_prob, draw_prob) = self.win_probability(
+                let (home_win_prob, away_win_prob, draw_prob) = self.win_probability_with_hca(
                     home_team.elo_rating,
                     away_team.elo_rating,
                     &match_data.sport,
+                    &match_data.league,
+                    get_home_advantage(pool, &home_team.id).await?,
                 );
 
                 predictions.push((
@@ -236,12 +358,13 @@ impl EloCalculator {
         strength
     }
 
-    /// Calculate adjustment based on recent form (e.g., "WLWDW")
+    /// Calculate adjustment based on recent form (e.g., "WLWDW", newest result first
+    /// as stored in team_stats.form)
     fn calculate_form_adjustment(&self, form: &str) -> f64 {
         let mut adjustment: f64 = 0.0;
         let mut weight = 1.0;
 
-        for result in form.chars().rev() { // Most recent first
+        for result in form.chars() {
             match result {
                 'W' => adjustment += 20.0 * weight,
                 'D' => adjustment += 10.0 * weight,
@@ -253,4 +376,156 @@ impl EloCalculator {
 
         adjustment.clamp(-100.0, 100.0) // Cap the adjustment
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn calculator(margin: MarginMultiplier) -> EloCalculator {
+        EloCalculator {
+            k_factor: 32.0,
+            football_margin: margin,
+            basketball_margin: margin,
+        }
+    }
+
+    #[test]
+    fn favorite_blowout_scores_lower_multiplier_than_underdog_blowout() {
+        let calc = calculator(MarginMultiplier::Autocorrelated);
+        let favorite = calc.margin_multiplier("football", 4, 300.0);
+        let underdog = calc.margin_multiplier("football", 4, -300.0);
+        assert!(favorite < underdog);
+    }
+
+    #[test]
+    fn favorite_blowout_adds_fewer_points_than_underdog_blowout() {
+        let calc = calculator(MarginMultiplier::Autocorrelated);
+        // Neutral venue, 1600 vs 1400, same 4-0 scoreline either way
+        let (fav_after, _) = calc.update_ratings_for_sport(1600.0, 1400.0, 4, 0, 0.0, "football");
+        let (dog_after, _) = calc.update_ratings_for_sport(1400.0, 1600.0, 4, 0, 0.0, "football");
+        assert!(fav_after - 1600.0 < dog_after - 1400.0);
+        // Beyond the expected-score effect: gain per unit of surprise is also smaller
+        let expected_fav = EloCalculator::expected_score(1600.0, 1400.0);
+        let fav_rate = (fav_after - 1600.0) / (1.0 - expected_fav);
+        let dog_rate = (dog_after - 1400.0) / expected_fav;
+        assert!(fav_rate < dog_rate);
+    }
+
+    #[test]
+    fn draws_still_move_ratings() {
+        let calc = calculator(MarginMultiplier::Autocorrelated);
+        let (home, away) = calc.update_ratings_for_sport(1500.0, 1500.0, 1, 1, 0.0, "football");
+        assert!(home == 1500.0 && away == 1500.0);
+        let (home, away) = calc.update_ratings_for_sport(1600.0, 1400.0, 1, 1, 0.0, "football");
+        assert!(home < 1600.0 && away > 1400.0);
+    }
+
+    #[test]
+    fn legacy_flag_keeps_step_multiplier() {
+        let calc = calculator(MarginMultiplier::Legacy);
+        assert_eq!(calc.margin_multiplier("football", 1, 0.0), 1.0);
+        assert_eq!(calc.margin_multiplier("football", 2, 0.0), 1.5);
+        assert_eq!(calc.margin_multiplier("football", 5, 0.0), 2.0);
+        assert_eq!(
+            calc.margin_multiplier("basketball", 12, 0.0),
+            crate::services::NbaPredictor::mov_multiplier(12)
+        );
+    }
+
+    #[test]
+    fn winning_form_raises_win_probability() {
+        let calc = calculator(MarginMultiplier::Autocorrelated);
+        let (plain, _, _) = calc.win_probability_with_form(1500.0, 1500.0, None, None, "football", home_advantage_for("EPL"));
+        let (hot, _, _) = calc.win_probability_with_form(1500.0, 1500.0, Some("WWWWW"), None, "football", home_advantage_for("EPL"));
+        let (cold, _, _) = calc.win_probability_with_form(1500.0, 1500.0, None, Some("WWWWW"), "football", home_advantage_for("EPL"));
+        assert!(hot > plain);
+        assert!(cold < plain);
+        assert_eq!(plain, calc.win_probability(1500.0, 1500.0, "football", "EPL").0);
+    }
+
+    #[test]
+    fn three_wins_against_equal_opponents_compound() {
+        let calc = calculator(MarginMultiplier::Autocorrelated);
+        let wins = [(1500.0, ProjectedResult::Win); 3];
+        let path = calc.project_path(1500.0, &wins, "football");
+
+        // Each step: K · ln(2) · 2.2 / (lead · 0.001 + 2.2) · (1 − expected), lead over a 1500 side
+        let mut expected = 1500.0;
+        for after in &path {
+            let lead = expected - 1500.0;
+            expected += 32.0 * 2f64.ln() * 2.2 / (lead * 0.001 + 2.2) * (1.0 - EloCalculator::expected_score(expected, 1500.0));
+            assert!((after - expected).abs() < 1e-9);
+        }
+        // The first win is worth 16 · ln 2; later ones slightly less as the lead grows
+        assert!((path[0] - 1500.0 - 16.0 * 2f64.ln()).abs() < 1e-9);
+        assert!((calc.project_rating(1500.0, &wins, "football") - 1532.0).abs() < 1.0);
+        assert!(path[2] - path[1] < path[0] - 1500.0);
+        assert_eq!(calc.project_rating(1500.0, &[], "football"), 1500.0);
+    }
+
+    #[test]
+    fn replay_chains_updates_in_memory_and_skips_unknown_teams() {
+        let calc = calculator(MarginMultiplier::Autocorrelated);
+        let game = |id: &str, home: &str, away: &str, hs, aws| Match {
+            id: id.into(),
+            home_team_id: home.into(),
+            away_team_id: away.into(),
+            home_team_name: home.into(),
+            away_team_name: away.into(),
+            sport: "football".into(),
+            league: "EPL".into(),
+            match_date: Utc::now(),
+            status: "finished".into(),
+            home_score: Some(hs),
+            away_score: Some(aws),
+            venue: None,
+            city: None,
+            tiebreak_winner: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let matches = [game("m1", "a", "b", 2, 0), game("m2", "b", "ghost", 1, 0), game("m3", "b", "a", 1, 1)];
+        let mut ratings = HashMap::from([("a".to_string(), 1200.0), ("b".to_string(), 1200.0)]);
+        let home_advantage = HashMap::from([("b".to_string(), 1.5)]);
+
+        let history = calc.replay(&matches, &mut ratings, &home_advantage);
+
+        let (a1, b1) = calc.update_ratings_for_sport(1200.0, 1200.0, 2, 0, 100.0, "football");
+        let (b2, a2) = calc.update_ratings_for_sport(b1, a1, 1, 1, 150.0, "football");
+        assert_eq!(ratings["a"], a2);
+        assert_eq!(ratings["b"], b2);
+        assert_eq!(history.len(), 4);
+        assert!(history.iter().all(|p| p.match_id.as_deref() != Some("m2")));
+        assert_eq!(history[3].elo_rating, a2);
+    }
+
+    #[test]
+    fn explicit_k_factor_scales_the_rating_change() {
+        let (low, _) = EloCalculator::with_k_factor(16.0).update_ratings_for_sport(1500.0, 1500.0, 1, 0, 0.0, "football");
+        let (high, _) = EloCalculator::with_k_factor(48.0).update_ratings_for_sport(1500.0, 1500.0, 1, 0, 0.0, "football");
+        assert!(((high - 1500.0) - 3.0 * (low - 1500.0)).abs() < 1e-9);
+        assert_eq!(EloCalculator::with_k_factor(16.0).k_factor(), 16.0);
+    }
+
+    #[test]
+    fn league_home_bonus_changes_expectations_for_equal_ratings() {
+        let calc = calculator(MarginMultiplier::Autocorrelated);
+        assert_eq!(home_advantage_for("EPL"), 100.0);
+        assert_eq!(home_advantage_for("Champions League"), 100.0);
+        assert_eq!(home_advantage_for("NBA"), 65.0);
+        assert_eq!(home_advantage_for("Serie A"), 100.0);
+
+        let (nba_home, nba_away, _) = calc.win_probability(1500.0, 1500.0, "basketball", "NBA");
+        let (epl_home, _, _) = calc.win_probability(1500.0, 1500.0, "basketball", "EPL");
+        assert!(nba_home > nba_away);
+        assert!(nba_home < epl_home);
+        assert!((nba_home - EloCalculator::expected_score(1565.0, 1500.0)).abs() < 1e-9);
+
+        // The smaller NBA bonus means a home win was less expected, so it earns more
+        let (nba_winner, _) = calc.update_ratings_for_sport(1500.0, 1500.0, 100, 90, home_advantage_for("NBA"), "basketball");
+        let (big_bonus_winner, _) = calc.update_ratings_for_sport(1500.0, 1500.0, 100, 90, home_advantage_for("EPL"), "basketball");
+        assert!(nba_winner > big_bonus_winner);
+    }
+}
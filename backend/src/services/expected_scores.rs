@@ -0,0 +1,61 @@
+use anyhow::Result;
+use sqlx::SqlitePool;
+
+use crate::db::{get_matches_for_team, MatchFilter};
+use crate::models::Match;
+
+/// Team scoring rates are shrunk toward the league average by this many average games.
+const PRIOR_GAMES: f64 = 5.0;
+
+/// Expected home and away scores: the league's home/away scoring averages scaled by the
+/// home side's attack and the away side's defence (and vice versa). Team rates come from
+/// finished games before the match and are shrunk toward the league average by
+/// PRIOR_GAMES phantom average games so short histories stay sane.
+///
+/// Shared by the predictor's Poisson component, /simulate and /totals so they all price
+/// the same scoreline.
+pub async fn expected_scores(pool: &SqlitePool, m: &Match) -> Result<(f64, f64)> {
+    let (default_home, default_away) = if m.sport == "football" { (1.5, 1.2) } else { (114.0, 111.0) };
+
+    let (avg_home, avg_away): (Option<f64>, Option<f64>) = sqlx::query_as(
+        r#"SELECT AVG(home_score), AVG(away_score) FROM matches
+           WHERE status = 'finished' AND home_score IS NOT NULL AND away_score IS NOT NULL
+             AND sport = ? AND league = ?"#,
+    )
+    .bind(&m.sport)
+    .bind(&m.league)
+    .fetch_one(pool)
+    .await?;
+    let avg_home = avg_home.unwrap_or(default_home);
+    let avg_away = avg_away.unwrap_or(default_away);
+    let league_avg = (avg_home + avg_away) / 2.0;
+
+    // (scored, conceded) per game, shrunk toward the league average
+    let rates = |games: Vec<Match>, team_id: &str| {
+        let (mut scored, mut conceded) = (0.0, 0.0);
+        for g in &games {
+            let (Some(hs), Some(aws)) = (g.home_score, g.away_score) else { continue };
+            let (s, c) = if g.home_team_id == team_id { (hs, aws) } else { (aws, hs) };
+            scored += s as f64;
+            conceded += c as f64;
+        }
+        let n = games.len() as f64 + PRIOR_GAMES;
+        ((scored + PRIOR_GAMES * league_avg) / n, (conceded + PRIOR_GAMES * league_avg) / n)
+    };
+    let history = MatchFilter {
+        status: Some("finished"),
+        scored_only: true,
+        before: Some(m.match_date),
+        sport: Some(&m.sport),
+        ..Default::default()
+    };
+    let home_games = get_matches_for_team(pool, &m.home_team_id, &history).await?;
+    let away_games = get_matches_for_team(pool, &m.away_team_id, &history).await?;
+    let (home_attack, home_defence) = rates(home_games, &m.home_team_id);
+    let (away_attack, away_defence) = rates(away_games, &m.away_team_id);
+
+    Ok((
+        avg_home * (home_attack / league_avg) * (away_defence / league_avg),
+        avg_away * (away_attack / league_avg) * (home_defence / league_avg),
+    ))
+}
@@ -0,0 +1,91 @@
+//! Shared reqwest client settings for every upstream API.
+//!
+//! Defaults can be overridden with HTTP_TIMEOUT_SECS, HTTP_CONNECT_TIMEOUT_SECS
+//! and HTTP_USER_AGENT.
+
+use reqwest::{Client, ClientBuilder};
+use std::env;
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    /// Whole-request timeout, including reading the body
+    pub timeout: Duration,
+    pub connect_timeout: Duration,
+    pub user_agent: String,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            connect_timeout: Duration::from_secs(10),
+            user_agent: format!("OddsForge/{}", env!("CARGO_PKG_VERSION")),
+        }
+    }
+}
+
+impl HttpConfig {
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        let secs = |key: &str| {
+            env::var(key).ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|s| *s > 0.0)
+                .map(Duration::from_secs_f64)
+        };
+        Self {
+            timeout: secs("HTTP_TIMEOUT_SECS").unwrap_or(default.timeout),
+            connect_timeout: secs("HTTP_CONNECT_TIMEOUT_SECS").unwrap_or(default.connect_timeout),
+            user_agent: env::var("HTTP_USER_AGENT").unwrap_or(default.user_agent),
+        }
+    }
+
+    /// Builder pre-loaded with timeouts and User-Agent; callers may layer extra headers on top.
+    pub fn client_builder(&self) -> ClientBuilder {
+        Client::builder()
+            .timeout(self.timeout)
+            .connect_timeout(self.connect_timeout)
+            .user_agent(self.user_agent.clone())
+    }
+
+    pub fn build_client(&self) -> reqwest::Result<Client> {
+        self.client_builder().build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::HeaderMap, routing::get, Router};
+
+    #[tokio::test]
+    async fn slow_upstream_times_out_instead_of_hanging() {
+        let app = Router::new()
+            .route("/slow", get(|| async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                "too late"
+            }))
+            .route("/ua", get(|headers: HeaderMap| async move {
+                headers.get("user-agent").and_then(|v| v.to_str().ok()).unwrap_or_default().to_string()
+            }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let config = HttpConfig {
+            timeout: Duration::from_millis(200),
+            user_agent: "OddsForge-test".into(),
+            ..HttpConfig::default()
+        };
+        let client = config.build_client().unwrap();
+
+        let started = std::time::Instant::now();
+        let err = client.get(format!("http://{}/slow", addr)).send().await.unwrap_err();
+        assert!(err.is_timeout());
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        let ua = client.get(format!("http://{}/ua", addr)).send().await.unwrap().text().await.unwrap();
+        assert_eq!(ua, "OddsForge-test");
+    }
+}
@@ -1,5 +1,8 @@
 pub mod data_fetcher;
 pub mod elo_calculator;
+pub mod expected_scores;
+pub mod http_client;
+pub mod model_registry;
 pub mod nba_players_fetcher;
 pub mod nba_predictor;
 pub mod nba_stats_fetcher;
@@ -8,6 +11,8 @@ pub mod predictor;
 
 pub use data_fetcher::*;
 pub use elo_calculator::*;
+pub use expected_scores::expected_scores;
+pub use http_client::HttpConfig;
 pub use nba_players_fetcher::NbaPlayersFetcher;
 pub use nba_predictor::{NbaPredictor, bayesian_shrinkage, four_factors_score, sigmoid};
 pub use nba_stats_fetcher::NbaStatsFetcher;
@@ -0,0 +1,156 @@
+use anyhow::Result;
+use sqlx::SqlitePool;
+
+use crate::models::MlEvaluation;
+use crate::services::predictor::{DEFAULT_POISSON_WEIGHT, FOOTBALL_MODEL_VERSION, FOOTBALL_WEIGHTS};
+use crate::utils::{brier_score, log_loss, match_outcome, model_pick};
+
+/// Name of the configuration whose predictions are served everywhere else.
+pub const BASELINE_MODEL: &str = "baseline";
+
+/// A named set of football ensemble parameters. Every configuration in the registry
+/// predicts each upcoming football match; only the baseline's prediction is the one
+/// the rest of the API reads.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelConfig {
+    pub name: String,
+    /// (ELO, H2H, form) ensemble weights, normalised to sum to 1
+    pub weights: (f64, f64, f64),
+    /// Share of the final probabilities taken from the Poisson goals model; the
+    /// (ELO, H2H, form) blend gets the rest
+    pub poisson_weight: f64,
+    pub form_season_weight: f64,
+    pub elo_form_adjustment: bool,
+}
+
+impl ModelConfig {
+    /// The configuration described by the existing env settings.
+    pub fn baseline() -> Self {
+        let [(_, elo), (_, h2h), (_, form)] = FOOTBALL_WEIGHTS;
+        Self {
+            name: BASELINE_MODEL.to_string(),
+            weights: (elo, h2h, form),
+            poisson_weight: std::env::var("POISSON_WEIGHT").ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(DEFAULT_POISSON_WEIGHT)
+                .clamp(0.0, 1.0),
+            form_season_weight: std::env::var("FORM_SEASON_WEIGHT").ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(0.35)
+                .clamp(0.0, 1.0),
+            elo_form_adjustment: std::env::var("ELO_FORM_ADJUSTMENT")
+                .map(|v| matches!(v.trim(), "1" | "true" | "on"))
+                .unwrap_or(false),
+        }
+    }
+
+    /// Stored on this configuration's predictions: the plain football version for the
+    /// baseline, `<version>@<name>` for the others.
+    pub fn model_version(&self) -> String {
+        if self.name == BASELINE_MODEL {
+            FOOTBALL_MODEL_VERSION.to_string()
+        } else {
+            format!("{}@{}", FOOTBALL_MODEL_VERSION, self.name)
+        }
+    }
+
+    /// Parse `name:key=value,...`, starting from the baseline's values.
+    /// Keys: elo, h2h, form (weights), poisson, form_season, elo_form (1/true/on).
+    fn parse(spec: &str) -> Option<Self> {
+        let (name, params) = spec.split_once(':').unwrap_or((spec, ""));
+        let name = name.trim();
+        if name.is_empty() || name == BASELINE_MODEL {
+            return None;
+        }
+        let mut config = Self { name: name.to_string(), ..Self::baseline() };
+        for pair in params.split(',').filter(|p| !p.trim().is_empty()) {
+            let (key, value) = pair.split_once('=')?;
+            let value = value.trim();
+            match key.trim() {
+                "elo" => config.weights.0 = value.parse().ok()?,
+                "h2h" => config.weights.1 = value.parse().ok()?,
+                "form" => config.weights.2 = value.parse().ok()?,
+                "poisson" => config.poisson_weight = value.parse::<f64>().ok()?.clamp(0.0, 1.0),
+                "form_season" => config.form_season_weight = value.parse::<f64>().ok()?.clamp(0.0, 1.0),
+                "elo_form" => config.elo_form_adjustment = matches!(value, "1" | "true" | "on"),
+                _ => return None,
+            }
+        }
+        let (elo, h2h, form) = config.weights;
+        let total = elo + h2h + form;
+        if elo < 0.0 || h2h < 0.0 || form < 0.0 || total <= 0.0 {
+            return None;
+        }
+        config.weights = (elo / total, h2h / total, form / total);
+        Some(config)
+    }
+}
+
+/// Configurations predicting alongside the baseline, from MODEL_REGISTRY
+/// (e.g. "candidate:elo=0.6,h2h=0.2,form=0.2;recent:form_season=0.1"). Malformed
+/// entries are skipped with a warning.
+pub fn registered_candidates() -> Vec<ModelConfig> {
+    let spec = std::env::var("MODEL_REGISTRY").unwrap_or_default();
+    spec.split(';')
+        .filter(|s| !s.trim().is_empty())
+        .filter_map(|s| {
+            let parsed = ModelConfig::parse(s);
+            if parsed.is_none() {
+                tracing::warn!("Ignoring MODEL_REGISTRY entry '{}'", s.trim());
+            }
+            parsed
+        })
+        .collect()
+}
+
+/// The baseline plus every registered candidate.
+pub fn model_registry() -> Vec<ModelConfig> {
+    std::iter::once(ModelConfig::baseline()).chain(registered_candidates()).collect()
+}
+
+pub fn find_model_config(name: &str) -> Option<ModelConfig> {
+    model_registry().into_iter().find(|c| c.name == name)
+}
+
+/// Score a configuration's last pre-kickoff prediction on every finished football match,
+/// in the same shape as the backtest folds. None until at least one such match resolves.
+pub async fn evaluate_model_config(pool: &SqlitePool, config: &ModelConfig) -> Result<Option<MlEvaluation>> {
+    let rows: Vec<(f64, f64, Option<f64>, i32, i32)> = sqlx::query_as(
+        r#"SELECT p.home_win_probability, p.away_win_probability, p.draw_probability, m.home_score, m.away_score
+           FROM matches m
+           JOIN predictions p ON p.id = (
+               SELECT id FROM predictions
+               WHERE match_id = m.id AND model_version = ? AND created_at <= m.match_date
+               ORDER BY created_at DESC LIMIT 1
+           )
+           WHERE m.sport = 'football' AND m.status = 'finished'
+             AND m.home_score IS NOT NULL AND m.away_score IS NOT NULL"#,
+    )
+    .bind(config.model_version())
+    .fetch_all(pool)
+    .await?;
+
+    if rows.is_empty() {
+        return Ok(None);
+    }
+
+    let (mut brier, mut loss, mut correct) = (0.0, 0.0, 0usize);
+    for &(home, away, draw, home_score, away_score) in &rows {
+        let outcome = match_outcome(home_score, away_score);
+        brier += brier_score(home, draw, away, outcome);
+        loss += log_loss(home, draw, away, outcome);
+        correct += usize::from(model_pick(home, draw, away) == outcome);
+    }
+    let n = rows.len() as f64;
+
+    Ok(Some(MlEvaluation {
+        model_name: format!("registry:{}", config.name),
+        fold: 0,
+        year: 0,
+        n_games: rows.len() as i32,
+        brier_score: brier / n,
+        log_loss: loss / n,
+        accuracy: correct as f64 / n,
+        evaluated_at: chrono::Utc::now().to_rfc3339(),
+    }))
+}
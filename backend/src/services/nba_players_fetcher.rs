@@ -15,6 +15,7 @@ use std::env;
 
 use crate::db::{get_teams_by_league, upsert_nba_player_stats};
 use crate::models::NbaPlayerStats;
+use crate::services::HttpConfig;
 
 const CURRENT_SEASON: &str = "2025";   // balldontlie year tag for the 2025-26 season
 const REFRESH_HOURS: i64 = 24;
@@ -79,9 +80,7 @@ impl NbaPlayersFetcher {
         let api_key = env::var("BALLDONTLIE_API_KEY")
             .map_err(|_| anyhow!("BALLDONTLIE_API_KEY not set"))?;
         Ok(Self {
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
-                .build()?,
+            client: HttpConfig::from_env().build_client()?,
             api_key,
         })
     }
@@ -7,7 +7,7 @@
 //! | Component               | Early season | Late season | Description                          |
 //! |-------------------------|-------------|-------------|--------------------------------------|
 //! | Bayesian Net Rating     | 15%         | 35%         | ORtg-DRtg, shrunk toward prior       |
-//! | MOV-Adjusted ELO        | 30%         | 15%         | K=20, +75 HCA, log(MOV) multiplier   |
+//! | MOV-Adjusted ELO        | 30%         | 15%         | K=20, +65 HCA, log(MOV) multiplier   |
 //! | Opponent-Adjusted Form  | 25%         | 25%         | Rolling point diff, SOS-adjusted     |
 //! | Dean Oliver Four Factors| 5%          | 20%         | eFG%, TOV%, OREB%, FTr (both sides)  |
 //! | Head-to-Head            | 25%         | 5%          | Historical record, heavily regressed |
@@ -20,16 +20,27 @@
 //! - Back-to-back (0 rest days):        ±5.0 pp
 //! - 3-in-4 nights (1 rest + prior 2):  ±2.5 pp
 //! - Road trip fatigue (3+ away):       ±1.5 pp (benefits home team)
+//! - Travel into a short turnaround:    ±1.0 pp per 1,000 km flown since the last game
+//!   (full on a back-to-back, half with one rest day)
+//!
+//! The combined schedule adjustment is capped at ±10 pp.
+//!
+//! ## Totals
+//! Expected points per side come from possessions (both teams' pace) times each
+//! offence's efficiency against the other's defence; over/under probabilities use a
+//! normal total whose spread grows with the number of possessions.
 
 use anyhow::Result;
 use chrono::{DateTime, Utc};
-use sqlx::{Row, SqlitePool};
+use sqlx::SqlitePool;
 use std::sync::OnceLock;
 use uuid::Uuid;
 
-use crate::db::get_nba_advanced_stats;
+use crate::db::{get_home_advantage, get_matches_for_team, get_nba_advanced_stats, MatchFilter, Venue};
 use crate::ml::meta_learner::{MlModelState, MlPredictor};
-use crate::models::{Match, NbaAdvancedStats, Prediction};
+use crate::models::{Match, NbaAdvancedStats, Prediction, TotalsPrediction};
+use crate::services::home_advantage_for;
+use crate::utils::{model_agreement, prediction_confidence};
 
 // ── Global ML state (tokio RwLock so guards are Send across awaits) ───────────
 
@@ -56,8 +67,21 @@ pub async fn set_ml_model(state: MlModelState) {
     guard.set_state(state);
 }
 
+/// Version tag new NBA predictions are produced under: the loaded ML model if any,
+/// otherwise the rule-based ensemble.
+pub async fn active_model_version() -> String {
+    let guard = ml_lock().read().await;
+    if guard.has_model() {
+        guard.model_version()
+    } else {
+        RULE_BASED_MODEL_VERSION.to_string()
+    }
+}
+
 // ── Constants ────────────────────────────────────────────────────────────────
 
+const RULE_BASED_MODEL_VERSION: &str = "nba_v3.0";
+
 /// Home court advantage expressed as equivalent net rating points.
 /// Calibrated so equal teams produce ~59% home win probability.
 const NBA_HCA_NET_RATING: f64 = 3.2;
@@ -69,17 +93,107 @@ const NBA_HCA_POINTS: f64 = 3.0;
 /// At exactly this many games played, the rating receives full credibility.
 const BAYESIAN_PRIOR_GAMES: f64 = 55.0;
 
-/// ELO home court advantage in ELO points.
-/// +75 → equal teams: 1/(1+10^(-75/400)) ≈ 60.7% — slightly above NBA empirical ~59%.
-const NBA_ELO_HCA: f64 = 75.0;
+/// (ELO, form, H2H) weights when advanced stats have not been fetched yet.
+const FALLBACK_WEIGHTS: (f64, f64, f64) = (0.40, 0.40, 0.20);
+
+/// Default probability cost of each 1,000 km flown into a back-to-back.
+const TRAVEL_PENALTY_PER_1000KM: f64 = 0.01;
+
+/// Rest, road-trip and travel adjustments together never move the home probability further.
+const MAX_SCHEDULE_DELTA: f64 = 0.10;
+
+/// League-average offensive/defensive rating (points per 100 possessions) and pace that
+/// team ratings are shrunk toward when estimating totals.
+const LEAGUE_RATING: f64 = 114.0;
+const LEAGUE_PACE: f64 = 99.0;
+
+/// Standard deviation of the combined score at league-average pace (NBA_TOTAL_POINTS_SD).
+const TOTAL_POINTS_SD: f64 = 18.0;
+
+/// Expected score of one game from pace and efficiency, for over/under pricing.
+#[derive(Debug, Clone, Copy)]
+pub struct TotalPointsEstimate {
+    pub home_points: f64,
+    pub away_points: f64,
+    pub possessions: f64,
+    /// Standard deviation of the combined score
+    pub total_sd: f64,
+    /// False when either side lacks advanced stats and league averages stood in
+    pub has_advanced: bool,
+}
+
+impl TotalPointsEstimate {
+    pub fn to_prediction(self, match_id: &str) -> TotalsPrediction {
+        TotalsPrediction {
+            match_id: match_id.to_string(),
+            model: if self.has_advanced { "pace_efficiency" } else { "pace_efficiency_league_avg" }.to_string(),
+            expected_home_score: self.home_points,
+            expected_away_score: self.away_points,
+            total_sd: Some(self.total_sd),
+            possessions: Some(self.possessions),
+            created_at: Utc::now(),
+        }
+    }
+}
 
 // ── Predictor ────────────────────────────────────────────────────────────────
 
-pub struct NbaPredictor;
+pub struct NbaPredictor {
+    /// Travel penalty per 1,000 km on zero rest (NBA_TRAVEL_PENALTY_PER_1000KM)
+    travel_penalty_per_1000km: f64,
+    /// Combined-score SD at league-average pace (NBA_TOTAL_POINTS_SD)
+    total_points_sd: f64,
+}
 
 impl NbaPredictor {
     pub fn new() -> Self {
-        Self
+        let env_f64 = |key: &str| std::env::var(key).ok().and_then(|v| v.parse::<f64>().ok());
+        Self {
+            travel_penalty_per_1000km: env_f64("NBA_TRAVEL_PENALTY_PER_1000KM")
+                .unwrap_or(TRAVEL_PENALTY_PER_1000KM)
+                .max(0.0),
+            total_points_sd: env_f64("NBA_TOTAL_POINTS_SD")
+                .filter(|sd| *sd > 0.0)
+                .unwrap_or(TOTAL_POINTS_SD),
+        }
+    }
+
+    /// Expected points for each side: possessions from both teams' pace, points per
+    /// possession from each offence against the other's defence, plus the home edge.
+    /// Ratings are shrunk toward league average by games played, as in the net-rating model.
+    pub async fn predict_total_points(&self, pool: &SqlitePool, match_data: &Match) -> Result<TotalPointsEstimate> {
+        let home_adv = get_nba_advanced_stats(pool, &match_data.home_team_id).await?;
+        let away_adv = get_nba_advanced_stats(pool, &match_data.away_team_id).await?;
+        let hca_multiplier = get_home_advantage(pool, &match_data.home_team_id).await?;
+        Ok(self.total_points_model(home_adv.as_ref(), away_adv.as_ref(), hca_multiplier))
+    }
+
+    fn total_points_model(
+        &self,
+        home: Option<&NbaAdvancedStats>,
+        away: Option<&NbaAdvancedStats>,
+        hca_multiplier: f64,
+    ) -> TotalPointsEstimate {
+        // (offensive rating, defensive rating, pace), shrunk toward league average
+        let ratings = |stats: Option<&NbaAdvancedStats>| match stats {
+            Some(s) => {
+                let shrink = |value: f64, prior: f64| prior + bayesian_shrinkage(value - prior, s.games_played);
+                (shrink(s.off_rating, LEAGUE_RATING), shrink(s.def_rating, LEAGUE_RATING), shrink(s.pace, LEAGUE_PACE))
+            }
+            None => (LEAGUE_RATING, LEAGUE_RATING, LEAGUE_PACE),
+        };
+        let (home_off, home_def, home_pace) = ratings(home);
+        let (away_off, away_def, away_pace) = ratings(away);
+
+        let possessions = home_pace * away_pace / LEAGUE_PACE;
+        let edge = NBA_HCA_POINTS * hca_multiplier / 2.0;
+        TotalPointsEstimate {
+            home_points: possessions / 100.0 * home_off * away_def / LEAGUE_RATING + edge,
+            away_points: possessions / 100.0 * away_off * home_def / LEAGUE_RATING - edge,
+            possessions,
+            total_sd: self.total_points_sd * (possessions / LEAGUE_PACE).sqrt(),
+            has_advanced: home.is_some() && away.is_some(),
+        }
     }
 
     /// Generate a prediction for a single NBA game.
@@ -105,7 +219,8 @@ impl NbaPredictor {
             _ => 0.55, // NBA baseline home win rate
         };
 
-        let elo_prob = self.elo_model(home_elo, away_elo);
+        let hca_multiplier = get_home_advantage(pool, &match_data.home_team_id).await?;
+        let elo_prob = self.elo_model(home_elo, away_elo, &match_data.league, hca_multiplier);
 
         let form_prob = self
             .form_model(pool, &match_data.home_team_id, &match_data.away_team_id)
@@ -134,7 +249,8 @@ impl NbaPredictor {
                 + h2h_prob * w_h2h
         } else {
             // Fallback: no advanced stats available
-            elo_prob * 0.40 + form_prob * 0.40 + h2h_prob * 0.20
+            let (w_elo, w_form, w_h2h) = FALLBACK_WEIGHTS;
+            elo_prob * w_elo + form_prob * w_form + h2h_prob * w_h2h
         };
 
         // ── Schedule adjustment (post-ensemble) ──────────────────────────────
@@ -166,15 +282,17 @@ impl NbaPredictor {
                     model_version: guard.model_version(),
                     confidence_score: ml_conf,
                     created_at: Utc::now(),
+                    insufficient_data: false,
+                    most_likely_score: None,
                 });
             }
         }
 
         // ── Fallback: rule-based ensemble ─────────────────────────────────────
         let model_version = if has_advanced {
-            "nba_v3.0"
+            RULE_BASED_MODEL_VERSION.to_string()
         } else {
-            "nba_v3.0_fallback"
+            format!("{}_fallback", RULE_BASED_MODEL_VERSION)
         };
 
         Ok(Prediction {
@@ -183,9 +301,11 @@ impl NbaPredictor {
             home_win_probability: final_home,
             away_win_probability: final_away,
             draw_probability: None,
-            model_version: model_version.to_string(),
+            model_version,
             confidence_score: confidence,
             created_at: Utc::now(),
+            insufficient_data: false,
+            most_likely_score: None,
         })
     }
 
@@ -203,9 +323,12 @@ impl NbaPredictor {
 
     // ── Model 2: ELO ─────────────────────────────────────────────────────────
 
-    /// NBA-calibrated ELO: K=20, +75 HCA, no draw outcome.
-    pub fn elo_model(&self, home_elo: f64, away_elo: f64) -> f64 {
-        1.0 / (1.0 + 10f64.powf((away_elo - (home_elo + NBA_ELO_HCA)) / 400.0))
+    /// NBA-calibrated ELO: K=20, the league's home bonus from `home_advantage_for` (+65 for
+    /// the NBA: equal teams ≈ 59.3%, the empirical rate) scaled by the home team's override,
+    /// no draw outcome. Ratings are updated with the same bonus.
+    pub fn elo_model(&self, home_elo: f64, away_elo: f64, league: &str, hca_multiplier: f64) -> f64 {
+        let home_bonus = home_advantage_for(league) * hca_multiplier;
+        1.0 / (1.0 + 10f64.powf((away_elo - (home_elo + home_bonus)) / 400.0))
     }
 
     /// Logarithmic margin-of-victory multiplier for ELO updates.
@@ -251,31 +374,23 @@ impl NbaPredictor {
             away_score: i32,
         }
 
-        let sql_home = "SELECT home_team_id, away_team_id, home_score, away_score
-                        FROM matches
-                        WHERE home_team_id = ? AND status = 'finished'
-                          AND home_score IS NOT NULL AND sport = 'basketball'
-                        ORDER BY match_date DESC LIMIT 15";
-
-        let sql_away = "SELECT home_team_id, away_team_id, home_score, away_score
-                        FROM matches
-                        WHERE away_team_id = ? AND status = 'finished'
-                          AND away_score IS NOT NULL AND sport = 'basketball'
-                        ORDER BY match_date DESC LIMIT 15";
-
-        let sql = if home_context { sql_home } else { sql_away };
-
-        let games: Vec<GameRow> = sqlx::query(sql)
-            .bind(team_id)
-            .fetch_all(pool)
+        let filter = MatchFilter {
+            venue: if home_context { Venue::Home } else { Venue::Away },
+            status: Some("finished"),
+            sport: Some("basketball"),
+            scored_only: true,
+            limit: Some(15),
+            ..Default::default()
+        };
+        let games: Vec<GameRow> = get_matches_for_team(pool, team_id, &filter)
             .await?
             .into_iter()
-            .filter_map(|r| {
+            .filter_map(|m| {
                 Some(GameRow {
-                    home_team_id: r.try_get("home_team_id").ok()?,
-                    away_team_id: r.try_get("away_team_id").ok()?,
-                    home_score: r.try_get("home_score").ok()?,
-                    away_score: r.try_get("away_score").ok()?,
+                    home_team_id: m.home_team_id,
+                    away_team_id: m.away_team_id,
+                    home_score: m.home_score?,
+                    away_score: m.away_score?,
                 })
             })
             .collect();
@@ -364,36 +479,21 @@ impl NbaPredictor {
         home_id: &str,
         away_id: &str,
     ) -> Result<f64> {
-        let rows = sqlx::query(
-            "SELECT home_team_id, home_score, away_score FROM matches
-             WHERE ((home_team_id = ? AND away_team_id = ?)
-                 OR (home_team_id = ? AND away_team_id = ?))
-               AND status = 'finished' AND sport = 'basketball'
-             ORDER BY match_date DESC LIMIT 10",
-        )
-        .bind(home_id)
-        .bind(away_id)
-        .bind(away_id)
-        .bind(home_id)
-        .fetch_all(pool)
+        let rows = get_matches_for_team(pool, home_id, &MatchFilter {
+            opponent: Some(away_id),
+            status: Some("finished"),
+            sport: Some("basketball"),
+            limit: Some(10),
+            ..Default::default()
+        })
         .await?;
 
         let mut home_wins = 0u32;
         let mut total = 0u32;
 
-        for row in &rows {
-            let row_home_id: String = match row.try_get("home_team_id") {
-                Ok(v) => v,
-                Err(_) => continue,
-            };
-            let hs: i32 = match row.try_get("home_score") {
-                Ok(v) => v,
-                Err(_) => continue,
-            };
-            let aws: i32 = match row.try_get("away_score") {
-                Ok(v) => v,
-                Err(_) => continue,
-            };
+        for game in &rows {
+            let (Some(hs), Some(aws)) = (game.home_score, game.away_score) else { continue };
+            let row_home_id = &game.home_team_id;
             total += 1;
             let current_home_won = hs > aws;
             // Did the team currently playing at home win this historical game?
@@ -461,7 +561,40 @@ impl NbaPredictor {
             delta += 0.015;
         }
 
-        Ok(delta)
+        // Long flights on short rest, for either side
+        delta -= self.travel_fatigue(pool, &match_data.home_team_id, match_data, home_rest).await?;
+        delta += self.travel_fatigue(pool, &match_data.away_team_id, match_data, away_rest).await?;
+
+        Ok(delta.clamp(-MAX_SCHEDULE_DELTA, MAX_SCHEDULE_DELTA))
+    }
+
+    /// Probability penalty for the distance a team flew from its previous game's city to
+    /// this venue: full on a back-to-back, half with one rest day, none beyond that.
+    async fn travel_fatigue(
+        &self,
+        pool: &SqlitePool,
+        team_id: &str,
+        match_data: &Match,
+        rest: Option<u32>,
+    ) -> Result<f64> {
+        let rest_factor = match rest {
+            Some(0) => 1.0,
+            Some(1) => 0.5,
+            _ => return Ok(0.0),
+        };
+        let previous = get_matches_for_team(pool, team_id, &MatchFilter {
+            status: Some("finished"),
+            before: Some(match_data.match_date),
+            limit: Some(1),
+            ..Default::default()
+        })
+        .await?
+        .pop();
+
+        let distance = previous
+            .and_then(|prev| Some(haversine_km(nba_game_location(&prev)?, nba_game_location(match_data)?)))
+            .unwrap_or(0.0);
+        Ok(self.travel_penalty_per_1000km * distance / 1000.0 * rest_factor)
     }
 
     /// Days of rest before this game (0 = back-to-back, None = no prior game on record).
@@ -471,26 +604,18 @@ impl NbaPredictor {
         team_id: &str,
         game_date: DateTime<Utc>,
     ) -> Result<Option<u32>> {
-        let last = sqlx::query(
-            "SELECT match_date FROM matches
-             WHERE (home_team_id = ? OR away_team_id = ?)
-               AND status = 'finished'
-               AND match_date < ?
-             ORDER BY match_date DESC LIMIT 1",
-        )
-        .bind(team_id)
-        .bind(team_id)
-        .bind(game_date.to_rfc3339())
-        .fetch_optional(pool)
-        .await?;
+        let last = get_matches_for_team(pool, team_id, &MatchFilter {
+            status: Some("finished"),
+            before: Some(game_date),
+            limit: Some(1),
+            ..Default::default()
+        })
+        .await?
+        .pop();
 
-        Ok(last.and_then(|row| {
-            let date_str: String = row.try_get("match_date").ok()?;
-            let last_date = chrono::DateTime::parse_from_rfc3339(&date_str)
-                .map(|d| d.with_timezone(&Utc))
-                .ok()?;
-            let days = (game_date - last_date).num_days().max(0) as u32;
-            Some(days.saturating_sub(1).min(7))
+        Ok(last.map(|m| {
+            let days = crate::utils::nights_between(m.match_date, game_date).max(0) as u32;
+            days.saturating_sub(1).min(7)
         }))
     }
 
@@ -525,26 +650,17 @@ impl NbaPredictor {
         team_id: &str,
         before: DateTime<Utc>,
     ) -> Result<u32> {
-        let rows = sqlx::query(
-            "SELECT home_team_id FROM matches
-             WHERE (home_team_id = ? OR away_team_id = ?)
-               AND status = 'finished'
-               AND match_date < ?
-             ORDER BY match_date DESC LIMIT 6",
-        )
-        .bind(team_id)
-        .bind(team_id)
-        .bind(before.to_rfc3339())
-        .fetch_all(pool)
+        let rows = get_matches_for_team(pool, team_id, &MatchFilter {
+            status: Some("finished"),
+            before: Some(before),
+            limit: Some(6),
+            ..Default::default()
+        })
         .await?;
 
         let mut streak = 0u32;
-        for row in &rows {
-            let home_id: String = match row.try_get("home_team_id") {
-                Ok(v) => v,
-                Err(_) => break,
-            };
-            if home_id == team_id {
+        for game in &rows {
+            if game.home_team_id == team_id {
                 break; // Home game ends the road trip
             }
             streak += 1;
@@ -589,27 +705,84 @@ impl NbaPredictor {
         )
     }
 
-    /// Confidence = blend of prediction strength + inter-model agreement + data quality bonus.
+    /// Named ensemble weights used by the rule-based path, for prediction audit snapshots.
+    pub fn component_weights(has_advanced: bool, home_games: i32, away_games: i32) -> Vec<(&'static str, f64)> {
+        if has_advanced {
+            let (w_nr, w_elo, w_form, w_ff, w_h2h) = Self::ensemble_weights(home_games, away_games);
+            vec![("net_rating", w_nr), ("elo", w_elo), ("form", w_form), ("four_factors", w_ff), ("h2h", w_h2h)]
+        } else {
+            let (w_elo, w_form, w_h2h) = FALLBACK_WEIGHTS;
+            vec![("elo", w_elo), ("form", w_form), ("h2h", w_h2h)]
+        }
+    }
+
+    /// Shared confidence (strength + inter-model agreement) plus a data quality bonus.
     fn compute_confidence(
         &self,
         model_probs: &[f64],
         final_home_prob: f64,
         has_advanced: bool,
     ) -> f64 {
-        let best = final_home_prob.max(1.0 - final_home_prob);
-        let strength = ((best - 0.5) * 2.5).clamp(0.0, 1.0);
-
-        let n = model_probs.len() as f64;
-        let mean = model_probs.iter().sum::<f64>() / n;
-        let std_dev = (model_probs.iter().map(|&p| (p - mean).powi(2)).sum::<f64>() / n).sqrt();
-        let agreement = (1.0 - std_dev / 0.15).clamp(0.0, 1.0);
-
+        let agreement = model_agreement(model_probs);
         let data_bonus = if has_advanced { 0.05 } else { 0.0 };
 
-        (0.40 + 0.30 * strength + 0.20 * agreement + data_bonus).clamp(0.40, 0.95)
+        (prediction_confidence(final_home_prob, None, 1.0 - final_home_prob, agreement) + data_bonus).min(0.95)
     }
 }
 
+// ── Travel ───────────────────────────────────────────────────────────────────
+
+/// (team name prefix, city, latitude, longitude) of each NBA arena.
+const NBA_ARENAS: [(&str, &str, f64, f64); 30] = [
+    ("Atlanta", "Atlanta", 33.757, -84.396),
+    ("Boston", "Boston", 42.366, -71.062),
+    ("Brooklyn", "Brooklyn", 40.683, -73.975),
+    ("Charlotte", "Charlotte", 35.225, -80.839),
+    ("Chicago", "Chicago", 41.881, -87.674),
+    ("Cleveland", "Cleveland", 41.496, -81.688),
+    ("Dallas", "Dallas", 32.790, -96.810),
+    ("Denver", "Denver", 39.749, -105.008),
+    ("Detroit", "Detroit", 42.341, -83.055),
+    ("Golden State", "San Francisco", 37.768, -122.388),
+    ("Houston", "Houston", 29.751, -95.362),
+    ("Indiana", "Indianapolis", 39.764, -86.156),
+    ("LA Clippers", "Inglewood", 33.945, -118.343),
+    ("Los Angeles", "Los Angeles", 34.043, -118.267),
+    ("Memphis", "Memphis", 35.138, -90.051),
+    ("Miami", "Miami", 25.781, -80.188),
+    ("Milwaukee", "Milwaukee", 43.045, -87.917),
+    ("Minnesota", "Minneapolis", 44.980, -93.276),
+    ("New Orleans", "New Orleans", 29.949, -90.082),
+    ("New York", "New York", 40.751, -73.993),
+    ("Oklahoma City", "Oklahoma City", 35.463, -97.515),
+    ("Orlando", "Orlando", 28.539, -81.384),
+    ("Philadelphia", "Philadelphia", 39.901, -75.172),
+    ("Phoenix", "Phoenix", 33.446, -112.071),
+    ("Portland", "Portland", 45.532, -122.667),
+    ("Sacramento", "Sacramento", 38.580, -121.500),
+    ("San Antonio", "San Antonio", 29.427, -98.437),
+    ("Toronto", "Toronto", 43.643, -79.379),
+    ("Utah", "Salt Lake City", 40.768, -111.901),
+    ("Washington", "Washington", 38.898, -77.021),
+];
+
+/// Where a game was played: its stored city when known, otherwise the home team's arena.
+fn nba_game_location(m: &Match) -> Option<(f64, f64)> {
+    let by_city = m.city.as_deref()
+        .and_then(|city| NBA_ARENAS.iter().find(|a| a.1.eq_ignore_ascii_case(city.trim())));
+    by_city
+        .or_else(|| NBA_ARENAS.iter().find(|a| m.home_team_name.starts_with(a.0)))
+        .map(|a| (a.2, a.3))
+}
+
+/// Great-circle distance in km between two (latitude, longitude) points.
+fn haversine_km((lat1, lon1): (f64, f64), (lat2, lon2): (f64, f64)) -> f64 {
+    const EARTH_RADIUS_KM: f64 = 6371.0;
+    let (dlat, dlon) = ((lat2 - lat1).to_radians(), (lon2 - lon1).to_radians());
+    let a = (dlat / 2.0).sin().powi(2) + lat1.to_radians().cos() * lat2.to_radians().cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
 // ── Pure Mathematical Functions ──────────────────────────────────────────────
 
 /// Logistic sigmoid: σ(x) = 1 / (1 + e^{−x})
@@ -719,22 +892,24 @@ mod tests {
     #[test]
     fn elo_equal_teams_home_advantage() {
         let p = NbaPredictor::new();
-        let prob = p.elo_model(1200.0, 1200.0);
-        // +75 HCA → ~60.7% home win rate for equal teams
-        assert!(prob > 0.60 && prob < 0.62, "equal teams should give ~60.7%, got {:.3}", prob);
+        let prob = p.elo_model(1200.0, 1200.0, "NBA", 1.0);
+        // +65 HCA → ~59.3% home win rate for equal teams
+        assert!(prob > 0.58 && prob < 0.60, "equal teams should give ~59.3%, got {:.3}", prob);
+        let ratings_side = crate::services::EloCalculator::new().win_probability(1200.0, 1200.0, "basketball", "NBA").0;
+        assert!((prob - ratings_side).abs() < 1e-12);
     }
 
     #[test]
     fn elo_strong_home_team() {
         let p = NbaPredictor::new();
-        let prob = p.elo_model(1400.0, 1200.0);
+        let prob = p.elo_model(1400.0, 1200.0, "NBA", 1.0);
         assert!(prob > 0.75, "strong home team should have >75% win prob");
     }
 
     #[test]
     fn elo_strong_away_team() {
         let p = NbaPredictor::new();
-        let prob = p.elo_model(1200.0, 1400.0);
+        let prob = p.elo_model(1200.0, 1400.0, "NBA", 1.0);
         assert!(prob < 0.40, "strong away team should give <40% to home side");
     }
 
@@ -889,6 +1064,9 @@ mod tests {
             status: "scheduled".into(),
             home_score: None,
             away_score: None,
+            venue: None,
+            city: None,
+            tiebreak_winner: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -897,4 +1075,82 @@ mod tests {
         let delta = p.schedule_adjustment(&pool, &m).await.unwrap();
         assert_eq!(delta, 0.0, "no prior games should give zero schedule delta");
     }
+
+    #[tokio::test]
+    async fn coast_to_coast_back_to_back_costs_more_than_short_hop() {
+        let pool = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::db::init_database_with_pool(&pool).await.unwrap();
+        let game = |id: &str, home: &str, away: &str, days_ago: i64, status: &str| Match {
+            id: id.into(),
+            home_team_id: home.to_lowercase(),
+            away_team_id: away.to_lowercase(),
+            home_team_name: home.into(),
+            away_team_name: away.into(),
+            sport: "basketball".into(),
+            league: "NBA".into(),
+            match_date: Utc::now() - chrono::Duration::days(days_ago),
+            status: status.into(),
+            home_score: (status == "finished").then_some(100),
+            away_score: (status == "finished").then_some(95),
+            venue: None,
+            city: None,
+            tiebreak_winner: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        for name in ["Boston Celtics", "New York Knicks", "Los Angeles Lakers", "Philadelphia 76ers", "Far", "Near"] {
+            crate::db::insert_team(&pool, &crate::models::Team {
+                id: name.to_lowercase(),
+                name: name.into(),
+                sport: "basketball".into(),
+                league: "NBA".into(),
+                logo_url: None,
+                elo_rating: 1500.0,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                elo_percentile: None,
+                elo_tier: None,
+            }).await.unwrap();
+        }
+        // Both road teams played last night: one in Boston, one in New York
+        crate::db::insert_match(&pool, &game("g1", "Boston Celtics", "Far", 1, "finished")).await.unwrap();
+        crate::db::insert_match(&pool, &game("g2", "New York Knicks", "Near", 1, "finished")).await.unwrap();
+
+        let p = NbaPredictor::new();
+        let at_lakers = game("g3", "Los Angeles Lakers", "Far", 0, "scheduled");
+        let at_sixers = game("g4", "Philadelphia 76ers", "Near", 0, "scheduled");
+        let coast_to_coast = p.travel_fatigue(&pool, "far", &at_lakers, Some(0)).await.unwrap();
+        let short_hop = p.travel_fatigue(&pool, "near", &at_sixers, Some(0)).await.unwrap();
+
+        assert!(short_hop > 0.0);
+        assert!(coast_to_coast > 10.0 * short_hop, "{} vs {}", coast_to_coast, short_hop);
+        // A rested team pays nothing however far it flew
+        assert_eq!(p.travel_fatigue(&pool, "far", &at_lakers, Some(2)).await.unwrap(), 0.0);
+    }
+
+    #[test]
+    fn fast_efficient_teams_project_a_higher_total() {
+        let p = NbaPredictor::new();
+        let stats = |off: f64, def: f64, pace: f64| NbaAdvancedStats {
+            team_id: "x".into(), off_rating: off, def_rating: def, net_rating: off - def,
+            pace, efg_pct: 0.52, opp_efg_pct: 0.52, tov_pct: 0.14, opp_tov_pct: 0.14,
+            oreb_pct: 0.28, opp_oreb_pct: 0.28, ft_rate: 0.24, opp_ft_rate: 0.24,
+            games_played: 55, wins: 27, season: "2025-26".into(), fetched_at: "2026-01-01T00:00:00Z".into(),
+        };
+        let (run_a, run_b) = (stats(120.0, 116.0, 104.0), stats(119.0, 117.0, 103.0));
+        let (grind_a, grind_b) = (stats(108.0, 108.0, 95.0), stats(107.0, 109.0, 94.0));
+
+        let total = |e: &TotalPointsEstimate| e.home_points + e.away_points;
+        let shootout = p.total_points_model(Some(&run_a), Some(&run_b), 1.0);
+        let grinder = p.total_points_model(Some(&grind_a), Some(&grind_b), 1.0);
+        assert!(total(&shootout) > total(&grinder) + 20.0, "{} vs {}", total(&shootout), total(&grinder));
+        assert!(shootout.possessions > grinder.possessions);
+        // More possessions, more spread in the total
+        assert!(shootout.total_sd > grinder.total_sd);
+
+        // Without advanced stats both sides are league average
+        let unknown = p.total_points_model(None, None, 1.0);
+        assert!(!unknown.has_advanced);
+        assert!((total(&unknown) - 2.0 * LEAGUE_RATING * LEAGUE_PACE / 100.0).abs() < 1e-9);
+    }
 }
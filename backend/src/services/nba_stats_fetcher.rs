@@ -16,6 +16,7 @@ use std::collections::HashMap;
 
 use crate::db::upsert_nba_advanced_stats;
 use crate::models::NbaAdvancedStats;
+use crate::services::HttpConfig;
 
 const NBA_STATS_BASE: &str = "https://stats.nba.com/stats";
 pub const CURRENT_SEASON: &str = "2025-26";
@@ -44,14 +45,14 @@ impl NbaStatsFetcher {
             HeaderValue::from_static("true"),
         );
 
-        let client = Client::builder()
+        // stats.nba.com rejects non-browser agents, so override the configured one
+        let client = HttpConfig::from_env().client_builder()
             .user_agent(
                 "Mozilla/5.0 (Windows NT 10.0; Win64; x64) \
                  AppleWebKit/537.36 (KHTML, like Gecko) \
                  Chrome/123.0.0.0 Safari/537.36",
             )
             .default_headers(headers)
-            .timeout(std::time::Duration::from_secs(30))
             .build()?;
 
         Ok(Self { client })
@@ -15,6 +15,7 @@ use serde::Deserialize;
 use sqlx::{Row, SqlitePool};
 
 use crate::db::upsert_market_odds;
+use crate::services::HttpConfig;
 
 // ── Odds API response types ───────────────────────────────────────────────────
 
@@ -155,10 +156,9 @@ async fn fetch_sport(
         sport_key, api_key, region
     );
 
-    let client = reqwest::Client::new();
+    let client = HttpConfig::from_env().build_client()?;
     let resp = client
         .get(&url)
-        .timeout(std::time::Duration::from_secs(20))
         .send()
         .await?;
 
@@ -177,8 +177,11 @@ async fn fetch_sport(
     let events: Vec<OddsEvent> = resp.json().await?;
     let mut upserted = 0u32;
 
+    // Soccer markets are 3-way; a book missing the draw price is unusable there
+    let three_way = sport_key.starts_with("soccer");
+
     for event in &events {
-        let Some(odds) = best_odds(event) else { continue };
+        let Some(odds) = best_odds(event, three_way) else { continue };
 
         // Match to our DB by kick-off time window (±4 h) + team name fuzzy match
         let Some(match_id) =
@@ -211,7 +214,8 @@ async fn fetch_sport(
 }
 
 /// Select the sharpest odds from a bookmaker priority list, fallback to lowest overround.
-fn best_odds(event: &OddsEvent) -> Option<BestOdds> {
+/// When `three_way` is set, only books quoting home, draw and away prices qualify.
+fn best_odds(event: &OddsEvent, three_way: bool) -> Option<BestOdds> {
     let priority = ["pinnacle", "betfair_ex_eu", "betfair_ex_uk", "williamhill", "bet365"];
 
     let extract = |bk: &Bookmaker| -> Option<(f64, Option<f64>, f64)> {
@@ -231,7 +235,11 @@ fn best_odds(event: &OddsEvent) -> Option<BestOdds> {
             .iter()
             .find(|o| o.name.to_lowercase() == "draw")
             .map(|o| o.price);
-        if home_price > 1.0 && away_price > 1.0 {
+        let draw_ok = match draw_price {
+            Some(d) => d > 1.0,
+            None => !three_way,
+        };
+        if home_price > 1.0 && away_price > 1.0 && draw_ok {
             Some((home_price, draw_price, away_price))
         } else {
             None
@@ -322,3 +330,39 @@ fn names_match(a: &str, b: &str) -> bool {
     let b = norm(b);
     a == b || a.contains(&b) || b.contains(&a)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(outcomes: &[(&str, f64)]) -> OddsEvent {
+        OddsEvent {
+            id: "e1".into(),
+            commence_time: Utc::now(),
+            home_team: "Arsenal".into(),
+            away_team: "Chelsea".into(),
+            bookmakers: vec![Bookmaker {
+                key: "pinnacle".into(),
+                title: "Pinnacle".into(),
+                markets: vec![Market {
+                    key: "h2h".into(),
+                    outcomes: outcomes.iter()
+                        .map(|(name, price)| Outcome { name: name.to_string(), price: *price })
+                        .collect(),
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    fn football_market_without_draw_is_rejected() {
+        let e = event(&[("Arsenal", 2.1), ("Chelsea", 3.6)]);
+        assert!(best_odds(&e, true).is_none());
+        // The same prices are a valid two-way market
+        assert!(best_odds(&e, false).is_some());
+
+        let full = event(&[("Arsenal", 2.1), ("Draw", 3.4), ("Chelsea", 3.6)]);
+        let odds = best_odds(&full, true).unwrap();
+        assert_eq!(odds.draw_odds, Some(3.4));
+    }
+}
@@ -5,9 +5,15 @@ use uuid::Uuid;
 // use nalgebra::{DVector, DMatrix}; // For future advanced statistical models
 // use statrs::distribution::{Normal, ContinuousCDF}; // For future probabilistic models
 
-use crate::db::{get_team_by_id, insert_prediction, get_prediction_by_match_id, get_market_odds};
-use crate::models::{Match, Prediction, Team};
-use crate::services::{EloCalculator, NbaPredictor};
+use crate::db::{
+    get_home_advantage, get_market_odds, get_matches_for_team, get_preseason_ratings, MatchFilter, Venue, get_nba_advanced_stats, get_prediction_by_match_id, get_team_by_id,
+    get_team_current_stats, insert_consensus_prediction, insert_prediction, insert_prediction_inputs, upsert_total_prediction,
+};
+use crate::models::{Match, Prediction, PredictionInputs, Team};
+use crate::ml::match_simulator::ScoreModel;
+use crate::services::{expected_scores, home_advantage_for, EloCalculator, NbaPredictor};
+use crate::services::model_registry::{registered_candidates, ModelConfig};
+use crate::utils::{model_agreement, prediction_confidence};
 
 /// Captures recent weighted performance for a team in a specific playing context (home or away).
 struct RollingForm {
@@ -17,28 +23,283 @@ struct RollingForm {
     sample_size: usize,
 }
 
+/// (home, away, draw) probabilities from one ensemble component.
+type ComponentProbs = (f64, f64, Option<f64>);
+
+/// What a prediction was actually built from, persisted as its inputs snapshot.
+struct UsedInputs {
+    home_elo: f64,
+    away_elo: f64,
+    /// Blended contextual form rates fed to the form model; None when it fell back to the
+    /// league average
+    form_rates: Option<(f64, f64)>,
+    /// H2H component output and the number of meetings behind it
+    h2h: Option<(ComponentProbs, usize)>,
+    /// Weights applied to each blended component
+    weights: Vec<(&'static str, f64)>,
+}
+
+impl UsedInputs {
+    fn ratings_only(home_team: &Team, away_team: &Team) -> Self {
+        Self { home_elo: home_team.elo_rating, away_elo: away_team.elo_rating, form_rates: None, h2h: None, weights: Vec::new() }
+    }
+}
+
+/// Version tag stored on football ensemble predictions.
+pub const FOOTBALL_MODEL_VERSION: &str = "ensemble_v2.0";
+
+/// Confidence reported for league-baseline predictions of teams with no finished matches,
+/// well under the ensemble's 0.40 floor.
+const INSUFFICIENT_DATA_CONFIDENCE: f64 = 0.2;
+
+/// Football ensemble weights: ELO, head-to-head, recent form.
+pub(crate) const FOOTBALL_WEIGHTS: [(&str, f64); 3] = [("elo", 0.5), ("h2h", 0.3), ("form", 0.2)];
+
+/// Share of football probabilities from the Poisson goals model (POISSON_WEIGHT).
+pub(crate) const DEFAULT_POISSON_WEIGHT: f64 = 0.2;
+
+/// Whether both sides have enough contextual games for the form model to use them.
+fn form_is_reliable(home: &RollingForm, away: &RollingForm) -> bool {
+    home.sample_size >= 3 && away.sample_size >= 3
+}
+
+/// (ELO, H2H, form) weights. An H2H override is clamped to [0, 1] and ELO and form
+/// share the remainder in their `base` proportions.
+fn football_weights(base: (f64, f64, f64), h2h_override: Option<f64>) -> (f64, f64, f64) {
+    let (elo, h2h, form) = base;
+    match h2h_override {
+        Some(w) => {
+            let w = w.clamp(0.0, 1.0);
+            let rest = (1.0 - w) / (elo + form);
+            (elo * rest, w, form * rest)
+        }
+        None => (elo, h2h, form),
+    }
+}
+
+/// Lookback window and recency weighting for head-to-head history.
+#[derive(Debug, Clone)]
+pub struct H2hConfig {
+    /// Most recent meetings to consider
+    pub max_matches: i64,
+    /// Ignore meetings older than this many days (None = no age limit)
+    pub max_age_days: Option<i64>,
+    /// Per-meeting exponential decay, newest first (1.0 = all meetings count equally)
+    pub decay: f64,
+    /// Judge each meeting against the venue it was played at, so a win away counts for
+    /// more than a win at home; false counts every win the same wherever it happened
+    pub venue_adjusted: bool,
+}
+
+impl Default for H2hConfig {
+    fn default() -> Self {
+        Self { max_matches: 10, max_age_days: None, decay: 0.85, venue_adjusted: true }
+    }
+}
+
+impl H2hConfig {
+    /// Defaults overridden by H2H_MAX_MATCHES, H2H_MAX_AGE_DAYS, H2H_DECAY and
+    /// H2H_VENUE_ADJUSTED when set.
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        let parse = |key: &str| std::env::var(key).ok().and_then(|v| v.parse::<f64>().ok());
+        Self {
+            max_matches: parse("H2H_MAX_MATCHES").map(|v| v as i64).unwrap_or(default.max_matches).max(1),
+            max_age_days: parse("H2H_MAX_AGE_DAYS").map(|v| v as i64).or(default.max_age_days),
+            decay: parse("H2H_DECAY").unwrap_or(default.decay).clamp(0.0, 1.0),
+            venue_adjusted: std::env::var("H2H_VENUE_ADJUSTED").ok()
+                .and_then(|v| v.trim().parse::<bool>().ok())
+                .unwrap_or(default.venue_adjusted),
+        }
+    }
+}
+
+/// Oldest a served prediction may be before it is regenerated (PREDICTION_MAX_AGE_HOURS,
+/// default 12; 0 disables on-demand regeneration).
+fn prediction_max_age() -> Option<chrono::Duration> {
+    let hours = std::env::var("PREDICTION_MAX_AGE_HOURS").ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .unwrap_or(12);
+    (hours > 0).then(|| chrono::Duration::hours(hours))
+}
+
+/// Smallest move in any probability that counts as a new prediction
+/// (PREDICTION_CHANGE_EPSILON, default 0.001). Smaller moves are not stored.
+fn prediction_change_epsilon() -> f64 {
+    std::env::var("PREDICTION_CHANGE_EPSILON").ok()
+        .and_then(|v| v.parse::<f64>().ok())
+        .unwrap_or(0.001)
+        .max(0.0)
+}
+
+/// (floor, ceiling) for every outcome probability (PROBABILITY_FLOOR, default 0.02, and
+/// PROBABILITY_CEILING, default 0.98). No fixture is ever certain — injuries, red cards and
+/// plain variance remain — so the model never claims more than this.
+fn probability_bounds() -> (f64, f64) {
+    let read = |key: &str, default: f64, range: std::ops::RangeInclusive<f64>| {
+        std::env::var(key).ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|v| range.contains(v))
+            .unwrap_or(default)
+    };
+    (read("PROBABILITY_FLOOR", 0.02, 0.0..=0.3), read("PROBABILITY_CEILING", 0.98, 0.6..=1.0))
+}
+
+/// Hold each probability within [floor, ceiling] while keeping the sum at 1: outcomes
+/// outside the band are pinned to it and the others rescaled to absorb the difference.
+fn bound_probabilities(probs: &mut [f64], floor: f64, ceiling: f64) {
+    let mut pinned = vec![false; probs.len()];
+    loop {
+        let (mut pinned_mass, mut free_mass) = (0.0, 0.0);
+        for (p, &fixed) in probs.iter().zip(&pinned) {
+            if fixed { pinned_mass += p } else { free_mass += p }
+        }
+        if free_mass <= 0.0 {
+            return;
+        }
+        let scale = (1.0 - pinned_mass) / free_mass;
+        for (p, &fixed) in probs.iter_mut().zip(&pinned) {
+            if !fixed {
+                *p *= scale;
+            }
+        }
+
+        // Lift longshots first, so a capped favourite gives up only what they need
+        let outside = |breach: &dyn Fn(f64) -> bool| -> Vec<usize> {
+            (0..probs.len()).filter(|&i| !pinned[i] && breach(probs[i])).collect()
+        };
+        let mut pin = outside(&|p| p < floor);
+        if pin.is_empty() {
+            pin = outside(&|p| p > ceiling);
+        }
+        if pin.is_empty() {
+            return;
+        }
+        for i in pin {
+            probs[i] = probs[i].clamp(floor, ceiling);
+            pinned[i] = true;
+        }
+    }
+}
+
+/// True when `new` moves no probability by `epsilon` or more from `previous` and keeps
+/// its model version and data-sufficiency flag.
+fn is_immaterial_change(previous: &Prediction, new: &Prediction, epsilon: f64) -> bool {
+    let moved = |a: f64, b: f64| (a - b).abs() >= epsilon;
+    let draw_moved = match (previous.draw_probability, new.draw_probability) {
+        (Some(a), Some(b)) => moved(a, b),
+        (None, None) => false,
+        _ => true,
+    };
+    previous.model_version == new.model_version
+        && previous.insufficient_data == new.insufficient_data
+        && !moved(previous.home_win_probability, new.home_win_probability)
+        && !moved(previous.away_win_probability, new.away_win_probability)
+        && !draw_moved
+}
+
 pub struct PredictionEngine {
     elo_calculator: EloCalculator,
     nba_predictor: NbaPredictor,
+    h2h_config: H2hConfig,
+    /// Share of the form signal taken from season-to-date form rather than the decayed last 8
+    form_season_weight: f64,
+    /// Shift ELO ratings by last-5 form before the ELO model runs (ELO_FORM_ADJUSTMENT)
+    elo_form_adjustment: bool,
+    /// Football ensemble (ELO, H2H, form) weights
+    weights: (f64, f64, f64),
+    /// Share of football probabilities from the Poisson goals model
+    poisson_weight: f64,
+    /// Stored on football ensemble predictions from this engine
+    model_version: String,
+    /// Registry configurations that also predict every upcoming football match
+    candidates: Vec<ModelConfig>,
+    /// (floor, ceiling) every outcome probability is held within
+    probability_bounds: (f64, f64),
+    /// Market weight of the consensus prediction stored alongside each refresh; 0 stores none
+    market_blend_weight: f64,
 }
 
 impl PredictionEngine {
     pub fn new() -> Self {
+        Self { candidates: registered_candidates(), ..Self::from_config(&ModelConfig::baseline()) }
+    }
+
+    /// Engine for a single registry configuration, with no candidates of its own.
+    pub fn from_config(config: &ModelConfig) -> Self {
         Self {
             elo_calculator: EloCalculator::new(),
             nba_predictor: NbaPredictor::new(),
+            h2h_config: H2hConfig::from_env(),
+            form_season_weight: config.form_season_weight,
+            elo_form_adjustment: config.elo_form_adjustment,
+            weights: config.weights,
+            poisson_weight: config.poisson_weight,
+            model_version: config.model_version(),
+            candidates: Vec::new(),
+            probability_bounds: probability_bounds(),
+            market_blend_weight: market_blend_weight(),
         }
     }
 
-    /// Generate predictions for a list of matches using multiple models
+    /// Generate predictions for a list of matches using multiple models.
+    ///
+    /// Football matches are also predicted under each registered candidate configuration.
+    /// Those rows are stored first so the engine's own prediction stays the latest one.
+    /// A match whose prediction moved less than PREDICTION_CHANGE_EPSILON since the last
+    /// stored one is skipped entirely, candidates included.
     pub async fn generate_predictions(&self, pool: &SqlitePool, matches: &[Match]) -> Result<()> {
+        self.store_predictions(pool, matches, prediction_change_epsilon()).await
+    }
+
+    async fn store_predictions(&self, pool: &SqlitePool, matches: &[Match], change_epsilon: f64) -> Result<()> {
+        let candidates: Vec<PredictionEngine> = self.candidates.iter().map(PredictionEngine::from_config).collect();
+
         for match_data in matches {
             if match_data.status != "scheduled" {
                 continue;
             }
 
-            let prediction = self.predict_match_outcome(pool, match_data).await?;
+            // Totals move with pace even when the winner odds don't, so store them first
+            if match_data.sport == "basketball" {
+                if let Err(e) = self.store_total_points(pool, match_data).await {
+                    tracing::warn!("Could not store totals for {}: {}", match_data.id, e);
+                }
+            }
+
+            let (prediction, used) = self.predict_with_inputs(pool, match_data, None).await?;
+            let previous = get_prediction_by_match_id(pool, &match_data.id).await?;
+            if let Some(previous) = previous.filter(|p| is_immaterial_change(p, &prediction, change_epsilon)) {
+                tracing::debug!("Prediction for {} unchanged, not stored", match_data.id);
+                // The market may still have moved
+                self.store_consensus(pool, &previous).await;
+                continue;
+            }
+
+            if match_data.sport == "football" {
+                for candidate in &candidates {
+                    let shadow = candidate.predict_match_outcome(pool, match_data, None).await?;
+                    // Gameless teams get the same league baseline under every configuration
+                    if !shadow.insufficient_data {
+                        insert_prediction(pool, &shadow).await?;
+                    }
+                }
+            }
+
+            // Stamped after the candidates so it remains the match's latest prediction
+            let prediction = Prediction { created_at: Utc::now(), ..prediction };
             insert_prediction(pool, &prediction).await?;
+
+            // The audit snapshot is best-effort: a failure here must not drop the prediction
+            match self.snapshot_inputs(pool, match_data, &prediction, used).await {
+                Ok(inputs) => {
+                    if let Err(e) = insert_prediction_inputs(pool, &inputs).await {
+                        tracing::warn!("Could not store inputs for prediction {}: {}", prediction.id, e);
+                    }
+                }
+                Err(e) => tracing::warn!("Could not snapshot inputs for {}: {}", match_data.id, e),
+            }
+            self.store_consensus(pool, &prediction).await;
             
             tracing::info!(
                 "Generated prediction for {} vs {}: Home {:.2}%, Away {:.2}%{}",
@@ -53,13 +314,139 @@ impl PredictionEngine {
         Ok(())
     }
 
+    /// Blend a stored prediction with its match's current market at `market_blend_weight`
+    /// and keep it in consensus_predictions. Best-effort; nothing is stored at weight 0, without
+    /// odds or with an incomplete market.
+    async fn store_consensus(&self, pool: &SqlitePool, prediction: &Prediction) {
+        if self.market_blend_weight == 0.0 {
+            return;
+        }
+        let odds = match get_market_odds(pool, &prediction.match_id).await {
+            Ok(Some(odds)) => odds,
+            Ok(None) => return,
+            Err(e) => {
+                tracing::warn!("Could not load odds for consensus on {}: {}", prediction.match_id, e);
+                return;
+            }
+        };
+        let Some(consensus) = blend_with_market(prediction, &odds, self.market_blend_weight) else { return };
+        if let Err(e) = insert_consensus_prediction(pool, &prediction.id, self.market_blend_weight, &consensus).await {
+            tracing::warn!("Could not store consensus for {}: {}", prediction.match_id, e);
+        }
+    }
+
+    async fn store_total_points(&self, pool: &SqlitePool, match_data: &Match) -> Result<()> {
+        let estimate = self.nba_predictor.predict_total_points(pool, match_data).await?;
+        upsert_total_prediction(pool, &estimate.to_prediction(&match_data.id)).await
+    }
+
+    /// Latest prediction for a match, regenerated on the spot when it is older than
+    /// PREDICTION_MAX_AGE_HOURS and the match has yet to start. Keeps reads fresh when
+    /// the scheduler is paused; if regeneration fails the old prediction is returned.
+    pub async fn fresh_prediction(&self, pool: &SqlitePool, match_data: &Match) -> Result<Option<Prediction>> {
+        let existing = get_prediction_by_match_id(pool, &match_data.id).await?;
+        let Some(max_age) = prediction_max_age() else { return Ok(existing) };
+
+        let now = Utc::now();
+        let is_stale = existing.as_ref().is_some_and(|p| now - p.created_at > max_age);
+        if !is_stale || match_data.status != "scheduled" || match_data.match_date <= now {
+            return Ok(existing);
+        }
+
+        // Always store: an unchanged prediction would otherwise stay stale and be
+        // regenerated on every read
+        if let Err(e) = self.store_predictions(pool, std::slice::from_ref(match_data), 0.0).await {
+            tracing::warn!("Could not regenerate stale prediction for {}: {}", match_data.id, e);
+            return Ok(existing);
+        }
+        Ok(get_prediction_by_match_id(pool, &match_data.id).await?.or(existing))
+    }
+
+    /// Price a fixture that is not in the schedule. At a neutral venue the fixture is
+    /// predicted both ways round and averaged, so neither side keeps home advantage.
+    /// `h2h_weight` overrides the football ensemble's H2H weight for this call only.
+    pub async fn predict_hypothetical(
+        &self,
+        pool: &SqlitePool,
+        home: &Team,
+        away: &Team,
+        neutral: bool,
+        h2h_weight: Option<f64>,
+    ) -> Result<Prediction> {
+        let fixture = |home: &Team, away: &Team| Match {
+            id: format!("hypothetical_{}_{}", home.id, away.id),
+            home_team_id: home.id.clone(),
+            away_team_id: away.id.clone(),
+            home_team_name: home.name.clone(),
+            away_team_name: away.name.clone(),
+            sport: home.sport.clone(),
+            league: home.league.clone(),
+            match_date: Utc::now(),
+            status: "scheduled".to_string(),
+            home_score: None,
+            away_score: None,
+            venue: None,
+            city: None,
+            tiebreak_winner: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        let mut prediction = self.predict_match_outcome(pool, &fixture(home, away), h2h_weight).await?;
+        if neutral {
+            let mirrored = self.predict_match_outcome(pool, &fixture(away, home), h2h_weight).await?;
+            prediction.home_win_probability = (prediction.home_win_probability + mirrored.away_win_probability) / 2.0;
+            prediction.away_win_probability = (prediction.away_win_probability + mirrored.home_win_probability) / 2.0;
+            prediction.draw_probability = prediction.draw_probability
+                .zip(mirrored.draw_probability)
+                .map(|(a, b)| (a + b) / 2.0);
+            prediction.confidence_score = (prediction.confidence_score + mirrored.confidence_score) / 2.0;
+        }
+        Ok(prediction)
+    }
+
     /// Predict match outcome using ensemble of models.
     /// NBA games are routed to the dedicated NbaPredictor (5-component ensemble).
-    /// Football games use the existing ELO + H2H + form ensemble.
-    pub async fn predict_match_outcome(&self, pool: &SqlitePool, match_data: &Match) -> Result<Prediction> {
-        // ── NBA: delegate to the sport-specific engine ───────────────────────
-        if match_data.sport == "basketball" {
-            return self.nba_predictor.predict(pool, match_data).await;
+    /// Football games use the existing ELO + H2H + form ensemble, with the H2H weight
+    /// optionally overridden (see `football_weights`).
+    pub async fn predict_match_outcome(
+        &self,
+        pool: &SqlitePool,
+        match_data: &Match,
+        h2h_weight: Option<f64>,
+    ) -> Result<Prediction> {
+        Ok(self.predict_with_inputs(pool, match_data, h2h_weight).await?.0)
+    }
+
+    /// `predict_match_outcome` together with the component values it was built from.
+    async fn predict_with_inputs(
+        &self,
+        pool: &SqlitePool,
+        match_data: &Match,
+        h2h_weight: Option<f64>,
+    ) -> Result<(Prediction, UsedInputs)> {
+        let (prediction, used) = self.predict_unbounded(pool, match_data, h2h_weight).await?;
+        let (floor, ceiling) = self.probability_bounds;
+        let mut probs = vec![prediction.home_win_probability, prediction.away_win_probability];
+        probs.extend(prediction.draw_probability);
+        bound_probabilities(&mut probs, floor, ceiling);
+        let prediction = Prediction {
+            home_win_probability: probs[0],
+            away_win_probability: probs[1],
+            draw_probability: probs.get(2).copied(),
+            ..prediction
+        };
+        Ok((prediction, used))
+    }
+
+    async fn predict_unbounded(
+        &self,
+        pool: &SqlitePool,
+        match_data: &Match,
+        h2h_weight: Option<f64>,
+    ) -> Result<(Prediction, UsedInputs)> {
+        if match_data.sport == "basketball" && h2h_weight.is_some() {
+            anyhow::bail!("h2h_weight override is only supported for football");
         }
 
         let home_team = get_team_by_id(pool, &match_data.home_team_id).await?
@@ -67,27 +454,60 @@ impl PredictionEngine {
         let away_team = get_team_by_id(pool, &match_data.away_team_id).await?
             .ok_or_else(|| anyhow::anyhow!("Away team not found"))?;
 
-        // Model 1: ELO-based prediction
-        let (elo_home_prob, elo_away_prob, elo_draw_prob) = self.elo_calculator.win_probability(
+        // A team that has never finished a game has only a default rating; don't dress that up
+        if let Some(baseline) = self.insufficient_data_prediction(pool, match_data, &home_team, &away_team).await? {
+            return Ok((baseline, UsedInputs::ratings_only(&home_team, &away_team)));
+        }
+
+        // ── NBA: delegate to the sport-specific engine ───────────────────────
+        if match_data.sport == "basketball" {
+            let prediction = self.nba_predictor.predict(pool, match_data).await?;
+            let mut used = UsedInputs::ratings_only(&home_team, &away_team);
+            // The ML model has no fixed ensemble weights
+            if prediction.model_version.starts_with("nba_v3") {
+                let home_adv = get_nba_advanced_stats(pool, &home_team.id).await.ok().flatten();
+                let away_adv = get_nba_advanced_stats(pool, &away_team.id).await.ok().flatten();
+                used.weights = match (home_adv, away_adv) {
+                    (Some(h), Some(a)) => NbaPredictor::component_weights(true, h.games_played, a.games_played),
+                    _ => NbaPredictor::component_weights(false, 0, 0),
+                };
+            }
+            return Ok((prediction, used));
+        }
+
+        // Model 1: ELO-based prediction, home bonus scaled by any per-team venue override
+        // and, when enabled, ratings shifted by last-5 form
+        let (home_form, away_form) = if self.elo_form_adjustment {
+            (
+                get_team_current_stats(pool, &home_team.id).await?.map(|s| s.form),
+                get_team_current_stats(pool, &away_team.id).await?.map(|s| s.form),
+            )
+        } else {
+            (None, None)
+        };
+        let (elo_home_prob, elo_away_prob, elo_draw_prob) = self.elo_calculator.win_probability_with_form(
             home_team.elo_rating,
             away_team.elo_rating,
+            home_form.as_deref(),
+            away_form.as_deref(),
             &match_data.sport,
+            home_advantage_for(&match_data.league) * get_home_advantage(pool, &home_team.id).await?,
         );
 
         // Model 2: Head-to-head and form-based prediction
-        let (h2h_home_prob, h2h_away_prob, h2h_draw_prob) = self.head_to_head_prediction(
+        let (h2h_probs, h2h_meetings) = self.head_to_head_prediction(
             pool, &home_team, &away_team, &match_data.sport
         ).await?;
+        let (h2h_home_prob, h2h_away_prob, h2h_draw_prob) = h2h_probs;
 
         // Model 3: Recent form prediction
-        let (form_home_prob, form_away_prob, form_draw_prob) = self.form_based_prediction(
-            pool, &home_team, &away_team, &match_data.sport
-        ).await?;
+        let home_form = self.blended_form(pool, &home_team.id, true, &match_data.sport).await?;
+        let away_form = self.blended_form(pool, &away_team.id, false, &match_data.sport).await?;
+        let (form_home_prob, form_away_prob, form_draw_prob) =
+            self.form_based_prediction(&home_form, &away_form, &match_data.sport)?;
 
         // Ensemble: Weighted average of models
-        let elo_weight = 0.5;
-        let h2h_weight = 0.3;
-        let form_weight = 0.2;
+        let (elo_weight, h2h_weight, form_weight) = football_weights(self.weights, h2h_weight);
 
         let final_home_prob = elo_home_prob * elo_weight + h2h_home_prob * h2h_weight + form_home_prob * form_weight;
         let final_away_prob = elo_away_prob * elo_weight + h2h_away_prob * h2h_weight + form_away_prob * form_weight;
@@ -100,9 +520,27 @@ impl PredictionEngine {
 
         // Normalize probabilities to sum to 1
         let total = final_home_prob + final_away_prob + final_draw_prob.unwrap_or(0.0);
-        let normalized_home = final_home_prob / total;
-        let normalized_away = final_away_prob / total;
-        let normalized_draw = final_draw_prob.map(|d| d / total);
+        let mut normalized_home = final_home_prob / total;
+        let mut normalized_away = final_away_prob / total;
+        let mut normalized_draw = final_draw_prob.map(|d| d / total);
+
+        // Model 4: Poisson goals on the same expected scores as /simulate and /totals
+        let poisson = if match_data.sport == "football" && self.poisson_weight > 0.0 {
+            let (home_rate, away_rate) = expected_scores(pool, match_data).await?;
+            Some(ScoreModel::Poisson { home_rate, away_rate })
+        } else {
+            None
+        };
+        let poisson_probs = poisson.map(|goals| goals.analytic_1x2());
+        let rest = if poisson.is_some() { 1.0 - self.poisson_weight } else { 1.0 };
+        let mut weights = vec![("elo", elo_weight * rest), ("h2h", h2h_weight * rest), ("form", form_weight * rest)];
+        if let Some((goals_home, goals_draw, goals_away)) = poisson_probs {
+            let w = self.poisson_weight;
+            weights.push(("poisson", w));
+            normalized_home = (1.0 - w) * normalized_home + w * goals_home;
+            normalized_away = (1.0 - w) * normalized_away + w * goals_away;
+            normalized_draw = normalized_draw.map(|d| (1.0 - w) * d + w * goals_draw);
+        }
 
         // NBA rest-day adjustment (compute once, reuse for both final probs and confidence).
         let rest_adj = if match_data.sport == "basketball" {
@@ -125,114 +563,215 @@ impl PredictionEngine {
         // Old formula was inverted: strong ELO favourites disagreed with the league-average
         // H2H/form fallbacks → high std_dev → low confidence for strong predictions.
         // New formula: a decisive ensemble + agreeing models = high confidence.
-        let home_probs = [elo_home_prob, h2h_home_prob, form_home_prob];
-        let mean_hp = home_probs.iter().sum::<f64>() / 3.0;
-        let std_dev = (home_probs.iter().map(|&p| (p - mean_hp).powi(2)).sum::<f64>() / 3.0).sqrt();
-        let agreement = (1.0 - std_dev / 0.15).clamp(0.0, 1.0);
-
-        let best_prob = final_home.max(final_away).max(normalized_draw.unwrap_or(0.0));
-        let strength = ((best_prob - 0.5) * 2.5).clamp(0.0, 1.0);
+        let mut home_probs = vec![elo_home_prob, h2h_home_prob, form_home_prob];
+        home_probs.extend(poisson_probs.map(|(home, _, _)| home));
+        let agreement = model_agreement(&home_probs);
+        let confidence = prediction_confidence(final_home, normalized_draw, final_away, agreement);
 
-        let confidence = (0.40_f64 + 0.35 * strength + 0.25 * agreement).clamp(0.40, 0.95);
-
-        Ok(Prediction {
+        let prediction = Prediction {
             id: Uuid::new_v4().to_string(),
             match_id: match_data.id.clone(),
             home_win_probability: final_home,
             away_win_probability: final_away,
             draw_probability: normalized_draw,
-            model_version: "ensemble_v2.0".to_string(),
+            model_version: self.model_version.clone(),
             confidence_score: confidence,
             created_at: Utc::now(),
+            insufficient_data: false,
+            most_likely_score: poisson.and_then(|goals| goals.most_likely_score()).map(|(h, a)| format!("{}-{}", h, a)),
+        };
+        let used = UsedInputs {
+            form_rates: form_is_reliable(&home_form, &away_form).then_some((home_form.rate, away_form.rate)),
+            h2h: Some((h2h_probs, h2h_meetings)),
+            weights,
+            ..UsedInputs::ratings_only(&home_team, &away_team)
+        };
+        Ok((prediction, used))
+    }
+
+    /// Flagged `insufficient_data` prediction when either team has no finished matches; None
+    /// when both have history. Cold start (e.g. opening week after a wipe) prices the match
+    /// off preseason ratings when every gameless side has one, otherwise league-average rates.
+    async fn insufficient_data_prediction(
+        &self,
+        pool: &SqlitePool,
+        match_data: &Match,
+        home_team: &Team,
+        away_team: &Team,
+    ) -> Result<Option<Prediction>> {
+        let played = MatchFilter { status: Some("finished"), scored_only: true, limit: Some(1), ..Default::default() };
+        let home_gameless = get_matches_for_team(pool, &home_team.id, &played).await?.is_empty();
+        let away_gameless = get_matches_for_team(pool, &away_team.id, &played).await?.is_empty();
+        if !home_gameless && !away_gameless {
+            return Ok(None);
+        }
+
+        let preseason = get_preseason_ratings(pool).await?;
+        let rating = |team: &Team, gameless: bool| {
+            if gameless { preseason.get(&team.id).copied() } else { Some(team.elo_rating) }
+        };
+        if let (Some(home_rating), Some(away_rating)) = (rating(home_team, home_gameless), rating(away_team, away_gameless)) {
+            let (home, away, draw) = self.elo_calculator.win_probability_with_hca(
+                home_rating,
+                away_rating,
+                &match_data.sport,
+                &match_data.league,
+                get_home_advantage(pool, &home_team.id).await?,
+            );
+            return Ok(Some(Prediction {
+                id: Uuid::new_v4().to_string(),
+                match_id: match_data.id.clone(),
+                home_win_probability: home,
+                away_win_probability: away,
+                draw_probability: draw,
+                model_version: "preseason_elo".to_string(),
+                confidence_score: INSUFFICIENT_DATA_CONFIDENCE,
+                created_at: Utc::now(),
+                insufficient_data: true,
+                most_likely_score: None,
+            }));
+        }
+
+        let (home_rate, draw_rate, n): (Option<f64>, Option<f64>, i64) = sqlx::query_as(
+            r#"SELECT AVG(home_score > away_score), AVG(home_score = away_score), COUNT(*) FROM matches
+               WHERE status = 'finished' AND home_score IS NOT NULL AND away_score IS NOT NULL
+                 AND sport = ? AND league = ?"#,
+        )
+        .bind(&match_data.sport)
+        .bind(&match_data.league)
+        .fetch_one(pool)
+        .await?;
+
+        // Too few league results to trust: fall back to typical home/draw rates
+        let is_football = match_data.sport == "football";
+        let (home, draw) = match (home_rate, draw_rate) {
+            (Some(h), Some(d)) if n >= 20 => (h, d),
+            _ if is_football => (0.45, 0.26),
+            _ => (0.57, 0.0),
+        };
+        let (home, draw) = if is_football { (home, Some(draw)) } else { (home / (1.0 - draw).max(f64::EPSILON), None) };
+
+        Ok(Some(Prediction {
+            id: Uuid::new_v4().to_string(),
+            match_id: match_data.id.clone(),
+            home_win_probability: home,
+            away_win_probability: 1.0 - home - draw.unwrap_or(0.0),
+            draw_probability: draw,
+            model_version: "league_baseline".to_string(),
+            confidence_score: INSUFFICIENT_DATA_CONFIDENCE,
+            created_at: Utc::now(),
+            insufficient_data: true,
+            most_likely_score: None,
+        }))
+    }
+
+    /// Snapshot the ratings, form, H2H and weights a prediction was built from, plus rest days.
+    async fn snapshot_inputs(&self, pool: &SqlitePool, match_data: &Match, prediction: &Prediction, used: UsedInputs) -> Result<PredictionInputs> {
+        let (h2h_probs, h2h_meetings) = used.h2h.unzip();
+        Ok(PredictionInputs {
+            prediction_id: prediction.id.clone(),
+            match_id: match_data.id.clone(),
+            model_version: prediction.model_version.clone(),
+            home_elo: used.home_elo,
+            away_elo: used.away_elo,
+            home_form_rate: used.form_rates.map(|(home, _)| home),
+            away_form_rate: used.form_rates.map(|(_, away)| away),
+            h2h_meetings: h2h_meetings.unwrap_or(0),
+            h2h_home_prob: h2h_probs.map(|(home, _, _)| home),
+            h2h_away_prob: h2h_probs.map(|(_, away, _)| away),
+            h2h_draw_prob: h2h_probs.and_then(|(_, _, draw)| draw),
+            home_rest_days: self.days_rest(pool, &match_data.home_team_id, match_data.match_date).await?,
+            away_rest_days: self.days_rest(pool, &match_data.away_team_id, match_data.match_date).await?,
+            weights: used.weights.into_iter().map(|(name, w)| (name.to_string(), w)).collect(),
+            captured_at: Utc::now(),
         })
     }
 
-    /// Head-to-head prediction based on historical matchups
+    /// Head-to-head prediction based on historical matchups, with the number of meetings
+    /// it drew on (0 when it fell back to the league average)
     async fn head_to_head_prediction(&self, 
         pool: &SqlitePool, 
         home_team: &Team, 
         away_team: &Team,
         sport: &str
-    ) -> Result<(f64, f64, Option<f64>)> {
+    ) -> Result<(ComponentProbs, usize)> {
         // Get historical matchups between these teams
         let h2h_matches = self.get_head_to_head_matches(pool, &home_team.id, &away_team.id).await?;
         
         if h2h_matches.is_empty() {
             // No historical data, fall back to league averages
-            return self.league_average_prediction(sport);
+            return Ok((self.league_average_prediction(sport)?, 0));
         }
 
-        let mut home_wins = 0;
-        let mut away_wins = 0;
-        let mut draws = 0;
+        // Each meeting is scored from the current home side's view as its result minus what
+        // the league average expected at that meeting's venue: winning away beats a lower
+        // baseline than winning at home. The averaged excess is venue-neutral dominance,
+        // which is then laid on top of the current fixture's own home/away baseline.
+        let (default_home, default_away, default_draw) = self.league_average_prediction(sport)?;
+        let default_draw = default_draw.unwrap_or(0.0);
+
+        // Rows are newest-first, so i=0 is the most recent meeting and carries full weight.
+        // (win, draw, loss) excess for the current home team
+        let mut excess = (0.0_f64, 0.0_f64, 0.0_f64);
+        let mut weight_total = 0.0_f64;
         let mut total_matches = 0;
 
         for match_data in &h2h_matches {
-            if let (Some(home_score), Some(away_score)) = (match_data.home_score, match_data.away_score) {
+            if let Some((home_score, away_score)) = match_data.deciding_score() {
+                let weight = self.h2h_config.decay.powi(total_matches);
                 total_matches += 1;
-                match home_score.cmp(&away_score) {
-                    std::cmp::Ordering::Greater => {
-                        if match_data.home_team_id == home_team.id {
-                            home_wins += 1;
-                        } else {
-                            away_wins += 1;
-                        }
-                    }
-                    std::cmp::Ordering::Less => {
-                        if match_data.away_team_id == away_team.id {
-                            away_wins += 1;
-                        } else {
-                            home_wins += 1;
-                        }
-                    }
-                    std::cmp::Ordering::Equal => draws += 1,
-                }
+                weight_total += weight;
+
+                let at_home = match_data.home_team_id == home_team.id;
+                let (own, other) = if at_home { (home_score, away_score) } else { (away_score, home_score) };
+                let result = match own.cmp(&other) {
+                    std::cmp::Ordering::Greater => (1.0, 0.0, 0.0),
+                    std::cmp::Ordering::Equal => (0.0, 1.0, 0.0),
+                    std::cmp::Ordering::Less => (0.0, 0.0, 1.0),
+                };
+                let (expected_win, expected_loss) = if at_home || !self.h2h_config.venue_adjusted {
+                    (default_home, default_away)
+                } else {
+                    (default_away, default_home)
+                };
+                excess.0 += weight * (result.0 - expected_win);
+                excess.1 += weight * (result.1 - default_draw);
+                excess.2 += weight * (result.2 - expected_loss);
             }
         }
 
-        if total_matches == 0 {
-            return self.league_average_prediction(sport);
+        if total_matches == 0 || weight_total <= 0.0 {
+            return Ok((self.league_average_prediction(sport)?, 0));
         }
 
-        let home_prob = home_wins as f64 / total_matches as f64;
-        let away_prob = away_wins as f64 / total_matches as f64;
-        let draw_prob = if sport == "football" {
-            Some(draws as f64 / total_matches as f64)
-        } else {
-            None
-        };
-
         // Regression to mean: scales down with sample size.
         // With 1 H2H match we regress 90%, with 10+ we regress ~30%.
         let regression_factor = (1.0 - (total_matches as f64).sqrt() / 4.0).clamp(0.30, 0.90);
-        let (default_home, default_away, default_draw) = self.league_average_prediction(sport)?;
-        
-        let adjusted_home = home_prob * (1.0 - regression_factor) + default_home * regression_factor;
-        let adjusted_away = away_prob * (1.0 - regression_factor) + default_away * regression_factor;
-        let adjusted_draw = match (draw_prob, default_draw) {
-            (Some(draw), Some(def_draw)) => Some(draw * (1.0 - regression_factor) + def_draw * regression_factor),
-            _ => None,
-        };
+        let shrink = (1.0 - regression_factor) / weight_total;
+
+        // Excesses sum to zero, so only a clamped negative needs renormalising
+        let home = (default_home + shrink * excess.0).max(0.0);
+        let away = (default_away + shrink * excess.2).max(0.0);
+        let draw = if sport == "football" { (default_draw + shrink * excess.1).max(0.0) } else { 0.0 };
+        let total = home + away + draw;
 
-        Ok((adjusted_home, adjusted_away, adjusted_draw))
+        Ok(((home / total, away / total, (sport == "football").then_some(draw / total)), total_matches as usize))
     }
 
     /// Form-based prediction using each team's real recent results from the database.
     ///
     /// Uses home team's last 8 HOME games and away team's last 8 AWAY games — the contextual
     /// split (home form vs away form) is more predictive than overall form.
-    /// Results are exponentially decayed so the most recent game weighs most heavily.
-    async fn form_based_prediction(&self,
-        pool: &SqlitePool,
-        home_team: &Team,
-        away_team: &Team,
+    /// Results are exponentially decayed so the most recent game weighs most heavily, then
+    /// blended with season-to-date form so a short streak doesn't swing the estimate alone
+    /// (see `blended_form`).
+    fn form_based_prediction(&self,
+        home_form: &RollingForm,
+        away_form: &RollingForm,
         sport: &str,
     ) -> Result<(f64, f64, Option<f64>)> {
-        let home_form = self.rolling_form(pool, &home_team.id, true, sport).await?;
-        let away_form = self.rolling_form(pool, &away_team.id, false, sport).await?;
-
         // Not enough real data yet — fall back to league average
-        if home_form.sample_size < 3 || away_form.sample_size < 3 {
+        if !form_is_reliable(home_form, away_form) {
             return self.league_average_prediction(sport);
         }
 
@@ -256,6 +795,54 @@ impl PredictionEngine {
         }
     }
 
+    /// Weighted recent points rate across home and away games (0 = all losses, 1 = all wins).
+    pub async fn team_form_rate(&self, pool: &SqlitePool, team_id: &str, sport: &str) -> Result<f64> {
+        let home = self.rolling_form(pool, team_id, true, sport).await?;
+        let away = self.rolling_form(pool, team_id, false, sport).await?;
+        let games = home.sample_size + away.sample_size;
+        if games == 0 {
+            return Ok(0.5);
+        }
+        Ok((home.rate * home.sample_size as f64 + away.rate * away.sample_size as f64) / games as f64)
+    }
+
+    /// Recent contextual form blended with season-to-date contextual form using
+    /// FORM_SEASON_WEIGHT. Sample size stays that of the recent window.
+    async fn blended_form(
+        &self,
+        pool: &SqlitePool,
+        team_id: &str,
+        home_context: bool,
+        sport: &str,
+    ) -> Result<RollingForm> {
+        let recent = self.rolling_form(pool, team_id, home_context, sport).await?;
+        let season = self.season_form(pool, team_id, home_context, sport).await?;
+        if season.sample_size == 0 {
+            return Ok(recent);
+        }
+        let w = self.form_season_weight;
+        Ok(RollingForm { rate: (1.0 - w) * recent.rate + w * season.rate, sample_size: recent.sample_size })
+    }
+
+    /// Undecayed contextual points rate over every finished game this season. The whole
+    /// stored history counts as the current season, as it does for team_stats.
+    async fn season_form(
+        &self,
+        pool: &SqlitePool,
+        team_id: &str,
+        home_context: bool,
+        sport: &str,
+    ) -> Result<RollingForm> {
+        let matches = get_matches_for_team(pool, team_id, &contextual_results(home_context, sport, None)).await?;
+
+        if matches.is_empty() {
+            return Ok(RollingForm { rate: 0.5, sample_size: 0 });
+        }
+        let max_pts = if sport == "football" { 3.0_f64 } else { 1.0_f64 };
+        let pts: f64 = matches.iter().map(|m| contextual_points(m, home_context, sport)).sum();
+        Ok(RollingForm { rate: pts / (max_pts * matches.len() as f64), sample_size: matches.len() })
+    }
+
     /// Compute exponentially-weighted recent form for a team in a specific playing context.
     ///
     /// `home_context = true`  → query only games the team played at home
@@ -267,29 +854,7 @@ impl PredictionEngine {
         home_context: bool,
         sport: &str,
     ) -> Result<RollingForm> {
-        let matches: Vec<Match> = if home_context {
-            sqlx::query_as::<_, Match>(
-                "SELECT * FROM matches
-                 WHERE home_team_id = ? AND status = 'finished'
-                   AND home_score IS NOT NULL AND sport = ?
-                 ORDER BY match_date DESC LIMIT 8",
-            )
-            .bind(team_id)
-            .bind(sport)
-            .fetch_all(pool)
-            .await?
-        } else {
-            sqlx::query_as::<_, Match>(
-                "SELECT * FROM matches
-                 WHERE away_team_id = ? AND status = 'finished'
-                   AND away_score IS NOT NULL AND sport = ?
-                 ORDER BY match_date DESC LIMIT 8",
-            )
-            .bind(team_id)
-            .bind(sport)
-            .fetch_all(pool)
-            .await?
-        };
+        let matches = get_matches_for_team(pool, team_id, &contextual_results(home_context, sport, Some(8))).await?;
 
         if matches.is_empty() {
             return Ok(RollingForm { rate: 0.5, sample_size: 0 });
@@ -304,20 +869,7 @@ impl PredictionEngine {
             // Rows are newest-first (ORDER BY DESC), so i=0 is the most recent match.
             let decay = 0.85_f64.powi(i as i32);
 
-            let pts = match (m.home_score, m.away_score) {
-                (Some(hs), Some(as_)) => {
-                    if home_context {
-                        if hs > as_ { max_pts }
-                        else if hs == as_ && sport == "football" { 1.0 }
-                        else { 0.0 }
-                    } else {
-                        if as_ > hs { max_pts }
-                        else if as_ == hs && sport == "football" { 1.0 }
-                        else { 0.0 }
-                    }
-                }
-                _ => max_pts * 0.5, // unknown score → assume average
-            };
+            let pts = contextual_points(m, home_context, sport);
 
             weighted_pts += decay * pts;
             weight_total += decay * max_pts;
@@ -344,60 +896,30 @@ impl PredictionEngine {
         }
     }
 
-    /// Get historical head-to-head matches
+    /// Get historical head-to-head matches within the configured lookback window
     async fn get_head_to_head_matches(&self, pool: &SqlitePool, team1_id: &str, team2_id: &str) -> Result<Vec<Match>> {
-        let rows = sqlx::query_as::<_, Match>(
-            r#"
-            SELECT * FROM matches 
-            WHERE ((home_team_id = ? AND away_team_id = ?) 
-                OR (home_team_id = ? AND away_team_id = ?))
-                AND status = 'finished'
-            ORDER BY match_date DESC 
-            LIMIT 10
-            "#
-        )
-        .bind(team1_id)
-        .bind(team2_id)
-        .bind(team2_id)
-        .bind(team1_id)
-        .fetch_all(pool)
+        let rows = get_matches_for_team(pool, team1_id, &MatchFilter {
+            opponent: Some(team2_id),
+            status: Some("finished"),
+            after: self.h2h_config.max_age_days.map(|days| Utc::now() - chrono::Duration::days(days)),
+            limit: Some(self.h2h_config.max_matches),
+            ..Default::default()
+        })
         .await?;
 
         Ok(rows)
     }
 
-    /// Calculate confidence score based on model agreement
-    fn calculate_confidence_score(&self, 
-        elo_probs: (f64, f64, Option<f64>),
-        h2h_probs: (f64, f64, Option<f64>),
-        form_probs: (f64, f64, Option<f64>),
-    ) -> f64 {
-        let models = vec![
-            vec![elo_probs.0, elo_probs.1],
-            vec![h2h_probs.0, h2h_probs.1],
-            vec![form_probs.0, form_probs.1],
-        ];
-
-        // Calculate standard deviation across models for home win probability
-        let home_probs: Vec<f64> = models.iter().map(|m| m[0]).collect();
-        let mean_home = home_probs.iter().sum::<f64>() / home_probs.len() as f64;
-        let variance = home_probs.iter().map(|&p| (p - mean_home).powi(2)).sum::<f64>() / home_probs.len() as f64;
-        let std_dev = variance.sqrt();
-
-        // Higher standard deviation = lower confidence
-        // Map std_dev (0.0 to ~0.2) to confidence (1.0 to 0.5)
-        let confidence = (1.0 - (std_dev / 0.2).min(0.5)).max(0.5);
-        
-        confidence
-    }
-
     /// Generate market edge analysis using real odds from The Odds API.
     ///
     /// Simulated odds are deliberately NOT used as a fallback — the previous simulated
     /// formula (market = our_prob ± fixed offset) had zero overround after devigging,
     /// which made every match show an identical 5% edge regardless of teams.
     /// Real edges only exist when we have genuine market disagreement.
-    pub async fn find_market_edges(&self, pool: &SqlitePool) -> Result<Vec<crate::models::Edge>> {
+    ///
+    /// One entry per match on its best side, or with `per_outcome` one entry for every
+    /// side (home / draw / away) above the threshold.
+    pub async fn find_market_edges(&self, pool: &SqlitePool, per_outcome: bool) -> Result<Vec<crate::models::Edge>> {
         let upcoming_matches = crate::db::get_upcoming_matches(pool, None).await?;
         let mut edges = Vec::new();
 
@@ -411,32 +933,20 @@ impl PredictionEngine {
                 continue;
             };
 
-            // Devig: remove bookmaker overround to get true implied probabilities
-            let (implied_home, implied_draw, implied_away) =
-                devig(live.home_odds, live.draw_odds, live.away_odds);
-
-            // Edge = our probability − devigged market probability (positive = value bet)
-            let home_edge = our_prediction.home_win_probability - implied_home;
-            let away_edge = our_prediction.away_win_probability - implied_away;
-            let draw_edge = match (our_prediction.draw_probability, implied_draw) {
-                (Some(ours), Some(mkt)) => ours - mkt,
-                _ => 0.0,
-            };
-
-            let max_edge = home_edge.max(away_edge).max(draw_edge);
-
-            if max_edge > 0.03 {
+            let settings = EdgeSettings::for_sport(&match_data.sport);
+            for (side, edge_value) in qualifying_edges(&our_prediction, &live, &settings, per_outcome) {
                 edges.push(crate::models::Edge {
                     match_id: match_data.id.clone(),
-                    match_info: match_data,
-                    our_prediction,
+                    match_info: match_data.clone(),
+                    our_prediction: our_prediction.clone(),
                     market_home_odds: live.home_odds,
                     market_away_odds: live.away_odds,
                     market_draw_odds: live.draw_odds,
-                    edge_value: max_edge,
+                    edge_value,
+                    side: side.to_string(),
                     is_live_odds: true,
-                    bookmaker: Some(live.bookmaker),
-                    odds_fetched_at: Some(live.fetched_at),
+                    bookmaker: Some(live.bookmaker.clone()),
+                    odds_fetched_at: Some(live.fetched_at.clone()),
                 });
             }
         }
@@ -445,6 +955,118 @@ impl PredictionEngine {
         Ok(edges)
     }
 
+    /// Edges plus the reason when there are none: no odds key, no odds stored yet,
+    /// or odds present but nothing above the threshold.
+    pub async fn edge_report(&self, pool: &SqlitePool, per_outcome: bool) -> Result<crate::models::EdgeReport> {
+        use crate::models::{EdgeReport, EdgeStatus};
+
+        let edges = self.find_market_edges(pool, per_outcome).await?;
+        let thresholds = format!(
+            "{:.0}% football / {:.0}% basketball",
+            EdgeSettings::for_sport("football").threshold * 100.0,
+            EdgeSettings::for_sport("basketball").threshold * 100.0,
+        );
+        let (status, message) = if !edges.is_empty() {
+            (EdgeStatus::EdgesFound, format!("{} edge(s) above {}", edges.len(), thresholds))
+        } else {
+            let odds_rows: i64 = sqlx::query_scalar(
+                "SELECT COUNT(*) FROM market_odds o JOIN matches m ON m.id = o.match_id
+                 WHERE m.match_date > datetime('now')",
+            )
+            .fetch_one(pool)
+            .await?;
+            let has_key = std::env::var("ODDS_API_KEY").is_ok_and(|k| !k.is_empty());
+            match (odds_rows, has_key) {
+                (0, false) => (
+                    EdgeStatus::NoOddsKey,
+                    "No market odds available: ODDS_API_KEY is not configured".to_string(),
+                ),
+                (0, true) => (
+                    EdgeStatus::NoMarketOdds,
+                    "No market odds available yet: odds sync from The Odds API every 12 hours".to_string(),
+                ),
+                _ => (
+                    EdgeStatus::NoEdges,
+                    format!("No current edges: no prediction beats the market by more than {}", thresholds),
+                ),
+            }
+        };
+        Ok(EdgeReport { edges, status, message })
+    }
+
+    /// Per-match explanation of why each upcoming match did or did not produce an edge.
+    pub async fn diagnose_market_edges(&self, pool: &SqlitePool) -> Result<Vec<crate::models::EdgeDiagnostic>> {
+        let upcoming_matches = crate::db::get_upcoming_matches(pool, None).await?;
+        let mut diagnostics = Vec::new();
+
+        for match_data in upcoming_matches {
+            let prediction = get_prediction_by_match_id(pool, &match_data.id).await?;
+            let odds = get_market_odds(pool, &match_data.id).await.ok().flatten();
+
+            let settings = EdgeSettings::for_sport(&match_data.sport);
+            let edge_value = match (&prediction, &odds) {
+                (Some(p), Some(o)) => best_edge(p, o),
+                _ => None,
+            };
+            let reason = match (&prediction, &odds, edge_value) {
+                (None, _, _) => "no prediction",
+                (Some(_), None, _) => "no odds",
+                (Some(_), Some(_), None) => "incomplete odds",
+                (_, Some(o), _) if !settings.accepts_market(o) => "overround out of band",
+                (_, _, Some(e)) if e > settings.threshold => "edge",
+                _ => "below threshold",
+            };
+
+            diagnostics.push(crate::models::EdgeDiagnostic {
+                match_id: match_data.id,
+                home_team_name: match_data.home_team_name,
+                away_team_name: match_data.away_team_name,
+                has_prediction: prediction.is_some(),
+                has_odds: odds.is_some(),
+                edge_value,
+                threshold: settings.threshold,
+                reason: reason.to_string(),
+            });
+        }
+
+        Ok(diagnostics)
+    }
+
+    /// Every upcoming match with stored odds, ranked by how far we disagree with the
+    /// market in either direction. Unlike edges there is no threshold.
+    pub async fn find_market_divergence(&self, pool: &SqlitePool) -> Result<Vec<crate::models::MarketDivergence>> {
+        let upcoming_matches = crate::db::get_upcoming_matches(pool, None).await?;
+        let mut ranked = Vec::new();
+
+        for match_data in upcoming_matches {
+            let Some(prediction) = get_prediction_by_match_id(pool, &match_data.id).await? else {
+                continue;
+            };
+            let Some(odds) = get_market_odds(pool, &match_data.id).await.ok().flatten() else {
+                continue;
+            };
+            let Some((side, ours, market)) = largest_divergence(&prediction, &odds) else {
+                continue;
+            };
+
+            ranked.push(crate::models::MarketDivergence {
+                match_id: match_data.id,
+                home_team_name: match_data.home_team_name,
+                away_team_name: match_data.away_team_name,
+                sport: match_data.sport,
+                match_date: match_data.match_date,
+                side: side.to_string(),
+                our_probability: ours,
+                market_probability: market,
+                divergence: ours - market,
+                bookmaker: odds.bookmaker,
+            });
+        }
+
+        ranked.sort_by(|a, b| b.divergence.abs().total_cmp(&a.divergence.abs()));
+        Ok(ranked)
+    }
+
     /// Convert probability to decimal odds
     fn probability_to_odds(&self, probability: f64) -> f64 {
         if probability <= 0.0 {
@@ -480,21 +1102,17 @@ impl PredictionEngine {
         team_id: &str,
         upcoming_date: DateTime<Utc>,
     ) -> Result<Option<u32>> {
-        let last = sqlx::query_as::<_, Match>(
-            "SELECT * FROM matches
-             WHERE (home_team_id = ? OR away_team_id = ?)
-               AND status = 'finished'
-               AND match_date < ?
-             ORDER BY match_date DESC LIMIT 1",
-        )
-        .bind(team_id)
-        .bind(team_id)
-        .bind(upcoming_date)
-        .fetch_optional(pool)
-        .await?;
+        let last = get_matches_for_team(pool, team_id, &MatchFilter {
+            status: Some("finished"),
+            before: Some(upcoming_date),
+            limit: Some(1),
+            ..Default::default()
+        })
+        .await?
+        .pop();
 
         Ok(last.map(|m| {
-            let days = (upcoming_date - m.match_date).num_days().max(0) as u32;
+            let days = crate::utils::nights_between(m.match_date, upcoming_date).max(0) as u32;
             days.saturating_sub(1).min(7)
         }))
     }
@@ -543,15 +1161,803 @@ impl PredictionEngine {
     }
 }
 
+/// Edge filtering for one sport. Two-way basketball lines carry a thinner margin and
+/// noisier disagreements than three-way football markets, so each sport gets its own
+/// threshold and its own band of believable bookmaker overrounds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeSettings {
+    /// Minimum edge (our probability − devigged market probability) worth surfacing
+    pub threshold: f64,
+    /// (min, max) overround a market may carry before devigging; outside it the prices
+    /// are treated as stale or mis-keyed rather than as disagreement with us
+    pub overround_band: (f64, f64),
+}
+
+impl EdgeSettings {
+    /// Sport defaults overridden by EDGE_THRESHOLD_<SPORT> and EDGE_MAX_OVERROUND_<SPORT>
+    /// (e.g. EDGE_THRESHOLD_BASKETBALL).
+    pub fn for_sport(sport: &str) -> Self {
+        let default = match sport {
+            "basketball" => Self { threshold: 0.05, overround_band: (0.0, 0.08) },
+            _ => Self { threshold: 0.03, overround_band: (0.0, 0.15) },
+        };
+        let parse = |key: &str| {
+            std::env::var(format!("{}_{}", key, sport.to_uppercase())).ok()
+                .and_then(|v| v.parse::<f64>().ok())
+        };
+        Self {
+            threshold: parse("EDGE_THRESHOLD").unwrap_or(default.threshold).max(0.0),
+            overround_band: (default.overround_band.0, parse("EDGE_MAX_OVERROUND").unwrap_or(default.overround_band.1)),
+        }
+    }
+
+    /// Whether the market's overround falls inside this sport's band.
+    pub fn accepts_market(&self, odds: &crate::models::MarketOdds) -> bool {
+        overround(odds.home_odds, odds.draw_odds, odds.away_odds)
+            .is_some_and(|o| o >= self.overround_band.0 && o <= self.overround_band.1)
+    }
+}
+
+/// Sides above the sport's threshold: the single best side, or with `per_outcome` every
+/// qualifying side. Empty when the market is incomplete or its overround is out of band.
+fn qualifying_edges(
+    prediction: &Prediction,
+    odds: &crate::models::MarketOdds,
+    settings: &EdgeSettings,
+    per_outcome: bool,
+) -> Vec<(&'static str, f64)> {
+    if !settings.accepts_market(odds) {
+        return vec![];
+    }
+    if per_outcome {
+        return outcome_edges(prediction, odds)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|&(_, edge)| edge > settings.threshold)
+            .collect();
+    }
+    match best_edge(prediction, odds) {
+        Some(max_edge) if max_edge > settings.threshold => vec![(edge_side(prediction, odds), max_edge)],
+        _ => vec![],
+    }
+}
+
+/// Each odds snapshot priced against the newest prediction made at or before it was
+/// fetched. Both slices are oldest first; snapshots older than every prediction, or
+/// with an incomplete market, are skipped.
+pub fn edge_timeline(
+    predictions: &[Prediction],
+    snapshots: &[crate::models::MarketOdds],
+) -> Vec<crate::models::EdgeTimelinePoint> {
+    snapshots
+        .iter()
+        .filter_map(|odds| {
+            let fetched_at = DateTime::parse_from_rfc3339(&odds.fetched_at).ok()?.with_timezone(&Utc);
+            let prediction = predictions.iter().rev().find(|p| p.created_at <= fetched_at)?;
+            let edge_value = best_edge(prediction, odds)?;
+            Some(crate::models::EdgeTimelinePoint {
+                fetched_at: odds.fetched_at.clone(),
+                bookmaker: odds.bookmaker.clone(),
+                home_odds: odds.home_odds,
+                draw_odds: odds.draw_odds,
+                away_odds: odds.away_odds,
+                prediction_id: prediction.id.clone(),
+                model_version: prediction.model_version.clone(),
+                side: edge_side(prediction, odds).to_string(),
+                edge_value,
+            })
+        })
+        .collect()
+}
+
+/// Default weight on the devigged market when blending a consensus prediction
+/// (MARKET_BLEND_WEIGHT, 0 = pure model, 1 = market only).
+pub fn market_blend_weight() -> f64 {
+    std::env::var("MARKET_BLEND_WEIGHT").ok()
+        .and_then(|v| v.parse::<f64>().ok())
+        .filter(|w| (0.0..=1.0).contains(w))
+        .unwrap_or(0.0)
+}
+
+/// "Consensus" prediction: each of our probabilities blended with the devigged market's
+/// at `weight` (0 = ours, 1 = the market's). The confidence score is kept from our model.
+/// The id carries the exact weight, so blends at nearby weights never share one.
+/// None when the market is incomplete, as for `best_edge`.
+pub fn blend_with_market(
+    prediction: &Prediction,
+    odds: &crate::models::MarketOdds,
+    weight: f64,
+) -> Option<Prediction> {
+    let (implied_home, implied_draw, implied_away) = market_probabilities(prediction, odds)?;
+
+    let mix = |ours: f64, market: f64| (1.0 - weight) * ours + weight * market;
+    Some(Prediction {
+        id: format!("{}_consensus_{}", prediction.match_id, weight),
+        home_win_probability: mix(prediction.home_win_probability, implied_home),
+        away_win_probability: mix(prediction.away_win_probability, implied_away),
+        draw_probability: prediction.draw_probability.zip(implied_draw).map(|(ours, mkt)| mix(ours, mkt)),
+        model_version: format!("{}+market{:.2}", prediction.model_version, weight),
+        created_at: Utc::now(),
+        ..prediction.clone()
+    })
+}
+
+/// Rough share of regulation time left, from wall-clock time since kickoff.
+/// Football: 90 minutes plus a 15-minute break. Basketball: ~2h15m of real time for 48 minutes.
+pub fn fraction_remaining(sport: &str, kickoff: DateTime<Utc>, now: DateTime<Utc>) -> f64 {
+    let elapsed = (now - kickoff).num_minutes().max(0) as f64;
+    let played = if sport == "basketball" {
+        elapsed / 135.0
+    } else {
+        let game_minutes = if elapsed > 45.0 { (elapsed - 15.0).max(45.0) } else { elapsed };
+        game_minutes / 90.0
+    };
+    (1.0 - played).clamp(0.0, 1.0)
+}
+
+/// Combined probability of independent-looking legs, as (naive, correlated).
+///
+/// Approximate: each pair of legs sharing a group (same league, same day) is given
+/// correlation `rho`, which for two Bernoulli events adds rho·σᵢ·σⱼ to P(i ∧ j). The
+/// pairwise lifts are multiplied onto the naive product and the result is capped at
+/// the least likely leg. `rho = 0` gives the plain product.
+pub fn parlay_probability<G: PartialEq>(legs: &[(f64, G)], rho: f64) -> (f64, f64) {
+    let naive: f64 = legs.iter().map(|(p, _)| p).product();
+    let mut lift = 1.0;
+    for (i, (pi, gi)) in legs.iter().enumerate() {
+        for (pj, gj) in &legs[i + 1..] {
+            if gi == gj && *pi > 0.0 && *pj > 0.0 {
+                lift *= 1.0 + rho * ((1.0 - pi) * (1.0 - pj) / (pi * pj)).sqrt();
+            }
+        }
+    }
+    let weakest = legs.iter().map(|(p, _)| *p).fold(1.0, f64::min);
+    (naive, (naive * lift).clamp(0.0, weakest))
+}
+
+/// Update a pre-match prediction for the current score.
+///
+/// The final margin is treated as normal: the current lead plus the pre-match expected
+/// margin scaled by time left, with spread shrinking as sqrt(time left). The expected
+/// margin is backed out of the pre-match home probability. Football adds a half-goal
+/// band around zero for the draw. Normal CDF uses the logistic approximation.
+pub fn in_play_probabilities(
+    prediction: &Prediction,
+    home_score: i32,
+    away_score: i32,
+    fraction_remaining: f64,
+    sport: &str,
+) -> (f64, f64, Option<f64>) {
+    let football = sport != "basketball";
+    // Full-game margin standard deviation: ~1.7 goals, ~12 points
+    let sigma = if football { 1.7 } else { 12.0 };
+    let lead = (home_score - away_score) as f64;
+
+    let two_way = prediction.home_win_probability
+        / (prediction.home_win_probability + prediction.away_win_probability);
+    let p = two_way.clamp(0.01, 0.99);
+    let expected_margin = sigma * (p / (1.0 - p)).ln() / 1.702;
+
+    let mean = lead + expected_margin * fraction_remaining;
+    let sd = sigma * fraction_remaining.sqrt();
+    let cdf = |x: f64| {
+        if sd < 1e-6 {
+            if x > 0.0 { 1.0 } else { 0.0 }
+        } else {
+            1.0 / (1.0 + (-1.702 * x / sd).exp())
+        }
+    };
+
+    if football {
+        let home = cdf(mean - 0.5);
+        let away = cdf(-mean - 0.5);
+        (home, away, Some((1.0 - home - away).max(0.0)))
+    } else {
+        let home = cdf(mean);
+        (home, 1.0 - home, None)
+    }
+}
+
+/// Largest edge across home / away / draw for one prediction against stored odds.
+/// None when the stored market is incomplete — including a football prediction
+/// (which carries a draw probability) against odds with no draw price.
+fn best_edge(prediction: &Prediction, odds: &crate::models::MarketOdds) -> Option<f64> {
+    outcome_edges(prediction, odds)?
+        .into_iter()
+        .map(|(_, edge)| edge)
+        .reduce(f64::max)
+}
+
+/// Outcome behind `best_edge`: the side where our probability beats the devigged market most.
+fn edge_side(prediction: &Prediction, odds: &crate::models::MarketOdds) -> &'static str {
+    outcome_edges(prediction, odds)
+        .and_then(|sides| sides.into_iter().max_by(|a, b| a.1.total_cmp(&b.1)))
+        .map_or("home", |(side, _)| side)
+}
+
+/// Best side and its edge for one prediction against stored odds, as (side, edge).
+/// None when the market is incomplete, as for `best_edge`.
+pub fn prediction_edge(prediction: &Prediction, odds: &crate::models::MarketOdds) -> Option<(&'static str, f64)> {
+    best_edge(prediction, odds).map(|edge| (edge_side(prediction, odds), edge))
+}
+
+/// Our probability minus the devigged market's for each priced outcome, as (side, edge).
+/// None when the market is incomplete, as for `best_edge`.
+fn outcome_edges(prediction: &Prediction, odds: &crate::models::MarketOdds) -> Option<Vec<(&'static str, f64)>> {
+    // Edge = our probability − devigged market probability (positive = value bet)
+    let (implied_home, implied_draw, implied_away) = market_probabilities(prediction, odds)?;
+    let mut sides = vec![
+        ("home", prediction.home_win_probability - implied_home),
+        ("away", prediction.away_win_probability - implied_away),
+    ];
+    if let (Some(ours), Some(mkt)) = (prediction.draw_probability, implied_draw) {
+        sides.push(("draw", ours - mkt));
+    }
+    Some(sides)
+}
+
+/// Outcome where our prediction and the devigged market disagree most, as
+/// (side, our probability, market probability). None when the market is incomplete.
+fn largest_divergence(
+    prediction: &Prediction,
+    odds: &crate::models::MarketOdds,
+) -> Option<(&'static str, f64, f64)> {
+    let (implied_home, implied_draw, implied_away) = market_probabilities(prediction, odds)?;
+
+    let mut sides = vec![
+        ("home", prediction.home_win_probability, implied_home),
+        ("away", prediction.away_win_probability, implied_away),
+    ];
+    if let (Some(ours), Some(mkt)) = (prediction.draw_probability, implied_draw) {
+        sides.push(("draw", ours, mkt));
+    }
+    sides.into_iter().max_by(|a, b| (a.1 - a.2).abs().total_cmp(&(b.1 - b.2).abs()))
+}
+
+/// The devigged market on the same outcomes as our prediction, as (home, draw, away).
+/// A three-way prediction needs a three-way market and is devigged proportionally. A
+/// two-way prediction (basketball) compares home and away only, ignoring any draw price,
+/// and is devigged with `devig_two_way`. None when the market is incomplete.
+fn market_probabilities(prediction: &Prediction, odds: &crate::models::MarketOdds) -> Option<(f64, Option<f64>, f64)> {
+    if prediction.draw_probability.is_none() {
+        let (home, away) = devig_two_way(odds.home_odds, odds.away_odds)?;
+        return Some((home, None, away));
+    }
+    odds.draw_odds?;
+    devig(odds.home_odds, odds.draw_odds, odds.away_odds)
+}
+
+/// Two-way devig by the power method: the exponent k with (1/home)^k + (1/away)^k = 1.
+/// Books load most of their margin onto the underdog, which proportional devigging
+/// spreads evenly and so leaves the favourite looking too cheap.
+fn devig_two_way(home_odds: f64, away_odds: f64) -> Option<(f64, f64)> {
+    let valid = |x: f64| x.is_finite() && x > 1.0;
+    if !valid(home_odds) || !valid(away_odds) {
+        return None;
+    }
+    let (h, a) = (1.0 / home_odds, 1.0 / away_odds);
+    if (h + a - 1.0).abs() < 1e-12 {
+        return Some((h / (h + a), a / (h + a)));
+    }
+    // The summed powers fall as k grows, so bisect
+    let (mut lo, mut hi) = (0.01_f64, 100.0_f64);
+    for _ in 0..100 {
+        let k = (lo + hi) / 2.0;
+        if h.powf(k) + a.powf(k) > 1.0 { lo = k } else { hi = k }
+    }
+    let k = (lo + hi) / 2.0;
+    let (home, away) = (h.powf(k), a.powf(k));
+    Some((home / (home + away), away / (home + away)))
+}
+
+/// Bookmaker margin: summed implied probabilities minus 1. None for invalid prices.
+fn overround(home_odds: f64, draw_odds: Option<f64>, away_odds: f64) -> Option<f64> {
+    let valid = |x: f64| x.is_finite() && x > 1.0;
+    if !valid(home_odds) || !valid(away_odds) || draw_odds.is_some_and(|d| !valid(d)) {
+        return None;
+    }
+    Some(1.0 / home_odds + draw_odds.map_or(0.0, |d| 1.0 / d) + 1.0 / away_odds - 1.0)
+}
+
 /// Remove bookmaker overround from decimal odds, returning true implied probabilities.
 /// Works for both 2-outcome (basketball) and 3-outcome (football) markets.
-fn devig(home_odds: f64, draw_odds: Option<f64>, away_odds: f64) -> (f64, Option<f64>, f64) {
-    let h = if home_odds > 0.0 { 1.0 / home_odds } else { 0.0 };
-    let d = draw_odds.map(|x| if x > 0.0 { 1.0 / x } else { 0.0 });
-    let a = if away_odds > 0.0 { 1.0 / away_odds } else { 0.0 };
+///
+/// Every price present must be a valid decimal price (> 1.0). A missing or zero price
+/// would understate the overround and inflate the others, so such markets return None.
+fn devig(home_odds: f64, draw_odds: Option<f64>, away_odds: f64) -> Option<(f64, Option<f64>, f64)> {
+    let valid = |x: f64| x.is_finite() && x > 1.0;
+    if !valid(home_odds) || !valid(away_odds) || draw_odds.is_some_and(|d| !valid(d)) {
+        return None;
+    }
+    let h = 1.0 / home_odds;
+    let d = draw_odds.map(|x| 1.0 / x);
+    let a = 1.0 / away_odds;
     let total = h + d.unwrap_or(0.0) + a;
-    if total <= 0.0 {
-        return (0.5, draw_odds.map(|_| 0.25), 0.5);
+    Some((h / total, d.map(|x| x / total), a / total))
+}
+
+/// Finished, scored games a team played at home (`home_context`) or away, newest first.
+fn contextual_results(home_context: bool, sport: &str, limit: Option<i64>) -> MatchFilter<'_> {
+    MatchFilter {
+        venue: if home_context { Venue::Home } else { Venue::Away },
+        status: Some("finished"),
+        sport: Some(sport),
+        scored_only: true,
+        limit,
+        ..Default::default()
+    }
+}
+
+/// Points a team took from one game, from the home side's view when `home_context`.
+/// Max is 3 (football win) or 1 (basketball win).
+fn contextual_points(m: &Match, home_context: bool, sport: &str) -> f64 {
+    let max_pts = if sport == "football" { 3.0_f64 } else { 1.0_f64 };
+    match (m.home_score, m.away_score) {
+        (Some(hs), Some(as_)) => {
+            let (own, other) = if home_context { (hs, as_) } else { (as_, hs) };
+            if own > other { max_pts }
+            else if own == other && sport == "football" { 1.0 }
+            else { 0.0 }
+        }
+        _ => max_pts * 0.5, // unknown score → assume average
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{init_database_with_pool, insert_match, insert_team};
+
+    fn team(id: &str) -> Team {
+        Team {
+            id: id.into(),
+            name: id.to_uppercase(),
+            sport: "football".into(),
+            league: "EPL".into(),
+            logo_url: None,
+            elo_rating: 1200.0,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            elo_percentile: None,
+            elo_tier: None,
+        }
+    }
+
+    fn finished(id: &str, home: &Team, away: &Team, score: (i32, i32), days_ago: i64) -> Match {
+        Match {
+            id: id.into(),
+            home_team_id: home.id.clone(),
+            away_team_id: away.id.clone(),
+            home_team_name: home.name.clone(),
+            away_team_name: away.name.clone(),
+            sport: "football".into(),
+            league: "EPL".into(),
+            match_date: Utc::now() - chrono::Duration::days(days_ago),
+            status: "finished".into(),
+            home_score: Some(score.0),
+            away_score: Some(score.1),
+            venue: None,
+            city: None,
+            tiebreak_winner: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn odds(home: f64, draw: Option<f64>, away: f64) -> crate::models::MarketOdds {
+        crate::models::MarketOdds {
+            match_id: "m".into(),
+            bookmaker: "Test".into(),
+            home_odds: home,
+            draw_odds: draw,
+            away_odds: away,
+            fetched_at: Utc::now().to_rfc3339(),
+        }
+    }
+
+    fn football_prediction() -> Prediction {
+        Prediction {
+            id: "p".into(),
+            match_id: "m".into(),
+            home_win_probability: 0.50,
+            away_win_probability: 0.25,
+            draw_probability: Some(0.25),
+            model_version: "test".into(),
+            confidence_score: 0.6,
+            created_at: Utc::now(),
+            insufficient_data: false,
+            most_likely_score: None,
+        }
+    }
+
+    #[test]
+    fn in_play_lead_late_is_near_certain() {
+        let p = football_prediction();
+        let (kickoff_home, _, _) = in_play_probabilities(&p, 0, 0, 1.0, "football");
+        let (late_home, late_away, _) = in_play_probabilities(&p, 2, 0, 0.05, "football");
+        assert!(late_home > 0.95 && late_home > kickoff_home);
+        assert!(late_away < 0.01);
+        // Full time: the score decides
+        let (h, a, d) = in_play_probabilities(&p, 1, 1, 0.0, "football");
+        assert_eq!((h, a, d), (0.0, 0.0, Some(1.0)));
+    }
+
+    #[test]
+    fn devig_three_way_sums_to_one() {
+        let (h, d, a) = devig(2.0, Some(3.5), 4.0).unwrap();
+        assert!((h + d.unwrap() + a - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn devig_rejects_missing_or_zero_prices() {
+        assert!(devig(2.0, Some(3.5), 0.0).is_none());
+        assert!(devig(2.0, Some(0.0), 4.0).is_none());
+        assert!(devig(1.0, None, 1.9).is_none());
+        // Two-way markets without a draw are still fine
+        assert!(devig(1.9, None, 1.9).is_some());
+    }
+
+    #[test]
+    fn two_way_devig_leaves_the_margin_on_the_underdog() {
+        let (home, away) = devig_two_way(1.25, 4.2).unwrap();
+        assert!((home + away - 1.0).abs() < 1e-9);
+        let (proportional, _, _) = devig(1.25, None, 4.2).unwrap();
+        assert!(home > proportional, "{} vs {}", home, proportional);
+        // No margin, nothing to remove
+        let (home, _) = devig_two_way(2.0, 2.0).unwrap();
+        assert!((home - 0.5).abs() < 1e-9);
+
+        // A two-way prediction only looks at home and away, even when a draw is priced
+        let p = Prediction { home_win_probability: 0.7, away_win_probability: 0.3, draw_probability: None, ..football_prediction() };
+        let sides = outcome_edges(&p, &odds(1.25, Some(15.0), 4.2)).unwrap();
+        assert_eq!(sides.iter().map(|s| s.0).collect::<Vec<_>>(), ["home", "away"]);
+        assert_eq!(sides, outcome_edges(&p, &odds(1.25, None, 4.2)).unwrap());
+        assert_eq!(edge_side(&p, &odds(1.25, None, 4.2)), "away");
+    }
+
+    #[test]
+    fn same_edge_clears_football_threshold_but_not_basketball() {
+        let p = Prediction { home_win_probability: 0.54, away_win_probability: 0.46, draw_probability: None, ..football_prediction() };
+        // 1.95 both ways devigs to 50/50, so home carries a 4-point edge
+        let market = odds(1.95, None, 1.95);
+        let (football, basketball) = (EdgeSettings::for_sport("football"), EdgeSettings::for_sport("basketball"));
+        assert!(football.threshold < basketball.threshold);
+
+        let found = qualifying_edges(&p, &market, &football, false);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, "home");
+        assert!((found[0].1 - 0.04).abs() < 1e-9);
+        assert!(qualifying_edges(&p, &market, &basketball, false).is_empty());
+        assert!(qualifying_edges(&p, &market, &basketball, true).is_empty());
+
+        // A 33% margin is bad data, not disagreement, whatever the threshold
+        let bloated = odds(1.5, None, 1.5);
+        assert!(qualifying_edges(&p, &bloated, &EdgeSettings { threshold: 0.0, ..football }, false).is_empty());
+    }
+
+    #[test]
+    fn football_market_missing_draw_yields_no_edge() {
+        let p = football_prediction();
+        // Without a draw price the two-way devig would inflate home/away and hide draw edges
+        assert!(best_edge(&p, &odds(2.5, None, 5.0)).is_none());
+        assert!(best_edge(&p, &odds(2.5, Some(3.4), 5.0)).is_some());
+    }
+
+    #[tokio::test]
+    async fn h2h_recent_meeting_outweighs_old_one() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        init_database_with_pool(&pool).await.unwrap();
+        let (h, a) = (team("h"), team("a"));
+        insert_team(&pool, &h).await.unwrap();
+        insert_team(&pool, &a).await.unwrap();
+        // Last month the home side won; years ago the away side won.
+        insert_match(&pool, &finished("recent", &h, &a, (2, 0), 30)).await.unwrap();
+        insert_match(&pool, &finished("old", &a, &h, (3, 1), 1500)).await.unwrap();
+
+        let flat = PredictionEngine { h2h_config: H2hConfig { decay: 1.0, ..H2hConfig::default() }, ..PredictionEngine::new() };
+        let ((flat_home, flat_away, _), _) = flat.head_to_head_prediction(&pool, &h, &a, "football").await.unwrap();
+
+        let decayed = PredictionEngine { h2h_config: H2hConfig::default(), ..PredictionEngine::new() };
+        let ((home, away, _), _) = decayed.head_to_head_prediction(&pool, &h, &a, "football").await.unwrap();
+        assert!(home > flat_home);
+        assert!(away < flat_away);
+
+        // An age limit drops the old meeting entirely
+        let windowed = PredictionEngine { h2h_config: H2hConfig { max_age_days: Some(365), ..H2hConfig::default() }, ..PredictionEngine::new() };
+        let recent_only = windowed.get_head_to_head_matches(&pool, &h.id, &a.id).await.unwrap();
+        assert_eq!(recent_only.len(), 1);
+        assert_eq!(recent_only[0].id, "recent");
+    }
+
+    #[tokio::test]
+    async fn h2h_credits_away_wins_above_home_wins() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        init_database_with_pool(&pool).await.unwrap();
+        let (h, a, home_winner, rival) = (team("h"), team("a"), team("home_winner"), team("rival"));
+        for t in [&h, &a, &home_winner, &rival] {
+            insert_team(&pool, t).await.unwrap();
+        }
+        // h won its last trip to a, then lost at home to a: each won away, lost at home
+        insert_match(&pool, &finished("h_away_win", &a, &h, (0, 1), 30)).await.unwrap();
+        insert_match(&pool, &finished("h_home_loss", &h, &a, (0, 1), 60)).await.unwrap();
+        // Same scorelines from home_winner's view, but both results went the home side's way
+        insert_match(&pool, &finished("hw_home_win", &home_winner, &rival, (1, 0), 30)).await.unwrap();
+        insert_match(&pool, &finished("hw_away_loss", &rival, &home_winner, (1, 0), 60)).await.unwrap();
+
+        let adjusted = PredictionEngine { h2h_config: H2hConfig::default(), ..PredictionEngine::new() };
+        let naive = PredictionEngine { h2h_config: H2hConfig { venue_adjusted: false, ..H2hConfig::default() }, ..PredictionEngine::new() };
+
+        let ((h_home, h_away, h_draw), meetings) = adjusted.head_to_head_prediction(&pool, &h, &a, "football").await.unwrap();
+        let ((hw_home, hw_away, _), _) = adjusted.head_to_head_prediction(&pool, &home_winner, &rival, "football").await.unwrap();
+        assert_eq!(meetings, 2);
+        assert!((h_home + h_away + h_draw.unwrap() - 1.0).abs() < 1e-9);
+        // Winning away is the stronger signal, so h rates above a side that only won at home
+        assert!(h_home > hw_home, "{} vs {}", h_home, hw_home);
+        assert!(h_away < hw_away);
+        // The current fixture's home side keeps its home advantage over a level rivalry
+        assert!(h_home > h_away);
+
+        // Without venue adjustment both records read as one win and one loss
+        let ((naive_h, _, _), _) = naive.head_to_head_prediction(&pool, &h, &a, "football").await.unwrap();
+        let ((naive_hw, _, _), _) = naive.head_to_head_prediction(&pool, &home_winner, &rival, "football").await.unwrap();
+        assert!((naive_h - naive_hw).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn season_form_tempers_a_short_slump() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        init_database_with_pool(&pool).await.unwrap();
+        let (strong, rival) = (team("strong"), team("rival"));
+        insert_team(&pool, &strong).await.unwrap();
+        insert_team(&pool, &rival).await.unwrap();
+
+        // Won ten home games across the season, then lost the last two at home
+        for i in 0..10 {
+            insert_match(&pool, &finished(&format!("w{i}"), &strong, &rival, (2, 0), 100 - i * 5)).await.unwrap();
+        }
+        insert_match(&pool, &finished("l1", &strong, &rival, (0, 1), 2)).await.unwrap();
+        insert_match(&pool, &finished("l2", &strong, &rival, (0, 2), 1)).await.unwrap();
+
+        let engine = PredictionEngine { form_season_weight: 0.35, ..PredictionEngine::new() };
+        let recent = engine.rolling_form(&pool, "strong", true, "football").await.unwrap().rate;
+        let season = engine.season_form(&pool, "strong", true, "football").await.unwrap().rate;
+        let blended = engine.blended_form(&pool, "strong", true, "football").await.unwrap().rate;
+
+        assert!(recent < season, "slump should drag recent form below the season rate");
+        assert!(recent < blended && blended < season);
+    }
+
+    #[tokio::test]
+    async fn full_h2h_weight_returns_pure_h2h_component() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        init_database_with_pool(&pool).await.unwrap();
+        let (home, away) = (team("rival_a"), Team { elo_rating: 1400.0, ..team("rival_b") });
+        insert_team(&pool, &home).await.unwrap();
+        insert_team(&pool, &away).await.unwrap();
+        for i in 0..4 {
+            insert_match(&pool, &finished(&format!("d{i}"), &home, &away, (0, 2), 30 + i)).await.unwrap();
+        }
+
+        // The Poisson goals blend sits on top of the ELO/H2H/form weights, so leave it out
+        let engine = PredictionEngine { poisson_weight: 0.0, ..PredictionEngine::new() };
+        let ((h2h_home, h2h_away, h2h_draw), _) =
+            engine.head_to_head_prediction(&pool, &home, &away, "football").await.unwrap();
+        let p = engine.predict_hypothetical(&pool, &home, &away, false, Some(1.0)).await.unwrap();
+
+        assert!((p.home_win_probability - h2h_home).abs() < 1e-9);
+        assert!((p.away_win_probability - h2h_away).abs() < 1e-9);
+        assert!((p.draw_probability.unwrap() - h2h_draw.unwrap()).abs() < 1e-9);
+        // Out-of-range overrides clamp rather than inverting the ensemble
+        let base = ModelConfig::baseline().weights;
+        assert_eq!(football_weights(base, Some(3.0)), football_weights(base, Some(1.0)));
+    }
+
+    #[tokio::test]
+    async fn home_advantage_override_raises_home_win_probability() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        init_database_with_pool(&pool).await.unwrap();
+        let (home, away) = (team("altitude"), team("visitor"));
+        insert_team(&pool, &home).await.unwrap();
+        insert_team(&pool, &away).await.unwrap();
+        insert_match(&pool, &finished("earlier", &away, &home, (1, 1), 30)).await.unwrap();
+
+        let engine = PredictionEngine::new();
+        let default = engine.predict_hypothetical(&pool, &home, &away, false, None).await.unwrap();
+        crate::db::set_home_advantage(&pool, &home.id, 1.5).await.unwrap();
+        let boosted = engine.predict_hypothetical(&pool, &home, &away, false, None).await.unwrap();
+
+        assert!(boosted.home_win_probability > default.home_win_probability);
+        assert!(boosted.away_win_probability < default.away_win_probability);
+    }
+
+    #[tokio::test]
+    async fn gameless_teams_get_flagged_low_confidence_baseline() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        init_database_with_pool(&pool).await.unwrap();
+        let (home, away) = (team("promoted"), team("newcomer"));
+        insert_team(&pool, &home).await.unwrap();
+        insert_team(&pool, &away).await.unwrap();
+
+        let engine = PredictionEngine::new();
+        let p = engine.predict_hypothetical(&pool, &home, &away, false, None).await.unwrap();
+        assert!(p.insufficient_data);
+        assert!(p.confidence_score < 0.4);
+        let total = p.home_win_probability + p.away_win_probability + p.draw_probability.unwrap();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert!((0.3..0.6).contains(&p.home_win_probability));
+
+        // One finished game each is enough to use the full model
+        insert_match(&pool, &finished("first", &home, &away, (2, 0), 3)).await.unwrap();
+        let p = engine.predict_hypothetical(&pool, &home, &away, false, None).await.unwrap();
+        assert!(!p.insufficient_data);
+    }
+
+    #[tokio::test]
+    async fn cold_start_prices_fixtures_off_carried_over_ratings() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        init_database_with_pool(&pool).await.unwrap();
+        // Last season: a champion and a relegation-zone side, both with results
+        let (champion, strugglers) = (Team { elo_rating: 1650.0, ..team("champion") }, Team { elo_rating: 1150.0, ..team("strugglers") });
+        let (mid_a, mid_b) = (Team { elo_rating: 1320.0, ..team("mid_a") }, Team { elo_rating: 1280.0, ..team("mid_b") });
+        for t in [&champion, &strugglers, &mid_a, &mid_b] {
+            insert_team(&pool, t).await.unwrap();
+        }
+        insert_match(&pool, &finished("last_season", &champion, &strugglers, (3, 0), 200)).await.unwrap();
+        insert_match(&pool, &finished("last_season_mid", &mid_a, &mid_b, (1, 1), 200)).await.unwrap();
+
+        // New season: everything wiped, teams back at a flat rating with no finished games
+        crate::db::clear_all_data(&pool).await.unwrap();
+        let fresh: Vec<Team> = ["champion", "strugglers", "mid_a", "mid_b", "promoted"].into_iter().map(team).collect();
+        for t in &fresh {
+            insert_team(&pool, t).await.unwrap();
+        }
+
+        let engine = PredictionEngine::new();
+        let mismatch = engine.predict_hypothetical(&pool, &fresh[0], &fresh[1], false, None).await.unwrap();
+        let even = engine.predict_hypothetical(&pool, &fresh[2], &fresh[3], false, None).await.unwrap();
+        assert!(mismatch.insufficient_data && even.insufficient_data);
+        assert_eq!(mismatch.model_version, "preseason_elo");
+        assert!(mismatch.home_win_probability > even.home_win_probability + 0.1);
+        assert!(mismatch.away_win_probability < even.away_win_probability);
+
+        // A team with no carried-over rating still gets the league baseline
+        let unknown = engine.predict_hypothetical(&pool, &fresh[4], &fresh[1], false, None).await.unwrap();
+        assert_eq!(unknown.model_version, "league_baseline");
+    }
+
+    #[tokio::test]
+    async fn registry_candidate_predicts_alongside_baseline() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        init_database_with_pool(&pool).await.unwrap();
+        let (home, away) = (Team { elo_rating: 1350.0, ..team("reg_home") }, team("reg_away"));
+        insert_team(&pool, &home).await.unwrap();
+        insert_team(&pool, &away).await.unwrap();
+        insert_match(&pool, &finished("reg_past", &away, &home, (1, 1), 20)).await.unwrap();
+        let upcoming = Match {
+            id: "reg_next".into(),
+            match_date: Utc::now() + chrono::Duration::days(3),
+            status: "scheduled".into(),
+            home_score: None,
+            away_score: None,
+            ..finished("reg_next", &home, &away, (0, 0), 0)
+        };
+        insert_match(&pool, &upcoming).await.unwrap();
+
+        let candidate = ModelConfig { name: "candidate".into(), weights: (0.8, 0.1, 0.1), ..ModelConfig::baseline() };
+        let engine = PredictionEngine { candidates: vec![candidate.clone()], ..PredictionEngine::new() };
+        engine.generate_predictions(&pool, std::slice::from_ref(&upcoming)).await.unwrap();
+
+        let versions: Vec<String> = sqlx::query_scalar("SELECT model_version FROM predictions WHERE match_id = 'reg_next'")
+            .fetch_all(&pool).await.unwrap();
+        assert_eq!(versions.len(), 2);
+        assert!(versions.contains(&FOOTBALL_MODEL_VERSION.to_string()));
+        assert!(versions.contains(&candidate.model_version()));
+        // The served prediction is still the baseline's
+        let served = get_prediction_by_match_id(&pool, "reg_next").await.unwrap().unwrap();
+        assert_eq!(served.model_version, FOOTBALL_MODEL_VERSION);
+    }
+
+    #[tokio::test]
+    async fn refresh_stores_one_consensus_per_exact_blend_weight() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        init_database_with_pool(&pool).await.unwrap();
+        let (home, away) = (team("cons_home"), team("cons_away"));
+        insert_team(&pool, &home).await.unwrap();
+        insert_team(&pool, &away).await.unwrap();
+        insert_match(&pool, &finished("cons_past", &away, &home, (1, 1), 20)).await.unwrap();
+        let upcoming = Match {
+            id: "cons_next".into(),
+            match_date: Utc::now() + chrono::Duration::days(3),
+            status: "scheduled".into(),
+            home_score: None,
+            away_score: None,
+            ..finished("cons_next", &home, &away, (0, 0), 0)
+        };
+        insert_match(&pool, &upcoming).await.unwrap();
+        crate::db::upsert_market_odds(&pool, "cons_next", "Test", 2.0, Some(4.0), 4.0).await.unwrap();
+
+        // Weights that round to the same two decimals still get their own rows
+        for weight in [0.5, 0.504, 0.5] {
+            let engine = PredictionEngine { market_blend_weight: weight, ..PredictionEngine::new() };
+            engine.generate_predictions(&pool, std::slice::from_ref(&upcoming)).await.unwrap();
+        }
+
+        let weights: Vec<f64> = sqlx::query_scalar("SELECT blend_weight FROM consensus_predictions ORDER BY blend_weight")
+            .fetch_all(&pool).await.unwrap();
+        assert_eq!(weights, vec![0.5, 0.504]);
+    }
+
+    #[tokio::test]
+    async fn unchanged_predictions_are_not_stored_again() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        init_database_with_pool(&pool).await.unwrap();
+        let (home, away) = (Team { elo_rating: 1350.0, ..team("quiet_home") }, team("quiet_away"));
+        insert_team(&pool, &home).await.unwrap();
+        insert_team(&pool, &away).await.unwrap();
+        insert_match(&pool, &finished("quiet_past", &away, &home, (1, 1), 20)).await.unwrap();
+        let upcoming = Match {
+            match_date: Utc::now() + chrono::Duration::days(3),
+            status: "scheduled".into(),
+            home_score: None,
+            away_score: None,
+            ..finished("quiet_next", &home, &away, (0, 0), 0)
+        };
+        insert_match(&pool, &upcoming).await.unwrap();
+
+        let engine = PredictionEngine { candidates: vec![], ..PredictionEngine::new() };
+        let rows = || async {
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM predictions WHERE match_id = 'quiet_next'")
+                .fetch_one(&pool).await.unwrap()
+        };
+        let matches = std::slice::from_ref(&upcoming);
+
+        engine.generate_predictions(&pool, matches).await.unwrap();
+        engine.generate_predictions(&pool, matches).await.unwrap();
+        assert_eq!(rows().await, 1);
+
+        // A real ratings change moves the probabilities, so it is stored
+        insert_team(&pool, &Team { elo_rating: 1650.0, ..home }).await.unwrap();
+        engine.generate_predictions(&pool, matches).await.unwrap();
+        assert_eq!(rows().await, 2);
     }
-    (h / total, d.map(|x| x / total), a / total)
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn extreme_elo_gap_stays_under_the_probability_ceiling() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        init_database_with_pool(&pool).await.unwrap();
+        let giant = Team { elo_rating: 2600.0, ..team("giant") };
+        let minnow = Team { elo_rating: 600.0, ..team("minnow") };
+        insert_team(&pool, &giant).await.unwrap();
+        insert_team(&pool, &minnow).await.unwrap();
+        for i in 0..6 {
+            insert_match(&pool, &finished(&format!("rout{i}"), &giant, &minnow, (6, 0), 10 + i)).await.unwrap();
+        }
+        let fixture = Match { id: "next".into(), status: "scheduled".into(), home_score: None, away_score: None,
+            ..finished("next", &giant, &minnow, (0, 0), -3) };
+
+        let unbounded = PredictionEngine { probability_bounds: (0.0, 1.0), ..PredictionEngine::new() };
+        let raw = unbounded.predict_match_outcome(&pool, &fixture, None).await.unwrap();
+        assert!(raw.home_win_probability > 0.8);
+
+        let bounded = PredictionEngine { probability_bounds: (0.05, 0.8), ..PredictionEngine::new() };
+        let p = bounded.predict_match_outcome(&pool, &fixture, None).await.unwrap();
+        let draw = p.draw_probability.unwrap();
+        assert!(p.home_win_probability <= 0.8 + 1e-9);
+        assert!(p.away_win_probability >= 0.05 - 1e-9 && draw >= 0.05 - 1e-9);
+        assert!((p.home_win_probability + draw + p.away_win_probability - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn poisson_component_favours_the_stronger_attack() {
+        let even = ScoreModel::Poisson { home_rate: 1.3, away_rate: 1.3 };
+        let (home, draw, away) = even.analytic_1x2();
+        assert!((home + draw + away - 1.0).abs() < 1e-9);
+        assert!((home - away).abs() < 1e-9);
+        assert_eq!(even.most_likely_score(), Some((1, 1)));
+
+        let lopsided = ScoreModel::Poisson { home_rate: 2.6, away_rate: 0.6 };
+        let (home, _, away) = lopsided.analytic_1x2();
+        assert!(home > 0.7 && away < 0.1);
+        assert_eq!(lopsided.most_likely_score(), Some((2, 0)));
+    }
+}
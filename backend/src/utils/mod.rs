@@ -1,11 +1,19 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::models::{MatchResult, Outcome};
+
 /// Calculate the difference between two dates in days
 pub fn days_between(date1: DateTime<Utc>, date2: DateTime<Utc>) -> i64 {
     (date2 - date1).num_days()
 }
 
+/// Whole days between two kickoffs, rounded to the nearest day so that evening
+/// games a few time zones apart still count as consecutive nights
+pub fn nights_between(earlier: DateTime<Utc>, later: DateTime<Utc>) -> i64 {
+    ((later - earlier).num_hours() + 12).div_euclid(24)
+}
+
 /// Convert a win/loss/draw record to a form string (e.g., "WLWDW")
 pub fn results_to_form(results: &[(char, DateTime<Utc>)]) -> String {
     let mut form = String::new();
@@ -19,6 +27,22 @@ pub fn results_to_form(results: &[(char, DateTime<Utc>)]) -> String {
     form
 }
 
+/// Season label such as "2025-26" for a date. Seasons run July to June, which spans both
+/// the EPL (August–May) and NBA (October–June) calendars.
+pub fn season_of(date: DateTime<Utc>) -> String {
+    use chrono::Datelike;
+    let start = if date.month() >= 7 { date.year() } else { date.year() - 1 };
+    format!("{}-{:02}", start, (start + 1) % 100)
+}
+
+/// First instant of a "2025-26" style season and the first instant of the next one.
+/// None for labels in any other shape.
+pub fn season_bounds(season: &str) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let start: i32 = season.split('-').next()?.parse().ok()?;
+    let july = |year| chrono::NaiveDate::from_ymd_opt(year, 7, 1).map(|d| d.and_time(chrono::NaiveTime::MIN).and_utc());
+    Some((july(start)?, july(start + 1)?))
+}
+
 /// Calculate win percentage from wins, draws, and losses
 pub fn calculate_win_percentage(wins: u32, draws: Option<u32>, losses: u32) -> f64 {
     let total_games = wins + losses + draws.unwrap_or(0);
@@ -30,6 +54,78 @@ pub fn calculate_win_percentage(wins: u32, draws: Option<u32>, losses: u32) -> f
     (points as f64) / ((total_games * 3) as f64) * 100.0
 }
 
+/// Expected points from a pre-match prediction, from one team's side:
+/// football 3·P(win) + 1·P(draw), basketball P(win).
+pub fn expected_points(win_prob: f64, draw_prob: Option<f64>, sport: &str) -> f64 {
+    if sport == "football" {
+        3.0 * win_prob + draw_prob.unwrap_or(0.0)
+    } else {
+        win_prob
+    }
+}
+
+/// Points actually earned on the same scale as `expected_points`.
+pub fn actual_points(scored: i32, conceded: i32, sport: &str) -> f64 {
+    match (scored.cmp(&conceded), sport == "football") {
+        (std::cmp::Ordering::Greater, true) => 3.0,
+        (std::cmp::Ordering::Equal, true) => 1.0,
+        (std::cmp::Ordering::Greater, false) => 1.0,
+        _ => 0.0,
+    }
+}
+
+/// Percentile of `rating` among every rating in its league (including itself):
+/// 0 = lowest, 100 = highest. A one-team league reports 100.
+pub fn elo_percentile(rating: f64, league_ratings: &[f64]) -> f64 {
+    let others = league_ratings.len().saturating_sub(1);
+    if others == 0 {
+        return 100.0;
+    }
+    let below = league_ratings.iter().filter(|&&r| r < rating).count();
+    below as f64 / others as f64 * 100.0
+}
+
+/// Minimum percentiles for Elite / Strong / Average, from ELO_TIER_PERCENTILES
+/// (e.g. "85,60,30"). Anything below the last cutoff is Weak.
+pub fn elo_tier_cutoffs() -> [f64; 3] {
+    let parsed: Option<Vec<f64>> = std::env::var("ELO_TIER_PERCENTILES").ok()
+        .map(|v| v.split(',').filter_map(|x| x.trim().parse().ok()).collect());
+    match parsed.as_deref() {
+        Some(&[elite, strong, average]) => [elite, strong, average],
+        _ => [85.0, 60.0, 30.0],
+    }
+}
+
+/// Decimal places kept when probabilities are serialized in API responses, from
+/// PROBABILITY_DECIMALS (default 4), read once on first use so serializing a response
+/// doesn't hit the environment per field. Calculations always use full precision.
+pub fn probability_decimals() -> i32 {
+    static DECIMALS: std::sync::OnceLock<i32> = std::sync::OnceLock::new();
+    *DECIMALS.get_or_init(|| {
+        std::env::var("PROBABILITY_DECIMALS").ok()
+            .and_then(|v| v.parse::<i32>().ok())
+            .map_or(4, |d| d.clamp(0, 15))
+    })
+}
+
+pub fn round_to(value: f64, decimals: i32) -> f64 {
+    let scale = 10f64.powi(decimals);
+    (value * scale).round() / scale
+}
+
+pub fn elo_tier(percentile: f64, cutoffs: [f64; 3]) -> &'static str {
+    let [elite, strong, average] = cutoffs;
+    if percentile >= elite {
+        "Elite"
+    } else if percentile >= strong {
+        "Strong"
+    } else if percentile >= average {
+        "Average"
+    } else {
+        "Weak"
+    }
+}
+
 /// Convert probability to implied odds
 pub fn probability_to_odds(probability: f64) -> f64 {
     if probability <= 0.0 || probability >= 1.0 {
@@ -105,14 +201,12 @@ pub fn calculate_elo_change(_rating: f64, expected_score: f64, actual_score: f64
     k_factor * (actual_score - expected_score)
 }
 
-/// Simple hash function for generating consistent IDs
-pub fn simple_hash(input: &str) -> String {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
-    
-    let mut hasher = DefaultHasher::new();
-    input.hash(&mut hasher);
-    format!("{:x}", hasher.finish())
+/// Hex SHA-256 of `input`; stable across Rust releases (unlike `DefaultHasher`), so it is
+/// safe to persist.
+pub fn sha256_hex(input: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    Sha256::digest(input.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -132,11 +226,77 @@ pub struct PredictionSummary {
     pub confidence: f64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct MatchResult {
-    pub home_score: i32,
-    pub away_score: i32,
-    pub winner: String, // "home", "away", "draw"
+/// The model's pick: the most probable outcome. Draw only counts when it is priced, and
+/// exact ties go to home, then away, so a level home/away split never becomes a draw pick.
+pub fn model_pick(home_prob: f64, draw_prob: Option<f64>, away_prob: f64) -> Outcome {
+    let mut pick = (Outcome::Home, home_prob);
+    for candidate in [(Outcome::Away, away_prob), (Outcome::Draw, draw_prob.unwrap_or(f64::NEG_INFINITY))] {
+        if candidate.1 > pick.1 {
+            pick = candidate;
+        }
+    }
+    pick.0
+}
+
+/// Final result from the home side's view.
+pub fn match_outcome(home_score: i32, away_score: i32) -> Outcome {
+    match home_score.cmp(&away_score) {
+        std::cmp::Ordering::Greater => Outcome::Home,
+        std::cmp::Ordering::Equal => Outcome::Draw,
+        std::cmp::Ordering::Less => Outcome::Away,
+    }
+}
+
+/// Multi-class Brier score of a prediction against the actual outcome: 0 is perfect, 2 is
+/// certain and wrong. Two-way markets score the draw as priced at 0.
+pub fn brier_score(home_prob: f64, draw_prob: Option<f64>, away_prob: f64, actual: Outcome) -> f64 {
+    [(Outcome::Home, home_prob), (Outcome::Draw, draw_prob.unwrap_or(0.0)), (Outcome::Away, away_prob)]
+        .iter()
+        .map(|&(outcome, p)| (p - if outcome == actual { 1.0 } else { 0.0 }).powi(2))
+        .sum()
+}
+
+/// Negative log of the probability given to the actual outcome, floored to stay finite.
+pub fn log_loss(home_prob: f64, draw_prob: Option<f64>, away_prob: f64, actual: Outcome) -> f64 {
+    let p = match actual {
+        Outcome::Home => home_prob,
+        Outcome::Draw => draw_prob.unwrap_or(0.0),
+        Outcome::Away => away_prob,
+    };
+    -p.max(1e-15).ln()
+}
+
+/// How decisive a prediction is, in [0, 1]: the favourite's share of the decisive
+/// (non-draw) probability, scaled so a 90/10 split is fully decisive, then discounted by
+/// the draw mass. A football favourite is judged against the other side rather than
+/// against 50% of the whole market, but still gives up whatever a draw could take away,
+/// so the same ELO gap reads less decisive in football than in basketball.
+pub fn prediction_strength(home_prob: f64, draw_prob: Option<f64>, away_prob: f64) -> f64 {
+    let decisive = home_prob + away_prob;
+    if decisive <= 0.0 {
+        return 0.0;
+    }
+    let favourite_share = home_prob.max(away_prob) / decisive;
+    ((favourite_share - 0.5) * 2.5).clamp(0.0, 1.0) * (1.0 - draw_prob.unwrap_or(0.0)).clamp(0.0, 1.0)
+}
+
+/// Agreement between component models' home probabilities, in [0, 1]: 1 when they
+/// coincide, 0 once their standard deviation reaches 15 points.
+pub fn model_agreement(home_probs: &[f64]) -> f64 {
+    if home_probs.is_empty() {
+        return 0.0;
+    }
+    let n = home_probs.len() as f64;
+    let mean = home_probs.iter().sum::<f64>() / n;
+    let std_dev = (home_probs.iter().map(|&p| (p - mean).powi(2)).sum::<f64>() / n).sqrt();
+    (1.0 - std_dev / 0.15).clamp(0.0, 1.0)
+}
+
+/// Confidence served with every prediction: decisiveness (primary) blended with model
+/// agreement (secondary), in [0.40, 0.95].
+pub fn prediction_confidence(home_prob: f64, draw_prob: Option<f64>, away_prob: f64, agreement: f64) -> f64 {
+    let strength = prediction_strength(home_prob, draw_prob, away_prob);
+    (0.40 + 0.35 * strength + 0.25 * agreement).clamp(0.40, 0.95)
 }
 
 /// Calculate prediction accuracy
@@ -147,32 +307,7 @@ pub fn calculate_prediction_accuracy(predictions: &[MatchSummary]) -> f64 {
     for match_summary in predictions {
         if let (Some(pred), Some(result)) = (&match_summary.our_prediction, &match_summary.result) {
             total += 1;
-            
-            let predicted_winner = if pred.home_win_prob > pred.away_win_prob {
-                if let Some(draw_prob) = pred.draw_prob {
-                    if pred.home_win_prob > draw_prob {
-                        "home"
-                    } else {
-                        "draw"
-                    }
-                } else {
-                    "home"
-                }
-            } else if pred.away_win_prob > pred.home_win_prob {
-                if let Some(draw_prob) = pred.draw_prob {
-                    if pred.away_win_prob > draw_prob {
-                        "away"
-                    } else {
-                        "draw"
-                    }
-                } else {
-                    "away"
-                }
-            } else {
-                "draw"
-            };
-            
-            if predicted_winner == result.winner {
+            if model_pick(pred.home_win_prob, pred.draw_prob, pred.away_win_prob) == result.winner {
                 correct += 1;
             }
         }
@@ -190,6 +325,21 @@ mod tests {
     use super::*;
     use chrono::TimeZone;
 
+    #[test]
+    fn seasons_turn_over_on_the_first_of_july() {
+        let date = |y, m, d| Utc.with_ymd_and_hms(y, m, d, 12, 0, 0).unwrap();
+        assert_eq!(season_of(date(2025, 8, 16)), "2025-26");
+        assert_eq!(season_of(date(2026, 6, 20)), "2025-26");
+        assert_eq!(season_of(date(2026, 7, 1)), "2026-27");
+        assert_eq!(season_of(date(2099, 10, 1)), "2099-00");
+
+        let (start, end) = season_bounds("2025-26").unwrap();
+        assert_eq!((start, end), (Utc.with_ymd_and_hms(2025, 7, 1, 0, 0, 0).unwrap(), Utc.with_ymd_and_hms(2026, 7, 1, 0, 0, 0).unwrap()));
+        assert_eq!(season_of(start), "2025-26");
+        assert_eq!(season_of(end), "2026-27");
+        assert!(season_bounds("current").is_none());
+    }
+
     #[test]
     fn test_probability_to_odds() {
         assert_eq!(probability_to_odds(0.5), 2.0);
@@ -211,6 +361,18 @@ mod tests {
         assert!((sum - 1.0).abs() < 0.001);
     }
 
+    #[test]
+    fn test_elo_percentile_and_tier() {
+        let league = [1400.0, 1450.0, 1500.0, 1550.0, 1600.0];
+        assert_eq!(elo_percentile(1600.0, &league), 100.0);
+        assert_eq!(elo_percentile(1400.0, &league), 0.0);
+        assert_eq!(elo_percentile(1500.0, &league), 50.0);
+        let cutoffs = [85.0, 60.0, 30.0];
+        assert_eq!(elo_tier(100.0, cutoffs), "Elite");
+        assert_eq!(elo_tier(50.0, cutoffs), "Average");
+        assert_eq!(elo_tier(0.0, cutoffs), "Weak");
+    }
+
     #[test]
     fn test_calculate_win_percentage() {
         // 3W + 1D + 1L → 10 football points out of 15 possible = 66.67%
@@ -218,4 +380,52 @@ mod tests {
         assert!((pct - 66.666_666).abs() < 0.001, "expected ~66.67, got {}", pct);
         assert_eq!(calculate_win_percentage(0, None, 0), 0.0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn model_pick_is_argmax_with_home_first_on_ties() {
+        // Level home/away with a long-shot draw is a home pick, not a draw
+        assert_eq!(model_pick(0.45, Some(0.10), 0.45), Outcome::Home);
+        assert_eq!(model_pick(0.5, None, 0.5), Outcome::Home);
+        // Draw is picked whenever it is the single most likely outcome
+        assert_eq!(model_pick(0.34, Some(0.36), 0.30), Outcome::Draw);
+        assert_eq!(model_pick(0.30, Some(0.34), 0.36), Outcome::Away);
+    }
+
+    #[test]
+    fn accuracy_scores_draw_argmax_as_draw_pick() {
+        let summary = |home, draw, away, winner| MatchSummary {
+            home_team: "A".into(),
+            away_team: "B".into(),
+            date: Utc::now(),
+            our_prediction: Some(PredictionSummary { home_win_prob: home, away_win_prob: away, draw_prob: draw, confidence: 0.5 }),
+            result: Some(MatchResult { winner, score: "1-1".into() }),
+        };
+        let games = [
+            summary(0.30, Some(0.40), 0.30, Outcome::Draw),
+            summary(0.45, Some(0.10), 0.45, Outcome::Draw),
+        ];
+        assert_eq!(calculate_prediction_accuracy(&games), 0.5);
+    }
+
+    #[test]
+    fn same_elo_gap_is_less_decisive_in_football() {
+        let elo = crate::services::EloCalculator::new();
+        let (fh, fa, fd) = elo.win_probability(1700.0, 1500.0, "football", "EPL");
+        let (bh, ba, bd) = elo.win_probability(1700.0, 1500.0, "basketball", "NBA");
+        assert!(fd.is_some() && bd.is_none());
+
+        let football = prediction_confidence(fh, fd, fa, 1.0);
+        let basketball = prediction_confidence(bh, bd, ba, 1.0);
+        assert!(football < basketball, "football {} vs basketball {}", football, basketball);
+        // The football favourite is still recognised: judged on its share of the decisive
+        // outcomes, diluted by the draw, rather than against half the market
+        let decisive_only = prediction_strength(fh / (fh + fa), None, fa / (fh + fa));
+        assert!((prediction_strength(fh, fd, fa) - decisive_only * (1.0 - fd.unwrap())).abs() < 1e-12);
+        assert!(prediction_strength(fh, fd, fa) > ((fh.max(fa) - 0.5) * 2.5).max(0.0));
+    }
+
+    #[test]
+    fn sha256_hex_is_the_standard_digest() {
+        assert_eq!(sha256_hex("abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    }
+}
@@ -45,14 +45,8 @@ const GameCard: React.FC<Props> = ({ match }) => {
   const awayPct = p ? Math.round(p.away_win_probability * 100) : 50;
   const drawPct = p?.draw_probability ? Math.round(p.draw_probability * 100) : 0;
 
-  const homeWins = p
-    ? p.home_win_probability > p.away_win_probability &&
-      (!p.draw_probability || p.home_win_probability > p.draw_probability)
-    : false;
-  const awayWins = p
-    ? p.away_win_probability > p.home_win_probability &&
-      (!p.draw_probability || p.away_win_probability > p.draw_probability)
-    : false;
+  const homeWins = p?.predicted_outcome === 'home';
+  const awayWins = p?.predicted_outcome === 'away';
 
   const toggleExpand = useCallback(async (e: React.MouseEvent) => {
     e.stopPropagation();
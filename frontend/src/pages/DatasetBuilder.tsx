@@ -9,11 +9,14 @@ interface DatasetRequest {
   date_to?: string;
   stats_categories: string[];
   format: string;
+  compress?: boolean;
 }
 
 interface DatasetResult {
   download_url?: string;
   format: string;
+  content_type?: string;
+  compressed?: boolean;
   rows: number;
   generated_at: string;
 }
@@ -153,6 +156,17 @@ const DatasetBuilder: React.FC = () => {
                   </label>
                 ))}
               </div>
+              <label className="checkbox-option">
+                <input
+                  type="checkbox"
+                  checked={!!request.compress}
+                  onChange={e => set('compress', e.target.checked)}
+                />
+                <div className="checkbox-content">
+                  <span className="checkbox-label">Gzip</span>
+                  <span className="checkbox-description">Smaller download for large exports (.gz)</span>
+                </div>
+              </label>
             </div>
           </div>
 
@@ -17,6 +17,7 @@ const fmtOdds = (n: number) => n.toFixed(2);
 
 const EdgeFinder: React.FC = () => {
   const [edges, setEdges]       = useState<Edge[]>([]);
+  const [emptyReason, setEmptyReason] = useState('');
   const [loading, setLoading]   = useState(true);
   const [error, setError]       = useState<string | null>(null);
   const [sortKey, setSortKey]   = useState<SortKey>('edge');
@@ -26,8 +27,9 @@ const EdgeFinder: React.FC = () => {
     setLoading(true);
     setError(null);
     try {
-      const data = await apiService.getPredictionEdges();
-      setEdges(data);
+      const report = await apiService.getPredictionEdges();
+      setEdges(report.edges);
+      setEmptyReason(report.message);
     } catch {
       setError('Failed to fetch edges. Ensure the backend is running and predictions are generated.');
     } finally {
@@ -146,7 +148,7 @@ const EdgeFinder: React.FC = () => {
           <div className="empty-state">
             <Target size={48} />
             <h3>No edges found yet</h3>
-            <p>Live market odds sync from The Odds API every 12 hours. Once synced, this page shows matches where our model disagrees with the market by &gt;3%.</p>
+            {emptyReason && <p>{emptyReason}.</p>}
           </div>
         ) : (
           <div className="edges-table-wrapper">
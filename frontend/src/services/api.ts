@@ -31,10 +31,20 @@ export interface Match {
   status: string;
   home_score?: number;
   away_score?: number;
+  venue?: string;
+  city?: string;
+  tiebreak_winner?: 'home' | 'away' | null;
+  /** Set for finished matches only */
+  result: MatchResult | null;
   created_at: string;
   updated_at: string;
 }
 
+export interface MatchResult {
+  winner: 'home' | 'away' | 'draw';
+  score: string;
+}
+
 export interface Prediction {
   id: string;
   match_id: string;
@@ -44,6 +54,12 @@ export interface Prediction {
   model_version: string;
   confidence_score: number;
   created_at: string;
+  /** A team had no finished matches: league-average baseline, low confidence */
+  insufficient_data?: boolean;
+  /** Likeliest scoreline under the football Poisson goals model, e.g. "2-1" */
+  most_likely_score?: string | null;
+  /** Most likely outcome; exact ties go to home, then away */
+  predicted_outcome: 'home' | 'away' | 'draw';
 }
 
 export interface UpcomingMatchWithPrediction {
@@ -61,11 +77,33 @@ export interface Edge {
   market_away_odds: number;
   market_draw_odds?: number;
   edge_value: number;
+  side: 'home' | 'away' | 'draw';
   is_live_odds: boolean;
   bookmaker?: string;
   odds_fetched_at?: string;
 }
 
+export type EdgeStatus = 'edges_found' | 'no_odds_key' | 'no_market_odds' | 'no_edges';
+
+export interface EdgeReport {
+  edges: Edge[];
+  status: EdgeStatus;
+  message: string;
+}
+
+export interface OddsCoverage {
+  match_id: string;
+  home_team_name: string;
+  away_team_name: string;
+  sport: string;
+  league: string;
+  match_date: string;
+  covered: boolean;
+  bookmaker?: string;
+  odds_fetched_at?: string;
+  odds_age_minutes?: number;
+}
+
 export interface ApiResponse<T> {
   success: boolean;
   data?: T;
@@ -108,9 +146,26 @@ export const apiService = {
     return response.data.data || [];
   },
 
+  async searchTeams(name: string, sport?: string, league?: string): Promise<Team[]> {
+    const params = new URLSearchParams({ name });
+    if (sport) params.append('sport', sport);
+    if (league) params.append('league', league);
+
+    const response = await api.get<ApiResponse<Team[]>>(`/teams/search?${params}`);
+    return response.data.data || [];
+  },
+
   // Predictions
-  async getPredictionEdges(): Promise<Edge[]> {
-    const response = await api.get<ApiResponse<Edge[]>>('/predictions/edges');
+  async getPredictionEdges(): Promise<EdgeReport> {
+    const response = await api.get<ApiResponse<EdgeReport>>('/predictions/edges');
+    return response.data.data || { edges: [], status: 'no_edges', message: '' };
+  },
+
+  async getOddsCoverage(sport?: string): Promise<OddsCoverage[]> {
+    const params = new URLSearchParams();
+    if (sport) params.append('sport', sport);
+
+    const response = await api.get<ApiResponse<OddsCoverage[]>>(`/odds/coverage?${params}`);
     return response.data.data || [];
   },
 
@@ -145,7 +200,8 @@ export const apiService = {
     date_from?: string;
     date_to?: string;
     stats_categories: string[];
-    format: string;
+    format?: string;
+    compress?: boolean;
   }): Promise<any> {
     const response = await api.post<ApiResponse<any>>('/datasets/generate', request);
     return response.data.data;